@@ -0,0 +1,15 @@
+// Build script: compiles proto/*.proto into Rust types with prost-build (only with --features protobuf).
+// The generated code lands in $OUT_DIR/ecosystem.user.rs and is include!d by src/codec/proto.rs.
+// protoc comes from protoc-bin-vendored, so no system protobuf compiler is needed.
+
+fn main() -> std::io::Result<()> {
+    #[cfg(feature = "protobuf")]
+    {
+        println!("cargo:rerun-if-changed=proto");
+        let protoc = protoc_bin_vendored::protoc_bin_path().map_err(std::io::Error::other)?;
+        prost_build::Config::new()
+            .protoc_executable(protoc)
+            .compile_protos(&["proto/user.proto"], &["proto"])?;
+    }
+    Ok(())
+}
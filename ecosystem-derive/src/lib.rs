@@ -0,0 +1,108 @@
+// ecosystem-derive: the crate's derive macros. Proc macros have to live in a crate of their own
+// (proc-macro = true), so this is a workspace member; ecosystem re-exports what's here.
+//
+// #[derive(Redact)]: Debug, with the fields marked #[redact] printed as [REDACTED]:
+//
+//   #[derive(Redact)]
+//   struct Credentials {
+//       user: String,
+//       #[redact]
+//       password: String,
+//   }
+//   format!("{creds:?}")   → Credentials { user: "alice", password: [REDACTED] }
+//
+// Same output shape as #[derive(Debug)] ({:#?} included), for structs with named fields, tuple
+// structs and unit structs. Unlike the Redacted<T> wrapper (ecosystem::telemetry::redact), the field
+// keeps its own type, so the rest of the code doesn't change; only {:?} does.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Field, Fields, Index};
+
+#[proc_macro_derive(Redact, attributes(redact))]
+pub fn derive_redact(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Redact can only be derived for structs",
+        ));
+    };
+    let name = &input.ident;
+    let label = name.to_string();
+
+    let body = match &data.fields {
+        Fields::Named(fields) => {
+            let entries = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let ident = field.ident.as_ref().expect("named field");
+                    let key = ident.to_string();
+                    let value = value(field, quote!(&self.#ident))?;
+                    Ok(quote!(.field(#key, #value)))
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote!(f.debug_struct(#label) #(#entries)* .finish())
+        }
+        Fields::Unnamed(fields) => {
+            let entries = fields
+                .unnamed
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    let index = Index::from(i);
+                    let value = value(field, quote!(&self.#index))?;
+                    Ok(quote!(.field(#value)))
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote!(f.debug_tuple(#label) #(#entries)* .finish())
+        }
+        Fields::Unit => quote!(f.write_str(#label)),
+    };
+
+    // like #[derive(Debug)]: every type parameter must be Debug
+    let params: Vec<_> = input
+        .generics
+        .type_params()
+        .map(|p| p.ident.clone())
+        .collect();
+    let where_clause = input.generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: ::core::fmt::Debug));
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+// The field's value for the debug builder: the field itself, or the mask if it's #[redact].
+fn value(field: &Field, access: TokenStream2) -> syn::Result<TokenStream2> {
+    let mut redact = false;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("redact")) {
+        // #[redact] only, no arguments
+        attr.meta.require_path_only()?;
+        redact = true;
+    }
+    Ok(if redact {
+        // format_args! Debug-prints its text as is: [REDACTED], not "[REDACTED]"
+        quote!(&::core::format_args!("[REDACTED]"))
+    } else {
+        access
+    })
+}
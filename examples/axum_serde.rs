@@ -1,15 +1,31 @@
-use std::sync::{Arc, Mutex};
+// The same service built from config instead of by hand, as an installable command: src/web/app.rs, `ecosystem serve`.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::{Ok, Result};
 use axum::{
     extract::State,
+    middleware,
     routing::{get, patch},
     Json, Router,
 };
+use ecosystem::{
+    audit::{AuditContext, AuditLog},
+    runtime::{self, RuntimeConfig},
+    telemetry::{self, LoggingConfig, TelemetryBuilder},
+    web::{
+        self,
+        server::ServerConfig,
+        users::{NewUser, UserStore},
+    },
+    worker::{HashJob, JobRegistry, JobStore},
+};
 use serde::{Deserialize, Serialize};
-use tokio::net::TcpListener;
-use tracing::{info, instrument};
-use tracing_subscriber::{filter::LevelFilter, fmt, prelude::*, Registry};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tracing::instrument;
 
 #[derive(Serialize, PartialEq, Debug, Clone)]
 struct User {
@@ -24,12 +40,27 @@ struct UserUpdate {
     skills: Option<Vec<String>>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Build and set a global subscriber using the latest tracing-subscriber APIs
-    let subscriber = Registry::default().with(fmt::layer().pretty().with_filter(LevelFilter::INFO));
+fn main() -> Result<()> {
+    // Runtime shape from [runtime] in server.toml (flavor, worker_threads, thread_name, metrics_interval_ms),
+    // overridable with RUNTIME_WORKER_THREADS etc., see src/runtime.rs
+    let runtime = runtime::build(&RuntimeConfig::load("server.toml")?)?;
+    runtime.block_on(run())
+}
 
-    tracing::subscriber::set_global_default(subscriber)?;
+async fn run() -> Result<()> {
+    // Build and set a global subscriber using the latest tracing-subscriber APIs
+    // Level and format come from [logging] in server.toml (level, format, per-module [logging.levels]),
+    // overridable with LOG_LEVEL / LOG_FORMAT (json for the log shipper) and RUST_LOG (e.g. RUST_LOG=ecosystem::web=debug)
+    // The level can be changed at runtime: PUT /admin/log-level (with ADMIN_TOKEN as bearer token), or
+    // kill -USR1 to toggle debug
+    // TelemetryBuilder also installs the panic hook: panics are logged as ERROR events
+    // (message, location, backtrace, request span) instead of stderr
+    let telemetry_guard = TelemetryBuilder::new("axum-serde")
+        .logging(LoggingConfig::load("server.toml")?)
+        .init()?;
+    let log_level = telemetry_guard.log_level().clone();
+    #[cfg(unix)]
+    telemetry::spawn_sigusr1_toggle(log_level.clone(), "debug")?;
 
     let user = User {
         name: "Alice".to_string(),
@@ -38,15 +69,69 @@ async fn main() -> Result<()> {
     };
     let user = Arc::new(Mutex::new(user));
 
-    let addr = "0.0.0.0:8080";
-    let listener = TcpListener::bind(addr).await?;
-    info!("Listening on {}", addr);
+    // Every user mutation is appended to this JSON-lines file (and queryable via GET /audit)
+    let audit_path = std::env::var("AUDIT_LOG").unwrap_or_else(|_| "audit.jsonl".to_string());
+    let audit = AuditLog::open(&audit_path).await?;
+
+    // Multi-user store shared by the JSON API (/users) and the HTML views (/ui/users)
+    let users = UserStore::new().with_audit(audit.clone());
+    let system = AuditContext::system();
+    users.create(
+        &system,
+        NewUser {
+            name: "Alice".to_string(),
+            age: 30,
+            skills: vec!["Rust".to_string(), "WebAssembly".to_string()],
+        },
+    );
+    users.create(
+        &system,
+        NewUser {
+            name: "Bob".to_string(),
+            age: 25,
+            skills: vec!["Go".to_string()],
+        },
+    );
+    // Soft-deleted users are kept for 30 days (restore window), then purged
+    users.spawn_purge_task(
+        Duration::from_secs(30 * 24 * 3600),
+        Duration::from_secs(3600),
+    );
+
+    let assets_dir = std::env::var("ASSETS_DIR").unwrap_or_else(|_| "assets".to_string());
+
+    // bind address, timeouts, body limit, CORS, TLS: server.toml + SERVER_* env overrides
+    let config = ServerConfig::load("server.toml")?;
 
     let app = Router::new()
         .route("/", get(user_handler))
         .route("/", patch(update_handler))
-        .with_state(user);
-    axum::serve(listener, app.into_make_service()).await?;
+        .with_state(user)
+        .merge(web::users::router(users.clone()))
+        .merge(web::search::router(users.clone()))
+        .merge(web::ui::router(users))
+        .merge(web::audit::router(audit))
+        // POST /jobs + GET /jobs/{id}: expensive work runs in the worker subsystem, not in the handler
+        // {"type": "hash", "payload": {"input": "..."}} runs through the JobRegistry
+        .merge(web::jobs::router(JobStore::new().with_registry(Arc::new(
+            JobRegistry::new().register::<HashJob>("hash"),
+        ))))
+        // GET /assets/*: a small front-end for the API above (ASSETS_DIR, default ./assets)
+        .merge(web::assets::router(assets_dir, Duration::from_secs(3600)))
+        // /admin/* only with Authorization: Bearer $ADMIN_TOKEN; disabled when it isn't set
+        .merge(web::admin::router(
+            log_level,
+            std::env::var("ADMIN_TOKEN").ok(),
+        ))
+        // X-Trace-Id on every response (and trace_id in error bodies), for bug reports → trace
+        .layer(middleware::from_fn(web::trace_id::middleware))
+        // one span per request: method, route, status, latency, user agent (OTel HTTP conventions)
+        .layer(web::trace::layer())
+        // X-Request-Id: generated (UUID) unless the client sent one, echoed back on the response,
+        // and recorded in every audit entry. Layers run outside-in, so SetRequestId goes last.
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid));
+    web::server::serve(app, &config).await?;
 
     Ok(())
 }
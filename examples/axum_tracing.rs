@@ -6,92 +6,128 @@
 // tracing: Structured logging framework
 // tracing_subscriber: Configures how logs are formatted and output
 
-use axum::{extract::Request, routing::get, Router};
-use opentelemetry::{
-    global,
-    propagation::Extractor,
-    trace::{TraceContextExt, TracerProvider as _},
-    KeyValue,
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::{self, Next},
+    response::Response,
+    routing::get,
+    Router,
 };
-use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
-use std::time::Duration;
+use ecosystem::{
+    telemetry::{
+        otel::OtlpConfig,
+        rolling::FileLogConfig,
+        task_metrics::{self, timed},
+        LoggingConfig, TelemetryBuilder,
+    },
+    web::{
+        self,
+        server::{self, ServerConfig},
+    },
+};
+use opentelemetry::{global, metrics::Counter, KeyValue};
+use std::{sync::LazyLock, time::Duration};
 use tokio::{
     join,
-    net::TcpListener,
     time::{sleep, Instant},
 };
-use tracing::{debug, info, instrument, level_filters::LevelFilter, warn};
-use tracing_opentelemetry::OpenTelemetrySpanExt; // for setting parent context on current span
-use tracing_subscriber::{
-    fmt::{self, format::FmtSpan},
-    layer::SubscriberExt,
-    util::SubscriberInitExt,
-    Layer,
-};
+use tracing::{debug, info, instrument};
+use tracing_subscriber::fmt::format::FmtSpan;
 
 // Main Function Setup
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // --------------------------
-    // Console Layer for tracing-subscriber
-    // --------------------------
-    let console = fmt::Layer::new()
-        .with_span_events(FmtSpan::CLOSE) // log when spans close
-        .pretty() // pretty formatting
-        .with_filter(LevelFilter::DEBUG); // console shows DEBUG+
-
-    // --------------------------
-    // File Layer
+    // Telemetry: console + file + OpenTelemetry, composed by telemetry::TelemetryBuilder
     // --------------------------
-    // Logging Configuration
     // Logging setup breakdown:
-    // File appender: Creates daily rotating log files in logs
-    // Non-blocking writer: Prevents I/O blocking the main thread
-    // Console layer: Logs to stdout with DEBUG level and pretty formatting
-    // File layer: Logs to file with INFO level and pretty formatting
+    // [logging] in server.toml: format (LOG_FORMAT=json switches both layers to one flattened JSON object
+    //   per line, for the log shipper) and per-module [logging.levels], applied to every sink
+    // Console layer: stdout, DEBUG+ unless RUST_LOG says otherwise, logs when spans close
+    // File layer: /tmp/logs/ecosystem.log, INFO+, rotated at 50 MiB keeping the 10 newest rotated files,
+    //   written by a non-blocking writer so I/O doesn't block the main thread
+    // OpenTelemetry: traces, metrics (request counter + app.task_duration histogram, see METRICS below,
+    //   and span.calls/span.duration for every span) and logs (every INFO+ event is also exported as an
+    //   OTel log record, stamped with the trace_id/span_id of the span it happened in).
+    //   Endpoint/protocol from OTEL_EXPORTER_OTLP_* (see telemetry::otel);
+    //   sampling: OTEL_TRACES_SAMPLER=parentbased_traceidratio OTEL_TRACES_SAMPLER_ARG=0.1 exports ~10% of traces;
+    //   identity: service.name "axum-tracing" unless OTEL_SERVICE_NAME / OTEL_RESOURCE_ATTRIBUTES say otherwise
+    // Also: tokio-console (--features tokio-console), Sentry (--features sentry + SENTRY_DSN),
+    //   and the panic hook (panics → ERROR events with backtrace, exported like any other log)
     // Registry: Combines multiple logging layers
-    let file_appender = tracing_appender::rolling::daily("/tmp/logs", "ecosystem.log");
-    let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
-    let file = fmt::Layer::new()
-        .with_writer(non_blocking) // write asynchronously to file
-        .pretty()
-        .with_filter(LevelFilter::INFO); // file shows INFO+
-
-    // --------------------------
-    // OpenTelemetry Layer for tracing-subscriber
-    // --------------------------
-    // Initialize OpenTelemetry (new API)
-    let tracer_provider = init_tracer_provider()?; // creates SdkTracerProvider with batch exporter.
-
-    // Create tracer bound to our SDK provider (SdkTracer implements required traits)
-    let otel_tracer = tracer_provider.tracer("axum-tracing");
-    let opentelemetry = tracing_opentelemetry::layer().with_tracer(otel_tracer);
-
-    // Then you combine them with:
-    // Compose subscriber:
-    tracing_subscriber::registry()
-        .with(console)
-        .with(file)
-        .with(opentelemetry)
-        .init();
+    let telemetry = TelemetryBuilder::new("axum-tracing")
+        .logging(LoggingConfig::load("server.toml")?)
+        .console_level("debug")
+        .console_span_events(FmtSpan::CLOSE)
+        .file(FileLogConfig::default())
+        .file_level("info")
+        .otel(OtlpConfig::default())
+        .init()?;
 
     // Server Setup
-    let addr = "127.0.0.1:8080";
+    // bind address, timeouts, body limit, CORS, TLS: server.toml + SERVER_* env overrides
+    let config = ServerConfig::load("server.toml")?;
     // ── AXUM: define routes, handlers (the “menu + chef”) ─────
-    let app = Router::new().route("/", get(index_handler));
+    // web::trace::layer(): one span per request with OTel HTTP semantic-convention fields
+    // web::trace_id::middleware: X-Trace-Id response header → paste it into the Jaeger/Tempo search
+    let app = Router::new()
+        .route("/", get(index_handler))
+        .route_layer(middleware::from_fn(count_requests))
+        .layer(middleware::from_fn(web::trace_id::middleware))
+        .layer(web::trace::layer());
 
     // --- bind a TCP socket with Tokio (OS socket via runtime reactor) ---
-    let listener = TcpListener::bind(addr).await?;
     // --- serve the app (Hyper under the hood via Axum server) ---
     // Axum converts `Router` into a Hyper `Service`, Hyper does HTTP I/O on Tokio.
-    info!("Starting server on {}", addr);
-    axum::serve(listener, app.into_make_service()).await?; // ← AXUM API, uses HYPER server on top of TOKIO. runs Hyper on Tokio.
+    // server::serve binds a TcpListener + axum::serve for plain HTTP, or axum_server + rustls for HTTPS
+    // (when [tls] is configured).
+    info!("Starting server on {}", config.addr);
+    let result = server::serve(app, &config).await; // ← AXUM API, uses HYPER server on top of TOKIO. runs Hyper on Tokio.
+
+    // Cleanup: flush + shut down traces, metrics and logs, at most 5s (a dead Collector can't hang the exit),
+    // and say how many spans were lost instead of dropping the providers and hoping.
+    if let Some(report) = telemetry.shutdown(Duration::from_secs(5)) {
+        eprintln!("{report}");
+    }
+    result
+}
 
-    // Optionally force flush before exiting (best-effort)
-    // (In 0.30, dropping the provider will flush. Explicit flush omitted for simplicity.)
-    drop(tracer_provider); // Cleanup: to flush spans on shutdown.
-    Ok(())
+// Instruments are created lazily, i.e. after init_meter_provider() installed the global provider.
+// Before this, the numbers only existed as log fields (app.task_duration = ...), now they are real metrics.
+// Task durations go to task.duration{task.name} (telemetry::task_metrics::timed), one histogram for all tasks.
+struct Metrics {
+    requests: Counter<u64>,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(|| {
+    let meter = global::meter("axum-tracing");
+    Metrics {
+        requests: meter
+            .u64_counter("http.server.requests")
+            .with_description("Number of HTTP requests handled")
+            .build(),
+    }
+});
+
+// Counts every routed request with the status it actually got (a handler error, a timeout, ...),
+// not the one the handler hopes to return. A route_layer, so MatchedPath is there.
+async fn count_requests(req: Request, next: Next) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| req.uri().path().to_owned(), |p| p.as_str().to_owned());
+    let res = next.run(req).await;
+    METRICS.requests.add(
+        1,
+        &[
+            KeyValue::new("http.route", route),
+            KeyValue::new(
+                "http.response.status_code",
+                i64::from(res.status().as_u16()),
+            ),
+        ],
+    );
+    res
 }
 
 // ── AXUM: handlers (your business logic = “recipes”) ──────────────────────
@@ -99,35 +135,32 @@ async fn main() -> anyhow::Result<()> {
 // When you put it on a function, it automatically creates and manages a span for every call to that function.
 // Request Handler Chain
 
-// Adds http.uri and http.method fields to the span.
-// extract_remote_context(req) tries to read W3C traceparent/tracestate headers; if present, sets current span’s parent.
-// Awaits long_task(); logs info with status_code=200; returns response string.
-#[instrument(fields(http.uri = req.uri().path(), http.method = req.method().as_str()))]
-async fn index_handler(req: Request) -> &'static str {
+// HTTP fields (method, route, status, latency, user agent) and the remote parent (traceparent)
+// live on the request span created by web::trace::layer(); this span is its child.
+// Awaits long_task(); logs info; returns response string (counted by count_requests).
+#[instrument]
+async fn index_handler() -> &'static str {
     debug!("index handler started");
-    // Extract remote trace context (if any) from incoming headers and set current span parent
-    if let Some(ctx) = extract_remote_context(&req) {
-        tracing::Span::current().set_parent(ctx);
-    }
     sleep(Duration::from_millis(10)).await;
     let ret = long_task().await;
-    info!(http.status_code = 200, "index handler completed");
+    info!("index handler completed");
     ret
 }
 
 // Starts timer, concurrently awaits sl + task1 + task2 + task3 via join!.
-// Logs warn! with total duration (ms).
+// Records the total duration (task.duration histogram); the slow-span layer warns if it exceeds the configured threshold.
 #[instrument]
 async fn long_task() -> &'static str {
     let start = Instant::now();
     let sl = sleep(Duration::from_millis(11));
-    // spawn multiple tasks
-    let t1 = task1();
-    let t2 = task2();
-    let t3 = task3();
+    // spawn multiple tasks, each timed into task.duration{task.name=...}
+    let t1 = timed("task1", task1());
+    let t2 = timed("task2", task2());
+    let t3 = timed("task3", task3());
     join!(sl, t1, t2, t3);
-    let elapsed = start.elapsed().as_millis() as u64;
-    warn!(app.task_duration = elapsed, "task takes too long");
+    task_metrics::record("long_task", start.elapsed());
+    // "task takes too long" is no longer hardcoded here: [logging.slow_spans] long_task = 50 in server.toml
+    // makes telemetry::slow_spans warn when this span outlives its threshold
     "Hello, World!"
 }
 
@@ -203,99 +236,6 @@ async fn task3() {
 //     Ok(provider)
 // }
 
-// ...existing code...
-fn init_tracer_provider() -> anyhow::Result<SdkTracerProvider> {
-    // Enables W3C context extraction/injection.
-    global::set_text_map_propagator(TraceContextPropagator::new());
-
-    // Endpoint selection:
-    // Prefer per-signal var, then global; default to local gRPC
-    // Reads OTEL_EXPORTER_OTLP_TRACES_ENDPOINT or OTEL_EXPORTER_OTLP_ENDPOINT.
-    // Default: http://127.0.0.1:4317.
-    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT")
-        .or_else(|_| std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT"))
-        .unwrap_or_else(|_| "http://127.0.0.1:4317".to_string());
-
-    // Allow switching protocol: grpc (4317) or http/protobuf (4318)
-    // Protocol selection:
-    // Reads OTEL_EXPORTER_OTLP_PROTOCOL; default "grpc".
-    // If "http" or "http/protobuf":
-    // Builds HTTP exporter; ensures endpoint ends with /v1/traces.
-    // Else:
-    // Builds gRPC exporter via tonic.
-    let protocol =
-        std::env::var("OTEL_EXPORTER_OTLP_PROTOCOL").unwrap_or_else(|_| "grpc".to_string()); // "grpc" or "http/protobuf"
-
-    // Resource describing this service
-    // service.name="axum-tracing", service.version from Cargo.
-    let resource = Resource::builder()
-        .with_attribute(KeyValue::new("service.name", "axum-tracing"))
-        .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
-        .build();
-
-    let provider = if protocol == "http/protobuf" || protocol == "http" {
-        // Requires opentelemetry-otlp feature: http-proto (you can enable both http-proto and grpc-tonic)
-        let http_endpoint = if endpoint.ends_with("/v1/traces") {
-            endpoint.clone()
-        } else {
-            format!("{endpoint}/v1/traces")
-        };
-        let exporter = opentelemetry_otlp::SpanExporter::builder()
-            .with_http() // OTLP/HTTP
-            .with_endpoint(http_endpoint)
-            .build()?;
-        SdkTracerProvider::builder()
-            .with_resource(resource)
-            .with_batch_exporter(exporter)
-            .build()
-    } else {
-        // Default gRPC over tonic (h2c, plaintext)
-        let exporter = opentelemetry_otlp::SpanExporter::builder()
-            .with_tonic() // OTLP/gRPC
-            .with_endpoint(endpoint)
-            .build()?;
-        SdkTracerProvider::builder()
-            .with_resource(resource)
-            .with_batch_exporter(exporter)
-            .build()
-    };
-
-    // Makes it discoverable by tracing-opentelemetry.
-    global::set_tracer_provider(provider.clone());
-    Ok(provider)
-}
-// ...existing code...
-
-// ---- Distributed trace context extraction helpers ----
-struct HeaderExtractor<'a>(&'a axum::http::HeaderMap);
-
-// HeaderExtractor implements opentelemetry::propagation::Extractor for Axum headers.
-impl<'a> Extractor for HeaderExtractor<'a> {
-    fn get(&self, key: &str) -> Option<&str> {
-        self.0.get(key).and_then(|v| v.to_str().ok())
-    }
-    fn keys(&self) -> Vec<&str> {
-        self.0.keys().map(|k| k.as_str()).collect()
-    }
-}
-
-// Uses global propagator to extract parent Context from headers.
-// Returns Some(ctx) only if a valid remote SpanContext exists.
-fn extract_remote_context(req: &Request) -> Option<opentelemetry::Context> {
-    let headers = req.headers();
-    if headers.is_empty() {
-        return None;
-    }
-    let carrier = HeaderExtractor(headers);
-    let ctx = global::get_text_map_propagator(|prop| prop.extract(&carrier));
-    // If no remote trace, the extracted context will have no span – heuristic check
-    if ctx.span().span_context().is_valid() {
-        Some(ctx)
-    } else {
-        None
-    }
-}
-
 // Env vars that control export
 
 // OTEL_EXPORTER_OTLP_PROTOCOL: "grpc" (4317) or "http/protobuf" (4318).
@@ -306,6 +246,7 @@ fn extract_remote_context(req: &Request) -> Option<opentelemetry::Context> {
 // Pretty console logs (DEBUG+), rotating file logs (INFO+).
 // Structured spans for each handler/task with automatic parenting.
 // Export to Collector with protocol chosen at runtime via envs.
+// Logs are exported too (OTel logs bridge), correlated with traces via trace_id/span_id.
 
 // Short answer: tracing is the idiomatic in-process instrumentation and logging API for Rust; OpenTelemetry is the vendor-neutral telemetry API/SDK and exporter. You typically want both, bridged by tracing-opentelemetry.
 
@@ -346,32 +287,35 @@ fn extract_remote_context(req: &Request) -> Option<opentelemetry::Context> {
 // main()
 
 // Build console layer:
-// Pretty logs, span close events, LevelFilter::DEBUG.
+// Pretty logs, span close events, EnvFilter (RUST_LOG, default debug).
 // Build file layer:
-// Daily rotating file at /tmp/logs/ecosystem.log.
+// Size-rotated file at /tmp/logs/ecosystem.log (50 MiB, 10 rotated files kept; telemetry::rolling).
 // Non-blocking writer (keeps _guard alive).
 // INFO+ to file.
 // OpenTelemetry:
-// init_tracer_provider() creates SdkTracerProvider with batch exporter.
+// Telemetry::init() creates SdkTracerProvider with batch exporter (plus meter and logger providers).
 // Get tracer and make tracing-opentelemetry layer with it.
 // Compose subscriber:
-// registry().with(console).with(file).with(opentelemetry).init().
+// TelemetryBuilder::init() does registry().with(console).with(file).with(opentelemetry)... .init().
 // Server:
 // Bind 127.0.0.1:8080 with TcpListener.
 // axum::serve(listener, app.into_make_service()) runs Hyper on Tokio.
 // Cleanup:
-// drop(tracer_provider) to flush spans on shutdown.
+// telemetry.shutdown(5s) flushes spans/metrics/logs and reports dropped spans.
 // Handlers and spans
 
 // #[instrument] on functions:
 // Automatically creates a span per call and records arguments.
-// index_handler(req):
-// Adds http.uri and http.method fields to the span.
-// extract_remote_context(req) tries to read W3C traceparent/tracestate headers; if present, sets current span’s parent.
-// Awaits long_task(); logs info with status_code=200; returns response string.
+// web::trace::layer() (tower-http TraceLayer):
+// One "http.request" span per request with http.request.method, http.route, url.path, user_agent.original,
+// http.response.status_code and latency_ms; reads W3C traceparent/tracestate headers and, if present, sets the span's parent.
+// index_handler():
+// Awaits long_task(); logs info; returns response string.
+// count_requests (route_layer):
+// Adds 1 to http.server.requests{http.route, http.response.status_code} with the real response status.
 // long_task():
 // Starts timer, concurrently awaits sl + task1 + task2 + task3 via join!.
-// Logs warn! with total duration (ms).
+// Records the total duration (task.duration histogram); the slow-span layer warns if it exceeds the configured threshold.
 // task1/task2/task3():
 // Sleep to simulate work; each has its own span from #[instrument].
 // OpenTelemetry initialization
@@ -396,11 +340,11 @@ fn extract_remote_context(req: &Request) -> Option<opentelemetry::Context> {
 // Makes it discoverable by tracing-opentelemetry.
 // HTTP context propagation helpers
 
-// HeaderExtractor implements opentelemetry::propagation::Extractor for Axum headers.
-// extract_remote_context(req):
+// HeaderExtractor implements opentelemetry::propagation::Extractor for Axum headers (now in web::trace).
+// extract_remote_context(headers), called by web::trace::layer() for every request:
 // Uses global propagator to extract parent Context from headers.
 // Returns Some(ctx) only if a valid remote SpanContext exists.
-// In index_handler, current span adopts that parent (distributed tracing).
+// The request span adopts that parent (distributed tracing); index_handler's span is its child.
 // Env vars that control export
 
 // OTEL_EXPORTER_OTLP_PROTOCOL: "grpc" (4317) or "http/protobuf" (4318).
@@ -1,3 +1,5 @@
+// The library version of this builder, with validation in build(): ecosystem::model::User (src/model/user.rs).
+
 use anyhow::Result;
 use chrono::{DateTime, Datelike, Utc}; // time handling; Datelike gives .year().
 use derive_builder::Builder; // procedural macro that generates a builder for your struct.
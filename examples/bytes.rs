@@ -1,5 +1,6 @@
 use anyhow::Result;
 use bytes::{BufMut, BytesMut};
+use ecosystem::codec::endian::{LittleEndian, Reader, Writer};
 
 fn main() -> Result<()> {
     let mut buf = BytesMut::with_capacity(1024);
@@ -48,5 +49,19 @@ fn main() -> Result<()> {
     println!("{:?}", b);
     println!("{:?}", buf);
 
+    // The same number little-endian, with the byte order chosen once as a type parameter
+    // (src/codec/endian.rs) instead of remembering put_i64_le / get_i64_le at every call:
+    Writer::<_, LittleEndian>::new(&mut buf).put(0xdeadbeef_i64);
+    println!("{:?}", buf);
+    // b"\xef\xbe\xad\xde\0\0\0\0"  ← lowest byte first
+
+    let mut input = Reader::<_, LittleEndian>::new(buf.freeze());
+    let n: i64 = input.get()?;
+    println!("{n:#x}"); // 0xdeadbeef
+
+    // Buf::get_i64 would panic here; Reader::get returns an error instead
+    let short: Result<i64, _> = input.get();
+    println!("{short:?}"); // Err(ShortBuffer { needed: 8, remaining: 0 })
+
     Ok(())
 }
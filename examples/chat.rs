@@ -57,6 +57,7 @@
 
 use anyhow::Result;
 use dashmap::DashMap;
+use ecosystem::telemetry::{self, LogFormat};
 use futures::{stream::SplitStream, SinkExt, StreamExt};
 use std::{fmt, net::SocketAddr, sync::Arc};
 use tokio::{
@@ -64,8 +65,10 @@ use tokio::{
     sync::mpsc,
 };
 use tokio_util::codec::{Framed, LinesCodec};
-use tracing::{info, level_filters::LevelFilter, warn};
-use tracing_subscriber::{fmt::Layer, layer::SubscriberExt, util::SubscriberInitExt, Layer as _}; //因为两个Layer冲突，后者是trait，我们只需要使用其方法，所以，用匿名_来引入。
+use tracing::{info, warn};
+use tracing_subscriber::{
+    fmt::format::FmtSpan, layer::SubscriberExt, util::SubscriberInitExt, Layer as _,
+}; //因为两个Layer冲突，后者是trait，我们只需要使用其方法，所以，用匿名_来引入。
 
 // The channel will allocate a bounded queue that can hold exactly 128 unread messages.
 // It Prevents Out-Of-Memory (OOM) Crashes
@@ -133,11 +136,17 @@ enum Message {
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initializes tracing/logging with INFO level
-    let layer = Layer::new().with_filter(LevelFilter::INFO);
-    tracing_subscriber::registry().with(layer).init();
+    let layer = LogFormat::from_env(LogFormat::Full)
+        .layer(std::io::stdout, FmtSpan::NONE)
+        .with_filter(telemetry::env_filter("info"));
+    tracing_subscriber::registry()
+        .with(layer)
+        .with(telemetry::console_layer())
+        .init();
 
     // 因为会和上述 tracing_subscriber::registry().with(layer).init() 冲突，所以直接用下面的init来初始化日志系统，默认是INFO级别
     // console_subscriber::init();
+    // 现在 console_subscriber 作为 telemetry::console_layer() 加入上面的 registry（需要 --features tokio-console）
 
     let addr = "0.0.0.0:8080";
     let listener = TcpListener::bind(addr).await?;
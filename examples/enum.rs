@@ -1,3 +1,5 @@
+// Data-bearing enums sent as (discriminant, payload) with EnumDiscriminants: ecosystem::tagged (src/tagged.rs).
+
 // Strum is a utility crate that adds derive macros and helpers for enums (and a few for structs). It saves you from hand-writing boilerplate like Display, FromStr, iterators, etc.
 
 use anyhow::Result;
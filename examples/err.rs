@@ -1,22 +1,17 @@
 use anyhow::Context;
+use ecosystem::{error::MyError, telemetry::error_reporting};
 use std::fs;
-use thiserror::Error;
 
-#[derive(Error, Debug)]
-pub enum MyError {
-    #[error("An I/O error occurred: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("A parsing error occurred: {0}")]
-    // Parse(#[from] std::str::Utf8Error),
-    Parse(#[from] std::num::ParseIntError),
-    #[error("A serialization json error occurred: {0}")]
-    Serialize(#[from] serde_json::Error),
-    #[error("A custom error occurred: {0}")]
-    Custom(String),
-}
+// MyError lives in the library (src/error.rs).
 
 fn main() -> Result<(), anyhow::Error> {
     println!("size of MyError is {}", std::mem::size_of::<MyError>());
+    // SENTRY_DSN=... cargo run --example err --features sentry → the parse error below shows up in Sentry
+    let _error_reporting = error_reporting::init();
+    if let Err(e) = "not a number".parse::<u8>().map_err(MyError::from) {
+        // the source() chain (MyError::Parse → ParseIntError) becomes the exception list
+        error_reporting::capture_error(&e);
+    }
     let filename = "non-existent.txt";
     // let _fd = fs::File::open(filename).context(format!("can't find {}", filename))?;
     let _fd = fs::File::open(filename).with_context(|| format!("can't find {}", filename))?;
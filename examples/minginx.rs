@@ -2,6 +2,7 @@
 // In summary:
 // This is a simple but functional load balancer/proxy that accepts connections on port 8081 and transparently relays all traffic to a service on port 8080.
 // It handles multiple concurrent connections using async/await.
+// The installable version, with proxy.toml, several upstreams and flag overrides: src/proxy.rs, `ecosystem proxy`.
 
 // Real-World Analogy: A Mail Forwarding Service
 // Imagine you run a mail forwarding company:
@@ -9,14 +10,15 @@
 //                  (Proxy Server)
 
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use tokio::{
-    io,
-    net::{TcpListener, TcpStream},
+use ecosystem::{
+    codec::BufPool,
+    runtime::{self, RuntimeConfig},
+    telemetry::{self, LogFormat, LoggingConfig, TelemetryBuilder},
 };
-use tracing::{info, level_filters::LevelFilter, warn};
-use tracing_subscriber::{fmt::Layer, layer::SubscriberExt, util::SubscriberInitExt, Layer as _};
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{info, warn};
 
 #[derive(Serialize, Deserialize, Clone)]
 struct Config {
@@ -24,16 +26,35 @@ struct Config {
     listen_addr: String,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initializes tracing/logging with INFO level
-    let layer = Layer::new().with_filter(LevelFilter::INFO);
-    tracing_subscriber::registry().with(layer).init();
+fn main() -> Result<()> {
+    // Proxy threads named minginx-0, minginx-1, ...; with runtime metrics every 10s (src/runtime.rs):
+    // runtime.tasks.alive ≈ open connections, since every connection is one task
+    let runtime = runtime::build(
+        &RuntimeConfig::multi_thread("minginx").sample_metrics(Duration::from_secs(10)),
+    )?;
+    runtime.block_on(run())
+}
+
+async fn run() -> Result<()> {
+    // Initializes tracing/logging: RUST_LOG if set, INFO level otherwise
+    // kill -USR1 <pid> toggles debug logging on the live proxy (and back), no restart needed
+    let telemetry_guard = TelemetryBuilder::new("minginx")
+        .logging(LoggingConfig {
+            format: LogFormat::from_env(LogFormat::Full),
+            ..LoggingConfig::default()
+        })
+        .init()?;
+    #[cfg(unix)]
+    telemetry::spawn_sigusr1_toggle(telemetry_guard.log_level().clone(), "debug")?;
     let config = resolve_config();
     let config = Arc::new(config);
     info!("Upstream is {}", config.upstream_addr);
     info!("Listening on {}", config.listen_addr);
 
+    // Copy buffers for every connection, reused instead of allocated per connection (src/codec/buf_pool.rs):
+    // 16 KiB each, at most 512 idle ones kept (2 per connection → 256 connections' worth)
+    let buffers = BufPool::new("minginx", 16 * 1024, 512);
+
     // Binds a TCP listener to the configured listen address
     let listener = TcpListener::bind(&config.listen_addr).await?;
 
@@ -42,6 +63,7 @@ async fn main() -> Result<()> {
         let (client, addr) = listener.accept().await?;
         info!("Accepted connection from {}", addr);
         let cloned_config = config.clone();
+        let buffers = buffers.clone();
 
         // Connection handling:
         // When a client connects, it spawns an async task
@@ -49,7 +71,7 @@ async fn main() -> Result<()> {
         // Calls proxy() to bridge the two connections
         tokio::spawn(async move {
             let upstream = TcpStream::connect(&cloned_config.upstream_addr).await?;
-            proxy(client, upstream).await?;
+            proxy(client, upstream, &buffers).await?;
             Ok::<(), anyhow::Error>(())
         });
     }
@@ -62,22 +84,33 @@ async fn main() -> Result<()> {
     Ok::<(), anyhow::Error>(())
 }
 
+// Distributed tracing: this is an L4 (TCP) proxy, it relays bytes without parsing HTTP, so it can't
+// read or inject traceparent headers and doesn't appear as a hop in traces. There is no L7 (HTTP) mode yet.
+// When one is added, it needs no new tracing code, just the two existing halves:
+//   web::trace::layer()       a server span per proxied request, child of the incoming traceparent,
+//                             or the root of a new trace when there is none
+//   client::TracedClient      an http.client span per upstream request, whose context is injected
+//                             as traceparent (OTEL_PROPAGATORS) into the upstream request headers
+// e.g. Router::new().fallback(forward).layer(web::trace::layer()), with forward() rebuilding the request
+// for the upstream and calling TracedClient::execute inside the request span.
+
 // proxy() function: The core logic
 
 // Splits both TCP streams into read/write halves
-// Uses tokio::io::copy() to bidirectionally forward data:
+// Uses BufPool::copy() (tokio::io::copy() with a pooled buffer) to bidirectionally forward data:
 // client_read → upstream_write (client to upstream)
 // upstream_read → client_write (upstream to client)
 // Uses tokio::try_join!() to run both copies concurrently until either completes or errors
 // Logs bytes transferred and any errors
-async fn proxy(mut client: TcpStream, mut upstream: TcpStream) -> Result<()> {
+async fn proxy(mut client: TcpStream, mut upstream: TcpStream, buffers: &BufPool) -> Result<()> {
     let (mut client_read, mut client_write) = client.split();
     let (mut upstream_read, mut upstream_write) = upstream.split();
-    let client_to_upstream = io::copy(&mut client_read, &mut upstream_write);
-    let upstream_to_client = io::copy(&mut upstream_read, &mut client_write);
+    let client_to_upstream = buffers.copy(&mut client_read, &mut upstream_write);
+    let upstream_to_client = buffers.copy(&mut upstream_read, &mut client_write);
     match tokio::try_join!(client_to_upstream, upstream_to_client) {
         Ok((n, m)) => info!(
-            "proxied {} bytes from client to upstream, {} bytes from upstream to client",
+            // ByteCount displays with its unit: "proxied 1.5 MiB from client to upstream, ..."
+            "proxied {} from client to upstream, {} from upstream to client",
             n, m
         ),
         Err(e) => warn!("error proxying: {:?}", e),
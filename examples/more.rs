@@ -1,3 +1,5 @@
+// The library version of these newtypes, with units, checked arithmetic and serde: ecosystem::units (src/units.rs).
+
 use anyhow::Result;
 use derive_more::{Add, Display, From, Into};
 
@@ -0,0 +1,127 @@
+// protocol.rs: two ends exchanging typed messages over TCP with ecosystem::codec::MessageCodec
+// What it demonstrates:
+
+// Our own wire protocol instead of LinesCodec (chat.rs): frames with a header (magic, version,
+// message type, flags, length) and a MessagePack body, see src/codec/frame.rs
+// Framed<TcpStream, MessageCodec<T>>: a Stream of Message<T> in, a Sink of Message<T> out
+// One enum for every message of the protocol, so both ends share a single T
+// Compression negotiated per connection: hello frames first (FrameCodec), then typed messages
+
+// key flow:
+
+// main()
+//   ├→ server task: accept → handshake() → Framed<TcpStream, MessageCodec<Command>>
+//   │     └→ for each Message<Command>: Ping → Pong, Hash → Hashed { hex }
+//   └→ client: connect → handshake() → send Ping, Hash → read the replies → close
+//
+// handshake(): send our hello → read the peer's → compress (zstd, bodies ≥ 1 KiB) only if it can decode it
+//
+// Run it as two processes (any machine running this crate's protocol can connect):
+//   cargo run --example protocol -- server 127.0.0.1:7070
+//   cargo run --example protocol -- client 127.0.0.1:7070
+// Without arguments both ends run in this process.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use ecosystem::codec::{self, Compression, FrameCodec, Message, MessageCodec};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::codec::Framed;
+
+// msg_type: requests and replies, so a receiver can tell them apart without decoding the body
+const REQUEST: u16 = 1;
+const REPLY: u16 = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Command {
+    Ping { seq: u32 },
+    Pong { seq: u32 },
+    Hash { input: String },
+    Hashed { hex: String },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let mode = args.next();
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:7070".to_string());
+    match mode.as_deref() {
+        Some("server") => serve(TcpListener::bind(&addr).await?).await,
+        Some("client") => client(&addr).await,
+        _ => {
+            let listener = TcpListener::bind("127.0.0.1:0").await?;
+            let addr = listener.local_addr()?.to_string();
+            tokio::spawn(serve(listener));
+            client(&addr).await
+        }
+    }
+}
+
+async fn serve(listener: TcpListener) -> Result<()> {
+    println!("listening on {}", listener.local_addr()?);
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream).await {
+                println!("connection {peer} failed: {e:#}");
+            }
+        });
+    }
+}
+
+async fn handle(stream: TcpStream) -> Result<()> {
+    let mut conn = handshake(stream).await?;
+    // None: the client closed the connection; Some(Err(..)): it sent something that isn't our protocol,
+    // or took longer than the read timeout for a message it started sending
+    while let Some(message) = codec::recv(&mut conn).await {
+        let reply = match message?.body {
+            Command::Ping { seq } => Command::Pong { seq },
+            Command::Hash { input } => Command::Hashed {
+                hex: blake3::hash(input.as_bytes()).to_string(),
+            },
+            other => anyhow::bail!("unexpected message from client: {other:?}"),
+        };
+        conn.send(Message::new(REPLY, reply)).await?;
+    }
+    Ok(())
+}
+
+async fn client(addr: &str) -> Result<()> {
+    let stream = TcpStream::connect(addr).await?;
+    let mut conn = handshake(stream).await?;
+    for command in [
+        Command::Ping { seq: 1 },
+        Command::Hash {
+            input: "hello".to_string(),
+        },
+    ] {
+        conn.send(Message::new(REQUEST, command)).await?;
+        let reply = conn
+            .next()
+            .await
+            .context("server closed the connection")??;
+        println!("reply (type {}): {:?}", reply.msg_type, reply.body);
+    }
+    Ok(())
+}
+
+// Both ends do the same: neither waits for the other's hello before sending its own.
+async fn handshake(stream: TcpStream) -> Result<Framed<TcpStream, MessageCodec<Command>>> {
+    let codec = FrameCodec::new()
+        .compression(Compression::Zstd, 1024)
+        .read_timeout(Duration::from_secs(10));
+    let mut conn = Framed::new(stream, codec);
+    let hello = conn.codec().hello();
+    conn.send(hello).await?;
+    let hello = conn.next().await.context("closed during the handshake")??;
+    let compression = conn.codec_mut().accept_hello(&hello)?;
+    println!("sending with compression: {compression:?}");
+    Ok(conn.map_codec(MessageCodec::with_frames))
+}
+
+// sending with compression: Zstd
+// sending with compression: Zstd
+// reply (type 2): Pong { seq: 1 }
+// reply (type 2): Hashed { hex: "ea8f163db38682925e4491c5e58d4bb3506ef8c14eb78a86e908c5624a67200f" }
@@ -29,6 +29,7 @@ use chacha20poly1305::{
     AeadCore, ChaCha20Poly1305, KeyInit,
 };
 use chrono::{DateTime, Utc};
+use ecosystem::telemetry::redact::Redacted;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 
@@ -92,8 +93,10 @@ struct User {
 // #[serde_as(as = "DisplayFromStr")] for a single T
 // #[serde_as(as = "Vec<DisplayFromStr>")] for Vec<T> where T: Display + FromStr
 
+// Debug prints SensitiveData([REDACTED]): the plaintext never reaches println!("{:?}") or a log line,
+// only the encrypted Display form goes out (via serde)
 #[derive(Debug)]
-struct SensitiveData(String);
+struct SensitiveData(Redacted<String>);
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", tag = "type", content = "details")] // WorkState is defined with Serde’s tagging attributes
@@ -250,7 +253,7 @@ fn decrypt(encoded: &str) -> Result<Vec<u8>> {
 
 impl fmt::Display for SensitiveData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let encrypted = encrypt(self.0.as_bytes()).unwrap();
+        let encrypted = encrypt(self.0.expose().as_bytes()).unwrap();
         write!(f, "{}", encrypted)
     }
 }
@@ -261,13 +264,13 @@ impl FromStr for SensitiveData {
     fn from_str(s: &str) -> Result<Self> {
         let decrypted = decrypt(s)?;
         let decrypted = String::from_utf8(decrypted)?;
-        Ok(Self(decrypted))
+        Ok(Self(Redacted::new(decrypted)))
     }
 }
 
 impl SensitiveData {
     fn new(data: impl Into<String>) -> Self {
-        Self(data.into())
+        Self(Redacted::new(data.into()))
     }
 }
 
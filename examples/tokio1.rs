@@ -1,7 +1,7 @@
 // tokio1.rs: Single-Threaded Runtime with Spawned Tasks
 // What it demonstrates:
 
-// Creating explicit runtime with Builder
+// Creating explicit runtime with Builder (ecosystem::runtime::build, see src/runtime.rs)
 // Running async code from sync context
 // Spawning multiple concurrent tasks
 // Mixing async I/O (fs::read) with blocking work
@@ -33,17 +33,34 @@
 
 use std::{thread, time::Duration}; //OS thread and timing utilities
 
+use ecosystem::{
+    runtime::{self, RuntimeConfig}, // runtime::build(): tokio::runtime::Builder from a config
+    telemetry, // console_layer(): tokio-console support (--features tokio-console)
+};
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
 use tokio::{
-    fs,                          //Async file system operations
-    runtime::{Builder, Runtime}, //tokio::runtime::Builder: to build runtime manually. Runtime: Tokio runtime type
-    time::sleep,                 //Async sleep (doesn't block thread)
+    fs,               //Async file system operations
+    runtime::Runtime, //Runtime: Tokio runtime type
+    time::sleep,      //Async sleep (doesn't block thread)
 };
 
 fn main() {
+    // Watch the stall live: the blocking hash occupies the only worker thread for 800ms, which
+    // tokio-console shows as a task with a long busy time and "future 1" waiting to be polled.
+    //   RUSTFLAGS="--cfg tokio_unstable" cargo run --example tokio1 --features tokio-console
+    //   tokio-console
+    // Without the feature, console_layer() is None and this is a no-op.
+    tracing_subscriber::registry()
+        .with(telemetry::console_layer())
+        .init();
+
     let handle = thread::spawn(|| {
         //creates new OS thread. Closure || { } runs in that thread
-        let rt = Builder::new_current_thread().enable_all().build().unwrap(); //Build single-threaded runtime with all features enabled
-                                                                              //run(&rt) is async function that receives runtime reference
+        // Build single-threaded runtime with all features enabled: Builder::new_current_thread().enable_all().build()
+        // under the hood, with blocking threads named tokio1-0, tokio1-1, ...
+        let rt = runtime::build(&RuntimeConfig::current_thread("tokio1")).unwrap();
+        //run(&rt) is async function that receives runtime reference
         rt.block_on(run(&rt)); //Execute async function from sync context. block this thread until async function completes.
     });
 
@@ -12,10 +12,10 @@
 //   ↓ spawn async task 1: producer (infinite send loop)
 //   ├→ sends 32 messages into channel (buffer of 32)
 //   ├→ .await on send if buffer full
-//   ↓ worker() thread receives messages
-//   ├→ spawns OS thread for each blocking task
-//   ├→ collects result via sync::mpsc
-//   └→ prints results
+//   ↓ WorkerPool (4 threads) receives messages
+//   ├→ each worker runs one blocking task at a time, 4 in parallel
+//   ├→ delivers (id, result) on the results channel as soon as a job finishes
+//   └→ main prints results
 
 // Key learning: Real-world pattern—async producer sends work, thread worker pool processes blocking operations, results reported back.
 
@@ -31,85 +31,94 @@
 // tokio1: Learning, debugging, CPU-bound work
 // tokio2: Production servers, producer-consumer patterns
 
-use std::{thread, time::Duration};
-use tokio::sync::mpsc;
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use ecosystem::{
+    runtime::{self, RuntimeConfig},
+    worker::{expensive_blocking_task, RateLimiter, WorkerPool},
+};
 
 // #[tokio::main] macro that:
 // Creates multi-threaded Tokio runtime automatically
 // Converts main() to async (runtime calls it)
 // Handles runtime cleanup on exit
-#[tokio::main]
-async fn main() {
+// Here main() does the same by hand with ecosystem::runtime::build (named threads, runtime metrics),
+// and run() is the async main.
+fn main() -> Result<()> {
+    // Worker threads named tokio2-0, tokio2-1, ...; runtime.tasks.alive / runtime.queue.depth sampled every 5s
+    // (src/runtime.rs): the tasks awaiting JobHandles pile up there when the pool falls behind
+    let runtime = runtime::build(
+        &RuntimeConfig::multi_thread("tokio2").sample_metrics(Duration::from_secs(5)),
+    )?;
+    runtime.block_on(run())
+}
+
+async fn run() -> Result<()> {
     // tokio task send string to expensive_blocking_task for execution
-    // 1, Create async channel
-    // mpsc::channel(32): Multi-producer, single-consumer with buffer of 32
-    // tx (transmitter): Send messages
-    // rx (receiver): Receive messages
-    // Buffer=32: Can hold 32 messages before blocking senders
-    let (tx, rx) = mpsc::channel(32);
-    // 2, Start worker thread
-    // worker(rx) spawns OS thread, returns JoinHandle
-    // Worker receives messages from channel
-    let handle = worker(rx); //Start worker thread to receive from channel
-
-    // 3, Producer task (async)
+    // 1, Create the worker pool (library: ecosystem::worker::WorkerPool)
+    // 4 OS threads ("hash-0".."hash-3") run expensive_blocking_task, 4 hashes at a time
+    // Queue of 32: like mpsc::channel(32) before, submit().await waits when it's full
+    // .from_env("HASH_POOL"): HASH_POOL_WORKERS=8 cargo run --example tokio2 tunes it without a rebuild
+    // Arc: shared by the producer (submit) and main (shutdown)
+    // Jobs are fallible (Result<String, MyError>); hashing can't fail, so wrap it in Ok
+    // .named("hash"): job.type label of the pool's metrics (worker.jobs.queued, worker.job.wait_time, ...,
+    // exported once telemetry is initialized with OTLP, see src/worker/metrics.rs)
+    // .rate_limited(): at most 2 hashes started per second (bursts of 4), whatever the producer does;
+    // the rest waits in the queue and submit().await holds the producer back (src/worker/rate_limit.rs)
+    let pool = Arc::new(
+        WorkerPool::builder()
+            .workers(4)
+            .queue_capacity(32)
+            .thread_name("hash")
+            .from_env("HASH_POOL")
+            .build(|s| Ok(expensive_blocking_task(s)))
+            .named("hash")
+            .rate_limited(Arc::new(RateLimiter::new(2.0, 4))),
+    );
+
+    // 2, Producer task (async)
     // tokio::spawn(): Create Tokio task (runs on runtime thread pool)
-    // async move: Moves tx into closure
-    // loop: Infinite sender
-    // tx.send().await: Send message, pause if buffer full
-    // Each sent message is a task description
+    // async move: Moves a pool clone into closure
+    // loop: Infinite sender, until the pool is shut down (submit → Err(Closed))
+    // pool.submit().await: Queue a job, pause if the queue is full; returns a JobHandle
+    // 3, Each handle is awaited in its own task: results print as each job finishes
+    // Previously worker() spawned one OS thread per message and then blocked on that thread's result,
+    // so only one hash ran at a time and threads were unbounded; now 4 run concurrently
+    let producer = pool.clone();
     tokio::spawn(async move {
         let mut i = 0;
         loop {
             i += 1;
             println!("sending task {}", i);
-            tx.send(format!("task {i}")).await.unwrap();
+            let Ok(handle) = producer.submit(format!("task {i}")).await else {
+                break;
+            };
+            tokio::spawn(async move {
+                let id = handle.id();
+                match handle.await {
+                    Ok(result) => println!("result {}: {}", id, result),
+                    Err(e) => println!("job {}: {}", id, e),
+                }
+            });
         }
     });
-    // 4, Wait for worker thread
-    // handle.join(): Block main task until worker thread finishes
-    // Never finishes (infinite loop), so main sleeps here
-    handle.join().unwrap();
-}
 
-// Worker function runs in OS thread
-// thread::spawn(move): New OS thread receives ownership of rx
-// Inside: rx is blocking-capable (not async)
-fn worker(mut rx: mpsc::Receiver<String>) -> thread::JoinHandle<()> {
-    thread::spawn(move || {
-        // Create sync channel and receive
-        // std::sync::mpsc: Standard library sync channel (not async)
-        // rx.blocking_recv(): Receive from Tokio async channel in sync way
-        // Blocks OS thread until message arrives
-        // Some(s): Message received, None if channel closed
-        // while let Some(s): Loop while messages exist
-        let (sender, receiver) = std::sync::mpsc::channel();
-        while let Some(s) = rx.blocking_recv() {
-            // Spawn OS thread for each task
-            // sender_clone: Clone sync channel sender for this thread
-            // thread::spawn(): New thread for blocking work
-            // expensive_blocking_task(s): Compute hash (800ms blocking)
-            // sender.send(ret): Send result back via sync channel
-            let sender_clone = sender.clone();
-            thread::spawn(move || {
-                let ret = expensive_blocking_task(s);
-                sender_clone.send(ret).unwrap();
-            });
-            // Receive result and print
-            // receiver.recv(): Block and wait for thread to complete
-            // println!(): Print hash result
-            // Loop continues for next message
-            let result = receiver.recv().unwrap();
-            println!("result: {}", result);
-        }
-    })
-}
+    // runs until Ctrl-C
+    tokio::signal::ctrl_c().await?;
 
-fn expensive_blocking_task(s: String) -> String {
-    thread::sleep(Duration::from_millis(800));
-    blake3::hash(s.as_bytes()).to_string()
+    // 4, Graceful shutdown: stop intake, drop what's queued, give running hashes 2s to finish
+    // → ShutdownSummary { completed: 57, aborted: 32, timed_out: false }
+    let summary = pool.shutdown(Duration::from_secs(2)).await;
+    println!("{summary:?}");
+    Ok(())
 }
 
+// Worker pool (src/worker/pool.rs):
+// N threads share the job queue; each blocking_recv()s the next job, runs it, sends the result to its JobHandle
+// expensive_blocking_task (src/worker.rs): Compute hash (800ms blocking)
+
+// Output before WorkerPool (one result per 800ms; with 4 workers, 4 results arrive every 800ms):
 // sending task 1-32 (buffer fills)     ← Producer sends messages
 // result: eb5...                       ← First task completes (800ms)
 // sending task 33                      ← Producer unblocked, sends more
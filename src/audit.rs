@@ -0,0 +1,411 @@
+// audit: append-only record of every mutation (who, when, which request, what changed).
+// The newest entries (max_entries, 10 000 by default) are kept in memory for querying (GET /audit)
+// and, when opened with a path, every entry is appended as JSON lines to a file by a background
+// writer task; the file is the complete log:
+//
+//   {"seq":1,"at":"2025-...Z","actor":"alice","request_id":"6f1c...","action":"update",
+//    "entity":"user","entity_id":1,"diff":{"age":{"from":30,"to":31}}}
+//
+// Handlers never wait for disk I/O: record() puts the entry in a bounded channel and returns. If
+// the writer falls WRITER_BACKLOG entries behind (a stuck disk), further entries are only kept in
+// memory, with a warning, instead of piling up without bound.
+//
+// A line that doesn't parse (a crash in the middle of a write, a hand edit) is skipped with a
+// warning when the file is loaded, instead of keeping the service from starting.
+
+use std::{
+    collections::VecDeque,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::{
+    fs::OpenOptions,
+    io::AsyncWriteExt,
+    sync::mpsc::{self, error::TrySendError, Sender},
+};
+use tracing::warn;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+    Purge,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    pub request_id: Option<String>,
+    pub action: AuditAction,
+    pub entity: String,
+    pub entity_id: u64,
+    pub diff: Value,
+}
+
+// Who caused a mutation. Built from request headers in the web layer,
+// or AuditContext::system() for background work (seeding, purge task).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditContext {
+    pub actor: String,
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    pub fn system() -> Self {
+        Self {
+            actor: "system".to_string(),
+            request_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AuditQuery {
+    pub entity_id: Option<u64>,
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    pub limit: Option<usize>,
+}
+
+// Entries kept in memory unless max_entries says otherwise.
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+// Entries waiting for the file writer before record() stops handing it more.
+pub const WRITER_BACKLOG: usize = 4096;
+
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    entries: Arc<RwLock<VecDeque<AuditEntry>>>,
+    max_entries: usize,
+    writer: Option<Sender<AuditEntry>>,
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self {
+            entries: Arc::default(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            writer: None,
+        }
+    }
+}
+
+impl AuditLog {
+    // Nothing is persisted; useful for tests and throwaway demos.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    // How many of the newest entries stay in memory (at least 1); older ones are only in the file.
+    pub fn max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max.max(1);
+        let mut entries = self.entries.write().unwrap();
+        let excess = entries.len().saturating_sub(self.max_entries);
+        entries.drain(..excess);
+        drop(entries);
+        self
+    }
+
+    // Loads existing entries from `path` (JSON lines) and appends new ones to it.
+    // Must be called inside a Tokio runtime: the writer is a spawned task.
+    pub async fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let mut entries = VecDeque::from(read_entries(path).await?);
+        let excess = entries.len().saturating_sub(DEFAULT_MAX_ENTRIES);
+        entries.drain(..excess);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        // a truncated last line: the next entry starts on a line of its own
+        if !ends_with_newline(path).await? {
+            file.write_all(b"\n").await?;
+        }
+        let (tx, mut rx) = mpsc::channel::<AuditEntry>(WRITER_BACKLOG);
+        tokio::spawn(async move {
+            while let Some(entry) = rx.recv().await {
+                let mut line = match serde_json::to_vec(&entry) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        warn!("failed to serialize audit entry {}: {e}", entry.seq);
+                        continue;
+                    }
+                };
+                line.push(b'\n');
+                if let Err(e) = file.write_all(&line).await {
+                    warn!("failed to append audit entry {}: {e}", entry.seq);
+                }
+            }
+        });
+
+        Ok(Self {
+            entries: Arc::new(RwLock::new(entries)),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            writer: Some(tx),
+        })
+    }
+
+    // `before`/`after` are the entity states around the mutation (None for create/purge).
+    pub fn record<T: Serialize>(
+        &self,
+        ctx: &AuditContext,
+        action: AuditAction,
+        entity: &str,
+        entity_id: u64,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) {
+        let to_value = |v: Option<&T>| {
+            v.and_then(|v| serde_json::to_value(v).ok())
+                .unwrap_or(Value::Null)
+        };
+        let diff = json_diff(&to_value(before), &to_value(after));
+
+        let mut entries = self.entries.write().unwrap();
+        let entry = AuditEntry {
+            seq: entries.back().map_or(1, |e| e.seq + 1),
+            at: Utc::now(),
+            actor: ctx.actor.clone(),
+            request_id: ctx.request_id.clone(),
+            action,
+            entity: entity.to_string(),
+            entity_id,
+            diff,
+        };
+        if let Some(writer) = &self.writer {
+            match writer.try_send(entry.clone()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(entry)) => {
+                    warn!(
+                        seq = entry.seq,
+                        "audit writer backlog full: entry not written to the file"
+                    )
+                }
+                // the writer task is gone (runtime shutting down)
+                Err(TrySendError::Closed(_)) => {}
+            }
+        }
+        if entries.len() >= self.max_entries {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    // The entries in memory (the newest max_entries), oldest first (exports; GET /audit uses query).
+    pub fn entries(&self) -> Vec<AuditEntry> {
+        self.entries.read().unwrap().iter().cloned().collect()
+    }
+
+    // Newest first.
+    pub fn query(&self, q: &AuditQuery) -> Vec<AuditEntry> {
+        let entries = self.entries.read().unwrap();
+        entries
+            .iter()
+            .rev()
+            .filter(|e| q.entity_id.is_none_or(|id| e.entity_id == id))
+            .filter(|e| q.actor.as_ref().is_none_or(|a| &e.actor == a))
+            .filter(|e| q.action.is_none_or(|a| e.action == a))
+            .take(q.limit.unwrap_or(100))
+            .cloned()
+            .collect()
+    }
+}
+
+// The entries of an audit log file, oldest first, without opening it for writing: for tools that
+// read the log of a stopped service. A missing file has none; lines that don't parse are skipped.
+pub async fn read_entries(path: impl AsRef<Path>) -> std::io::Result<Vec<AuditEntry>> {
+    let path = path.as_ref();
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let entries = content
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .filter_map(|(i, line)| {
+            serde_json::from_str(line)
+                .inspect_err(|e| {
+                    warn!(path = %path.display(), line = i + 1, "skipping unreadable audit entry: {e}")
+                })
+                .ok()
+        })
+        .collect();
+    Ok(entries)
+}
+
+// True for an empty file too: there's no line to finish.
+async fn ends_with_newline(path: &Path) -> std::io::Result<bool> {
+    let content = tokio::fs::read(path).await?;
+    Ok(content.last().is_none_or(|&b| b == b'\n'))
+}
+
+// Field-level diff of two JSON objects: {"field": {"from": old, "to": new}} for every changed field.
+// Non-object values (e.g. Null for create) are treated as empty objects.
+pub fn json_diff(before: &Value, after: &Value) -> Value {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut diff = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        if diff.contains_key(key) {
+            continue;
+        }
+        let from = before.get(key).cloned().unwrap_or(Value::Null);
+        let to = after.get(key).cloned().unwrap_or(Value::Null);
+        if from != to {
+            diff.insert(key.clone(), serde_json::json!({ "from": from, "to": to }));
+        }
+    }
+    Value::Object(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn temp_log() -> PathBuf {
+        std::env::temp_dir().join(format!("audit-{}.jsonl", nanoid::nanoid!()))
+    }
+
+    fn record_age(log: &AuditLog, id: u64, from: u8, to: u8) {
+        log.record(
+            &AuditContext::system(),
+            AuditAction::Update,
+            "user",
+            id,
+            Some(&json!({ "age": from })),
+            Some(&json!({ "age": to })),
+        );
+    }
+
+    // The writer task appends in the background.
+    async fn wait_for_lines(path: &Path, lines: usize) -> Vec<AuditEntry> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let entries = read_entries(path).await.unwrap();
+                if entries.len() >= lines {
+                    return entries;
+                }
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("audit entries not written within 5s")
+    }
+
+    #[test]
+    fn create_diff_lists_every_field_from_null() {
+        let diff = json_diff(&Value::Null, &json!({ "name": "ann", "age": 30 }));
+        assert_eq!(
+            diff,
+            json!({
+                "name": { "from": null, "to": "ann" },
+                "age": { "from": null, "to": 30 },
+            })
+        );
+    }
+
+    #[test]
+    fn diff_covers_keys_on_one_side_only_and_skips_unchanged() {
+        let before = json!({ "name": "ann", "age": 30, "nick": "a" });
+        let after = json!({ "name": "ann", "age": 31, "email": "ann@example.com" });
+        assert_eq!(
+            json_diff(&before, &after),
+            json!({
+                "age": { "from": 30, "to": 31 },
+                "nick": { "from": "a", "to": null },
+                "email": { "from": null, "to": "ann@example.com" },
+            })
+        );
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_at_max_entries() {
+        let log = AuditLog::in_memory().max_entries(3);
+        for id in 1..=5 {
+            record_age(&log, id, 30, 31);
+        }
+        let entries = log.entries();
+        assert_eq!(entries.iter().map(|e| e.seq).collect::<Vec<_>>(), [3, 4, 5]);
+        assert_eq!(entries[0].entity_id, 3);
+    }
+
+    #[test]
+    fn max_entries_trims_an_already_full_log() {
+        let log = AuditLog::in_memory();
+        for id in 1..=5 {
+            record_age(&log, id, 30, 31);
+        }
+        let log = log.max_entries(2);
+        assert_eq!(
+            log.entries().iter().map(|e| e.seq).collect::<Vec<_>>(),
+            [4, 5]
+        );
+        record_age(&log, 6, 30, 31);
+        assert_eq!(
+            log.entries().iter().map(|e| e.seq).collect::<Vec<_>>(),
+            [5, 6]
+        );
+    }
+
+    #[tokio::test]
+    async fn unreadable_lines_are_skipped_on_load() {
+        let path = temp_log();
+        let log = AuditLog::open(&path).await.unwrap();
+        record_age(&log, 1, 30, 31);
+        record_age(&log, 2, 40, 41);
+        wait_for_lines(&path, 2).await;
+        let mut content = tokio::fs::read_to_string(&path).await.unwrap();
+        content.insert_str(0, "not json\n\n");
+        content.push_str("{\"seq\": 3, \"trunc\n");
+        tokio::fs::write(&path, content).await.unwrap();
+
+        let entries = read_entries(&path).await.unwrap();
+        assert_eq!(entries.iter().map(|e| e.seq).collect::<Vec<_>>(), [1, 2]);
+        let reopened = AuditLog::open(&path).await.unwrap();
+        assert_eq!(reopened.entries().len(), 2);
+        assert!(read_entries(temp_log()).await.unwrap().is_empty());
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn truncated_last_line_is_finished_before_the_next_append() {
+        let path = temp_log();
+        let log = AuditLog::open(&path).await.unwrap();
+        record_age(&log, 1, 30, 31);
+        wait_for_lines(&path, 1).await;
+        drop(log);
+        // a crash in the middle of a write: no trailing newline
+        let mut content = tokio::fs::read(&path).await.unwrap();
+        content.extend_from_slice(b"{\"seq\":2,\"at\"");
+        tokio::fs::write(&path, &content).await.unwrap();
+        assert!(!ends_with_newline(&path).await.unwrap());
+
+        let log = AuditLog::open(&path).await.unwrap();
+        record_age(&log, 3, 50, 51);
+        let entries = wait_for_lines(&path, 2).await;
+        assert_eq!(
+            entries.iter().map(|e| e.entity_id).collect::<Vec<_>>(),
+            [1, 3]
+        );
+        assert!(ends_with_newline(&path).await.unwrap());
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
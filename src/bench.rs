@@ -0,0 +1,266 @@
+// bench: a small HTTP load generator, to measure a change (the proxy's copy loop, a handler) without
+// installing wrk or hey. N workers send requests back to back for a fixed duration; the report has
+// throughput, status codes and latency percentiles:
+//
+//   let report = bench::run(&BenchConfig::new("http://127.0.0.1:8081/users").concurrency(50)).await?;
+//   println!("{report}");
+//
+//   requests   48210 in 10.0s, 4821.0/s, 0 errors
+//   received   13.2 MiB, 1.3 MiB/s
+//   statuses   200 × 48210
+//   latency    min 1.1ms, p50 9.8ms, p90 14.2ms, p99 22.7ms, max 61.0ms
+//
+// Going through the proxy: point the URL at the proxy's listen address (it relays bytes, so the
+// request reaches the upstream unchanged) and compare with the upstream's own address.
+//
+// Request template: "{n}" in the URL or body becomes the request's sequence number (0, 1, 2, ...),
+// so requests can differ: /users/{n}, {"name": "user-{n}"}.
+//
+// Latency is measured from send to the last byte of the body. A plain reqwest::Client, not
+// client::TracedClient: a span per request and retries would be part of what's measured.
+// Closed-loop: each worker waits for its response before sending the next request, so a slower
+// server gets fewer requests (the throughput drops instead of a queue building up).
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method,
+};
+
+use crate::{error::MyError, units::ByteCount};
+
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    pub url: String,
+    pub method: Method,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    // requests in flight at once
+    pub concurrency: usize,
+    pub duration: Duration,
+    // a request taking longer counts as an error
+    pub timeout: Duration,
+}
+
+impl BenchConfig {
+    // GET `url`, 10 workers for 10s, 30s timeout.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            method: Method::GET,
+            headers: Vec::new(),
+            body: None,
+            concurrency: 10,
+            duration: Duration::from_secs(10),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    pub fn concurrency(mut self, n: usize) -> Self {
+        self.concurrency = n.max(1);
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BenchReport {
+    pub elapsed: Duration,
+    pub statuses: BTreeMap<u16, u64>,
+    // connect errors, timeouts, broken responses
+    pub errors: u64,
+    // the first error seen, to tell "connection refused" from a slow server
+    pub first_error: Option<String>,
+    pub received: ByteCount,
+    // of the completed requests (errors excluded), sorted
+    latencies: Vec<Duration>,
+}
+
+impl BenchReport {
+    // Requests that got a response, whatever its status.
+    pub fn completed(&self) -> u64 {
+        self.statuses.values().sum()
+    }
+
+    pub fn per_second(&self) -> f64 {
+        self.completed() as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    // The latency `p` percent of requests stayed under (nearest rank); None before any response.
+    pub fn percentile(&self, p: f64) -> Option<Duration> {
+        let last = self.latencies.len().checked_sub(1)?;
+        let rank = ((p / 100.0) * self.latencies.len() as f64).ceil() as usize;
+        Some(self.latencies[rank.saturating_sub(1).min(last)])
+    }
+
+    fn merge(&mut self, other: Sample) {
+        for (status, count) in other.statuses {
+            *self.statuses.entry(status).or_default() += count;
+        }
+        self.errors += other.errors;
+        self.first_error = self.first_error.take().or(other.first_error);
+        self.received = self.received.saturating_add(other.received);
+        self.latencies.extend(other.latencies);
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.elapsed.as_secs_f64().max(f64::EPSILON);
+        writeln!(
+            f,
+            "requests   {} in {:.1?}, {:.1}/s, {} errors",
+            self.completed(),
+            self.elapsed,
+            self.per_second(),
+            self.errors
+        )?;
+        let rate = ByteCount((self.received.get() as f64 / secs) as u64);
+        writeln!(f, "received   {}, {rate}/s", self.received)?;
+        let statuses: Vec<String> = self
+            .statuses
+            .iter()
+            .map(|(status, count)| format!("{status} × {count}"))
+            .collect();
+        writeln!(f, "statuses   {}", statuses.join(", "))?;
+        if let (Some(min), Some(max)) = (self.latencies.first(), self.latencies.last()) {
+            let at = |p| self.percentile(p).unwrap_or_default();
+            writeln!(
+                f,
+                "latency    min {min:.1?}, p50 {:.1?}, p90 {:.1?}, p99 {:.1?}, max {max:.1?}",
+                at(50.0),
+                at(90.0),
+                at(99.0)
+            )?;
+        }
+        if let Some(error) = &self.first_error {
+            writeln!(f, "error      {error}")?;
+        }
+        Ok(())
+    }
+}
+
+// What one worker saw.
+#[derive(Default)]
+struct Sample {
+    statuses: BTreeMap<u16, u64>,
+    errors: u64,
+    first_error: Option<String>,
+    received: ByteCount,
+    latencies: Vec<Duration>,
+}
+
+// Fails only when the config can't be used (a bad header); request errors are counted in the report.
+pub async fn run(config: &BenchConfig) -> Result<BenchReport, MyError> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.headers {
+        let name = HeaderName::try_from(name.as_str())
+            .map_err(|e| MyError::Custom(format!("invalid header name {name:?}: {e}")))?;
+        let value = HeaderValue::try_from(value.as_str())
+            .map_err(|e| MyError::Custom(format!("invalid value for header {name}: {e}")))?;
+        headers.append(name, value);
+    }
+    let client = reqwest::Client::builder()
+        .timeout(config.timeout)
+        .pool_max_idle_per_host(config.concurrency)
+        .default_headers(headers)
+        .build()
+        .map_err(|e| MyError::Custom(format!("building the HTTP client: {e}")))?;
+
+    let config = Arc::new(config.clone());
+    let next = Arc::new(AtomicU64::new(0));
+    let started = Instant::now();
+    let deadline = started + config.duration;
+    let workers: Vec<_> = (0..config.concurrency.max(1))
+        .map(|_| {
+            tokio::spawn(worker(
+                client.clone(),
+                config.clone(),
+                next.clone(),
+                deadline,
+            ))
+        })
+        .collect();
+
+    let mut report = BenchReport::default();
+    for worker in workers {
+        let sample = worker
+            .await
+            .map_err(|e| MyError::Custom(format!("bench worker failed: {e}")))?;
+        report.merge(sample);
+    }
+    report.elapsed = started.elapsed();
+    report.latencies.sort_unstable();
+    Ok(report)
+}
+
+async fn worker(
+    client: reqwest::Client,
+    config: Arc<BenchConfig>,
+    next: Arc<AtomicU64>,
+    deadline: Instant,
+) -> Sample {
+    let mut sample = Sample::default();
+    while Instant::now() < deadline {
+        let n = next.fetch_add(1, Ordering::Relaxed).to_string();
+        let mut request = client.request(config.method.clone(), config.url.replace("{n}", &n));
+        if let Some(body) = &config.body {
+            request = request.body(body.replace("{n}", &n));
+        }
+        let sent = Instant::now();
+        // the body is part of the response: a server that's quick with headers but slow with
+        // the rest isn't fast
+        let outcome = match request.send().await {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                response.bytes().await.map(|body| (status, body.len()))
+            }
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok((status, len)) => {
+                sample.latencies.push(sent.elapsed());
+                *sample.statuses.entry(status).or_default() += 1;
+                sample.received += ByteCount(len as u64);
+            }
+            Err(e) => {
+                sample.errors += 1;
+                sample.first_error.get_or_insert_with(|| e.to_string());
+            }
+        }
+    }
+    sample
+}
@@ -0,0 +1,222 @@
+// cli: the clap definitions of src/main.rs and the dispatch to one module per subcommand.
+// Each subcommand module has an `Args` struct (its flags) and `run(...)`, returning miette::Result
+// so main can render the error: MyError converts with `?` (it's a Diagnostic), anything else
+// (config, I/O outside the library) with .into_diagnostic().
+
+mod bench;
+mod completions;
+mod config;
+mod convert;
+mod crypt;
+mod daemon;
+#[cfg(feature = "parquet")]
+mod export;
+mod generate;
+mod hash;
+#[cfg(feature = "sqlite-queue")]
+mod jobs;
+mod proxy;
+mod serve;
+
+use std::path::Path;
+
+use clap::{Parser, Subcommand};
+use ecosystem::telemetry::{rolling::FileLogConfig, LogFormat, LoggingConfig, TelemetryBuilder};
+use miette::{IntoDiagnostic, Result, WrapErr};
+use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "ecosystem",
+    version,
+    about = "Servers, a proxy and file tools built on the ecosystem crate"
+)]
+pub struct Cli {
+    /// Config file ([runtime], [logging], server settings)
+    #[arg(
+        short,
+        long,
+        global = true,
+        env = "ECOSYSTEM_CONFIG",
+        default_value = "server.toml"
+    )]
+    pub config: String,
+
+    /// Log level or filter directives, e.g. "debug" or "info,sqlx=warn"
+    #[arg(long, global = true)]
+    pub log_level: Option<String>,
+
+    /// Log format: full, pretty or json
+    #[arg(long, global = true)]
+    pub log_format: Option<LogFormat>,
+
+    /// Override a config value, winning over file and env vars: --set logging.level=debug (repeatable)
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE", value_parser = ecosystem::config::parse_override)]
+    pub overrides: Vec<(String, String)>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Serve the users API, HTML views, audit and jobs endpoints
+    Serve(serve::Args),
+    /// Relay TCP connections to one or more upstreams
+    Proxy(proxy::Args),
+    /// Checksum files or stdin (BLAKE3 or SHA-256), or verify a checksum file
+    Hash(hash::Args),
+    /// Encrypt stdin or a file (base64 token or raw bytes)
+    Encrypt(crypt::EncryptArgs),
+    /// Decrypt what `encrypt` made
+    Decrypt(crypt::DecryptArgs),
+    /// Generate a key for ECOSYSTEM_KEY or --key-file
+    Keygen(crypt::KeygenArgs),
+    /// Load-test a URL and report throughput and latency percentiles
+    Bench(bench::Args),
+    /// Convert between JSON, YAML, TOML, MessagePack and CBOR
+    Convert(convert::Args),
+    /// Submit, list and requeue jobs of the durable queue
+    #[cfg(feature = "sqlite-queue")]
+    Jobs(jobs::Args),
+    /// Export users and the audit log as Parquet files
+    #[cfg(feature = "parquet")]
+    Export(export::Args),
+    /// Generate fake data for demos and load tests
+    #[command(subcommand)]
+    Generate(generate::Command),
+    /// Work with config files
+    #[command(subcommand)]
+    Config(config::Command),
+    /// Stop a process started with --daemon
+    Stop(daemon::StopArgs),
+    /// Print shell completions
+    Completions(completions::CompletionsArgs),
+    /// Generate man pages
+    #[command(hide = true)]
+    Mangen(completions::MangenArgs),
+}
+
+impl Command {
+    // `config check` reads the files itself: a broken one mustn't stop it before it starts.
+    // Conversions, stop, completions and man pages don't depend on any config.
+    pub fn uses_config(&self) -> bool {
+        !matches!(
+            self,
+            Command::Config(_)
+                | Command::Convert(_)
+                | Command::Stop(_)
+                | Command::Completions(_)
+                | Command::Mangen(_)
+        )
+    }
+
+    // The --daemon flags of the long-running commands, when given.
+    fn daemon(&self) -> Option<&daemon::DaemonArgs> {
+        let args = match self {
+            Command::Serve(args) => &args.daemon,
+            Command::Proxy(args) => &args.daemon,
+            _ => return None,
+        };
+        args.daemon.then_some(args)
+    }
+}
+
+impl Cli {
+    // With --daemon: forks into the background (the parent exits here). Must run before the Tokio
+    // runtime is built; keep the PidFile until the end of main.
+    pub fn detach(&self) -> Result<Option<daemon::PidFile>> {
+        self.command.daemon().map(daemon::detach).transpose()
+    }
+
+    pub async fn run(self) -> Result<()> {
+        // the flags win over the file and LOG_* env vars
+        let mut logging = match self.command.uses_config() {
+            true => LoggingConfig::load(&self.config).into_diagnostic()?,
+            false => LoggingConfig::default(),
+        };
+        if let Some(level) = self.log_level {
+            logging.level = level;
+        }
+        if let Some(format) = self.log_format {
+            logging.format = format;
+        }
+        // a daemon's stdio is /dev/null: the log file is the only place its logs can go
+        let detached = self.command.daemon().is_some();
+        let mut telemetry = TelemetryBuilder::new("ecosystem")
+            .console(!detached)
+            .console_stderr(true);
+        if let Some(file) = logging
+            .file
+            .clone()
+            .or(detached.then(FileLogConfig::default))
+        {
+            telemetry = telemetry.file(file);
+        }
+        let telemetry = telemetry.logging(logging).init().into_diagnostic()?;
+
+        let command = async {
+            match self.command {
+                Command::Serve(args) => {
+                    serve::run(&self.config, args, telemetry.log_level().clone()).await
+                }
+                Command::Proxy(args) => {
+                    proxy::run(&self.config, args, telemetry.log_level().clone()).await
+                }
+                Command::Hash(args) => hash::run(args).await,
+                Command::Encrypt(args) => crypt::encrypt(args).await,
+                Command::Decrypt(args) => crypt::decrypt(args).await,
+                Command::Keygen(args) => crypt::keygen(args).await,
+                Command::Bench(args) => bench::run(args).await,
+                Command::Convert(args) => convert::run(args).await,
+                #[cfg(feature = "sqlite-queue")]
+                Command::Jobs(args) => jobs::run(args).await,
+                #[cfg(feature = "parquet")]
+                Command::Export(args) => export::run(&self.config, args).await,
+                Command::Generate(command) => generate::run(&self.config, command).await,
+                Command::Config(command) => config::run(&self.config, command).await,
+                Command::Stop(args) => daemon::stop(args).await,
+                Command::Completions(args) => completions::completions(args),
+                Command::Mangen(args) => completions::mangen(args),
+            }
+        };
+        if !detached {
+            return command.await;
+        }
+        // nobody sees the miette report on /dev/null
+        let result = daemon::until_terminated(command).await;
+        if let Err(e) = &result {
+            tracing::error!("{e:?}");
+        }
+        result
+    }
+}
+
+// A file's content, or stdin's for "-".
+async fn read_input(path: &str) -> Result<Vec<u8>> {
+    if path == "-" {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf).await.into_diagnostic()?;
+        Ok(buf)
+    } else {
+        tokio::fs::read(path)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("reading {path}"))
+    }
+}
+
+// To a file (created or truncated), or to stdout when there is none.
+async fn write_output(path: Option<&Path>, bytes: &[u8]) -> Result<()> {
+    match path {
+        Some(path) => tokio::fs::write(path, bytes)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("writing {}", path.display())),
+        None => {
+            let mut stdout = io::stdout();
+            stdout.write_all(bytes).await.into_diagnostic()?;
+            stdout.flush().await.into_diagnostic()
+        }
+    }
+}
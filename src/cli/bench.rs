@@ -0,0 +1,80 @@
+// ecosystem bench: crate::bench on the command line, load against a URL for a while, then the report
+// (throughput, statuses, latency percentiles) on stdout.
+//
+//   ecosystem bench http://127.0.0.1:8080/users --concurrency 50 -d 10s
+//   ecosystem bench http://127.0.0.1:8081/users --concurrency 50 -d 10s        # the same, through `ecosystem proxy`
+//   ecosystem bench -X POST -H 'content-type: application/json' \
+//       --body '{"name": "user-{n}", "age": 30}' http://127.0.0.1:8080/users
+
+use std::path::PathBuf;
+
+use ecosystem::{
+    bench::{self, BenchConfig},
+    units::Millis,
+};
+use miette::{miette, IntoDiagnostic, Result, WrapErr};
+use reqwest::Method;
+use tracing::info;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// URL to load; "{n}" is replaced by the request number
+    url: String,
+
+    /// Requests in flight at once
+    #[arg(long, default_value_t = 10)]
+    concurrency: usize,
+
+    /// How long to run, e.g. 10s, 2m
+    #[arg(short, long, default_value = "10s")]
+    duration: Millis,
+
+    /// Per-request timeout; slower requests count as errors
+    #[arg(long, default_value = "30s")]
+    timeout: Millis,
+
+    /// HTTP method
+    #[arg(short = 'X', long, default_value = "GET")]
+    method: Method,
+
+    /// Request header "Name: value", repeatable
+    #[arg(short = 'H', long = "header")]
+    headers: Vec<String>,
+
+    /// Request body; "{n}" is replaced by the request number
+    #[arg(long, conflicts_with = "body_file")]
+    body: Option<String>,
+
+    /// Read the request body from this file
+    #[arg(long)]
+    body_file: Option<PathBuf>,
+}
+
+pub async fn run(args: Args) -> Result<()> {
+    let mut config = BenchConfig::new(&args.url)
+        .method(args.method)
+        .concurrency(args.concurrency)
+        .duration(args.duration.as_duration())
+        .timeout(args.timeout.as_duration());
+    for header in &args.headers {
+        let (name, value) = header
+            .split_once(':')
+            .ok_or_else(|| miette!("invalid header {header:?}, expected \"Name: value\""))?;
+        config = config.header(name.trim(), value.trim());
+    }
+    if let Some(body) = args.body {
+        config = config.body(body);
+    }
+    if let Some(path) = &args.body_file {
+        let body = tokio::fs::read_to_string(path)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("reading {}", path.display()))?;
+        config = config.body(body);
+    }
+
+    info!(url = %config.url, concurrency = config.concurrency, duration = %args.duration, "benchmarking");
+    let report = bench::run(&config).await?;
+    print!("{report}");
+    Ok(())
+}
@@ -0,0 +1,51 @@
+// ecosystem completions / mangen: shell completions and man pages generated from the clap definitions
+// in cli.rs, so they never drift from the actual flags. For packaging:
+//
+//   ecosystem completions bash > /usr/share/bash-completion/completions/ecosystem
+//   ecosystem completions zsh > /usr/share/zsh/site-functions/_ecosystem
+//   ecosystem completions fish > ~/.config/fish/completions/ecosystem.fish
+//   ecosystem mangen --out-dir target/man        # ecosystem.1, ecosystem-serve.1, ... (hidden command)
+//   ecosystem mangen | man -l -                  # preview the top-level page
+
+use std::path::PathBuf;
+
+use clap::CommandFactory;
+use clap_complete::Shell;
+use miette::{IntoDiagnostic, Result, WrapErr};
+
+use super::Cli;
+
+#[derive(Debug, clap::Args)]
+pub struct CompletionsArgs {
+    /// bash, zsh, fish, elvish or powershell
+    shell: Shell,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct MangenArgs {
+    /// Write a page per (sub)command into this directory, instead of the top-level page to stdout
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+}
+
+pub fn completions(args: CompletionsArgs) -> Result<()> {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(args.shell, &mut command, name, &mut std::io::stdout());
+    Ok(())
+}
+
+pub fn mangen(args: MangenArgs) -> Result<()> {
+    let command = Cli::command();
+    match args.out_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir).into_diagnostic()?;
+            clap_mangen::generate_to(command, &dir)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("writing man pages to {}", dir.display()))
+        }
+        None => clap_mangen::Man::new(command)
+            .render(&mut std::io::stdout())
+            .into_diagnostic(),
+    }
+}
@@ -0,0 +1,65 @@
+// ecosystem config check: config::check on the command line, for CI before a config change ships.
+// Prints every issue of every file (the line, the value underlined, a suggested fix) and exits
+// non-zero if any file has an error; warnings (unknown keys, missing TLS files) are printed only.
+//
+//   ecosystem config check                                  # the --config file (server.toml)
+//   ecosystem config check server.toml proxy.toml
+//   ecosystem config check --kind worker deploy/jobs.toml   # kind is guessed from the name otherwise
+
+use std::path::PathBuf;
+
+use ecosystem::config::check::{self, ConfigKind};
+use miette::{miette, IntoDiagnostic, NamedSource, Report, Result, WrapErr};
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Validate config files, reporting every problem
+    Check(CheckArgs),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct CheckArgs {
+    /// Files to check; the --config file when none are given
+    files: Vec<PathBuf>,
+
+    /// server, proxy or worker; by default from each file name (proxy*.toml → proxy, ...)
+    #[arg(long)]
+    kind: Option<ConfigKind>,
+}
+
+pub async fn run(config: &str, command: Command) -> Result<()> {
+    let Command::Check(args) = command;
+    let files = if args.files.is_empty() {
+        vec![PathBuf::from(config)]
+    } else {
+        args.files
+    };
+
+    let mut failed = Vec::new();
+    for file in &files {
+        let source = tokio::fs::read_to_string(file)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("reading {}", file.display()))?;
+        let kind = args.kind.unwrap_or_else(|| ConfigKind::from_path(file));
+        let issues = check::check(kind, &source);
+        let errors = issues.iter().filter(|issue| issue.is_error()).count();
+        let name = file.display().to_string();
+        for issue in issues {
+            let report =
+                Report::new(issue).with_source_code(NamedSource::new(&name, source.clone()));
+            eprintln!("{report:?}");
+        }
+        if errors > 0 {
+            failed.push(format!("{name} ({errors})"));
+        } else {
+            eprintln!("{name}: ok ({kind})");
+        }
+    }
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(miette!("errors in {}", failed.join(", ")))
+    }
+}
@@ -0,0 +1,79 @@
+// ecosystem convert: a document re-encoded in another format with formats::transcode, for moving
+// configs and fixtures between JSON, YAML, TOML, MessagePack and CBOR (with the "cbor" feature).
+//
+//   ecosystem convert --from json --to yaml < users.json > users.yaml
+//   ecosystem convert server.toml --to json --compact           # --from taken from the extension
+//   ecosystem convert fixtures.yaml -o fixtures.mpk              # --to taken from the extension
+//   ecosystem convert --from msgpack --to json dump.bin
+//
+// --from / --to default to the input / output file extension; stdin and stdout have none, so the
+// flag is needed there. Text output is pretty (indented) unless --compact; binary output has no layout.
+//
+// Map order is kept. What the target can't express is an error, not silently dropped: null in TOML,
+// a list at the top of a TOML document, non-string keys in JSON.
+
+use std::path::PathBuf;
+
+use ecosystem::formats::{self, Format};
+use miette::{miette, IntoDiagnostic, Result, WrapErr};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// File to convert; "-" reads stdin
+    #[arg(default_value = "-")]
+    input: String,
+
+    /// Where to write the result; stdout when not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Input format: json, yaml, toml, msgpack or cbor; default: the input's extension
+    #[arg(long)]
+    from: Option<Format>,
+
+    /// Output format; default: the output's extension
+    #[arg(long)]
+    to: Option<Format>,
+
+    /// Indent text output (the default)
+    #[arg(long, overrides_with = "compact")]
+    pretty: bool,
+
+    /// As little whitespace as the format allows (JSON on one line)
+    #[arg(long, overrides_with = "pretty")]
+    compact: bool,
+}
+
+pub async fn run(args: Args) -> Result<()> {
+    let from = args
+        .from
+        .or_else(|| Format::from_path(&args.input))
+        .ok_or_else(|| {
+            miette!(
+                help = "pass --from json|yaml|toml|msgpack|cbor",
+                "can't tell the input format of {}",
+                args.input
+            )
+        })?;
+    let to = args
+        .to
+        .or_else(|| args.output.as_ref().and_then(Format::from_path))
+        .ok_or_else(|| {
+            miette!(
+                help = "pass --to json|yaml|toml|msgpack|cbor",
+                "can't tell the output format"
+            )
+        })?;
+
+    let input = super::read_input(&args.input).await?;
+    // --pretty and --compact override each other: the last one given wins
+    let pretty = args.pretty || !args.compact;
+    let mut output = formats::transcode(from, to, &input, pretty)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("converting {from:?} to {to:?}"))?;
+    // a terminal or a pipe to the next tool expects text to end with a newline
+    if !to.is_binary() && !output.ends_with(b"\n") {
+        output.push(b'\n');
+    }
+    super::write_output(args.output.as_deref(), &output).await
+}
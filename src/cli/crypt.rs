@@ -0,0 +1,138 @@
+// ecosystem keygen / encrypt / decrypt: crate::crypto on the command line, for values like
+// passwords and connection strings that shouldn't sit in a config file in clear text, and for whole files.
+//
+//   ecosystem keygen --output ~/.config/ecosystem/key   # or: export ECOSYSTEM_KEY=$(ecosystem keygen)
+//   echo -n 'postgres://app:hunter2@db' | ecosystem encrypt          # → token for a config value
+//   echo "$TOKEN" | ecosystem decrypt                                 # postgres://app:hunter2@db
+//   ecosystem encrypt --binary backup.tar -o backup.tar.sealed        # whole file, raw bytes
+//   ecosystem decrypt --binary backup.tar.sealed -o backup.tar
+//
+// Key, first found: --key-file (or ECOSYSTEM_KEY_FILE), then ECOSYSTEM_KEY. decrypt needs the same
+// key and --cipher as encrypt, and --binary if encrypt had it.
+//
+// Output: armored by default, the sealed bytes as one line of URL-safe base64 (pastes into TOML,
+// env vars, JSON); --binary writes nonce | ciphertext | tag as is, a third smaller, for files.
+// Decrypted output is written as is, so it may be binary: redirect it or use -o.
+
+use std::path::{Path, PathBuf};
+
+use ecosystem::{
+    crypto::{self, Key},
+    error::MyError,
+    modes::Cipher,
+};
+use miette::{IntoDiagnostic, Result, WrapErr};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+
+#[derive(Debug, clap::Args)]
+pub struct KeyArgs {
+    /// File holding the base64 key; ECOSYSTEM_KEY is used when not given
+    #[arg(long, env = "ECOSYSTEM_KEY_FILE")]
+    key_file: Option<PathBuf>,
+
+    /// chacha20-poly1305 or aes-256-gcm; must match between encrypt and decrypt
+    #[arg(long, default_value_t)]
+    pub(super) cipher: Cipher,
+}
+
+impl KeyArgs {
+    pub(super) fn key(&self) -> Result<Key> {
+        let key = match &self.key_file {
+            Some(path) => Key::from_file(path),
+            None => Key::from_env(),
+        };
+        Ok(key.map_err(MyError::from)?)
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct EncryptArgs {
+    /// File to encrypt; "-" reads stdin
+    #[arg(default_value = "-")]
+    input: String,
+
+    /// Where to write the result; stdout when not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Write raw bytes instead of base64 text
+    #[arg(long)]
+    binary: bool,
+
+    #[command(flatten)]
+    key: KeyArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct DecryptArgs {
+    /// File holding the encrypted data; "-" reads stdin
+    #[arg(default_value = "-")]
+    input: String,
+
+    /// Where to write the plaintext; stdout when not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// The input is raw bytes (encrypted with --binary), not base64 text
+    #[arg(long)]
+    binary: bool,
+
+    #[command(flatten)]
+    key: KeyArgs,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct KeygenArgs {
+    /// Write the key to this file (owner read/write only) instead of stdout
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub async fn keygen(args: KeygenArgs) -> Result<()> {
+    let key = Key::generate();
+    let encoded = format!("{}\n", key.to_base64().expose());
+    match &args.output {
+        Some(path) => write_private(path, encoded.as_bytes()).await,
+        None => super::write_output(None, encoded.as_bytes()).await,
+    }
+}
+
+pub async fn encrypt(args: EncryptArgs) -> Result<()> {
+    let key = args.key.key()?;
+    let plaintext = super::read_input(&args.input).await?;
+    let sealed = if args.binary {
+        crypto::seal(args.key.cipher, &key, &plaintext)
+    } else {
+        crypto::seal_to_string(args.key.cipher, &key, &plaintext)
+            .map(|token| format!("{token}\n").into_bytes())
+    }
+    .map_err(MyError::from)?;
+    super::write_output(args.output.as_deref(), &sealed).await
+}
+
+pub async fn decrypt(args: DecryptArgs) -> Result<()> {
+    let key = args.key.key()?;
+    let sealed = super::read_input(&args.input).await?;
+    let plaintext = if args.binary {
+        crypto::open(args.key.cipher, &key, &sealed)
+    } else {
+        crypto::open_base64(args.key.cipher, &key, &String::from_utf8_lossy(&sealed))
+    }
+    .map_err(MyError::from)?;
+    super::write_output(args.output.as_deref(), &plaintext).await
+}
+
+// A new file only its owner can read (0600 on Unix), so the key isn't world-readable even briefly.
+// An existing file is left alone: overwriting a key makes everything sealed with it unreadable.
+async fn write_private(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options
+        .open(path)
+        .await
+        .into_diagnostic()
+        .wrap_err_with(|| format!("creating {}", path.display()))?;
+    file.write_all(bytes).await.into_diagnostic()
+}
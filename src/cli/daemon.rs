@@ -0,0 +1,236 @@
+// ecosystem serve / proxy --daemon, and ecosystem stop: running in the background on machines without
+// systemd (or another supervisor), with a PID file to find the process again.
+//
+//   ecosystem proxy --config proxy.toml --daemon --pid-file /run/ecosystem-proxy.pid
+//   ecosystem stop --pid-file /run/ecosystem-proxy.pid        # SIGTERM, waits until it's gone
+//
+// --daemon (Unix only): fork, the parent prints the child's pid and exits (the shell gets its prompt
+// back); the child starts a new session (setsid: no controlling terminal, not hit by the terminal's
+// SIGHUP), writes its own pid to --pid-file, and points stdin / stdout / stderr at /dev/null. Logs go to the
+// [logging.file] sink only (telemetry::rolling; its defaults when the section is missing), nothing
+// to the console. The working directory is kept, so relative config and storage paths still work.
+//
+// The fork happens in main before the Tokio runtime exists: a forked child only keeps the forking
+// thread, so a runtime built before would have lost its worker threads.
+//
+// Key flow:
+// main() → Cli::detach()
+//   ├→ --pid-file names a running process → error, nothing started
+//   ├→ fork() ── parent: print the child's pid, exit 0
+//   └→ child: setsid, write own pid to --pid-file, stdio → /dev/null → runtime → Cli::run (file logging only)
+//        └→ SIGTERM / SIGINT → the command is dropped, PidFile removes --pid-file, exit 0
+//
+// stop: read the pid, SIGTERM, poll until the process is gone (--timeout), remove a PID file it
+// left behind. A PID file whose process no longer exists is stale: removed, and not an error.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use ecosystem::units::Millis;
+use miette::{miette, IntoDiagnostic, Result, WrapErr};
+
+#[derive(Debug, clap::Args)]
+pub struct DaemonArgs {
+    /// Run in the background, logging to the [logging.file] sink only (Unix)
+    #[arg(long)]
+    pub daemon: bool,
+
+    /// Where --daemon writes the process id, for `ecosystem stop`
+    #[arg(long, default_value = "ecosystem.pid")]
+    pub pid_file: PathBuf,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct StopArgs {
+    /// The PID file the process was started with
+    #[arg(long, default_value = "ecosystem.pid")]
+    pid_file: PathBuf,
+
+    /// How long to wait for the process to exit
+    #[arg(long, default_value = "10s")]
+    timeout: Millis,
+}
+
+// Removes the PID file when the daemon exits normally (dropped at the end of main).
+#[derive(Debug)]
+pub struct PidFile(PathBuf);
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+// In the parent: never returns. In the child: the PidFile to keep until exit.
+#[cfg(unix)]
+pub fn detach(args: &DaemonArgs) -> Result<PidFile> {
+    use std::os::fd::AsRawFd;
+
+    if let Some(pid) = read_pid(&args.pid_file)? {
+        if is_running(pid) {
+            return Err(miette!(
+                help = format!("ecosystem stop --pid-file {}", args.pid_file.display()),
+                "already running as pid {pid} ({})",
+                args.pid_file.display()
+            ));
+        }
+    }
+    // the parent fails here, before forking, if the PID file can't be written
+    std::fs::write(&args.pid_file, "")
+        .into_diagnostic()
+        .wrap_err_with(|| format!("writing {}", args.pid_file.display()))?;
+
+    // SAFETY: no other threads exist yet (main calls this before building the runtime), so the
+    // child doesn't inherit locks held by threads that are gone.
+    match unsafe { libc::fork() } {
+        -1 => Err(std::io::Error::last_os_error())
+            .into_diagnostic()
+            .wrap_err("fork"),
+        0 => {
+            // SAFETY: plain syscalls on our own process and fds.
+            unsafe {
+                if libc::setsid() == -1 {
+                    return Err(std::io::Error::last_os_error())
+                        .into_diagnostic()
+                        .wrap_err("setsid");
+                }
+            }
+            // written by the process it names, which also removes it at exit; before stdio goes
+            // to /dev/null, so a failure still reaches the terminal
+            std::fs::write(&args.pid_file, format!("{}\n", std::process::id()))
+                .into_diagnostic()
+                .wrap_err_with(|| format!("writing {}", args.pid_file.display()))?;
+            let null = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/null")
+                .into_diagnostic()?;
+            for fd in [libc::STDIN_FILENO, libc::STDOUT_FILENO, libc::STDERR_FILENO] {
+                // SAFETY: `null` is open for the whole call; dup2 replaces fd atomically.
+                unsafe { libc::dup2(null.as_raw_fd(), fd) };
+            }
+            Ok(PidFile(args.pid_file.clone()))
+        }
+        child => {
+            println!("started, pid {child} ({})", args.pid_file.display());
+            std::process::exit(0);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn detach(_args: &DaemonArgs) -> Result<PidFile> {
+    Err(miette!(
+        help = "run it as a service of the platform's service manager instead",
+        "--daemon is only supported on Unix"
+    ))
+}
+
+// Runs `command` until it ends or the process gets SIGTERM / SIGINT (`ecosystem stop`, kill), so
+// the daemon exits through main and its PID file is removed.
+pub async fn until_terminated(
+    command: impl std::future::Future<Output = Result<()>>,
+) -> Result<()> {
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut term) => {
+                term.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        result = command => result,
+        _ = terminate => {
+            tracing::info!("SIGTERM, shutting down");
+            Ok(())
+        }
+        _ = tokio::signal::ctrl_c() => {
+            tracing::info!("SIGINT, shutting down");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(unix)]
+pub async fn stop(args: StopArgs) -> Result<()> {
+    let Some(pid) = read_pid(&args.pid_file)? else {
+        return Err(miette!(
+            "no pid in {}: not running, or started without --daemon",
+            args.pid_file.display()
+        ));
+    };
+    if !is_running(pid) {
+        let _ = std::fs::remove_file(&args.pid_file);
+        eprintln!(
+            "pid {pid} is not running; removed {}",
+            args.pid_file.display()
+        );
+        return Ok(());
+    }
+
+    // SAFETY: kill has no memory-safety preconditions.
+    if unsafe { libc::kill(pid, libc::SIGTERM) } == -1 {
+        return Err(std::io::Error::last_os_error())
+            .into_diagnostic()
+            .wrap_err_with(|| format!("sending SIGTERM to pid {pid}"));
+    }
+    let deadline = tokio::time::Instant::now() + args.timeout.as_duration();
+    while is_running(pid) {
+        if tokio::time::Instant::now() >= deadline {
+            return Err(miette!(
+                help = format!("kill -9 {pid} forces it, without a clean shutdown"),
+                "pid {pid} still running {} after SIGTERM",
+                args.timeout
+            ));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    // a daemon that was killed hard can't have removed it itself
+    let _ = std::fs::remove_file(&args.pid_file);
+    eprintln!("stopped pid {pid}");
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn stop(_args: StopArgs) -> Result<()> {
+    Err(miette!("stop is only supported on Unix"))
+}
+
+// None when the file is missing or empty (a parent that failed between creating and filling it).
+// Only positive pids: kill(0, ..) signals our process group and kill(-1, ..) every process we may.
+#[cfg(unix)]
+fn read_pid(path: &Path) -> Result<Option<i32>> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e)
+                .into_diagnostic()
+                .wrap_err_with(|| format!("reading {}", path.display()))
+        }
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.parse::<i32>() {
+        Ok(pid) if pid > 0 => Ok(Some(pid)),
+        _ => Err(miette!("{} doesn't hold a pid: {text:?}", path.display())),
+    }
+}
+
+// Signal 0 checks that the process exists without sending anything; EPERM means it exists but
+// belongs to someone else.
+#[cfg(unix)]
+fn is_running(pid: i32) -> bool {
+    // SAFETY: kill has no memory-safety preconditions.
+    (unsafe { libc::kill(pid, 0) } == 0)
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
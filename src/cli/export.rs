@@ -0,0 +1,66 @@
+// ecosystem export (--features parquet): the users and the audit log of the service as Parquet files
+// (crate::export), for the data warehouse's loader.
+//
+//   ecosystem export                          # into [app.export] dir, "exports" by default
+//   ecosystem export --dir /data/landing      # e.g. a directory the loader watches
+//   ecosystem -c server.toml export && aws s3 sync exports/ s3://warehouse/ecosystem/
+//
+// Reads what the service leaves on disk: the [app.storage] file and the audit_log. Users kept in
+// memory can't be exported from outside; the service's own [app.export] schedule (web::app) can.
+// Like generate --seed-store, it sees the storage file as of the service's last flush.
+
+use std::path::PathBuf;
+
+use chrono::Utc;
+use ecosystem::{
+    audit, export,
+    web::app::{self, AppConfig, StorageConfig},
+};
+use miette::{miette, IntoDiagnostic, Result, WrapErr};
+use tracing::warn;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Directory for the Parquet files; default: [app.export] dir, or "exports"
+    #[arg(long)]
+    dir: Option<PathBuf>,
+}
+
+pub async fn run(config: &str, args: Args) -> Result<()> {
+    let app = AppConfig::load(config).into_diagnostic()?;
+    let StorageConfig::File { path, .. } = &app.storage else {
+        return Err(miette!(
+            help = "set [app.storage] kind = \"file\", or [app.export] schedule in the service",
+            "users are kept in memory ({}): nothing to export from outside the service",
+            app.storage
+        ));
+    };
+    let users = app::read_snapshot(path).await?.users;
+    let entries = match &app.audit_log {
+        Some(log) => audit::read_entries(log)
+            .await
+            .into_diagnostic()
+            .wrap_err_with(|| format!("reading {}", log.display()))?,
+        None => {
+            warn!("no [app] audit_log: the audit export is empty");
+            Vec::new()
+        }
+    };
+    let dir = args
+        .dir
+        .or_else(|| app.export.as_ref().map(|e| e.dir.clone()))
+        .unwrap_or_else(|| PathBuf::from("exports"));
+
+    let summary =
+        tokio::task::spawn_blocking(move || export::export_to(&dir, &users, &entries, Utc::now()))
+            .await
+            .into_diagnostic()?
+            .into_diagnostic()?;
+    eprintln!("{}: {} users", summary.users_file.display(), summary.users);
+    eprintln!(
+        "{}: {} audit entries",
+        summary.audit_file.display(),
+        summary.audit_entries
+    );
+    Ok(())
+}
@@ -0,0 +1,144 @@
+// ecosystem generate users: crate::fake users for demos and load tests of the web service, written
+// as JSON, NDJSON or CSV, or put straight into the service's storage.
+//
+//   ecosystem generate users -n 1000 --format ndjson -o users.ndjson
+//   ecosystem generate users -n 50 --format csv --seed 42          # the same 50 users every time
+//   ecosystem generate users -n 100 --encrypt                      # email / phone sealed with ECOSYSTEM_KEY
+//   ecosystem generate users -n 10000 --seed-store                 # into the [app.storage] file
+//
+// Without --seed, one is picked from the clock and logged, so a run can still be repeated.
+//
+// --seed-store: the service's users live in memory or in the file of [app.storage] kind = "file"
+// (web::app); there is no database to insert into. The users are appended to that file's snapshot,
+// with ids after the ones already there. Stop the service first: it would overwrite the file with
+// its own snapshot on the next flush.
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ecosystem::{
+    fake::{self, FakeUser, FakeUsers},
+    modes::RecordFormat,
+    web::app::{self, AppConfig, StorageConfig},
+};
+use miette::{miette, IntoDiagnostic, Result};
+use tracing::info;
+
+use super::crypt::KeyArgs;
+
+#[derive(Debug, clap::Subcommand)]
+pub enum Command {
+    /// Users with names, ages, skills, homepages and (optionally sealed) emails and phones
+    Users(UsersArgs),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct UsersArgs {
+    /// How many users
+    #[arg(short = 'n', long, default_value_t = 100)]
+    count: usize,
+
+    /// json, ndjson or csv
+    #[arg(long, default_value_t)]
+    format: RecordFormat,
+
+    /// Where to write them; stdout when not given
+    #[arg(short, long, conflicts_with = "seed_store")]
+    output: Option<PathBuf>,
+
+    /// Seed of the generator; the same seed gives the same users
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Seal email and phone with the key (--key-file or ECOSYSTEM_KEY)
+    #[arg(long)]
+    encrypt: bool,
+
+    /// Append the users to the [app.storage] file of the config instead of printing them
+    #[arg(long)]
+    seed_store: bool,
+
+    #[command(flatten)]
+    key: KeyArgs,
+}
+
+pub async fn run(config: &str, command: Command) -> Result<()> {
+    let Command::Users(args) = command;
+    let seed = args.seed.unwrap_or_else(clock_seed);
+    info!(seed, count = args.count, "generating users");
+    let mut generator = FakeUsers::new(seed);
+    if args.encrypt {
+        generator = generator.seal_with(args.key.cipher, args.key.key()?);
+    }
+
+    if args.seed_store {
+        return seed_store(config, generator, args.count).await;
+    }
+    let users: Vec<FakeUser> = generator.take(args.count).collect::<Result<_, _>>()?;
+    let bytes = match args.format {
+        RecordFormat::Json => {
+            let mut bytes = serde_json::to_vec_pretty(&users).into_diagnostic()?;
+            bytes.push(b'\n');
+            bytes
+        }
+        RecordFormat::Ndjson => {
+            let mut bytes = Vec::new();
+            for user in &users {
+                serde_json::to_writer(&mut bytes, user).into_diagnostic()?;
+                bytes.push(b'\n');
+            }
+            bytes
+        }
+        RecordFormat::Csv => {
+            let mut text = format!("{}\n", fake::CSV_HEADER);
+            for user in &users {
+                text.push_str(&user.csv_record());
+                text.push('\n');
+            }
+            text.into_bytes()
+        }
+    };
+    super::write_output(args.output.as_deref(), &bytes).await
+}
+
+async fn seed_store(config: &str, generator: FakeUsers, count: usize) -> Result<()> {
+    let app = AppConfig::load(config).into_diagnostic()?;
+    let StorageConfig::File { path, .. } = &app.storage else {
+        return Err(miette!(
+            help = "set [app.storage] kind = \"file\" in the config, or use --output",
+            "users are kept in memory ({}): nothing to seed",
+            app.storage
+        ));
+    };
+
+    let mut snapshot = app::read_snapshot(path).await?;
+    let first_id = snapshot
+        .users
+        .iter()
+        .map(|u| u.id)
+        .max()
+        .unwrap_or(0)
+        .max(snapshot.next_id)
+        + 1;
+    for user in generator.first_id(first_id).take(count) {
+        snapshot.users.push(user?.to_user());
+    }
+    snapshot.next_id = first_id + count as u64 - 1;
+    app::save_snapshot(path, &snapshot).await?;
+    eprintln!(
+        "{}: {count} users added, {} in total",
+        path.display(),
+        snapshot.users.len()
+    );
+    Ok(())
+}
+
+// Nanoseconds since the epoch: different on every run, and logged so the run can be repeated.
+fn clock_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,137 @@
+// ecosystem hash: checksums of files or stdin with crate::hash, one "<hex>  <path>" line each, the
+// b3sum / sha256sum layout, so the output is a checksum file either tool can check (and vice versa).
+//
+//   ecosystem hash Cargo.toml Cargo.lock
+//   ecosystem hash --algo sha256 dist/* -o SHA256SUMS
+//   curl -s https://example.com | ecosystem hash -
+//   ecosystem hash --algo sha256 --verify SHA256SUMS        # "dist/app.tar: OK" per line
+//
+// Files are hashed --jobs at a time; each big file also spreads over every core with BLAKE3
+// (hash::update above PARALLEL_THRESHOLD). Output keeps the order of the arguments.
+// --verify fails (exit code 1) when any file is missing or doesn't match.
+
+use std::{path::PathBuf, sync::Arc};
+
+use ecosystem::{error::MyError, hash, modes::HashAlgorithm};
+use miette::{miette, IntoDiagnostic, Result};
+use tokio::{io::AsyncReadExt, sync::Semaphore, task::JoinHandle};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Files to hash, or with --verify the checksum files to check; "-" reads stdin
+    #[arg(required = true)]
+    paths: Vec<String>,
+
+    /// blake3 or sha256
+    #[arg(long, default_value_t)]
+    algo: HashAlgorithm,
+
+    /// Write the checksum lines to this file instead of stdout
+    #[arg(short, long, conflicts_with = "verify")]
+    output: Option<PathBuf>,
+
+    /// Check the files listed in the given checksum files (sha256sum -c)
+    #[arg(long)]
+    verify: bool,
+
+    /// Files hashed at the same time
+    #[arg(short, long, default_value_t = 4)]
+    jobs: usize,
+}
+
+pub async fn run(args: Args) -> Result<()> {
+    if args.verify {
+        return verify(&args).await;
+    }
+    let digests = checksum_all(args.algo, args.jobs, args.paths.clone()).await?;
+    let lines: String = args
+        .paths
+        .iter()
+        .zip(digests)
+        .map(|(path, digest)| Ok(format!("{}  {path}\n", digest?)))
+        .collect::<Result<_, MyError>>()?;
+    super::write_output(args.output.as_deref(), lines.as_bytes()).await
+}
+
+async fn verify(args: &Args) -> Result<()> {
+    let mut expected = Vec::new();
+    for list in &args.paths {
+        let text = super::read_input(list).await?;
+        let text = String::from_utf8(text).into_diagnostic()?;
+        for (digest, path) in text.lines().filter_map(hash::parse_checksum_line) {
+            expected.push((digest.to_ascii_lowercase(), path.to_string()));
+        }
+    }
+    if expected.is_empty() {
+        return Err(miette!(
+            "no checksum lines found in {}",
+            args.paths.join(", ")
+        ));
+    }
+
+    let paths = expected.iter().map(|(_, path)| path.clone()).collect();
+    let digests = checksum_all(args.algo, args.jobs, paths).await?;
+    let mut failed = 0;
+    for ((want, path), got) in expected.iter().zip(digests) {
+        match got {
+            Ok(got) if got == *want => println!("{path}: OK"),
+            Ok(_) => {
+                failed += 1;
+                println!("{path}: FAILED");
+            }
+            Err(e) => {
+                failed += 1;
+                println!("{path}: FAILED open or read ({e})");
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(miette!(
+            help = format!(
+                "check that --algo {} is the algorithm the list was made with",
+                args.algo
+            ),
+            "{failed} of {} files did not match",
+            expected.len()
+        ));
+    }
+    Ok(())
+}
+
+// The digests of `paths` in order, at most `jobs` files at a time. A file's own error is kept in
+// its slot (verify reports it and goes on); a panicked task fails the whole command.
+async fn checksum_all(
+    algo: HashAlgorithm,
+    jobs: usize,
+    paths: Vec<String>,
+) -> Result<Vec<Result<String, MyError>>> {
+    let permits = Arc::new(Semaphore::new(jobs.max(1)));
+    let tasks: Vec<JoinHandle<Result<String, MyError>>> = paths
+        .into_iter()
+        .map(|path| {
+            let permits = permits.clone();
+            tokio::spawn(async move {
+                let _permit = permits
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore is never closed");
+                if path == "-" {
+                    let mut data = Vec::new();
+                    tokio::io::stdin().read_to_end(&mut data).await?;
+                    // multi-threaded above PARALLEL_THRESHOLD: off the async threads
+                    tokio::task::spawn_blocking(move || hash::checksum(algo, &data))
+                        .await
+                        .map_err(|e| MyError::Custom(format!("hashing task failed: {e}")))
+                } else {
+                    hash::checksum_file(&path, algo).await
+                }
+            })
+        })
+        .collect();
+
+    let mut digests = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        digests.push(task.await.into_diagnostic()?);
+    }
+    Ok(digests)
+}
@@ -0,0 +1,180 @@
+// ecosystem jobs: the durable job queue (worker::durable, --features sqlite-queue) for operators:
+// put a job in, see what's waiting or dead, and give dead jobs another go, without writing code.
+//
+//   ecosystem jobs submit hash '{"input": "task 1"}'                 # → 42 (the job's queue id)
+//   echo '{"input": "task 2"}' | ecosystem jobs submit hash -
+//   ecosystem jobs submit hash --format msgpack - < big.json       # stored as MessagePack
+//   ecosystem jobs list                                             # every job, oldest first
+//   ecosystem jobs list --state dead                                # out of attempts, with the last error
+//   ecosystem jobs list --state pending --json | jq .
+//   ecosystem jobs requeue 17 18                                    # attempts reset, leased again
+//   ecosystem jobs requeue --all
+//   ecosystem jobs delete 19                                        # a dead job given up on
+//
+// --queue (or ECOSYSTEM_QUEUE) is the SQLite URL the workers feed from. The job type isn't checked
+// here: it must be registered in the workers' JobRegistry, or the job fails there (and ends up dead).
+
+use ecosystem::{
+    formats::Format,
+    worker::durable::{JobState, QueueError, SqliteQueue},
+};
+use miette::{miette, IntoDiagnostic, Result, WrapErr};
+use serde_json::Value;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The queue database
+    #[arg(
+        long,
+        env = "ECOSYSTEM_QUEUE",
+        default_value = "sqlite://jobs.db?mode=rwc"
+    )]
+    queue: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Enqueue a job: its type and a JSON payload
+    Submit {
+        /// Job type, as registered by the workers (e.g. "hash")
+        kind: String,
+        /// JSON payload; "-" reads stdin
+        #[arg(default_value = "{}")]
+        payload: String,
+        /// How the payload is stored: json, or msgpack (smaller, for big payloads)
+        #[arg(long, default_value = "json")]
+        format: Format,
+    },
+    /// List queued jobs, oldest first
+    List {
+        /// pending, leased or dead; all when not given
+        #[arg(long)]
+        state: Option<JobState>,
+        /// At most this many
+        #[arg(long, default_value_t = 100)]
+        limit: i64,
+        /// One JSON object per line instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Put dead jobs back in the queue, attempts reset
+    Requeue {
+        /// Queue ids of dead jobs
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        ids: Vec<i64>,
+        /// Every dead job
+        #[arg(long)]
+        all: bool,
+    },
+    /// Delete dead jobs
+    Delete {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+}
+
+pub async fn run(args: Args) -> Result<()> {
+    let queue = SqliteQueue::open(&args.queue)
+        .await
+        .into_diagnostic()
+        .wrap_err_with(|| format!("opening {}", args.queue))?;
+    match args.command {
+        Command::Submit {
+            kind,
+            payload,
+            format,
+        } => {
+            let text = match payload.as_str() {
+                "-" => String::from_utf8(super::read_input("-").await?).into_diagnostic()?,
+                _ => payload,
+            };
+            if !format.self_describing() {
+                return Err(miette!(
+                    help = "enqueue bincode jobs from code, with the job's own type",
+                    "a {} payload can't be built from JSON",
+                    format.name()
+                ));
+            }
+            let payload: Value = serde_json::from_str(&text)
+                .into_diagnostic()
+                .wrap_err("the payload is not valid JSON")?;
+            let id = queue
+                .with_payload_format(format)
+                .enqueue(&kind, &payload)
+                .await
+                .into_diagnostic()?;
+            println!("{id}");
+        }
+        Command::List { state, limit, json } => {
+            let jobs = queue.list(state, limit).await.into_diagnostic()?;
+            for job in &jobs {
+                if json {
+                    let mut line = serde_json::to_value(job).into_diagnostic()?;
+                    line["state"] = job.state().to_string().into();
+                    // bincode payloads can't be shown without their type
+                    match job.payload_json() {
+                        Ok(payload) => line["payload"] = payload,
+                        Err(e) => line["payload_error"] = e.to_string().into(),
+                    }
+                    println!("{line}");
+                    continue;
+                }
+                println!(
+                    "{:>6}  {:<7}  {:<16}  attempts {:<3} {}  {}",
+                    job.id,
+                    job.state(),
+                    job.kind,
+                    job.attempts,
+                    job.created().format("%Y-%m-%d %H:%M:%S"),
+                    job.payload_json()
+                        .map_or_else(|e| format!("<{e}>"), |p| p.to_string())
+                );
+                if let Some(error) = &job.last_error {
+                    println!("        last error: {error}");
+                }
+            }
+            if !json {
+                eprintln!("{} jobs", jobs.len());
+            }
+        }
+        Command::Requeue { ids, all } => {
+            if all {
+                let count = queue.requeue_dead().await.into_diagnostic()?;
+                eprintln!("{count} dead jobs requeued");
+                return Ok(());
+            }
+            settle(&ids, "requeued", |id| queue.requeue(id)).await?;
+        }
+        Command::Delete { ids } => {
+            settle(&ids, "deleted", |id| queue.delete_dead(id)).await?;
+        }
+    }
+    Ok(())
+}
+
+// Applies `op` to each id; an id that isn't a dead job is reported, and fails the command at the end.
+async fn settle<F, Fut>(ids: &[i64], done: &str, op: F) -> Result<()>
+where
+    F: Fn(i64) -> Fut,
+    Fut: std::future::Future<Output = Result<bool, QueueError>>,
+{
+    let mut missing = Vec::new();
+    for &id in ids {
+        if op(id).await.into_diagnostic()? {
+            eprintln!("job {id} {done}");
+        } else {
+            missing.push(id.to_string());
+        }
+    }
+    if !missing.is_empty() {
+        return Err(miette!(
+            help = "ecosystem jobs list --state dead",
+            "not dead jobs: {}",
+            missing.join(", ")
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+// ecosystem proxy: crate::proxy, the TCP relay of examples/minginx.rs, with its settings from the
+// config file (and PROXY_* env vars) and the flags given here on top.
+//
+//   ecosystem proxy --config proxy.toml
+//   ecosystem proxy --config proxy.toml --listen 0.0.0.0:9000 --upstream host:8080
+//   ecosystem proxy --upstream 10.0.0.1:8080 --upstream 10.0.0.2:8080 --strategy least-conn
+//   ecosystem proxy --daemon --pid-file /run/ecosystem-proxy.pid   # background, see cli/daemon.rs
+//   kill -USR1 $(cat /run/ecosystem-proxy.pid)                      # toggle debug logging (Unix)
+
+use ecosystem::{
+    modes::BalanceStrategy,
+    proxy::{Proxy, ProxyConfig},
+    telemetry::LogLevelHandle,
+};
+use miette::{IntoDiagnostic, Result};
+
+use super::daemon::DaemonArgs;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Address to accept connections on, instead of `listen` in the config file
+    #[arg(long)]
+    listen: Option<String>,
+
+    /// Upstream address, repeatable; replaces `upstreams` in the config file
+    #[arg(long)]
+    upstream: Vec<String>,
+
+    /// How connections are spread over the upstreams
+    #[arg(long)]
+    strategy: Option<BalanceStrategy>,
+
+    #[command(flatten)]
+    pub daemon: DaemonArgs,
+}
+
+impl Args {
+    // The flags that were given replace the file's values, the others leave them alone.
+    fn apply(self, config: &mut ProxyConfig) {
+        if let Some(listen) = self.listen {
+            config.listen = listen;
+        }
+        if !self.upstream.is_empty() {
+            config.upstreams = self.upstream;
+        }
+        if let Some(strategy) = self.strategy {
+            config.strategy = strategy;
+        }
+    }
+}
+
+// `log_level`: toggled by SIGUSR1, the proxy has no admin endpoint to change it.
+pub async fn run(config: &str, args: Args, log_level: LogLevelHandle) -> Result<()> {
+    let mut proxy_config = ProxyConfig::load(config).into_diagnostic()?;
+    args.apply(&mut proxy_config);
+    #[cfg(unix)]
+    ecosystem::telemetry::spawn_sigusr1_toggle(log_level, "debug").into_diagnostic()?;
+    #[cfg(not(unix))]
+    let _ = log_level;
+    Proxy::new(proxy_config)?.run().await.into_diagnostic()
+}
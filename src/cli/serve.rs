@@ -0,0 +1,92 @@
+// ecosystem serve: the web service of web::app (users API, search, HTML views, audit, jobs, admin),
+// with the server settings of the config file (bind address, timeouts, CORS, TLS) and [app]
+// (storage, audit log, assets), and the flags given here on top.
+//
+//   ecosystem serve --config server.toml
+//   ecosystem serve --port 9000 --storage file:users.json
+//   ecosystem serve --tls-cert certs/cert.pem --tls-key certs/key.pem
+//   ecosystem serve --daemon --pid-file serve.pid      # background, see cli/daemon.rs
+//   kill -USR1 $(cat serve.pid)                        # toggle debug logging (Unix; or PUT /admin/log-level)
+
+use std::{net::SocketAddr, path::PathBuf};
+
+use ecosystem::{
+    telemetry::LogLevelHandle,
+    web::{
+        self,
+        app::{AppConfig, StorageConfig},
+        server::ServerConfig,
+        tls::TlsConfig,
+    },
+};
+use miette::{miette, IntoDiagnostic, Result};
+
+use super::daemon::DaemonArgs;
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Bind address, instead of `addr` in the config file
+    #[arg(long)]
+    addr: Option<SocketAddr>,
+
+    /// Port to listen on, keeping the configured IP
+    #[arg(short, long)]
+    port: Option<u16>,
+
+    /// "memory" or "file:<path>", instead of [app.storage]
+    #[arg(long)]
+    storage: Option<StorageConfig>,
+
+    /// Certificate chain (PEM) to serve HTTPS with; needs --tls-key
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// Private key (PEM) of --tls-cert
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub daemon: DaemonArgs,
+}
+
+impl Args {
+    // The flags that were given replace the file's values, the others leave them alone.
+    fn apply(self, server: &mut ServerConfig, app: &mut AppConfig) {
+        if let Some(addr) = self.addr {
+            server.addr = addr;
+        }
+        if let Some(port) = self.port {
+            server.addr.set_port(port);
+        }
+        if let Some(storage) = self.storage {
+            app.storage = storage;
+        }
+        if let (Some(cert_path), Some(key_path)) = (self.tls_cert, self.tls_key) {
+            // a configured HTTP → HTTPS redirect still applies
+            let (redirect_http_from, public_host) = server
+                .tls
+                .take()
+                .map_or((None, None), |t| (t.redirect_http_from, t.public_host));
+            server.tls = Some(TlsConfig {
+                cert_path,
+                key_path,
+                redirect_http_from,
+                public_host,
+            });
+        }
+    }
+}
+
+pub async fn run(config: &str, args: Args, log_level: LogLevelHandle) -> Result<()> {
+    let mut server = ServerConfig::load(config).into_diagnostic()?;
+    let mut app = AppConfig::load(config).into_diagnostic()?;
+    args.apply(&mut server, &mut app);
+
+    #[cfg(unix)]
+    ecosystem::telemetry::spawn_sigusr1_toggle(log_level.clone(), "debug").into_diagnostic()?;
+    let router = web::app::router(&app, log_level).await?;
+    // serve() returns anyhow::Error, which isn't a std Error: keep its context chain in the message
+    web::server::serve(router, &server)
+        .await
+        .map_err(|e| miette!("{e:#}"))
+}
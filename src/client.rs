@@ -0,0 +1,157 @@
+// client: outgoing HTTP (reqwest) with the same observability as the server side.
+// The counterpart of web::trace::extract_remote_context:
+//   - every request gets an "http.client" span (OTel HTTP client semantic conventions)
+//   - the W3C traceparent/tracestate of that span is injected into the request headers,
+//     so the downstream service's spans join our trace
+//   - idempotent requests (GET, HEAD, PUT, DELETE, OPTIONS, TRACE) are retried with exponential
+//     backoff on connect/timeout errors and 502/503/504; POST/PATCH are never retried.
+
+use std::time::Duration;
+
+use opentelemetry::{global, propagation::Injector};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, Request, RequestBuilder, Response, StatusCode,
+};
+use tracing::{field::Empty, info_span, warn, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+#[derive(Debug, Clone)]
+pub struct TracedClient {
+    inner: reqwest::Client,
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl Default for TracedClient {
+    fn default() -> Self {
+        Self::new(reqwest::Client::new())
+    }
+}
+
+impl TracedClient {
+    // 2 retries, starting at 100ms (100ms, 200ms)
+    pub fn new(inner: reqwest::Client) -> Self {
+        Self {
+            inner,
+            max_retries: 2,
+            backoff: Duration::from_millis(100),
+        }
+    }
+
+    pub fn with_retries(mut self, max_retries: u32, backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.backoff = backoff;
+        self
+    }
+
+    // Build with the usual reqwest API, then pass the builder to send():
+    //   let res = client.send(client.get("http://localhost:8080/users")).await?;
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.inner.get(url)
+    }
+
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.inner.request(method, url)
+    }
+
+    pub async fn send(&self, builder: RequestBuilder) -> reqwest::Result<Response> {
+        self.execute(builder.build()?).await
+    }
+
+    pub async fn execute(&self, mut req: Request) -> reqwest::Result<Response> {
+        let span = info_span!(
+            "http.client",
+            otel.name = req.method().as_str(),
+            otel.kind = "client",
+            otel.status_code = Empty,
+            http.request.method = req.method().as_str(),
+            url.full = req.url().as_str(),
+            server.address = req.url().host_str(),
+            server.port = req.url().port_or_known_default(),
+            http.response.status_code = Empty,
+            http.request.resend_count = Empty,
+        );
+        // inject *this* span's context: the downstream server span becomes its child
+        let cx = span.context();
+        global::get_text_map_propagator(|prop| {
+            prop.inject_context(&cx, &mut HeaderInjector(req.headers_mut()))
+        });
+
+        let retries = if is_idempotent(req.method()) {
+            self.max_retries
+        } else {
+            0
+        };
+        async move {
+            let mut attempt = 0;
+            let mut backoff = self.backoff;
+            loop {
+                // streaming bodies can't be cloned → no retry for them
+                let retry = if attempt < retries {
+                    req.try_clone()
+                } else {
+                    None
+                };
+                let result = self.inner.execute(req).await;
+                let span = tracing::Span::current();
+                let should_retry = match &result {
+                    Ok(res) => is_retryable_status(res.status()),
+                    Err(e) => e.is_connect() || e.is_timeout(),
+                };
+                let Some(next) = retry.filter(|_| should_retry) else {
+                    match &result {
+                        Ok(res) => {
+                            span.record("http.response.status_code", res.status().as_u16());
+                            if res.status().is_server_error() {
+                                span.record("otel.status_code", "ERROR");
+                            }
+                        }
+                        Err(_) => {
+                            span.record("otel.status_code", "ERROR");
+                        }
+                    }
+                    return result;
+                };
+                match &result {
+                    Ok(res) => warn!(status = res.status().as_u16(), attempt, "retrying request"),
+                    Err(e) => warn!(error = %e, attempt, "retrying request"),
+                }
+                req = next;
+                attempt += 1;
+                span.record("http.request.resend_count", attempt);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+        .instrument(span)
+        .await
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS | Method::TRACE
+    )
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+// clock: "now" as a dependency instead of a call to Utc::now() buried in the logic, so time-dependent
+// behavior (ages, soft-delete retention) can be pinned in tests and replayed for a given date.
+//
+//   let clock = FixedClock::new(Utc.with_ymd_and_hms(2023, 2, 28, 12, 0, 0).unwrap());
+//   let born = "2000-02-29T00:00:00Z";
+//   assert_eq!(User::builder().name("Leap").dob(born).build_with(&clock)?.age, 22);
+//   clock.advance(chrono::Duration::days(1));  // Mar 1, 2023: no Feb 29 this year, so the birthday counts today
+//   assert_eq!(User::builder().name("Leap").dob(born).build_with(&clock)?.age, 23);
+//
+// SystemClock: the real time, the default everywhere.
+// FixedClock: a time that only moves when told to; clones share it, so a store holding one
+// sees advance() / set() from the test.
+//
+// age_on: completed years between two calendar dates, the way people count birthdays:
+// the age goes up on the birthday itself, and a Feb 29 birthday counts as passed on Mar 1 in other years.
+// (Subtracting years, like examples/builder.rs does, is off by one until the birthday each year.)
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+// Shared between components (UserStore, ...), cheap to clone.
+pub type SharedClock = Arc<dyn Clock>;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl FixedClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().expect("clock lock poisoned") = now;
+    }
+
+    pub fn advance(&self, by: chrono::Duration) {
+        *self.now.lock().expect("clock lock poisoned") += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().expect("clock lock poisoned")
+    }
+}
+
+// Completed years from `born` to `on`; 0 if `on` is before `born`.
+pub fn age_on(born: NaiveDate, on: NaiveDate) -> u32 {
+    let years = on.year() - born.year();
+    // (month, day) order: Feb 29 sorts before Mar 1, so in a non-leap year it's "passed" on Mar 1
+    let had_birthday = (on.month(), on.day()) >= (born.month(), born.day());
+    let age = if had_birthday { years } else { years - 1 };
+    age.max(0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn leap_day_birthday_counts_on_mar_1_in_other_years() {
+        let born = date(2000, 2, 29);
+        // 2023 has no Feb 29
+        assert_eq!(age_on(born, date(2023, 2, 28)), 22);
+        assert_eq!(age_on(born, date(2023, 3, 1)), 23);
+        // 2024 does
+        assert_eq!(age_on(born, date(2024, 2, 28)), 23);
+        assert_eq!(age_on(born, date(2024, 2, 29)), 24);
+        assert_eq!(age_on(born, date(2024, 3, 1)), 24);
+    }
+
+    #[test]
+    fn age_goes_up_on_the_birthday_itself() {
+        let born = date(1990, 6, 15);
+        assert_eq!(age_on(born, date(2020, 6, 14)), 29);
+        assert_eq!(age_on(born, date(2020, 6, 15)), 30);
+        assert_eq!(age_on(born, date(2020, 12, 31)), 30);
+        assert_eq!(age_on(born, date(2021, 1, 1)), 30);
+        assert_eq!(age_on(born, born), 0);
+        assert_eq!(age_on(born, date(1980, 1, 1)), 0);
+    }
+
+    #[test]
+    fn fixed_clock_is_shared_by_its_clones() {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2023, 2, 28, 12, 0, 0).unwrap());
+        let shared: SharedClock = Arc::new(clock.clone());
+        clock.advance(chrono::Duration::days(1));
+        assert_eq!(shared.now().date_naive(), date(2023, 3, 1));
+    }
+}
@@ -0,0 +1,131 @@
+// codec: byte-level building blocks for talking to other processes over TCP (or anything that moves bytes).
+// examples/bytes.rs shows the raw BytesMut/Bytes operations, examples/chat.rs a ready-made codec (LinesCodec);
+// this module has the pieces for our own protocols:
+//
+// codec::endian (codec/endian.rs): fixed-size numbers with the byte order as a type parameter, reads checked
+// codec::varint (codec/varint.rs): LEB128 varints, and VarintLengthCodec (varint length + payload frames)
+// FrameCodec (codec/frame.rs): our protocol's frames, header (magic, version, type, flags, length) + payload,
+//   optionally CRC32-checked, messages over the frame size split into chunks and reassembled,
+//   payloads compressed with zstd / gzip (codec/compression.rs), negotiated per connection
+// FrameAccumulator (codec/accumulator.rs): push bytes in any pieces, get complete Frames; no runtime or I/O
+// CobsCodec (codec/cobs.rs): the same Frames, zero-delimited and byte-stuffed, for serial-like links
+// BufPool (codec/buf_pool.rs): reusable BytesMut buffers for codec output and proxy copy loops
+// MessageCodec<T> (codec/message.rs): Message<T> { msg_type, flags, body: T } over FrameCodec (or CobsCodec),
+//   body as MessagePack, or bincode (codec/bincode_body.rs, --features bincode) between our own processes
+// TaggedCodec<T> (codec/tagged.rs): a crate::tagged enum, the variant as msg_type and its payload as the body
+// ProtoCodec<M> (codec/proto.rs, --features protobuf): Message<M> with a prost-encoded protobuf body
+// SendFramed / RecvFramed (codec/ext.rs): send_message / recv_message on any AsyncWrite / AsyncRead, no Framed
+// codec::recv (codec/slow_read.rs): Framed::next with FrameCodec's read timeout, against slow peers
+// NdjsonCodec<T> (codec/ndjson.rs): one JSON value per line, for log shipping and streaming APIs
+//
+// Every codec here implements tokio_util's Decoder/Encoder, so it plugs into Framed like LinesCodec does:
+//   let mut frames = Framed::new(stream, VarintLengthCodec::new());
+//   frames.send(Bytes::from_static(b"hello")).await?;
+//   while let Some(frame) = frames.next().await { let frame: Bytes = frame?; ... }
+// examples/protocol.rs: a client and server exchanging typed messages over TCP with MessageCodec.
+
+mod accumulator;
+mod bincode_body;
+mod buf_pool;
+mod cobs;
+mod compression;
+pub mod endian;
+mod ext;
+mod frame;
+mod message;
+mod ndjson;
+#[cfg(feature = "protobuf")]
+pub mod proto;
+mod slow_read;
+mod tagged;
+pub mod varint;
+
+use std::io;
+
+use thiserror::Error;
+
+pub use accumulator::FrameAccumulator;
+pub use buf_pool::{BufPool, BufPoolStats, PooledBuf};
+pub use cobs::CobsCodec;
+pub use compression::Compression;
+pub use endian::ShortBuffer;
+pub use ext::{RecvFramed, SendFramed};
+pub use frame::{Flags, Frame, FrameCodec, Framing};
+pub use message::{Message, MessageCodec};
+pub use ndjson::NdjsonCodec;
+#[cfg(feature = "protobuf")]
+pub use proto::ProtoCodec;
+pub use slow_read::{recv, ReadDeadline};
+pub use tagged::TaggedCodec;
+pub use varint::{VarintError, VarintLengthCodec};
+
+// Decoder/Encoder error of the codecs in this module. A decode error leaves the stream at an
+// unknown position, so Framed ends the stream: the connection should be closed.
+#[derive(Error, Debug)]
+pub enum CodecError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid varint: {0}")]
+    Varint(#[from] VarintError),
+    #[error(transparent)]
+    ShortBuffer(#[from] ShortBuffer),
+    // FrameCodec::max_payload, checked on the header: the payload isn't buffered
+    #[error("frame of {len} bytes exceeds the limit of {max} bytes")]
+    FrameTooLarge { len: usize, max: usize },
+    // the peer isn't speaking our protocol (FrameCodec)
+    #[error("bad frame magic {0:#06x}")]
+    BadMagic(u16),
+    #[error("unsupported protocol version {0}")]
+    UnsupportedVersion(u8),
+    // FrameCodec::max_message, for messages sent in several frames
+    #[error("message of {len} bytes exceeds the limit of {max} bytes")]
+    MessageTooLarge { len: usize, max: usize },
+    // a frame of another message type arrived before the last chunk (Flags::MORE) of a message
+    #[error("expected the next chunk of a type {expected} message, got a type {got} frame")]
+    InterleavedChunks { expected: u16, got: u16 },
+    // corrupt compressed data (Flags::ZSTD / Flags::GZIP frames)
+    #[error("payload decompression failed: {0}")]
+    Decompress(io::Error),
+    // CobsCodec: a block running past the delimiter, or a frame too short for its header
+    #[error("invalid COBS frame")]
+    InvalidCobs,
+    // TaggedCodec / Frame::tagged_body: a msg_type that isn't one of the enum's codes
+    #[error("unknown message type {0}")]
+    UnknownMessageType(u16),
+    // FrameCodec::accept_hello got a regular frame
+    #[error("expected a hello frame, got message type {0}")]
+    ExpectedHello(u16),
+    // the payload changed on the way (Flags::CHECKSUM frames)
+    #[error("frame checksum mismatch: expected {expected:#010x}, got {actual:#010x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
+    #[error("message body serialization failed: {0}")]
+    Encode(#[from] rmp_serde::encode::Error),
+    #[error("message body deserialization failed: {0}")]
+    Decode(#[from] rmp_serde::decode::Error),
+    #[cfg(feature = "bincode")]
+    #[error("bincode body serialization failed: {0}")]
+    BincodeEncode(#[from] bincode::error::EncodeError),
+    #[cfg(feature = "bincode")]
+    #[error("bincode body deserialization failed: {0}")]
+    BincodeDecode(#[from] bincode::error::DecodeError),
+    // the body type on the other end is laid out differently (a new field, a wider integer, ...)
+    #[error("bincode body has {0} bytes left over: the sender's type doesn't match")]
+    BincodeTrailing(usize),
+    // a Flags::BINCODE frame (or MessageCodec::bincode) in a build without the bincode feature
+    #[error("bincode message body, but built without the bincode feature")]
+    BincodeDisabled,
+    #[cfg(feature = "protobuf")]
+    #[error("protobuf encoding failed: {0}")]
+    ProtoEncode(#[from] prost::EncodeError),
+    #[cfg(feature = "protobuf")]
+    #[error("protobuf decoding failed: {0}")]
+    ProtoDecode(#[from] prost::DecodeError),
+    #[error("invalid JSON line: {0}")]
+    Json(#[from] serde_json::Error),
+    // FrameCodec::read_timeout: a frame started arriving but wasn't complete in time
+    #[error("peer too slow: frame not complete within the read timeout")]
+    ReadTimeout,
+    // NdjsonCodec: no newline within the limit
+    #[error("line exceeds the limit of {max} bytes")]
+    LineTooLong { max: usize },
+}
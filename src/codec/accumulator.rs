@@ -0,0 +1,90 @@
+// FrameAccumulator: the frame decoding of FrameCodec (or CobsCodec) for bytes that don't come from
+// an AsyncRead: WebSocket messages, reassembled UDP datagrams, a C library's callback, test vectors.
+// Push bytes in whatever pieces they arrive in, get back the frames they complete:
+//
+//   let mut frames = FrameAccumulator::new();
+//   for chunk in [&wire[..3], &wire[3..17], &wire[17..]] {   // any split, even inside a header
+//       for frame in frames.push(chunk)? { handle(frame); }
+//   }
+//   frames.finish()?;                                        // Err if the input stopped mid-frame
+//
+// Key flow:
+// push(bytes) → appended to the buffer → codec.decode() until it needs more → the complete frames
+//               (chunks reassembled, checksums verified, decompressed: the same code path as Framed)
+//
+// No runtime, no I/O: plain method calls, usable from sync code and any async runtime.
+// After an error the position in the byte stream is lost (like Framed, which ends the stream):
+// drop the accumulator, or reset() it when the source has its own message boundaries to resync on.
+
+use bytes::BytesMut;
+use tokio_util::codec::Decoder;
+
+use super::{
+    frame::{Frame, FrameCodec},
+    CodecError,
+};
+
+#[derive(Debug, Default)]
+pub struct FrameAccumulator<F = FrameCodec> {
+    codec: F,
+    buf: BytesMut,
+}
+
+impl FrameAccumulator {
+    pub fn new() -> Self {
+        Self::with_codec(FrameCodec::new())
+    }
+}
+
+impl<F: Decoder<Item = Frame, Error = CodecError>> FrameAccumulator<F> {
+    // e.g. FrameCodec::new().checksums(true), or CobsCodec::new()
+    pub fn with_codec(codec: F) -> Self {
+        Self {
+            codec,
+            buf: BytesMut::new(),
+        }
+    }
+
+    // The frames `bytes` completes, in order; usually none or one, more when a piece holds several.
+    pub fn push(&mut self, bytes: &[u8]) -> Result<Vec<Frame>, CodecError> {
+        self.buf.extend_from_slice(bytes);
+        let mut frames = Vec::new();
+        while let Some(frame) = self.codec.decode(&mut self.buf)? {
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
+    // End of input: the frames still buffered, or an error if it ends in the middle of one.
+    pub fn finish(&mut self) -> Result<Vec<Frame>, CodecError> {
+        let mut frames = Vec::new();
+        while let Some(frame) = self.codec.decode_eof(&mut self.buf)? {
+            frames.push(frame);
+        }
+        Ok(frames)
+    }
+
+    // Bytes received that aren't part of a returned frame yet.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    // Drops the buffered bytes and any half-received message; the codec's settings stay
+    // (a codec's clone starts without decoding state).
+    pub fn reset(&mut self)
+    where
+        F: Clone,
+    {
+        self.buf.clear();
+        self.codec = self.codec.clone();
+    }
+
+    pub fn codec(&self) -> &F {
+        &self.codec
+    }
+
+    // e.g. FrameCodec::accept_hello after the hello frame came out of push
+    pub fn codec_mut(&mut self) -> &mut F {
+        &mut self.codec
+    }
+}
@@ -0,0 +1,52 @@
+// Bincode message bodies (Flags::BINCODE frames, --features bincode): for traffic between our own
+// processes, built from the same code, where MessagePack's field names are pure overhead.
+//
+//   MessagePack  {"seq":1,"name":"ping"}  →  82 a3 736571 01 a4 6e616d65 a4 70696e67   16 bytes
+//   bincode      the same struct          →  01 04 70696e67                             6 bytes
+//
+// Standard config: varint integers, little-endian. Not self-describing, so both ends need the same
+// Rust type per msg_type; see the Flags::BINCODE notes in codec/frame.rs for evolving one.
+// A payload with bytes left over after T is an error rather than ignored: the usual sign that the
+// peer's type has more (or bigger) fields than ours.
+//
+// Without the feature a BINCODE frame fails with CodecError::BincodeDisabled instead of being read
+// as MessagePack.
+
+#[cfg(feature = "bincode")]
+use bytes::BufMut;
+use bytes::BytesMut;
+use serde::{Deserialize, Serialize};
+
+use super::CodecError;
+
+#[cfg(feature = "bincode")]
+pub(super) fn encode<T: Serialize + ?Sized>(
+    body: &T,
+    dst: &mut BytesMut,
+) -> Result<(), CodecError> {
+    bincode::serde::encode_into_std_write(body, &mut dst.writer(), bincode::config::standard())?;
+    Ok(())
+}
+
+#[cfg(feature = "bincode")]
+pub(super) fn decode<'de, T: Deserialize<'de>>(payload: &'de [u8]) -> Result<T, CodecError> {
+    let (body, read) =
+        bincode::serde::borrow_decode_from_slice(payload, bincode::config::standard())?;
+    if read != payload.len() {
+        return Err(CodecError::BincodeTrailing(payload.len() - read));
+    }
+    Ok(body)
+}
+
+#[cfg(not(feature = "bincode"))]
+pub(super) fn encode<T: Serialize + ?Sized>(
+    _body: &T,
+    _dst: &mut BytesMut,
+) -> Result<(), CodecError> {
+    Err(CodecError::BincodeDisabled)
+}
+
+#[cfg(not(feature = "bincode"))]
+pub(super) fn decode<'de, T: Deserialize<'de>>(_payload: &'de [u8]) -> Result<T, CodecError> {
+    Err(CodecError::BincodeDisabled)
+}
@@ -0,0 +1,231 @@
+// BufPool: reusable BytesMut buffers, so busy I/O loops don't allocate and free a buffer per
+// read / per connection (tokio::io::copy allocates 8 KiB for every call, minginx.rs makes two per connection).
+//
+// Key flow:
+// pool.get()            → a free buffer from the pool (hit) or a new one of buf_size (miss)
+//   └→ PooledBuf        → derefs to BytesMut: read into it, encode frames into it, split it
+// drop(PooledBuf)       → cleared and returned to the pool, if it still has its capacity
+//                         (split-off Bytes still alive → can't be reused, it's dropped) and the pool isn't full
+//
+//   let pool = BufPool::new("proxy", 16 * 1024, 256);     // 16 KiB buffers, keeps at most 256 idle ones
+//   let n = pool.copy(&mut client_read, &mut upstream_write).await?;   // io::copy with a pooled buffer, n: ByteCount
+//   let mut buf = pool.get();
+//   FrameCodec::new().encode(frame, &mut buf)?;            // codec output without a fresh allocation
+//
+// Metrics (same global meter provider as worker/metrics.rs):
+//   bufpool.gets{pool, outcome}     counter, outcome = hit | miss; hit rate = hit / (hit + miss)
+//   bufpool.outstanding{pool}       up-down counter: buffers handed out and not yet returned
+// plus BufPool::stats(): the same counts and the high-water mark of outstanding buffers, for sizing
+// max_idle (a high-water mark above it means misses under peak load).
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, LazyLock, Mutex,
+    },
+};
+
+use bytes::BytesMut;
+use opentelemetry::{
+    global,
+    metrics::{Counter, UpDownCounter},
+    KeyValue,
+};
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::units::ByteCount;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BufPoolStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub outstanding: usize,
+    pub high_water: usize,
+    pub idle: usize,
+}
+
+impl BufPoolStats {
+    pub fn hit_rate(&self) -> f64 {
+        let gets = self.hits + self.misses;
+        if gets == 0 {
+            0.0
+        } else {
+            self.hits as f64 / gets as f64
+        }
+    }
+}
+
+// Cheap to clone: every clone shares the same buffers.
+#[derive(Clone)]
+pub struct BufPool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    name: &'static str,
+    buf_size: usize,
+    max_idle: usize,
+    idle: Mutex<Vec<BytesMut>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    outstanding: AtomicUsize,
+    high_water: AtomicUsize,
+}
+
+impl fmt::Debug for BufPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufPool")
+            .field("name", &self.inner.name)
+            .field("buf_size", &self.inner.buf_size)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl BufPool {
+    // `name`: the pool label of the metrics; `max_idle`: buffers kept for reuse, the rest is freed.
+    pub fn new(name: &'static str, buf_size: usize, max_idle: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                name,
+                buf_size: buf_size.max(1),
+                max_idle,
+                idle: Mutex::new(Vec::new()),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                outstanding: AtomicUsize::new(0),
+                high_water: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    // An empty buffer with at least buf_size capacity.
+    pub fn get(&self) -> PooledBuf {
+        let inner = &self.inner;
+        let reused = inner.idle.lock().expect("buffer pool lock poisoned").pop();
+        let outcome = if reused.is_some() {
+            inner.hits.fetch_add(1, Ordering::Relaxed);
+            "hit"
+        } else {
+            inner.misses.fetch_add(1, Ordering::Relaxed);
+            "miss"
+        };
+        let buf = reused.unwrap_or_else(|| BytesMut::with_capacity(inner.buf_size));
+        let outstanding = inner.outstanding.fetch_add(1, Ordering::Relaxed) + 1;
+        inner.high_water.fetch_max(outstanding, Ordering::Relaxed);
+        let pool = KeyValue::new("pool", inner.name);
+        METRICS
+            .gets
+            .add(1, &[pool.clone(), KeyValue::new("outcome", outcome)]);
+        METRICS.outstanding.add(1, &[pool]);
+        PooledBuf {
+            buf,
+            pool: self.inner.clone(),
+        }
+    }
+
+    pub fn buf_size(&self) -> usize {
+        self.inner.buf_size
+    }
+
+    pub fn stats(&self) -> BufPoolStats {
+        let inner = &self.inner;
+        BufPoolStats {
+            hits: inner.hits.load(Ordering::Relaxed),
+            misses: inner.misses.load(Ordering::Relaxed),
+            outstanding: inner.outstanding.load(Ordering::Relaxed),
+            high_water: inner.high_water.load(Ordering::Relaxed),
+            idle: inner.idle.lock().expect("buffer pool lock poisoned").len(),
+        }
+    }
+
+    // tokio::io::copy with one pooled buffer for the whole copy; returns the bytes copied.
+    // Like io::copy: reads until EOF, then flushes (the writer isn't shut down).
+    pub async fn copy<R, W>(&self, reader: &mut R, writer: &mut W) -> io::Result<ByteCount>
+    where
+        R: AsyncRead + Unpin + ?Sized,
+        W: AsyncWrite + Unpin + ?Sized,
+    {
+        let mut buf = self.get();
+        let mut copied = ByteCount::ZERO;
+        loop {
+            buf.clear();
+            if reader.read_buf(&mut *buf).await? == 0 {
+                break;
+            }
+            writer.write_all(&buf).await?;
+            copied = copied.saturating_add(ByteCount(buf.len() as u64));
+        }
+        writer.flush().await?;
+        Ok(copied)
+    }
+}
+
+// A buffer on loan from a BufPool; goes back to the pool when dropped.
+pub struct PooledBuf {
+    buf: BytesMut,
+    pool: Arc<Inner>,
+}
+
+impl fmt::Debug for PooledBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.buf.fmt(f)
+    }
+}
+
+impl Deref for PooledBuf {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &BytesMut {
+        &self.buf
+    }
+}
+
+impl DerefMut for PooledBuf {
+    fn deref_mut(&mut self) -> &mut BytesMut {
+        &mut self.buf
+    }
+}
+
+impl Drop for PooledBuf {
+    fn drop(&mut self) {
+        let pool = &self.pool;
+        pool.outstanding.fetch_sub(1, Ordering::Relaxed);
+        METRICS
+            .outstanding
+            .add(-1, &[KeyValue::new("pool", pool.name)]);
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.clear();
+        // try_reclaim: gets the full capacity back without allocating, unless split-off parts
+        // (frame payloads handed out as Bytes) still use it
+        if !buf.try_reclaim(pool.buf_size) {
+            return;
+        }
+        let mut idle = pool.idle.lock().expect("buffer pool lock poisoned");
+        if idle.len() < pool.max_idle {
+            idle.push(buf);
+        }
+    }
+}
+
+struct PoolMetrics {
+    gets: Counter<u64>,
+    outstanding: UpDownCounter<i64>,
+}
+
+// Created on first use, like task_metrics: after telemetry installed the global provider.
+static METRICS: LazyLock<PoolMetrics> = LazyLock::new(|| {
+    let meter = global::meter("ecosystem.codec");
+    PoolMetrics {
+        gets: meter
+            .u64_counter("bufpool.gets")
+            .with_description("Buffers taken from a buffer pool, by outcome (hit = reused)")
+            .build(),
+        outstanding: meter
+            .i64_up_down_counter("bufpool.outstanding")
+            .with_description("Buffers handed out by a buffer pool and not yet returned")
+            .build(),
+    }
+});
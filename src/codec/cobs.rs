@@ -0,0 +1,329 @@
+// CobsCodec: frames delimited by a zero byte, for serial-like transports (UART, RS-485, USB CDC,
+// pipes between microcontrollers) where a length prefix is a liability: after one lost or corrupted
+// byte a length-prefixed reader is out of step for good, while here the next 0x00 is always the start
+// of a fresh frame.
+//
+// COBS (Consistent Overhead Byte Stuffing) removes every zero from the data, so 0x00 can only be the
+// delimiter. Each block starts with a code byte: the distance to the next zero (which isn't sent),
+// or 0xFF for 254 data bytes without a zero. Overhead: 1 byte per 254 bytes, plus the delimiter.
+//
+//   data    11 22 00 33          encoded  03 11 22 02 33 00
+//           ^^^^^ ^^ ^^                   ^^ ^^^^^ ^^^^^ ^^ delimiter
+//                                         3: two bytes, then a zero; 2: one byte, then the end
+//
+// A frame is COBS(msg_type u16 | flags u8 | payload) + 0x00, so MessageCodec works on top unchanged:
+//
+//   let port = tokio_serial::new("/dev/ttyUSB0", 115_200).open_native_async()?;
+//   let mut link = Framed::new(port, MessageCodec::<Telemetry, _>::with_frames(CobsCodec::new()));
+//
+// No chunking, compression or checksums here (the flags travel as they are): serial frames are small,
+// and a link that corrupts bytes wants a CRC inside the payload. Decoded payloads are copies.
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::{
+    frame::{Flags, Frame, Framing},
+    CodecError,
+};
+
+// msg_type + flags, in front of the payload
+const HEADER_LEN: usize = 3;
+
+#[derive(Debug)]
+pub struct CobsCodec {
+    max_payload: usize,
+    // bytes of the buffer already searched for the delimiter
+    scanned: usize,
+}
+
+impl Default for CobsCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The settings; a clone starts with nothing scanned.
+impl Clone for CobsCodec {
+    fn clone(&self) -> Self {
+        Self::new().max_payload(self.max_payload)
+    }
+}
+
+impl CobsCodec {
+    // Payloads up to 64 KiB.
+    pub fn new() -> Self {
+        Self {
+            max_payload: 64 * 1024,
+            scanned: 0,
+        }
+    }
+
+    // Larger frames fail with CodecError::FrameTooLarge, on both ends.
+    pub fn max_payload(mut self, bytes: usize) -> Self {
+        self.max_payload = bytes;
+        self
+    }
+
+    // The most a frame of max_payload can take on the wire, delimiter excluded.
+    fn max_encoded(&self) -> usize {
+        let len = HEADER_LEN + self.max_payload;
+        len + len / 254 + 1
+    }
+}
+
+impl Decoder for CobsCodec {
+    type Item = Frame;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, CodecError> {
+        loop {
+            let Some(offset) = src[self.scanned..].iter().position(|b| *b == 0) else {
+                if src.len() > self.max_encoded() {
+                    return Err(CodecError::FrameTooLarge {
+                        len: src.len(),
+                        max: self.max_payload,
+                    });
+                }
+                self.scanned = src.len();
+                return Ok(None);
+            };
+            let end = self.scanned + offset;
+            self.scanned = 0;
+            let encoded = src.split_to(end + 1);
+            // empty frames: senders may send an extra 0x00 to resynchronize the receiver
+            if end == 0 {
+                continue;
+            }
+            let mut data = unstuff(&encoded[..end], HEADER_LEN + self.max_payload)?;
+            if data.len() < HEADER_LEN {
+                return Err(CodecError::InvalidCobs);
+            }
+            let msg_type = data.get_u16();
+            let flags = Flags::from_bits(data.get_u8());
+            return Ok(Some(Frame {
+                msg_type,
+                flags,
+                payload: data.freeze(),
+            }));
+        }
+    }
+}
+
+impl Encoder<Frame> for CobsCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, frame: Frame, dst: &mut BytesMut) -> Result<(), CodecError> {
+        if frame.payload.len() > self.max_payload {
+            return Err(CodecError::FrameTooLarge {
+                len: frame.payload.len(),
+                max: self.max_payload,
+            });
+        }
+        let mut header = [0; HEADER_LEN];
+        header[..2].copy_from_slice(&frame.msg_type.to_be_bytes());
+        header[2] = frame.flags.bits();
+        let len = HEADER_LEN + frame.payload.len();
+        dst.reserve(len + len / 254 + 2);
+        stuff(header.iter().chain(frame.payload.iter()).copied(), dst);
+        dst.put_u8(0);
+        Ok(())
+    }
+}
+
+// The default encode_with: the payload is stuffed on its way into dst anyway, so there's no in-place path.
+impl Framing for CobsCodec {}
+
+// COBS-encodes `data` onto `dst` (without the delimiter).
+fn stuff(data: impl Iterator<Item = u8>, dst: &mut BytesMut) {
+    // where the current block's code byte goes, filled in when the block ends
+    let mut code_at = dst.len();
+    let mut code = 1u8;
+    dst.put_u8(0);
+    for byte in data {
+        if byte != 0 {
+            dst.put_u8(byte);
+            code += 1;
+        }
+        if byte == 0 || code == 0xFF {
+            dst[code_at] = code;
+            code_at = dst.len();
+            code = 1;
+            dst.put_u8(0);
+        }
+    }
+    dst[code_at] = code;
+}
+
+// Decodes one frame's COBS bytes (delimiter removed); more than `max` bytes is FrameTooLarge.
+fn unstuff(encoded: &[u8], max: usize) -> Result<BytesMut, CodecError> {
+    let mut data = BytesMut::with_capacity(encoded.len());
+    let mut rest = encoded;
+    while let Some((&code, tail)) = rest.split_first() {
+        // the block's data bytes; a code past the end of the frame means a lost byte
+        let len = code as usize - 1;
+        if len > tail.len() {
+            return Err(CodecError::InvalidCobs);
+        }
+        let (block, tail) = tail.split_at(len);
+        data.extend_from_slice(block);
+        rest = tail;
+        // a block ends in an implied zero, except a full one (0xFF) and the last one
+        if code != 0xFF && !rest.is_empty() {
+            data.put_u8(0);
+        }
+        if data.len() > max {
+            return Err(CodecError::FrameTooLarge {
+                len: data.len() - HEADER_LEN,
+                max: max - HEADER_LEN,
+            });
+        }
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn stuffed(data: &[u8]) -> Vec<u8> {
+        let mut dst = BytesMut::new();
+        stuff(data.iter().copied(), &mut dst);
+        dst.to_vec()
+    }
+
+    fn round_trip(codec: &mut CobsCodec, frame: Frame) -> Frame {
+        let mut buf = BytesMut::new();
+        codec.encode(frame, &mut buf).unwrap();
+        // the delimiter is the only zero on the wire
+        assert_eq!(buf.iter().filter(|b| **b == 0).count(), 1);
+        assert_eq!(buf.last(), Some(&0));
+        let decoded = codec.decode(&mut buf).unwrap().unwrap();
+        assert!(buf.is_empty());
+        decoded
+    }
+
+    #[test]
+    fn stuffing_removes_every_zero() {
+        assert_eq!(
+            stuffed(&[0x11, 0x22, 0x00, 0x33]),
+            [0x03, 0x11, 0x22, 0x02, 0x33]
+        );
+        assert_eq!(stuffed(&[]), [0x01]);
+        assert_eq!(stuffed(&[0x00]), [0x01, 0x01]);
+        assert_eq!(stuffed(&[0x00, 0x00]), [0x01, 0x01, 0x01]);
+        // 254 non-zero bytes fill a block: 0xFF, no implied zero, then an empty last block
+        let full: Vec<u8> = (1..=254).collect();
+        let encoded = stuffed(&full);
+        assert_eq!(encoded.len(), 256);
+        assert_eq!((encoded[0], encoded[255]), (0xFF, 0x01));
+    }
+
+    #[test]
+    fn unstuff_inverts_stuff() {
+        let cases: [Vec<u8>; 6] = [
+            vec![],
+            vec![0],
+            vec![1, 0, 2, 0, 0, 3],
+            (0..=255).collect(),
+            vec![7; 254],
+            vec![7; 600],
+        ];
+        for data in cases {
+            let decoded = unstuff(&stuffed(&data), usize::MAX).unwrap();
+            assert_eq!(decoded, data, "{} bytes", data.len());
+        }
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let mut codec = CobsCodec::new();
+        let frame = Frame {
+            msg_type: 0x0100,
+            flags: Flags::from_bits(0x02),
+            payload: Bytes::from_static(&[0, 1, 0, 0, 2, 0]),
+        };
+        assert_eq!(round_trip(&mut codec, frame.clone()), frame);
+        let big = Frame::new(7, vec![0xAB; 1000]);
+        assert_eq!(round_trip(&mut codec, big.clone()), big);
+        let empty = Frame::new(0, Bytes::new());
+        assert_eq!(round_trip(&mut codec, empty.clone()), empty);
+    }
+
+    #[test]
+    fn frames_arriving_in_pieces_are_decoded_once_complete() {
+        let mut codec = CobsCodec::new();
+        let mut wire = BytesMut::new();
+        codec.encode(Frame::new(1, "first"), &mut wire).unwrap();
+        codec.encode(Frame::new(2, "second"), &mut wire).unwrap();
+
+        let mut buf = BytesMut::new();
+        let mut frames = Vec::new();
+        for byte in wire {
+            buf.put_u8(byte);
+            frames.extend(codec.decode(&mut buf).unwrap());
+        }
+        assert_eq!(frames, [Frame::new(1, "first"), Frame::new(2, "second")]);
+    }
+
+    #[test]
+    fn extra_delimiters_are_skipped() {
+        let mut codec = CobsCodec::new();
+        let mut buf = BytesMut::from(&[0, 0][..]);
+        codec.encode(Frame::new(3, "x"), &mut buf).unwrap();
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(Frame::new(3, "x")));
+    }
+
+    #[test]
+    fn a_lost_byte_fails_only_its_frame() {
+        let mut codec = CobsCodec::new();
+        let mut buf = BytesMut::new();
+        codec.encode(Frame::new(1, "lost"), &mut buf).unwrap();
+        // drop the last data byte: the last block's code now runs past the delimiter
+        let end = buf.len() - 2;
+        buf = BytesMut::from(&[&buf[..end], &buf[end + 1..]].concat()[..]);
+        codec.encode(Frame::new(2, "next"), &mut buf).unwrap();
+
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::InvalidCobs)
+        ));
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(Frame::new(2, "next")));
+    }
+
+    #[test]
+    fn frames_shorter_than_the_header_are_invalid() {
+        let mut codec = CobsCodec::new();
+        let mut buf = BytesMut::from(&[0x03, 0x01, 0x02, 0x00][..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::InvalidCobs)
+        ));
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected_on_both_ends() {
+        let mut codec = CobsCodec::new().max_payload(8);
+        let mut buf = BytesMut::new();
+        assert!(matches!(
+            codec.encode(Frame::new(1, vec![1; 9]), &mut buf),
+            Err(CodecError::FrameTooLarge { len: 9, max: 8 })
+        ));
+
+        let mut big = CobsCodec::new();
+        big.encode(Frame::new(1, vec![1; 9]), &mut buf).unwrap();
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::FrameTooLarge { len: 9, max: 8 })
+        ));
+
+        // no delimiter in sight: give up once the bytes can't be a frame of max_payload
+        let mut endless = BytesMut::from(&[0x01; 64][..]);
+        assert!(matches!(
+            codec.decode(&mut endless),
+            Err(CodecError::FrameTooLarge { .. })
+        ));
+    }
+}
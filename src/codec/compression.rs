@@ -0,0 +1,107 @@
+// Payload compression for FrameCodec: zstd (fast, good ratio, for our own peers) or gzip (everyone has it).
+//
+// Key flow:
+// encode: payload ≥ threshold → compressed with the connection's algorithm → kept only if smaller
+//         → Flags::ZSTD / Flags::GZIP set (on every chunk, if it's still too big for one frame)
+// decode: whole message in (chunks reassembled) → flag set → decompressed, at most max_message bytes
+//         (a small compressed frame can't expand into gigabytes: "zip bomb" → MessageTooLarge)
+//
+// Negotiated per connection: each side sends a hello frame listing what it can decode, and only
+// compresses with an algorithm the peer listed (a peer that doesn't know zstd gets gzip or nothing):
+//
+//   let mut conn = Framed::new(stream, FrameCodec::new().compression(Compression::Zstd, 1024));
+//   conn.send(conn.codec().hello()).await?;
+//   let hello = conn.next().await.context("closed during hello")??;
+//   conn.codec_mut().accept_hello(&hello)?;    // → the algorithm this side will send with
+//   let mut conn = conn.map_codec(MessageCodec::<Request>::with_frames);   // typed messages from here on
+//
+// Small payloads aren't worth it (headers, CPU) and compressed data (images, archives) doesn't shrink,
+// hence the threshold and the "only if smaller" check. Decompressed payloads are new buffers,
+// not views into the receive buffer.
+
+use std::io::{self, Read, Write};
+
+use bytes::Bytes;
+use flate2::{read::GzDecoder, write::GzEncoder};
+
+use super::{frame::Flags, CodecError};
+
+// msg_type of hello frames; not for applications
+pub const HELLO: u16 = u16::MAX;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+    Gzip,
+}
+
+impl Compression {
+    // What this build can decode, in order of preference.
+    pub const SUPPORTED: [Compression; 2] = [Compression::Zstd, Compression::Gzip];
+
+    pub(super) fn flag(self) -> Flags {
+        match self {
+            Compression::None => Flags::empty(),
+            Compression::Zstd => Flags::ZSTD,
+            Compression::Gzip => Flags::GZIP,
+        }
+    }
+
+    pub(super) fn from_flags(flags: Flags) -> Compression {
+        if flags.contains(Flags::ZSTD) {
+            Compression::Zstd
+        } else if flags.contains(Flags::GZIP) {
+            Compression::Gzip
+        } else {
+            Compression::None
+        }
+    }
+
+    pub(super) fn compress(self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(payload.to_vec()),
+            Compression::Zstd => zstd::bulk::compress(payload, zstd::DEFAULT_COMPRESSION_LEVEL),
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(payload)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    pub(super) fn decompress(self, payload: &[u8], max: usize) -> Result<Bytes, CodecError> {
+        let mut out = Vec::new();
+        // one byte over the limit is enough to know it's too big
+        let limit = max as u64 + 1;
+        let read = match self {
+            Compression::None => return Ok(Bytes::copy_from_slice(payload)),
+            Compression::Zstd => zstd::stream::read::Decoder::new(payload)
+                .and_then(|decoder| decoder.take(limit).read_to_end(&mut out)),
+            Compression::Gzip => GzDecoder::new(payload).take(limit).read_to_end(&mut out),
+        };
+        read.map_err(CodecError::Decompress)?;
+        if out.len() > max {
+            return Err(CodecError::MessageTooLarge {
+                len: out.len(),
+                max,
+            });
+        }
+        Ok(Bytes::from(out))
+    }
+}
+
+// Hello payload: one byte, a bit per algorithm the sender can decode (the Flags bits).
+pub(super) fn hello_payload() -> Bytes {
+    let bits = Compression::SUPPORTED
+        .iter()
+        .fold(0, |bits, c| bits | c.flag().bits());
+    Bytes::from(vec![bits])
+}
+
+// The peer's decodable algorithms from its hello payload.
+pub(super) fn peer_supports(payload: &[u8], compression: Compression) -> bool {
+    let bits = payload.first().copied().unwrap_or(0);
+    compression == Compression::None || Flags::from_bits(bits).contains(compression.flag())
+}
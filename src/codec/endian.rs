@@ -0,0 +1,171 @@
+// Fixed-size numbers in a chosen byte order, with the order as a type parameter instead of a
+// method name: bytes has put_i64 / put_i64_le / get_i64 / get_i64_le, and a protocol that mixes
+// them up by accident compiles fine. Here the order is picked once, for the Writer / Reader:
+//
+//   let mut out = Writer::<_, LittleEndian>::new(&mut buf);
+//   out.put(0xdeadbeef_i64);                  // 8 bytes: ef be ad de 00 00 00 00
+//   out.put(1.5_f32);
+//   let mut input = Reader::<_, LittleEndian>::new(bytes);
+//   let n: i64 = input.get()?;
+//   let x: f32 = input.get()?;
+//
+// Reads return Err(ShortBuffer) instead of panicking like Buf::get_i64 does on a short buffer, and
+// consume nothing then, so a decoder can wait for more bytes and retry.
+//
+// BigEndian (= NetworkEndian) is what our frame headers, TCP/IP and most file formats use;
+// LittleEndian is what x86/ARM CPUs use in memory, and formats written by dumping structs.
+
+use std::marker::PhantomData;
+
+use bytes::{Buf, BufMut};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("buffer too short: {needed} bytes needed, {remaining} left")]
+pub struct ShortBuffer {
+    pub needed: usize,
+    pub remaining: usize,
+}
+
+// The byte orders; never constructed, they only exist as type parameters.
+#[derive(Debug, Clone, Copy)]
+pub enum BigEndian {}
+#[derive(Debug, Clone, Copy)]
+pub enum LittleEndian {}
+pub type NetworkEndian = BigEndian;
+
+pub trait ByteOrder {
+    fn put<N: Number>(buf: &mut impl BufMut, value: N);
+    // `buf` has at least N::SIZE bytes
+    fn get<N: Number>(buf: &mut impl Buf) -> N;
+}
+
+impl ByteOrder for BigEndian {
+    fn put<N: Number>(buf: &mut impl BufMut, value: N) {
+        value.put_be(buf);
+    }
+
+    fn get<N: Number>(buf: &mut impl Buf) -> N {
+        N::get_be(buf)
+    }
+}
+
+impl ByteOrder for LittleEndian {
+    fn put<N: Number>(buf: &mut impl BufMut, value: N) {
+        value.put_le(buf);
+    }
+
+    fn get<N: Number>(buf: &mut impl Buf) -> N {
+        N::get_le(buf)
+    }
+}
+
+// The fixed-size numbers Buf / BufMut can read and write.
+pub trait Number: Copy {
+    const SIZE: usize;
+
+    fn put_be(self, buf: &mut impl BufMut);
+    fn put_le(self, buf: &mut impl BufMut);
+    fn get_be(buf: &mut impl Buf) -> Self;
+    fn get_le(buf: &mut impl Buf) -> Self;
+}
+
+// One impl per type, mapping to the bytes method of each order.
+macro_rules! number {
+    ($($ty:ty => $put_be:ident $put_le:ident $get_be:ident $get_le:ident),* $(,)?) => {$(
+        impl Number for $ty {
+            const SIZE: usize = std::mem::size_of::<$ty>();
+
+            fn put_be(self, buf: &mut impl BufMut) {
+                buf.$put_be(self);
+            }
+
+            fn put_le(self, buf: &mut impl BufMut) {
+                buf.$put_le(self);
+            }
+
+            fn get_be(buf: &mut impl Buf) -> Self {
+                buf.$get_be()
+            }
+
+            fn get_le(buf: &mut impl Buf) -> Self {
+                buf.$get_le()
+            }
+        }
+    )*};
+}
+
+number! {
+    u16 => put_u16 put_u16_le get_u16 get_u16_le,
+    u32 => put_u32 put_u32_le get_u32 get_u32_le,
+    u64 => put_u64 put_u64_le get_u64 get_u64_le,
+    u128 => put_u128 put_u128_le get_u128 get_u128_le,
+    i16 => put_i16 put_i16_le get_i16 get_i16_le,
+    i32 => put_i32 put_i32_le get_i32 get_i32_le,
+    i64 => put_i64 put_i64_le get_i64 get_i64_le,
+    i128 => put_i128 put_i128_le get_i128 get_i128_le,
+    f32 => put_f32 put_f32_le get_f32 get_f32_le,
+    f64 => put_f64 put_f64_le get_f64 get_f64_le,
+}
+
+// Writes numbers in byte order O into any BufMut (BytesMut, Vec<u8>, &mut [u8] ...).
+#[derive(Debug)]
+pub struct Writer<B, O> {
+    buf: B,
+    _order: PhantomData<O>,
+}
+
+impl<B: BufMut, O: ByteOrder> Writer<B, O> {
+    pub fn new(buf: B) -> Self {
+        Self {
+            buf,
+            _order: PhantomData,
+        }
+    }
+
+    // Like BufMut's put_*: panics if a fixed-size buffer (&mut [u8]) is full.
+    pub fn put<N: Number>(&mut self, value: N) -> &mut Self {
+        O::put(&mut self.buf, value);
+        self
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+// Reads numbers in byte order O from any Buf (Bytes, BytesMut, &[u8] ...).
+#[derive(Debug)]
+pub struct Reader<B, O> {
+    buf: B,
+    _order: PhantomData<O>,
+}
+
+impl<B: Buf, O: ByteOrder> Reader<B, O> {
+    pub fn new(buf: B) -> Self {
+        Self {
+            buf,
+            _order: PhantomData,
+        }
+    }
+
+    pub fn get<N: Number>(&mut self) -> Result<N, ShortBuffer> {
+        let remaining = self.buf.remaining();
+        if remaining < N::SIZE {
+            return Err(ShortBuffer {
+                needed: N::SIZE,
+                remaining,
+            });
+        }
+        Ok(O::get(&mut self.buf))
+    }
+
+    // Bytes not read yet.
+    pub fn remaining(&self) -> usize {
+        self.buf.remaining()
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
@@ -0,0 +1,142 @@
+// SendFramed / RecvFramed: one typed message at a time over any AsyncWrite / AsyncRead, without
+// setting up a Framed (request/response code, handshakes, tests):
+//
+//   use ecosystem::codec::{Message, RecvFramed, SendFramed};
+//   stream.send_message(Message::new(PING, Command::Ping { seq: 1 })).await?;
+//   let reply: Option<Message<Command>> = stream.recv_message().await?;   // None: the peer closed
+//
+// Key flow:
+// send_message → MessageCodec::encode into a buffer (chunks and all) → write_all → flush
+// recv_message → read exactly one header → FrameCodec::frame_len checks it (magic, version, size limit)
+//                → read exactly the rest of the frame → FrameCodec::decode → another chunk? read the next frame
+//
+// Reads never go past the end of the message, so nothing is lost between calls, and the stream can be
+// handed to a Framed (or anything else) afterwards. Framed is still the better fit for a long-lived
+// connection: it reads in big chunks instead of two reads per frame.
+// The *_with variants take the connection's FrameCodec (size limits, checksums, negotiated compression);
+// read_timeout doesn't apply here, wrap the call in tokio::time::timeout instead.
+
+use std::future::Future;
+
+use bytes::BytesMut;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::{
+    frame::{Frame, FrameCodec, HEADER_LEN},
+    message::{Message, MessageCodec},
+    CodecError,
+};
+
+pub trait SendFramed {
+    fn send_message<T>(
+        &mut self,
+        message: Message<T>,
+    ) -> impl Future<Output = Result<(), CodecError>> + Send
+    where
+        T: Serialize + Send;
+
+    fn send_message_with<T>(
+        &mut self,
+        codec: &FrameCodec,
+        message: Message<T>,
+    ) -> impl Future<Output = Result<(), CodecError>> + Send
+    where
+        T: Serialize + Send;
+}
+
+pub trait RecvFramed {
+    fn recv_message<T>(
+        &mut self,
+    ) -> impl Future<Output = Result<Option<Message<T>>, CodecError>> + Send
+    where
+        T: DeserializeOwned;
+
+    fn recv_message_with<T>(
+        &mut self,
+        codec: &mut FrameCodec,
+    ) -> impl Future<Output = Result<Option<Message<T>>, CodecError>> + Send
+    where
+        T: DeserializeOwned;
+
+    // The next whole message as a Frame (chunks reassembled, decompressed); None at a clean end of stream.
+    fn recv_frame(
+        &mut self,
+        codec: &mut FrameCodec,
+    ) -> impl Future<Output = Result<Option<Frame>, CodecError>> + Send;
+}
+
+impl<W: AsyncWrite + Unpin + Send + ?Sized> SendFramed for W {
+    async fn send_message<T>(&mut self, message: Message<T>) -> Result<(), CodecError>
+    where
+        T: Serialize + Send,
+    {
+        self.send_message_with(&FrameCodec::new(), message).await
+    }
+
+    async fn send_message_with<T>(
+        &mut self,
+        codec: &FrameCodec,
+        message: Message<T>,
+    ) -> Result<(), CodecError>
+    where
+        T: Serialize + Send,
+    {
+        let mut buf = BytesMut::new();
+        MessageCodec::with_frames(codec.clone()).encode(message, &mut buf)?;
+        self.write_all(&buf).await?;
+        self.flush().await?;
+        Ok(())
+    }
+}
+
+impl<R: AsyncRead + Unpin + Send + ?Sized> RecvFramed for R {
+    async fn recv_message<T>(&mut self) -> Result<Option<Message<T>>, CodecError>
+    where
+        T: DeserializeOwned,
+    {
+        self.recv_message_with(&mut FrameCodec::new()).await
+    }
+
+    async fn recv_message_with<T>(
+        &mut self,
+        codec: &mut FrameCodec,
+    ) -> Result<Option<Message<T>>, CodecError>
+    where
+        T: DeserializeOwned,
+    {
+        match self.recv_frame(codec).await? {
+            Some(frame) => Message::from_frame(&frame).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn recv_frame(&mut self, codec: &mut FrameCodec) -> Result<Option<Frame>, CodecError> {
+        let mut buf = BytesMut::new();
+        let mut first = true;
+        loop {
+            // the header: EOF before its first byte is the peer closing between messages
+            buf.resize(HEADER_LEN, 0);
+            let mut filled = 0;
+            while filled < HEADER_LEN {
+                let n = self.read(&mut buf[filled..]).await?;
+                if n == 0 {
+                    if filled == 0 && first {
+                        return Ok(None);
+                    }
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                }
+                filled += n;
+            }
+            let frame_len = codec.frame_len(&buf)?;
+            buf.resize(frame_len, 0);
+            self.read_exact(&mut buf[HEADER_LEN..]).await?;
+            // None: that was a chunk (Flags::MORE), the codec keeps it until the last one
+            if let Some(frame) = codec.decode(&mut buf)? {
+                return Ok(Some(frame));
+            }
+            first = false;
+        }
+    }
+}
@@ -0,0 +1,752 @@
+// FrameCodec: the frame format of our own wire protocol, a fixed 10-byte header then the payload:
+//
+//   0      2         3              5       6                10
+//   ┌──────┬─────────┬──────────────┬───────┬────────────────┬──────────────────┐
+//   │ 0xEC │ version │ message type │ flags │ payload length │ payload ...      │
+//   │ 0x05 │ (1)     │ u16          │ u8    │ u32            │ (length bytes)   │
+//   └──────┴─────────┴──────────────┴───────┴────────────────┴──────────────────┘
+//   all integers big-endian (network byte order)
+//
+// The magic catches a peer speaking something else (HTTP, TLS, an old build) on the first frame instead
+// of reading garbage lengths; the version lets the header change later without guessing.
+// The message type says what the payload is (codec::message maps it to a Rust type), the flags how
+// it's encoded. Frames with a bad magic, an unknown version or a length over the limit are decode errors.
+//
+// Flags::CHECKSUM: a CRC32 of the payload sits between header and payload (4 bytes, not counted in the
+// length). Set by FrameCodec::checksums(true) on encode; on decode every frame that carries one is
+// verified, so a corrupted payload fails with CodecError::ChecksumMismatch before serde sees it.
+// TCP has its own (16-bit) checksum; this is for links and hops that don't, or don't reliably
+// (serial lines, UDP reassembly, buggy middleboxes). Peers without checksums enabled still read them.
+//
+// Zero-copy: a decoded Frame's payload is a Bytes view into the receive buffer (split_to + freeze),
+// not a fresh Vec, and Frame::body deserializes borrowing from it (&str / &[u8] fields point into the
+// payload). The flip side: a payload view keeps its part of the receive buffer alive, so a small
+// payload held for long pins that memory; copy it (Bytes::copy_from_slice) if it's kept around.
+//
+// Messages larger than one frame (FrameCodec::max_message above max_payload): the payload is sent as
+// several frames of the same message type, all but the last with Flags::MORE:
+//
+//   [type 7, MORE | 8 MiB] [type 7, MORE | 8 MiB] [type 7 | 3 MiB]   →   one Frame, type 7, 19 MiB
+//
+// The decoder collects the chunks (a copy: they arrive in separate reads) and yields the whole message
+// once the last one is in, failing with MessageTooLarge past max_message, so a peer can't make it
+// buffer without bound. Chunks of one message must not be interleaved with other frames.
+//
+// Flags::ZSTD / Flags::GZIP: the (whole message's) payload is compressed, see codec/compression.rs.
+//
+// Flags::BINCODE: the payload is bincode (standard config: varint integers, little-endian) instead
+// of MessagePack, see codec/bincode_body.rs. Frame::body and MessageCodec read it either way; a
+// MessageCodec::bincode() sender sets it. Compatibility, since bincode carries no field names:
+//   - the flag is per frame, so one connection can mix both encodings, and a receiver decodes a
+//     frame by its flag, never by its own settings
+//   - a body type changed in any way (field added, reordered, retyped) is a new msg_type: bincode
+//     would read the old bytes into the new fields without noticing, or fail with leftover bytes
+//   - the header VERSION stays 1: the flag used a bit that was always 0. Peers built before it
+//     ignore unknown flags and would read the payload as MessagePack, so switch senders to
+//     bincode only once every receiver is upgraded; one built without the "bincode" feature fails
+//     such frames with CodecError::BincodeDisabled
+//
+// Untrusted peers: the length in a header is checked against max_payload before anything else,
+// and buffer space is reserved as the payload actually arrives, not for the announced length up
+// front (a header alone can't make us allocate 8 MiB). read_timeout bounds how long a started
+// frame may take to arrive, see codec/slow_read.rs.
+
+use std::time::Duration;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use serde::Deserialize;
+use tokio::time::Instant;
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::{
+    bincode_body,
+    compression::{self, Compression, HELLO},
+    slow_read::ReadDeadline,
+    CodecError,
+};
+
+pub const MAGIC: u16 = 0xEC05;
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 10;
+pub const CHECKSUM_LEN: usize = 4;
+// most buffer space reserved ahead for an incomplete frame; the rest grows as bytes arrive
+const RESERVE_LIMIT: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Flags(u8);
+
+impl Flags {
+    // a CRC32 of the payload follows the header
+    pub const CHECKSUM: Flags = Flags(0b0000_0001);
+    // more chunks of this message follow
+    pub const MORE: Flags = Flags(0b0000_0010);
+    // the payload is compressed (codec/compression.rs)
+    pub const ZSTD: Flags = Flags(0b0000_0100);
+    pub const GZIP: Flags = Flags(0b0000_1000);
+    // the payload is bincode, not MessagePack (codec/bincode_body.rs)
+    pub const BINCODE: Flags = Flags(0b0001_0000);
+
+    pub const fn empty() -> Self {
+        Flags(0)
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Flags(bits)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn contains(self, other: Flags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Flags) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Flags) {
+        self.0 &= !other.0;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub msg_type: u16,
+    pub flags: Flags,
+    pub payload: Bytes,
+}
+
+impl Frame {
+    pub fn new(msg_type: u16, payload: impl Into<Bytes>) -> Self {
+        Self {
+            msg_type,
+            flags: Flags::empty(),
+            payload: payload.into(),
+        }
+    }
+
+    // The MessagePack (or, with Flags::BINCODE, bincode) payload as T, borrowing from the frame
+    // where T allows it:
+    //   #[derive(Deserialize)] struct Log<'a> { level: &'a str, #[serde(borrow)] line: Cow<'a, str> }
+    //   let log: Log = frame.body()?;   // no String allocated per field
+    pub fn body<'de, T: Deserialize<'de>>(&'de self) -> Result<T, CodecError> {
+        if self.flags.contains(Flags::BINCODE) {
+            return bincode_body::decode(&self.payload);
+        }
+        Ok(rmp_serde::from_slice(&self.payload)?)
+    }
+}
+
+#[derive(Debug)]
+pub struct FrameCodec {
+    max_payload: usize,
+    max_message: usize,
+    checksums: bool,
+    compression: Compression,
+    // payloads smaller than this are sent uncompressed
+    compress_above: usize,
+    read_timeout: Option<Duration>,
+    // when the first byte of the frame (or chunked message) being received arrived
+    started: Option<Instant>,
+    // the chunks of a message received so far
+    partial: Option<Partial>,
+}
+
+#[derive(Debug)]
+struct Partial {
+    msg_type: u16,
+    flags: Flags,
+    payload: BytesMut,
+}
+
+impl Default for FrameCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The settings; a clone starts without a half-received message.
+impl Clone for FrameCodec {
+    fn clone(&self) -> Self {
+        Self {
+            max_payload: self.max_payload,
+            max_message: self.max_message,
+            checksums: self.checksums,
+            compression: self.compression,
+            compress_above: self.compress_above,
+            read_timeout: self.read_timeout,
+            started: None,
+            partial: None,
+        }
+    }
+}
+
+impl FrameCodec {
+    // Payloads up to 8 MiB, in one frame.
+    pub fn new() -> Self {
+        Self {
+            max_payload: 8 * 1024 * 1024,
+            max_message: 8 * 1024 * 1024,
+            checksums: false,
+            compression: Compression::None,
+            compress_above: 0,
+            read_timeout: None,
+            started: None,
+            partial: None,
+        }
+    }
+
+    // Larger frames fail to decode with CodecError::FrameTooLarge; larger payloads are encoded in
+    // chunks (up to max_message, which is raised to at least this).
+    pub fn max_payload(mut self, bytes: usize) -> Self {
+        self.max_payload = bytes.max(1);
+        self.max_message = self.max_message.max(self.max_payload);
+        self
+    }
+
+    // Messages up to `bytes`, sent as several frames when they're over max_payload;
+    // larger ones fail with CodecError::MessageTooLarge.
+    pub fn max_message(mut self, bytes: usize) -> Self {
+        self.max_message = bytes.max(self.max_payload);
+        self
+    }
+
+    // Adds a CRC32 to every encoded frame (decoding verifies them either way).
+    pub fn checksums(mut self, enabled: bool) -> Self {
+        self.checksums = enabled;
+        self
+    }
+
+    // Compresses payloads of at least `above` bytes with `algorithm` (decoding handles every algorithm
+    // either way). Until the hello exchange, the peer is assumed to support it.
+    pub fn compression(mut self, algorithm: Compression, above: usize) -> Self {
+        self.compression = algorithm;
+        self.compress_above = above;
+        self
+    }
+
+    // A frame, or all chunks of a message, must arrive within `limit` of its first byte; after that
+    // decoding fails with CodecError::ReadTimeout. Use codec::recv to also catch a peer that stops sending.
+    pub fn read_timeout(mut self, limit: Duration) -> Self {
+        self.read_timeout = Some(limit);
+        self
+    }
+
+    // This side's hello: the compression algorithms it can decode. Send it first on a new connection.
+    pub fn hello(&self) -> Frame {
+        Frame::new(HELLO, compression::hello_payload())
+    }
+
+    // Reads the peer's hello; compression stays on only if the peer can decode our algorithm.
+    // Returns the algorithm this side sends with from now on.
+    pub fn accept_hello(&mut self, hello: &Frame) -> Result<Compression, CodecError> {
+        if hello.msg_type != HELLO {
+            return Err(CodecError::ExpectedHello(hello.msg_type));
+        }
+        if !compression::peer_supports(&hello.payload, self.compression) {
+            self.compression = Compression::None;
+        }
+        Ok(self.compression)
+    }
+
+    // Writes a message whose payload `write` puts straight into `dst` after the header (MessageCodec
+    // serializes the body there), so the payload isn't built separately and copied in.
+    // A payload to compress, or over max_payload, is moved out again and goes through encode_payload.
+    fn encode_in_place<F>(
+        &self,
+        msg_type: u16,
+        flags: Flags,
+        dst: &mut BytesMut,
+        write: F,
+    ) -> Result<(), CodecError>
+    where
+        F: FnOnce(&mut BytesMut) -> Result<(), CodecError>,
+    {
+        let flags = self.flags(flags);
+        let start = dst.len();
+        self.put_header(msg_type, flags, 0, dst);
+        let payload_start = dst.len();
+        if let Err(e) = write(dst) {
+            dst.truncate(start);
+            return Err(e);
+        }
+        let len = dst.len() - payload_start;
+        if len > self.max_payload || self.compresses(len) {
+            let payload = dst.split_off(payload_start);
+            dst.truncate(start);
+            return self.encode_payload(msg_type, flags, &payload, dst);
+        }
+        // length and checksum are filled in once the payload is written
+        dst[start + 6..start + HEADER_LEN].copy_from_slice(&(len as u32).to_be_bytes());
+        if flags.contains(Flags::CHECKSUM) {
+            let crc = crc32fast::hash(&dst[payload_start..]);
+            dst[payload_start - CHECKSUM_LEN..payload_start].copy_from_slice(&crc.to_be_bytes());
+        }
+        Ok(())
+    }
+
+    fn compresses(&self, len: usize) -> bool {
+        self.compression != Compression::None && len >= self.compress_above
+    }
+
+    // Compression (if it pays off), then one frame or several chunks.
+    fn encode_payload(
+        &self,
+        msg_type: u16,
+        mut flags: Flags,
+        payload: &[u8],
+        dst: &mut BytesMut,
+    ) -> Result<(), CodecError> {
+        let compressed = if self.compresses(payload.len()) {
+            Some(self.compression.compress(payload)?).filter(|c| c.len() < payload.len())
+        } else {
+            None
+        };
+        let payload = match &compressed {
+            Some(compressed) => {
+                flags.insert(self.compression.flag());
+                &compressed[..]
+            }
+            None => payload,
+        };
+        if payload.len() > self.max_payload {
+            return self.encode_chunks(msg_type, flags, payload, dst);
+        }
+        dst.reserve(HEADER_LEN + CHECKSUM_LEN + payload.len());
+        self.put_frame(msg_type, flags, payload, dst);
+        Ok(())
+    }
+
+    fn flags(&self, mut flags: Flags) -> Flags {
+        flags.remove(Flags::MORE);
+        flags.remove(Flags::ZSTD);
+        flags.remove(Flags::GZIP);
+        if self.checksums {
+            flags.insert(Flags::CHECKSUM);
+        } else {
+            flags.remove(Flags::CHECKSUM);
+        }
+        flags
+    }
+
+    // Header, with a zeroed checksum if the flags call for one.
+    fn put_header(&self, msg_type: u16, flags: Flags, len: usize, dst: &mut BytesMut) {
+        dst.put_u16(MAGIC);
+        dst.put_u8(VERSION);
+        dst.put_u16(msg_type);
+        dst.put_u8(flags.bits());
+        dst.put_u32(len as u32);
+        if flags.contains(Flags::CHECKSUM) {
+            dst.put_u32(0);
+        }
+    }
+
+    fn put_frame(&self, msg_type: u16, flags: Flags, payload: &[u8], dst: &mut BytesMut) {
+        self.put_header(msg_type, flags, payload.len(), dst);
+        if flags.contains(Flags::CHECKSUM) {
+            let at = dst.len() - CHECKSUM_LEN;
+            dst[at..].copy_from_slice(&crc32fast::hash(payload).to_be_bytes());
+        }
+        dst.extend_from_slice(payload);
+    }
+
+    fn encode_chunks(
+        &self,
+        msg_type: u16,
+        flags: Flags,
+        payload: &[u8],
+        dst: &mut BytesMut,
+    ) -> Result<(), CodecError> {
+        if payload.len() > self.max_message {
+            return Err(CodecError::MessageTooLarge {
+                len: payload.len(),
+                max: self.max_message,
+            });
+        }
+        let chunks = payload.len().div_ceil(self.max_payload).max(1);
+        dst.reserve(payload.len() + chunks * (HEADER_LEN + CHECKSUM_LEN));
+        let mut rest = payload;
+        loop {
+            let (chunk, tail) = rest.split_at(rest.len().min(self.max_payload));
+            rest = tail;
+            let mut chunk_flags = flags;
+            if !rest.is_empty() {
+                chunk_flags.insert(Flags::MORE);
+            }
+            self.put_frame(msg_type, chunk_flags, chunk, dst);
+            if rest.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    // The checked header's whole frame length: header, checksum and payload.
+    pub(super) fn frame_len(&self, mut header: &[u8]) -> Result<usize, CodecError> {
+        let magic = header.get_u16();
+        if magic != MAGIC {
+            return Err(CodecError::BadMagic(magic));
+        }
+        let version = header.get_u8();
+        if version != VERSION {
+            return Err(CodecError::UnsupportedVersion(version));
+        }
+        let _msg_type = header.get_u16();
+        let flags = Flags(header.get_u8());
+        let len = header.get_u32() as usize;
+        if len > self.max_payload {
+            return Err(CodecError::FrameTooLarge {
+                len,
+                max: self.max_payload,
+            });
+        }
+        let checksum_len = if flags.contains(Flags::CHECKSUM) {
+            CHECKSUM_LEN
+        } else {
+            0
+        };
+        Ok(HEADER_LEN + checksum_len + len)
+    }
+
+    // One frame off the front of `src`, chunk or not.
+    fn decode_frame(&self, src: &mut BytesMut) -> Result<Option<Frame>, CodecError> {
+        if src.len() < HEADER_LEN {
+            return Ok(None);
+        }
+        // peek at the header; it's only consumed once the whole frame is there
+        let frame_len = self.frame_len(&src[..HEADER_LEN])?;
+        if src.len() < frame_len {
+            src.reserve((frame_len - src.len()).min(RESERVE_LIMIT));
+            return Ok(None);
+        }
+        // after magic and version
+        let mut header = &src[3..HEADER_LEN];
+        let msg_type = header.get_u16();
+        let flags = Flags(header.get_u8());
+        let len = header.get_u32() as usize;
+        let checksum_len = frame_len - HEADER_LEN - len;
+        src.advance(HEADER_LEN);
+        let expected = (checksum_len > 0).then(|| src.get_u32());
+        let payload = src.split_to(len).freeze();
+        if let Some(expected) = expected {
+            let actual = crc32fast::hash(&payload);
+            if actual != expected {
+                return Err(CodecError::ChecksumMismatch { expected, actual });
+            }
+        }
+        Ok(Some(Frame {
+            msg_type,
+            flags,
+            payload,
+        }))
+    }
+}
+
+impl Decoder for FrameCodec {
+    type Item = Frame;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, CodecError> {
+        let Some(mut frame) = self.decode_message(src)? else {
+            if src.is_empty() && self.partial.is_none() {
+                self.started = None;
+            } else {
+                let started = *self.started.get_or_insert_with(Instant::now);
+                if self
+                    .read_timeout
+                    .is_some_and(|limit| started.elapsed() > limit)
+                {
+                    return Err(CodecError::ReadTimeout);
+                }
+            }
+            return Ok(None);
+        };
+        // the next frame's clock starts with its first byte
+        self.started = None;
+        let compression = Compression::from_flags(frame.flags);
+        if compression != Compression::None {
+            frame.payload = compression.decompress(&frame.payload, self.max_message)?;
+            frame.flags.remove(compression.flag());
+        }
+        Ok(Some(frame))
+    }
+}
+
+impl FrameCodec {
+    // A whole message: one frame, or its chunks reassembled.
+    fn decode_message(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, CodecError> {
+        // several chunks may be in the buffer already: keep going until a whole message is there
+        while let Some(frame) = self.decode_frame(src)? {
+            let more = frame.flags.contains(Flags::MORE);
+            let Some(partial) = &mut self.partial else {
+                if !more {
+                    return Ok(Some(frame));
+                }
+                let mut flags = frame.flags;
+                flags.remove(Flags::MORE);
+                self.partial = Some(Partial {
+                    msg_type: frame.msg_type,
+                    flags,
+                    payload: BytesMut::from(&frame.payload[..]),
+                });
+                continue;
+            };
+            if frame.msg_type != partial.msg_type {
+                return Err(CodecError::InterleavedChunks {
+                    expected: partial.msg_type,
+                    got: frame.msg_type,
+                });
+            }
+            let len = partial.payload.len() + frame.payload.len();
+            if len > self.max_message {
+                return Err(CodecError::MessageTooLarge {
+                    len,
+                    max: self.max_message,
+                });
+            }
+            partial.payload.extend_from_slice(&frame.payload);
+            if !more {
+                let Partial {
+                    msg_type,
+                    flags,
+                    payload,
+                } = self.partial.take().expect("partial message present");
+                return Ok(Some(Frame {
+                    msg_type,
+                    flags,
+                    payload: payload.freeze(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl ReadDeadline for FrameCodec {
+    fn read_deadline(&self) -> Option<Instant> {
+        Some(self.started? + self.read_timeout?)
+    }
+}
+
+impl Encoder<Frame> for FrameCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, frame: Frame, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let flags = self.flags(frame.flags);
+        self.encode_payload(frame.msg_type, flags, &frame.payload, dst)
+    }
+}
+
+// A framing that MessageCodec can sit on: Frames (message type, flags, payload) in and out.
+// FrameCodec is ours; CobsCodec (codec/cobs.rs) frames the same Frames for serial-like links.
+pub trait Framing:
+    Decoder<Item = Frame, Error = CodecError> + Encoder<Frame, Error = CodecError>
+{
+    // Encodes a frame whose payload `write` produces. By default the payload is written to a buffer
+    // of its own and then encoded as a Frame; FrameCodec writes it straight into `dst`.
+    fn encode_with<W>(
+        &mut self,
+        msg_type: u16,
+        flags: Flags,
+        dst: &mut BytesMut,
+        write: W,
+    ) -> Result<(), CodecError>
+    where
+        W: FnOnce(&mut BytesMut) -> Result<(), CodecError>,
+    {
+        let mut payload = BytesMut::new();
+        write(&mut payload)?;
+        self.encode(
+            Frame {
+                msg_type,
+                flags,
+                payload: payload.freeze(),
+            },
+            dst,
+        )
+    }
+}
+
+impl Framing for FrameCodec {
+    fn encode_with<W>(
+        &mut self,
+        msg_type: u16,
+        flags: Flags,
+        dst: &mut BytesMut,
+        write: W,
+    ) -> Result<(), CodecError>
+    where
+        W: FnOnce(&mut BytesMut) -> Result<(), CodecError>,
+    {
+        self.encode_in_place(msg_type, flags, dst, write)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(codec: &mut FrameCodec, frame: Frame) -> BytesMut {
+        let mut buf = BytesMut::new();
+        codec.encode(frame, &mut buf).unwrap();
+        buf
+    }
+
+    // Every whole message in `buf`.
+    fn decode_all(codec: &mut FrameCodec, buf: &mut BytesMut) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        while let Some(frame) = codec.decode(buf).unwrap() {
+            frames.push(frame);
+        }
+        frames
+    }
+
+    #[test]
+    fn frames_round_trip_with_the_documented_header() {
+        let mut codec = FrameCodec::new();
+        let mut buf = encode(&mut codec, Frame::new(0x0102, "hi"));
+        assert_eq!(
+            &buf[..],
+            [0xEC, 0x05, VERSION, 0x01, 0x02, 0x00, 0, 0, 0, 2, b'h', b'i']
+        );
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::new(0x0102, "hi"))
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn partial_frames_wait_for_the_rest() {
+        let mut codec = FrameCodec::new();
+        let wire = encode(&mut codec, Frame::new(1, "payload"));
+        let mut buf = BytesMut::new();
+        for (i, byte) in wire.iter().enumerate() {
+            buf.put_u8(*byte);
+            let decoded = codec.decode(&mut buf).unwrap();
+            assert_eq!(
+                decoded.is_some(),
+                i == wire.len() - 1,
+                "after {} bytes",
+                i + 1
+            );
+        }
+    }
+
+    #[test]
+    fn checksums_are_verified() {
+        let mut codec = FrameCodec::new().checksums(true);
+        let mut buf = encode(&mut codec, Frame::new(1, "data"));
+        assert_eq!(buf.len(), HEADER_LEN + CHECKSUM_LEN + 4);
+        // a receiver without checksums enabled still verifies them
+        let frame = FrameCodec::new().decode(&mut buf.clone()).unwrap().unwrap();
+        assert!(frame.flags.contains(Flags::CHECKSUM));
+        assert_eq!(frame.payload, "data");
+
+        let last = buf.len() - 1;
+        buf[last] ^= 0xFF;
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn foreign_and_future_headers_are_rejected() {
+        let mut codec = FrameCodec::new();
+        let mut http = BytesMut::from(&b"GET / HTTP/1.1\r\n"[..]);
+        assert!(matches!(
+            codec.decode(&mut http),
+            Err(CodecError::BadMagic(0x4745))
+        ));
+
+        let mut buf = encode(&mut codec, Frame::new(1, "x"));
+        buf[2] = VERSION + 1;
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::UnsupportedVersion(v)) if v == VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn an_oversized_length_fails_on_the_header_alone() {
+        let mut codec = FrameCodec::new().max_payload(16);
+        let mut buf = BytesMut::new();
+        codec.put_header(1, Flags::empty(), 17, &mut buf);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::FrameTooLarge { len: 17, max: 16 })
+        ));
+
+        // a header announcing 4 GiB doesn't reserve 4 GiB
+        let mut codec = FrameCodec::new().max_payload(u32::MAX as usize);
+        let mut buf = BytesMut::new();
+        codec.put_header(1, Flags::empty(), u32::MAX as usize, &mut buf);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert!(buf.capacity() <= HEADER_LEN + RESERVE_LIMIT);
+    }
+
+    #[test]
+    fn large_messages_are_chunked_and_reassembled() {
+        let mut codec = FrameCodec::new().max_payload(4).max_message(10);
+        let mut buf = encode(&mut codec, Frame::new(9, "0123456789"));
+        // 4 + 4 + 2, MORE on all but the last
+        assert_eq!(buf.len(), 3 * HEADER_LEN + 10);
+        assert_eq!(
+            Flags::from_bits(buf[5]),
+            Flags::MORE,
+            "first chunk without MORE"
+        );
+
+        let frames = decode_all(&mut codec, &mut buf);
+        assert_eq!(frames, [Frame::new(9, "0123456789")]);
+
+        assert!(matches!(
+            codec.encode(Frame::new(9, "0123456789A"), &mut buf),
+            Err(CodecError::MessageTooLarge { len: 11, max: 10 })
+        ));
+    }
+
+    #[test]
+    fn reassembly_stops_at_max_message() {
+        let mut sender = FrameCodec::new().max_payload(4).max_message(100);
+        let mut buf = encode(&mut sender, Frame::new(9, vec![1; 12]));
+
+        let mut receiver = FrameCodec::new().max_payload(4).max_message(8);
+        assert!(matches!(
+            receiver.decode(&mut buf),
+            Err(CodecError::MessageTooLarge { len: 12, max: 8 })
+        ));
+    }
+
+    #[test]
+    fn interleaved_chunks_are_rejected() {
+        let mut codec = FrameCodec::new().max_payload(4).max_message(100);
+        let mut buf = BytesMut::new();
+        let mut more = Flags::empty();
+        more.insert(Flags::MORE);
+        codec.put_frame(9, more, b"abcd", &mut buf);
+        codec.put_frame(3, Flags::empty(), b"x", &mut buf);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::InterleavedChunks {
+                expected: 9,
+                got: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn compressed_payloads_round_trip() {
+        for algorithm in [Compression::Zstd, Compression::Gzip] {
+            let mut codec = FrameCodec::new().compression(algorithm, 16);
+            let payload = vec![b'a'; 4096];
+            let mut buf = encode(&mut codec, Frame::new(1, payload.clone()));
+            assert!(buf.len() < HEADER_LEN + payload.len());
+            let frame = codec.decode(&mut buf).unwrap().unwrap();
+            assert_eq!(frame.payload, payload);
+            assert_eq!(frame.flags, Flags::empty());
+        }
+    }
+}
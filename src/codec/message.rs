@@ -0,0 +1,169 @@
+// Message<T>: a typed value in a frame. The body is serialized with MessagePack (rmp-serde, compact and
+// self-describing, one of the formats in crate::formats), the message type goes in the frame header.
+// MessageCodec::bincode() sends bincode bodies instead (Flags::BINCODE, --features bincode): smaller
+// and faster, for processes built from the same code; any MessageCodec decodes both.
+//
+// Key flow:
+// send: Message::new(PING, Ping { seq: 1 }) → MessageCodec::encode → body to MessagePack → Frame → header + bytes
+// recv: bytes → FrameCodec::decode → Frame → body from MessagePack → Message<T>
+//
+// One T per connection: for a protocol with several kinds of messages make T an enum (serde tags the
+// variant), and use msg_type for what the receiver must know before decoding (routing, versioning).
+//
+//   let mut conn = Framed::new(stream, MessageCodec::<Request>::new());
+//   conn.send(Message::new(1, Request::Get { key: "a".into() })).await?;
+//   let reply: Message<Request> = conn.next().await.transpose()?.context("connection closed")?;
+//
+// No payload copies: MessageCodec serializes the body straight into the write buffer, and decodes it
+// from the receive buffer (frame payloads are Bytes views, see codec/frame.rs). For bodies that borrow
+// (&str fields, high message rates), decode with FrameCodec and Frame::body instead: MessageCodec's T
+// has to be owned, since each message outlives the buffer it came from.
+//
+// The framing underneath is a type parameter, FrameCodec by default: MessageCodec::with_frames(CobsCodec::new())
+// sends the same messages over a zero-delimited serial link (codec/cobs.rs).
+
+use std::marker::PhantomData;
+
+use bytes::{BufMut, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio_util::codec::{Decoder, Encoder};
+
+use tokio::time::Instant;
+
+use super::{
+    bincode_body,
+    frame::{Flags, Frame, FrameCodec, Framing},
+    slow_read::ReadDeadline,
+    CodecError,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message<T> {
+    pub msg_type: u16,
+    pub flags: Flags,
+    pub body: T,
+}
+
+impl<T> Message<T> {
+    pub fn new(msg_type: u16, body: T) -> Self {
+        Self {
+            msg_type,
+            flags: Flags::empty(),
+            body,
+        }
+    }
+}
+
+impl<T: Serialize> Message<T> {
+    // The body as bincode when the flags have Flags::BINCODE, MessagePack otherwise.
+    pub fn to_frame(&self) -> Result<Frame, CodecError> {
+        let mut payload = BytesMut::new();
+        write_body(self.flags, &self.body, &mut payload)?;
+        Ok(Frame {
+            msg_type: self.msg_type,
+            flags: self.flags,
+            payload: payload.freeze(),
+        })
+    }
+}
+
+impl<T: DeserializeOwned> Message<T> {
+    pub fn from_frame(frame: &Frame) -> Result<Self, CodecError> {
+        Ok(Self {
+            msg_type: frame.msg_type,
+            flags: frame.flags,
+            body: frame.body()?,
+        })
+    }
+}
+
+fn write_body<T: Serialize>(flags: Flags, body: &T, dst: &mut BytesMut) -> Result<(), CodecError> {
+    if flags.contains(Flags::BINCODE) {
+        return bincode_body::encode(body, dst);
+    }
+    // write_named: structs as maps (field names on the wire), so fields can be added later
+    rmp_serde::encode::write_named(&mut dst.writer(), body)?;
+    Ok(())
+}
+
+// A framing (FrameCodec unless said otherwise) plus the (de)serialization of T.
+#[derive(Debug)]
+pub struct MessageCodec<T, F = FrameCodec> {
+    frames: F,
+    // Flags::BINCODE on every message sent
+    bincode: bool,
+    _body: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for MessageCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, F: Clone> Clone for MessageCodec<T, F> {
+    fn clone(&self) -> Self {
+        Self {
+            frames: self.frames.clone(),
+            bincode: self.bincode,
+            _body: PhantomData,
+        }
+    }
+}
+
+impl<T> MessageCodec<T> {
+    pub fn new() -> Self {
+        Self::with_frames(FrameCodec::new())
+    }
+}
+
+impl<T, F> MessageCodec<T, F> {
+    // e.g. FrameCodec::new().max_payload(64 * 1024), or CobsCodec::new()
+    pub fn with_frames(frames: F) -> Self {
+        Self {
+            frames,
+            bincode: false,
+            _body: PhantomData,
+        }
+    }
+
+    // Send bodies as bincode; received frames are decoded by their own flag either way.
+    // Both ends must have the same T for each msg_type (see Flags::BINCODE in codec/frame.rs).
+    pub fn bincode(mut self) -> Self {
+        self.bincode = true;
+        self
+    }
+}
+
+impl<T: DeserializeOwned, F: Framing> Decoder for MessageCodec<T, F> {
+    type Item = Message<T>;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Message<T>>, CodecError> {
+        match self.frames.decode(src)? {
+            Some(frame) => Message::from_frame(&frame).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T, F: ReadDeadline> ReadDeadline for MessageCodec<T, F> {
+    fn read_deadline(&self) -> Option<Instant> {
+        self.frames.read_deadline()
+    }
+}
+
+impl<T: Serialize, F: Framing> Encoder<Message<T>> for MessageCodec<T, F> {
+    type Error = CodecError;
+
+    fn encode(&mut self, message: Message<T>, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let mut flags = message.flags;
+        if self.bincode {
+            flags.insert(Flags::BINCODE);
+        }
+        self.frames
+            .encode_with(message.msg_type, flags, dst, |dst| {
+                write_body(flags, &message.body, dst)
+            })
+    }
+}
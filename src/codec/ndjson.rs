@@ -0,0 +1,119 @@
+// NdjsonCodec<T>: newline-delimited JSON, one value per line, the format of log shippers (Vector, Fluent Bit,
+// Loki's push API) and streaming HTTP APIs:
+//
+//   {"level":"info","msg":"started"}
+//   {"level":"warn","msg":"slow request","ms":812}
+//
+// Like chat.rs's LinesCodec, but each line is parsed into T (and each T written as one line).
+// serde_json's compact output never contains a raw newline (they're escaped inside strings),
+// so every value fits on one line. Empty lines and a trailing \r (CRLF senders) are ignored.
+//
+// max_line_length: a peer that never sends \n would otherwise make the buffer grow without bound;
+// a longer line fails with CodecError::LineTooLong (the stream ends, like any other decode error).
+//
+//   let mut lines = FramedRead::new(socket, NdjsonCodec::<LogEvent>::new());
+//   while let Some(event) = lines.next().await { ship(event?).await; }
+
+use std::marker::PhantomData;
+
+use bytes::{BufMut, BytesMut};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::CodecError;
+
+#[derive(Debug)]
+pub struct NdjsonCodec<T> {
+    max_line_length: usize,
+    // bytes of the buffer already searched for \n, so a long line isn't rescanned on every read
+    scanned: usize,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for NdjsonCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for NdjsonCodec<T> {
+    fn clone(&self) -> Self {
+        Self::new().max_line_length(self.max_line_length)
+    }
+}
+
+impl<T> NdjsonCodec<T> {
+    // Lines up to 1 MiB.
+    pub fn new() -> Self {
+        Self {
+            max_line_length: 1024 * 1024,
+            scanned: 0,
+            _value: PhantomData,
+        }
+    }
+
+    pub fn max_line_length(mut self, bytes: usize) -> Self {
+        self.max_line_length = bytes;
+        self
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for NdjsonCodec<T> {
+    type Item = T;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, CodecError> {
+        loop {
+            let Some(offset) = src[self.scanned..].iter().position(|b| *b == b'\n') else {
+                if src.len() > self.max_line_length {
+                    return Err(CodecError::LineTooLong {
+                        max: self.max_line_length,
+                    });
+                }
+                self.scanned = src.len();
+                return Ok(None);
+            };
+            let end = self.scanned + offset;
+            self.scanned = 0;
+            if end > self.max_line_length {
+                return Err(CodecError::LineTooLong {
+                    max: self.max_line_length,
+                });
+            }
+            let line = src.split_to(end + 1);
+            let line = line[..end].strip_suffix(b"\r").unwrap_or(&line[..end]);
+            if line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            return Ok(Some(serde_json::from_slice(line)?));
+        }
+    }
+
+    // The last line may come without a \n before EOF.
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<T>, CodecError> {
+        if let Some(value) = self.decode(src)? {
+            return Ok(Some(value));
+        }
+        self.scanned = 0;
+        let line = src.split();
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(line)?))
+    }
+}
+
+impl<T: Serialize> Encoder<T> for NdjsonCodec<T> {
+    type Error = CodecError;
+
+    fn encode(&mut self, value: T, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let start = dst.len();
+        if let Err(e) = serde_json::to_writer(BufMut::writer(&mut *dst), &value) {
+            dst.truncate(start);
+            return Err(e.into());
+        }
+        dst.put_u8(b'\n');
+        Ok(())
+    }
+}
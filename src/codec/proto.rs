@@ -0,0 +1,120 @@
+// Protobuf bodies (--features protobuf): Message<M> with M a prost message instead of a serde type,
+// for talking to services written in Go, Java, Python ... that already define their messages in .proto files.
+//
+// Key flow:
+// proto/user.proto → build.rs (prost-build) → $OUT_DIR/ecosystem.user.rs → codec::proto::user::User
+// send: Message::new(USER, user) → ProtoCodec::encode → prost encodes the body straight into the frame
+// recv: FrameCodec::decode → Frame → M::decode(payload) → Message<M>
+//
+//   let mut conn = Framed::new(stream, ProtoCodec::<proto::user::User>::new());
+//   conn.send(Message::new(USER, proto::user::User::from(&user))).await?;
+//
+// Same frames as MessageCodec (header, chunking, checksums, compression, or CobsCodec underneath);
+// only the body encoding differs, so both ends must agree on it per msg_type, like any other protocol detail.
+// Frame::proto_body decodes a single frame's payload when the type depends on msg_type.
+
+use std::marker::PhantomData;
+
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::{
+    frame::{Frame, FrameCodec, Framing},
+    message::Message,
+    slow_read::ReadDeadline,
+    CodecError,
+};
+use crate::web::users;
+
+// Generated by build.rs from proto/user.proto.
+pub mod user {
+    include!(concat!(env!("OUT_DIR"), "/ecosystem.user.rs"));
+}
+
+impl Frame {
+    // The payload as protobuf message M.
+    pub fn proto_body<M: prost::Message + Default>(&self) -> Result<M, CodecError> {
+        Ok(M::decode(self.payload.clone())?)
+    }
+}
+
+// A framing (FrameCodec unless said otherwise) plus the protobuf encoding of M.
+#[derive(Debug)]
+pub struct ProtoCodec<M, F = FrameCodec> {
+    frames: F,
+    _body: PhantomData<fn() -> M>,
+}
+
+impl<M> Default for ProtoCodec<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M, F: Clone> Clone for ProtoCodec<M, F> {
+    fn clone(&self) -> Self {
+        Self::with_frames(self.frames.clone())
+    }
+}
+
+impl<M> ProtoCodec<M> {
+    pub fn new() -> Self {
+        Self::with_frames(FrameCodec::new())
+    }
+}
+
+impl<M, F> ProtoCodec<M, F> {
+    pub fn with_frames(frames: F) -> Self {
+        Self {
+            frames,
+            _body: PhantomData,
+        }
+    }
+}
+
+impl<M: prost::Message + Default, F: Framing> Decoder for ProtoCodec<M, F> {
+    type Item = Message<M>;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Message<M>>, CodecError> {
+        let Some(frame) = self.frames.decode(src)? else {
+            return Ok(None);
+        };
+        Ok(Some(Message {
+            msg_type: frame.msg_type,
+            flags: frame.flags,
+            body: frame.proto_body()?,
+        }))
+    }
+}
+
+impl<M, F: ReadDeadline> ReadDeadline for ProtoCodec<M, F> {
+    fn read_deadline(&self) -> Option<tokio::time::Instant> {
+        self.frames.read_deadline()
+    }
+}
+
+impl<M: prost::Message, F: Framing> Encoder<Message<M>> for ProtoCodec<M, F> {
+    type Error = CodecError;
+
+    fn encode(&mut self, message: Message<M>, dst: &mut BytesMut) -> Result<(), CodecError> {
+        self.frames
+            .encode_with(message.msg_type, message.flags, dst, |dst| {
+                // BytesMut grows as needed, so this can't run out of space
+                message.body.encode(dst)?;
+                Ok(())
+            })
+    }
+}
+
+impl From<&users::User> for user::User {
+    fn from(u: &users::User) -> Self {
+        Self {
+            id: u.id,
+            name: u.name.clone(),
+            age: u.age.into(),
+            skills: u.skills.clone(),
+            deleted_at: u.deleted_at.map(|at| at.to_rfc3339()),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+// Slow-read protection: a frame that has started arriving must be complete within a time limit.
+// Without it a peer can open many connections and send each frame a byte at a time (or half a frame
+// and then nothing), and every connection holds its buffer and its task forever ("slowloris").
+//
+// Key flow (FrameCodec::read_timeout(limit)):
+// first byte of a frame arrives → decode() notes the time
+//   ├→ more bytes arrive, still incomplete, past the limit → decode() fails: CodecError::ReadTimeout
+//   ├→ nothing more arrives → recv() wakes up at the deadline → CodecError::ReadTimeout
+//   └→ frame complete → the clock stops; waiting between frames (an idle connection) isn't limited
+//
+// Framed's own next() only calls decode() when bytes arrive, so a peer that stops sending mid-frame
+// is caught by recv() only:
+//
+//   let mut conn = Framed::new(stream, FrameCodec::new().read_timeout(Duration::from_secs(10)));
+//   while let Some(frame) = codec::recv(&mut conn).await { handle(frame?).await; }
+//   // Err(ReadTimeout) → the stream ends, dropping conn closes the connection
+
+use std::{
+    future::{poll_fn, Future},
+    pin::Pin,
+    task::Poll,
+};
+
+use futures_core::Stream;
+use tokio::{
+    io::AsyncRead,
+    time::{self, Instant},
+};
+use tokio_util::codec::{Decoder, Framed};
+
+use super::CodecError;
+
+// Codecs with a read timeout: when the frame being received has to be complete.
+pub trait ReadDeadline {
+    // None: no timeout configured, or no partial frame in the buffer.
+    fn read_deadline(&self) -> Option<Instant>;
+}
+
+// Framed::next(), failing with CodecError::ReadTimeout once the codec's read deadline passes.
+pub async fn recv<S, C>(conn: &mut Framed<S, C>) -> Option<Result<C::Item, CodecError>>
+where
+    S: AsyncRead + Unpin,
+    C: Decoder<Error = CodecError> + ReadDeadline + Unpin,
+{
+    let mut timer = Box::pin(time::sleep_until(Instant::now()));
+    poll_fn(|cx| {
+        if let Poll::Ready(item) = Pin::new(&mut *conn).poll_next(cx) {
+            return Poll::Ready(item);
+        }
+        // decode() ran on whatever arrived, so the deadline is current: wake up then unless
+        // more bytes come first
+        let Some(deadline) = conn.codec().read_deadline() else {
+            return Poll::Pending;
+        };
+        timer.as_mut().reset(deadline);
+        match timer.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Some(Err(CodecError::ReadTimeout))),
+            Poll::Pending => Poll::Pending,
+        }
+    })
+    .await
+}
@@ -0,0 +1,105 @@
+// TaggedCodec<T>: frames carrying a Tagged enum (crate::tagged), the variant in the header's msg_type
+// and only the payload, as MessagePack, in the body. MessageCodec<T> with an enum T puts the variant
+// name inside the body instead, so the receiver can't tell what a frame is without deserializing it.
+//
+// Key flow:
+// send: Request::Put(put) → msg_type = Request::code(Put) → body = put as MessagePack → Frame
+// recv: Frame → Request::from_code(msg_type) (unknown → CodecError::UnknownMessageType)
+//             → deserialize the body as that variant's payload → Request::Put(put)
+//
+//   let mut conn = Framed::new(stream, TaggedCodec::<Request>::new());
+//   conn.send(Request::Get(Get { key: "a".into() })).await?;
+//   match conn.next().await.transpose()?.context("connection closed")? {
+//       Request::Get(get) => ..., Request::Put(put) => ...,
+//   }
+//
+// Frame::tagged_body does the same for a frame already decoded (FrameAccumulator, a router that
+// looks at msg_type first). Code u16::MAX is the compression hello (codec/compression.rs): don't use it.
+
+use std::marker::PhantomData;
+
+use bytes::{BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::{
+    frame::{Flags, Frame, FrameCodec, Framing},
+    slow_read::ReadDeadline,
+    CodecError,
+};
+use crate::tagged::Tagged;
+
+impl Frame {
+    // The payload as the variant of T that msg_type names.
+    pub fn tagged_body<T: Tagged>(&self) -> Result<T, CodecError> {
+        let tag =
+            T::from_code(self.msg_type).ok_or(CodecError::UnknownMessageType(self.msg_type))?;
+        let mut de = rmp_serde::Deserializer::from_read_ref(&self.payload[..]);
+        Ok(T::deserialize_payload(tag, &mut de)?)
+    }
+}
+
+#[derive(Debug)]
+pub struct TaggedCodec<T, F = FrameCodec> {
+    frames: F,
+    _body: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for TaggedCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, F: Clone> Clone for TaggedCodec<T, F> {
+    fn clone(&self) -> Self {
+        Self::with_frames(self.frames.clone())
+    }
+}
+
+impl<T> TaggedCodec<T> {
+    pub fn new() -> Self {
+        Self::with_frames(FrameCodec::new())
+    }
+}
+
+impl<T, F> TaggedCodec<T, F> {
+    pub fn with_frames(frames: F) -> Self {
+        Self {
+            frames,
+            _body: PhantomData,
+        }
+    }
+}
+
+impl<T: Tagged, F: Framing> Decoder for TaggedCodec<T, F> {
+    type Item = T;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, CodecError> {
+        match self.frames.decode(src)? {
+            Some(frame) => frame.tagged_body().map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T, F: ReadDeadline> ReadDeadline for TaggedCodec<T, F> {
+    fn read_deadline(&self) -> Option<tokio::time::Instant> {
+        self.frames.read_deadline()
+    }
+}
+
+impl<T: Tagged, F: Framing> Encoder<T> for TaggedCodec<T, F> {
+    type Error = CodecError;
+
+    fn encode(&mut self, body: T, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let msg_type = T::code(body.tag());
+        self.frames
+            .encode_with(msg_type, Flags::empty(), dst, |dst| {
+                // struct map, like MessageCodec: field names on the wire
+                let mut ser = rmp_serde::Serializer::new(dst.writer()).with_struct_map();
+                body.serialize_payload(&mut ser)?;
+                Ok(())
+            })
+    }
+}
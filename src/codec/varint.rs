@@ -0,0 +1,317 @@
+// LEB128 varints: 7 bits per byte, low bits first, high bit set on every byte but the last.
+// Small numbers take few bytes (0..=127 → 1 byte, up to 16383 → 2, u64::MAX → 10), which is why
+// protobuf uses them for field tags and lengths:
+//
+//   300 = 0b10_0101100  →  [0b1_0101100, 0b0_0000010]  →  [0xAC, 0x02]
+//
+// Signed values are zigzag-mapped first (0 → 0, -1 → 1, 1 → 2, -2 → 3, ...) so small negative numbers
+// stay short too; without it -1 would take all 10 bytes.
+//
+//   varint::put_u64(&mut buf, 300);           // BytesMut (any BufMut)
+//   let n = varint::get_u64(&mut bytes)?;     // Bytes / BytesMut / &[u8] (any Buf)
+//   varint::decode_u64(&buf[..])?             // peek: Some((value, bytes used)), None = needs more bytes
+//
+// get_* don't consume anything on error, so a decoder can simply wait for more bytes on Incomplete.
+//
+// VarintLengthCodec: frames as <varint length><payload>, the framing of protobuf's writeDelimitedTo.
+// 1 byte of overhead for payloads under 128 bytes (a u32 prefix always costs 4).
+
+use std::io::IoSlice;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use thiserror::Error;
+use tokio_util::codec::{Decoder, Encoder};
+
+use super::CodecError;
+
+// u64::MAX needs ceil(64 / 7) bytes
+pub const MAX_LEN: usize = 10;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarintError {
+    #[error("buffer ends in the middle of a varint")]
+    Incomplete,
+    // more than MAX_LEN bytes, or bits set above the 64th
+    #[error("varint doesn't fit in 64 bits")]
+    Overflow,
+}
+
+pub fn put_u64(buf: &mut impl BufMut, mut value: u64) {
+    while value >= 0x80 {
+        buf.put_u8((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.put_u8(value as u8);
+}
+
+pub fn put_i64(buf: &mut impl BufMut, value: i64) {
+    put_u64(buf, zigzag(value));
+}
+
+// Reads and consumes a varint from the front of `buf`; nothing is consumed on error.
+pub fn get_u64(buf: &mut impl Buf) -> Result<u64, VarintError> {
+    let (value, len) = decode_chunks(buf)?.ok_or(VarintError::Incomplete)?;
+    buf.advance(len);
+    Ok(value)
+}
+
+pub fn get_i64(buf: &mut impl Buf) -> Result<i64, VarintError> {
+    get_u64(buf).map(unzigzag)
+}
+
+// Peeks at the front of `bytes`: Some((value, bytes used)), None when the varint isn't complete yet.
+pub fn decode_u64(bytes: &[u8]) -> Result<Option<(u64, usize)>, VarintError> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().take(MAX_LEN).enumerate() {
+        let bits = u64::from(byte & 0x7f);
+        // the 10th byte only has room for the 64th bit
+        if i == MAX_LEN - 1 && bits > 1 {
+            return Err(VarintError::Overflow);
+        }
+        value |= bits << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(Some((value, i + 1)));
+        }
+    }
+    if bytes.len() >= MAX_LEN {
+        Err(VarintError::Overflow)
+    } else {
+        Ok(None)
+    }
+}
+
+// Bytes the varint encoding of `value` takes.
+pub fn encoded_len(value: u64) -> usize {
+    // bits in use (at least 1), 7 per byte
+    let bits = 64 - (value | 1).leading_zeros() as usize;
+    bits.div_ceil(7)
+}
+
+pub fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+// Buf isn't always one contiguous slice (Chain): gather the first MAX_LEN bytes without consuming them.
+fn decode_chunks(buf: &impl Buf) -> Result<Option<(u64, usize)>, VarintError> {
+    let chunk = buf.chunk();
+    if chunk.len() >= MAX_LEN || chunk.len() == buf.remaining() {
+        return decode_u64(chunk);
+    }
+    let mut slices = [IoSlice::new(&[]); MAX_LEN];
+    let n = buf.chunks_vectored(&mut slices);
+    let mut head = [0u8; MAX_LEN];
+    let mut filled = 0;
+    for slice in &slices[..n] {
+        let take = slice.len().min(MAX_LEN - filled);
+        head[filled..filled + take].copy_from_slice(&slice[..take]);
+        filled += take;
+        if filled == MAX_LEN {
+            break;
+        }
+    }
+    decode_u64(&head[..filled])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VarintLengthCodec {
+    max_length: usize,
+}
+
+impl Default for VarintLengthCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VarintLengthCodec {
+    // Frames up to 8 MiB, like tokio_util's LengthDelimitedCodec.
+    pub fn new() -> Self {
+        Self {
+            max_length: 8 * 1024 * 1024,
+        }
+    }
+
+    // Larger frames fail to decode (and encode) with CodecError::FrameTooLarge.
+    pub fn max_length(mut self, bytes: usize) -> Self {
+        self.max_length = bytes;
+        self
+    }
+}
+
+impl Decoder for VarintLengthCodec {
+    type Item = Bytes;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Bytes>, CodecError> {
+        let Some((len, prefix)) = decode_u64(src)? else {
+            return Ok(None);
+        };
+        let len = usize::try_from(len).unwrap_or(usize::MAX);
+        if len > self.max_length {
+            return Err(CodecError::FrameTooLarge {
+                len,
+                max: self.max_length,
+            });
+        }
+        if src.len() < prefix + len {
+            // room for the rest of the frame, so the next reads don't reallocate piecemeal
+            src.reserve(prefix + len - src.len());
+            return Ok(None);
+        }
+        src.advance(prefix);
+        // split_to + freeze: a view into the receive buffer, no copy
+        Ok(Some(src.split_to(len).freeze()))
+    }
+}
+
+impl Encoder<Bytes> for VarintLengthCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: Bytes, dst: &mut BytesMut) -> Result<(), CodecError> {
+        if item.len() > self.max_length {
+            return Err(CodecError::FrameTooLarge {
+                len: item.len(),
+                max: self.max_length,
+            });
+        }
+        dst.reserve(encoded_len(item.len() as u64) + item.len());
+        put_u64(dst, item.len() as u64);
+        dst.extend_from_slice(&item);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EDGES: [u64; 9] = [
+        0,
+        1,
+        127,
+        128,
+        300,
+        16_383,
+        16_384,
+        u32::MAX as u64,
+        u64::MAX,
+    ];
+
+    #[test]
+    fn u64_round_trips_in_encoded_len_bytes() {
+        for value in EDGES {
+            let mut buf = BytesMut::new();
+            put_u64(&mut buf, value);
+            assert_eq!(buf.len(), encoded_len(value), "{value}");
+            assert_eq!(decode_u64(&buf).unwrap(), Some((value, buf.len())));
+            let mut bytes = buf.freeze();
+            assert_eq!(get_u64(&mut bytes).unwrap(), value);
+            assert!(bytes.is_empty());
+        }
+        let mut buf = BytesMut::new();
+        put_u64(&mut buf, 300);
+        assert_eq!(&buf[..], [0xAC, 0x02]);
+        assert_eq!(encoded_len(u64::MAX), MAX_LEN);
+    }
+
+    #[test]
+    fn i64_is_zigzagged() {
+        assert_eq!([0, -1, 1, -2, 2].map(zigzag), [0, 1, 2, 3, 4]);
+        for value in [0, -1, 1, i64::MIN, i64::MAX] {
+            assert_eq!(unzigzag(zigzag(value)), value);
+            let mut buf = BytesMut::new();
+            put_i64(&mut buf, value);
+            assert_eq!(get_i64(&mut buf).unwrap(), value);
+        }
+        let mut buf = BytesMut::new();
+        put_i64(&mut buf, -1);
+        assert_eq!(buf.len(), 1);
+    }
+
+    #[test]
+    fn incomplete_varints_consume_nothing() {
+        let mut bytes = &[0x80, 0x80][..];
+        assert_eq!(decode_u64(bytes), Ok(None));
+        assert_eq!(get_u64(&mut bytes), Err(VarintError::Incomplete));
+        assert_eq!(bytes.len(), 2);
+        assert_eq!(decode_u64(&[]), Ok(None));
+    }
+
+    #[test]
+    fn overlong_or_too_wide_varints_overflow() {
+        // 11 bytes: past MAX_LEN
+        let overlong = [0xFF; 11];
+        assert_eq!(decode_u64(&overlong), Err(VarintError::Overflow));
+        // 10 continuation bytes: can't end within MAX_LEN either
+        assert_eq!(decode_u64(&[0x80; MAX_LEN]), Err(VarintError::Overflow));
+        // the 10th byte may only hold the 64th bit
+        let mut too_wide = [0xFF; MAX_LEN];
+        too_wide[MAX_LEN - 1] = 0x02;
+        assert_eq!(decode_u64(&too_wide), Err(VarintError::Overflow));
+        too_wide[MAX_LEN - 1] = 0x01;
+        assert_eq!(decode_u64(&too_wide), Ok(Some((u64::MAX, MAX_LEN))));
+
+        let mut bytes = &overlong[..];
+        assert_eq!(get_u64(&mut bytes), Err(VarintError::Overflow));
+        assert_eq!(bytes.len(), 11);
+    }
+
+    #[test]
+    fn varints_split_across_chunks_decode() {
+        let mut buf = BytesMut::new();
+        put_u64(&mut buf, u64::MAX);
+        put_u64(&mut buf, 5);
+        let (head, tail) = buf.split_at(3);
+        let mut chained = head.chain(tail);
+        assert_eq!(get_u64(&mut chained).unwrap(), u64::MAX);
+        assert_eq!(get_u64(&mut chained).unwrap(), 5);
+        assert!(!chained.has_remaining());
+    }
+
+    #[test]
+    fn length_codec_round_trips_and_waits_for_the_payload() {
+        let mut codec = VarintLengthCodec::new();
+        let mut buf = BytesMut::new();
+        codec
+            .encode(Bytes::from_static(b"hello"), &mut buf)
+            .unwrap();
+        codec.encode(Bytes::new(), &mut buf).unwrap();
+        assert_eq!(&buf[..6], b"\x05hello");
+
+        let mut partial = BytesMut::from(&buf[..3]);
+        assert_eq!(codec.decode(&mut partial).unwrap(), None);
+        assert_eq!(partial.len(), 3);
+
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), "hello");
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), "");
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn length_codec_rejects_oversized_and_malformed_lengths() {
+        let mut codec = VarintLengthCodec::new().max_length(4);
+        let mut buf = BytesMut::new();
+        assert!(matches!(
+            codec.encode(Bytes::from_static(b"hello"), &mut buf),
+            Err(CodecError::FrameTooLarge { len: 5, max: 4 })
+        ));
+        assert!(buf.is_empty());
+
+        // the length alone is enough to refuse: the payload isn't waited for
+        let mut announced = BytesMut::from(&[0x80, 0x01][..]);
+        assert!(matches!(
+            codec.decode(&mut announced),
+            Err(CodecError::FrameTooLarge { len: 128, max: 4 })
+        ));
+
+        let mut garbage = BytesMut::from(&[0xFF; 11][..]);
+        assert!(matches!(
+            codec.decode(&mut garbage),
+            Err(CodecError::Varint(VarintError::Overflow))
+        ));
+    }
+}
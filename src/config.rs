@@ -0,0 +1,320 @@
+// config: typed configuration shared by the binaries.
+// Values are merged in order, later sources win:
+//   1. defaults   (the struct's Default impl)
+//   2. file       (TOML, YAML or JSON by extension: server.toml, server.yaml, server.json; anything
+//                  else is read as TOML. A missing file is fine, defaults/env still apply)
+//   3. env vars   (PREFIX_FIELD, nested fields joined by "__": SERVER_TLS__CERT_PATH)
+//   4. overrides  (set_overrides: `ecosystem --set logging.level=debug --set app.retention_ms=1h`),
+//                  keys are paths from the top of the file, whatever section a loader reads
+// figment keeps track of where every value came from, so errors read like
+//   invalid type: found string "abc", expected u64 for key "request_timeout_secs" in server.toml TOML file
+//   invalid type: found boolean true, expected a string for key "logging.level" in command line (--set)
+//
+// ${VAR} in a file is replaced by the env var's value before the file is parsed, so secrets and
+// per-host values stay out of the file; ${VAR:-default} when it may be unset; $${ for a literal "${".
+// A ${VAR} that isn't set (and has no default) is an error naming the file and line:
+//
+//   [app.storage]
+//   path = "${DATA_DIR:-/var/lib/ecosystem}/users.json"
+//   [tls]
+//   key_path = "${TLS_KEY}"
+//
+// Substitution is textual (lines starting with # are skipped): a value with quotes or newlines must
+// be quoted accordingly by whoever sets the variable.
+// config::check (config/check.rs) reads a file without loading it and reports every problem at once,
+// unknown keys included: what `ecosystem config check` runs in CI.
+
+pub mod check;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use figment::{
+    providers::{Env, Format as _, Json, Serialized, Toml, Yaml},
+    value::{Dict, Map, Value},
+    Figment, Metadata, Profile, Provider, Source,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::formats::Format;
+
+#[derive(Error, Debug)]
+#[error(transparent)]
+pub struct ConfigError(#[from] Box<figment::Error>);
+
+// key path → value, applied by every load after the env vars (see set_overrides).
+static OVERRIDES: RwLock<Vec<(String, String)>> = RwLock::new(Vec::new());
+
+// Values that win over file and env for the rest of the process, e.g. from `--set key=value` flags.
+// Keys are paths from the top of the file ("listen", "logging.level"); values are parsed like env
+// vars ("8080" is a number, "true" a bool, "[1, 2]" an array, anything else a string).
+pub fn set_overrides(overrides: impl IntoIterator<Item = (String, String)>) {
+    *OVERRIDES.write().expect("config overrides lock poisoned") = overrides.into_iter().collect();
+}
+
+pub fn load<T>(file: impl AsRef<Path>, env_prefix: &str) -> Result<T, ConfigError>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    Figment::from(Serialized::defaults(T::default()))
+        .merge(ConfigFile::new(file))
+        .merge(Env::prefixed(env_prefix).split("__"))
+        .merge(Overrides::current())
+        .extract()
+        .map_err(|e| ConfigError(Box::new(e)))
+}
+
+// Same as load, but reads one table of the file ([logging] in server.toml), so several
+// components can share a config file; env vars are PREFIX_FIELD relative to that table (LOG_LEVEL).
+pub fn load_section<T>(
+    file: impl AsRef<Path>,
+    section: &str,
+    env_prefix: &str,
+) -> Result<T, ConfigError>
+where
+    T: Serialize + DeserializeOwned + Default,
+{
+    Figment::from(Serialized::defaults(T::default()))
+        .merge(Figment::from(ConfigFile::new(file)).focus(section))
+        .merge(Env::prefixed(env_prefix).split("__"))
+        .merge(Figment::from(Overrides::current()).focus(section))
+        .extract()
+        .map_err(|e| ConfigError(Box::new(e)))
+}
+
+// "logging.level=debug" → ("logging.level", "debug"), for clap's value_parser.
+pub fn parse_override(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected key=value, got {arg:?}")),
+    }
+}
+
+// A config file in the format of its extension, ${VAR}s substituted; named in errors by its path.
+struct ConfigFile {
+    path: PathBuf,
+    format: Format,
+}
+
+impl ConfigFile {
+    fn new(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let format = Format::from_path(&path).unwrap_or(Format::Toml);
+        Self { path, format }
+    }
+}
+
+impl Provider for ConfigFile {
+    fn metadata(&self) -> Metadata {
+        let name = match self.format {
+            Format::Yaml => "YAML file",
+            Format::Json => "JSON file",
+            _ => "TOML file",
+        };
+        Metadata::from(name, Source::File(self.path.clone()))
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        let text = match std::fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Map::new()),
+            Err(e) => return Err(format!("reading {}: {e}", self.path.display()).into()),
+        };
+        let text = interpolate(&text, |name| std::env::var(name).ok())
+            .map_err(|e| figment::Error::from(format!("{}: {e}", self.path.display())))?;
+        match self.format {
+            Format::Toml => Toml::string(&text).data(),
+            Format::Yaml => Yaml::string(&text).data(),
+            Format::Json => Json::string(&text).data(),
+            other => Err(format!(
+                "{}: {other:?} isn't a config file format (use .toml, .yaml or .json)",
+                self.path.display()
+            )
+            .into()),
+        }
+    }
+}
+
+// `text` with every ${NAME} / ${NAME:-default} replaced by lookup(NAME) (or the default); $${ is a
+// literal "${". Lines starting with # (comments) are left alone.
+pub fn interpolate(text: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    for (number, line) in text.split_inclusive('\n').enumerate() {
+        if line.trim_start().starts_with('#') {
+            out.push_str(line);
+            continue;
+        }
+        let mut rest = line;
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                out.push_str(&rest[..start - 1]);
+                out.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            out.push_str(&rest[..start]);
+            let Some(len) = rest[start + 2..].find('}') else {
+                return Err(format!("line {}: unclosed ${{", number + 1));
+            };
+            let expr = &rest[start + 2..start + 2 + len];
+            let (name, default) = match expr.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (expr, None),
+            };
+            match lookup(name).or_else(|| default.map(str::to_string)) {
+                Some(value) => out.push_str(&value),
+                None => {
+                    return Err(format!(
+                        "line {}: ${{{name}}} is not set (give a default with ${{{name}:-value}})",
+                        number + 1
+                    ))
+                }
+            }
+            rest = &rest[start + 2 + len + 1..];
+        }
+        out.push_str(rest);
+    }
+    Ok(out)
+}
+
+// The set_overrides values as a figment source, named "command line (--set)" in errors.
+struct Overrides(Vec<(String, String)>);
+
+impl Overrides {
+    fn current() -> Self {
+        Self(
+            OVERRIDES
+                .read()
+                .expect("config overrides lock poisoned")
+                .clone(),
+        )
+    }
+}
+
+impl Provider for Overrides {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("command line (--set)")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, figment::Error> {
+        let mut figment = Figment::new();
+        for (key, value) in &self.0 {
+            let value: Value = value.parse().unwrap_or_else(|e| match e {});
+            figment = figment.merge(Serialized::default(key, value));
+        }
+        figment.data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    fn vars(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("db.internal".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn substitutes_set_vars_and_defaults() {
+        assert_eq!(
+            interpolate("url = \"postgres://${HOST}:5432\"\n", vars).unwrap(),
+            "url = \"postgres://db.internal:5432\"\n"
+        );
+        assert_eq!(
+            interpolate("dir = \"${DATA_DIR:-/var/lib/x}/users.json\"", vars).unwrap(),
+            "dir = \"/var/lib/x/users.json\""
+        );
+        // a set variable wins over the default, even when it's empty
+        assert_eq!(
+            interpolate("${HOST:-localhost}", vars).unwrap(),
+            "db.internal"
+        );
+        assert_eq!(interpolate("[${EMPTY:-x}]", vars).unwrap(), "[]");
+    }
+
+    #[test]
+    fn double_dollar_is_a_literal() {
+        assert_eq!(
+            interpolate("cmd = \"echo $${HOME} ${HOST}\"", vars).unwrap(),
+            "cmd = \"echo ${HOME} db.internal\""
+        );
+    }
+
+    #[test]
+    fn comment_lines_are_left_alone() {
+        let text = "# key = \"${UNSET}\"\n  # ${ALSO_UNSET}\nhost = \"${HOST}\"\n";
+        assert_eq!(
+            interpolate(text, vars).unwrap(),
+            "# key = \"${UNSET}\"\n  # ${ALSO_UNSET}\nhost = \"db.internal\"\n"
+        );
+    }
+
+    #[test]
+    fn unset_var_error_names_the_line() {
+        let text = "a = 1\nb = \"${HOST}\"\nkey = \"${TLS_KEY}\"\n";
+        let err = interpolate(text, vars).unwrap_err();
+        assert!(err.starts_with("line 3: ${TLS_KEY} is not set"), "{err}");
+        assert_eq!(
+            interpolate("a = 1\nb = \"${HOST\"", vars).unwrap_err(),
+            "line 2: unclosed ${"
+        );
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Layered {
+        from_default: String,
+        from_file: String,
+        from_env: String,
+        from_set: String,
+    }
+
+    impl Default for Layered {
+        fn default() -> Self {
+            let default = || "default".to_string();
+            Self {
+                from_default: default(),
+                from_file: default(),
+                from_env: default(),
+                from_set: default(),
+            }
+        }
+    }
+
+    // The only test that touches the process's env vars and overrides: its prefix and keys are its own.
+    #[test]
+    fn later_sources_win() {
+        let path = std::env::temp_dir().join(format!("config-{}.toml", nanoid::nanoid!()));
+        std::fs::write(
+            &path,
+            "from_file = \"file\"\nfrom_env = \"file\"\nfrom_set = \"file\"\n",
+        )
+        .unwrap();
+        std::env::set_var("CFGTEST_LAYERS_FROM_ENV", "env");
+        std::env::set_var("CFGTEST_LAYERS_FROM_SET", "env");
+        set_overrides([("from_set".to_string(), "set".to_string())]);
+
+        let loaded: Result<Layered, _> = load(&path, "CFGTEST_LAYERS_");
+        set_overrides([]);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            loaded.unwrap(),
+            Layered {
+                from_default: "default".into(),
+                from_file: "file".into(),
+                from_env: "env".into(),
+                from_set: "set".into(),
+            }
+        );
+    }
+}
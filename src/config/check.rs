@@ -0,0 +1,409 @@
+// config::check: everything wrong with a config file, found before a deploy instead of at startup.
+// config::load stops at the first bad value and says nothing about keys it doesn't know (a typo like
+// `request_timout_secs` is silently ignored, and the default applies); check() goes through every
+// section of the file and reports all of it, with where in the file and, when it can tell, the fix:
+//
+//   let issues = check::check(ConfigKind::Proxy, &std::fs::read_to_string("proxy.toml")?);
+//   for issue in &issues { eprintln!("{:?}", Report::new(issue.clone()).with_source_code(source.clone())) }
+//
+// Issues are miette Diagnostics with a byte span into the file, so the CLI (`ecosystem config check`)
+// prints the offending line with the value underlined.
+//
+// Key flow:
+// check(kind, source)
+//   ├→ TOML syntax error → that one issue (nothing else can be read)
+//   └→ every section of the kind ((top level) server/proxy fields, [app], [logging], [runtime])
+//        ├→ deserialized as its config type → the first bad value, with its span
+//        ├→ keys the type doesn't have → warning, "did you mean `...`?" when one is close
+//        └→ checks serde can't express: proxy upstreams, TLS files exist, log directives parse
+// Errors fail the check, warnings don't.
+//
+// Env var overrides (SERVER_*, LOG_*, ...) aren't applied: this checks the file as written.
+
+use std::{fmt, marker::PhantomData, ops::Range, path::Path};
+
+use miette::{Diagnostic, LabeledSpan, Severity};
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor},
+    Deserialize, Serialize,
+};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use strum::{Display, EnumString, VariantNames};
+use thiserror::Error;
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    error::MyError,
+    proxy::ProxyConfig,
+    runtime::RuntimeConfig,
+    telemetry::LoggingConfig,
+    web::{app::AppConfig, server::ServerConfig},
+};
+
+// What a file configures, which decides the sections it may have.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    EnumString,
+    Display,
+    VariantNames,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum ConfigKind {
+    // server.toml: ServerConfig at the top level, [app], [logging], [runtime]
+    #[default]
+    #[strum(to_string = "server")]
+    Server,
+    // proxy.toml: ProxyConfig at the top level, [logging], [runtime]
+    #[strum(to_string = "proxy")]
+    Proxy,
+    // a worker process: [logging] and [runtime] only
+    #[strum(to_string = "worker")]
+    Worker,
+}
+
+impl ConfigKind {
+    // From the file name: proxy.toml, proxy-prod.toml → Proxy; worker*.toml → Worker; else Server.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let name = path
+            .as_ref()
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        if name.contains("proxy") {
+            Self::Proxy
+        } else if name.contains("worker") {
+            Self::Worker
+        } else {
+            Self::Server
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message}")]
+pub struct Issue {
+    pub severity: IssueSeverity,
+    pub message: String,
+    // byte range in the file; None when the problem isn't at one place (a missing key)
+    pub span: Option<Range<usize>>,
+    pub help: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+impl Issue {
+    fn error(message: impl Into<String>, span: Option<Range<usize>>) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            message: message.into(),
+            span,
+            help: None,
+        }
+    }
+
+    fn warning(message: impl Into<String>, span: Option<Range<usize>>) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            ..Self::error(message, span)
+        }
+    }
+
+    fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == IssueSeverity::Error
+    }
+}
+
+impl Diagnostic for Issue {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new("ecosystem::config"))
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        Some(match self.severity {
+            IssueSeverity::Error => Severity::Error,
+            IssueSeverity::Warning => Severity::Warning,
+        })
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.help
+            .as_ref()
+            .map(|help| Box::new(help) as Box<dyn fmt::Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let span = self.span.clone()?;
+        Some(Box::new(std::iter::once(LabeledSpan::at(span, "here"))))
+    }
+}
+
+// All issues of `source` read as a `kind` file, in file order; empty when it's fine.
+pub fn check(kind: ConfigKind, source: &str) -> Vec<Issue> {
+    let table: toml::Table = match toml::from_str(source) {
+        Ok(table) => table,
+        Err(e) => return vec![Issue::error(e.message(), e.span())],
+    };
+
+    let mut issues = Vec::new();
+    let sections: &[&str] = match kind {
+        ConfigKind::Server => &["app", "logging", "runtime"],
+        ConfigKind::Proxy | ConfigKind::Worker => &["logging", "runtime"],
+    };
+    // the top level: the kind's own fields and its sections
+    match kind {
+        ConfigKind::Server => {
+            let server = section::<ServerConfig>(source, &table, None, sections, &mut issues);
+            if let Some(tls) = server.and_then(|s| s.tls) {
+                for (key, path) in [("cert_path", &tls.cert_path), ("key_path", &tls.key_path)] {
+                    if !path.exists() {
+                        issues.push(
+                            Issue::warning(
+                                format!("{} does not exist", path.display()),
+                                key_span(source, Some("tls"), key),
+                            )
+                            .with_help("paths are relative to the directory the server starts in"),
+                        );
+                    }
+                }
+            }
+        }
+        ConfigKind::Proxy => {
+            let proxy = section::<ProxyConfig>(source, &table, None, sections, &mut issues);
+            if let Some(Err(MyError::Validation(errors))) = proxy.map(|p| p.validate()) {
+                for (field, messages) in errors.iter() {
+                    for message in messages {
+                        issues.push(Issue::error(
+                            format!("{field} {message}"),
+                            key_span(source, None, field),
+                        ));
+                    }
+                }
+            }
+        }
+        ConfigKind::Worker => unknown_keys(source, &table, None, sections, &mut issues),
+    }
+
+    if kind == ConfigKind::Server {
+        section::<AppConfig>(source, &table, Some("app"), &[], &mut issues);
+    }
+    section::<RuntimeConfig>(source, &table, Some("runtime"), &[], &mut issues);
+    if let Some(logging) =
+        section::<LoggingConfig>(source, &table, Some("logging"), &[], &mut issues)
+    {
+        let targets = logging
+            .levels
+            .iter()
+            .map(|(t, l)| (Some(t.as_str()), format!("{t}={l}")));
+        for (target, directive) in std::iter::once((None, logging.level.clone())).chain(targets) {
+            if let Err(e) = EnvFilter::builder().parse(&directive) {
+                let span = match target {
+                    Some(target) => key_span(source, Some("logging.levels"), target),
+                    None => key_span(source, Some("logging"), "level"),
+                };
+                issues.push(
+                    Issue::error(format!("invalid log directive {directive:?}: {e}"), span)
+                        .with_help(
+                            "a level (trace, debug, info, warn, error) or target=level pairs",
+                        ),
+                );
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.span.as_ref().map_or(usize::MAX, |s| s.start));
+    issues
+}
+
+// Deserializes one section as T (None: the top level), pushing its issues; the value if it's valid.
+fn section<T>(
+    source: &str,
+    table: &toml::Table,
+    name: Option<&str>,
+    subsections: &[&str],
+    issues: &mut Vec<Issue>,
+) -> Option<T>
+where
+    T: DeserializeOwned + Serialize + Default,
+{
+    // from the source text rather than `table`, so errors keep their span in the file
+    let parsed = match name {
+        None => toml::from_str::<T>(source),
+        Some(name) => SectionSeed {
+            name,
+            marker: PhantomData,
+        }
+        .deserialize(toml::Deserializer::new(source))
+        .map(Option::unwrap_or_default),
+    };
+    let fields = field_names::<T>();
+    let known: Vec<&str> = fields
+        .iter()
+        .map(String::as_str)
+        .chain(subsections.iter().copied())
+        .collect();
+    unknown_keys(source, table, name, &known, issues);
+    match parsed {
+        Ok(value) => Some(value),
+        Err(e) => {
+            issues.push(Issue::error(e.message(), e.span()));
+            None
+        }
+    }
+}
+
+// Reads one top-level table as T and skips the others, straight from the TOML deserializer, so
+// errors inside the table keep their span in the file. The table's name is only known at runtime,
+// hence a seed rather than a struct with a #[serde(rename)] field.
+struct SectionSeed<'a, T> {
+    name: &'a str,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for SectionSeed<'_, T> {
+    type Value = Option<T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Option<T>, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SectionSeed<'_, T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a TOML document")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Option<T>, A::Error> {
+        let mut found = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == self.name {
+                found = Some(map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(found)
+    }
+}
+
+// The keys `section` has in the file that aren't in `known`.
+fn unknown_keys(
+    source: &str,
+    table: &toml::Table,
+    section: Option<&str>,
+    known: &[&str],
+    issues: &mut Vec<Issue>,
+) {
+    let keys = match section {
+        None => Some(table),
+        Some(name) => table.get(name).and_then(toml::Value::as_table),
+    };
+    for key in keys.into_iter().flat_map(|t| t.keys()) {
+        if known.contains(&key.as_str()) {
+            continue;
+        }
+        let place = section.map_or_else(|| "at the top level".to_string(), |s| format!("in [{s}]"));
+        let mut issue = Issue::warning(
+            format!("unknown key `{key}` {place}, it is ignored"),
+            key_span(source, section, key),
+        );
+        if let Some(close) = closest(key, known) {
+            issue = issue.with_help(format!("did you mean `{close}`?"));
+        }
+        issues.push(issue);
+    }
+}
+
+// The field names of T, from its default serialized to JSON (which keeps None fields, as null).
+fn field_names<T: Serialize + Default>() -> Vec<String> {
+    match serde_json::to_value(T::default()) {
+        Ok(serde_json::Value::Object(fields)) => fields.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+// The known key nearest to a typo, if it's near enough to be what was meant.
+fn closest<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|k| (edit_distance(key, k), *k))
+        .filter(|(distance, k)| *distance <= (k.len() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, k)| k)
+}
+
+// Levenshtein distance, on chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = (above + 1)
+                .min(row[j] + 1)
+                .min(diagonal + usize::from(ca != *cb));
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+// Where `key` is written in `[section]` (None: before any header): the key of a `key = value` line,
+// or a `[section.key]` header. A line scan, not a parser, good enough to point at the line.
+fn key_span(source: &str, section: Option<&str>, key: &str) -> Option<Range<usize>> {
+    let mut current: Option<String> = None;
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let start = offset + (line.len() - trimmed.len());
+        offset += line.len();
+        if let Some(header) = trimmed.strip_prefix('[') {
+            let name = header
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default();
+            let name: String = name
+                .split('.')
+                .map(|p| p.trim().trim_matches('"'))
+                .collect::<Vec<_>>()
+                .join(".");
+            let wanted = section.map_or_else(|| key.to_string(), |s| format!("{s}.{key}"));
+            if name == wanted {
+                return Some(start..start + trimmed.trim_end().len());
+            }
+            current = Some(name);
+            continue;
+        }
+        if current.as_deref() != section {
+            continue;
+        }
+        let Some((name, _)) = trimmed.split_once('=') else {
+            continue;
+        };
+        if name.trim().trim_matches('"') == key {
+            return Some(start..start + name.trim_end().len());
+        }
+    }
+    None
+}
@@ -0,0 +1,275 @@
+// crypto: authenticated encryption of small values and files, the encrypt()/decrypt() of
+// examples/serde1.rs with the key passed in instead of a constant, and the cipher picked at runtime.
+//
+//   let key = Key::generate();                                   // 32 random bytes
+//   println!("{}", key.to_base64().expose());                    // store it: ECOSYSTEM_KEY=...
+//   let key = Key::from_env()?;                                  // later, in another process
+//   let token = crypto::seal_to_string(Cipher::default(), &key, b"postgres://app:hunter2@db")?;
+//   let plain = crypto::open_str(Cipher::default(), &key, &token)?;
+//
+// Wire format (the same as serde1.rs): nonce (12 bytes) | ciphertext | tag (16 bytes), and as text,
+// URL-safe base64 without padding. A fresh random nonce per seal, so sealing the same value twice
+// gives different outputs; the tag makes open() fail on a wrong key or any modified byte.
+//
+// Both ciphers (crate::modes::Cipher) take the same 32-byte key. The output doesn't record which
+// cipher made it: both sides must agree, like they agree on the key.
+
+use std::{fmt, path::Path};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, Nonce, OsRng},
+    AeadCore, ChaCha20Poly1305, KeyInit,
+};
+use thiserror::Error;
+
+use crate::{modes::Cipher, telemetry::redact::Redacted};
+
+// Where Key::from_env looks.
+pub const KEY_ENV: &str = "ECOSYSTEM_KEY";
+
+#[derive(Error, Debug)]
+pub enum CryptoError {
+    #[error("invalid key: {0}")]
+    InvalidKey(String),
+    #[error("ECOSYSTEM_KEY is not set (create a key with `ecosystem keygen`)")]
+    MissingKey,
+    #[error("sealed data is too short ({0} bytes)")]
+    TooShort(usize),
+    #[error("not valid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+    // wrong key, wrong cipher, or the data was modified: AEAD can't tell which
+    #[error("decryption failed: wrong key or corrupted data")]
+    Decrypt,
+    #[error("encryption failed")]
+    Encrypt,
+    #[error("decrypted value is not UTF-8")]
+    NotUtf8,
+}
+
+// A 256-bit key. Debug prints [REDACTED]; the encoded key only comes out through Redacted::expose.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Key([u8; Cipher::KEY_LEN]);
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Key").field(&Redacted::new(())).finish()
+    }
+}
+
+impl Key {
+    pub fn generate() -> Self {
+        let mut bytes = [0; Cipher::KEY_LEN];
+        bytes.copy_from_slice(&ChaCha20Poly1305::generate_key(&mut OsRng));
+        Self(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let bytes = bytes.try_into().map_err(|_| {
+            CryptoError::InvalidKey(format!(
+                "expected {} bytes, got {}",
+                Cipher::KEY_LEN,
+                bytes.len()
+            ))
+        })?;
+        Ok(Self(bytes))
+    }
+
+    // Standard or URL-safe base64, padded or not (what keygen prints, or `openssl rand -base64 32`).
+    pub fn from_base64(encoded: &str) -> Result<Self, CryptoError> {
+        let encoded = encoded.trim().trim_end_matches('=');
+        let bytes = URL_SAFE_NO_PAD
+            .decode(encoded.replace('+', "-").replace('/', "_"))
+            .map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    // The key in ECOSYSTEM_KEY.
+    pub fn from_env() -> Result<Self, CryptoError> {
+        let encoded = std::env::var(KEY_ENV).map_err(|_| CryptoError::MissingKey)?;
+        Self::from_base64(&encoded)
+    }
+
+    // A file holding the base64 key (what `ecosystem keygen --output` writes); surrounding whitespace is ignored.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, CryptoError> {
+        let path = path.as_ref();
+        let encoded = std::fs::read_to_string(path)
+            .map_err(|e| CryptoError::InvalidKey(format!("{}: {e}", path.display())))?;
+        Self::from_base64(&encoded)
+    }
+
+    // Redacted, so printing it is a deliberate .expose().
+    pub fn to_base64(&self) -> Redacted<String> {
+        Redacted::new(URL_SAFE_NO_PAD.encode(self.0))
+    }
+}
+
+pub fn seal(cipher: Cipher, key: &Key, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    match cipher {
+        Cipher::ChaCha20Poly1305 => seal_with::<ChaCha20Poly1305>(key, plaintext),
+        Cipher::Aes256Gcm => seal_with::<aes_gcm::Aes256Gcm>(key, plaintext),
+    }
+}
+
+pub fn open(cipher: Cipher, key: &Key, sealed: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    match cipher {
+        Cipher::ChaCha20Poly1305 => open_with::<ChaCha20Poly1305>(key, sealed),
+        Cipher::Aes256Gcm => open_with::<aes_gcm::Aes256Gcm>(key, sealed),
+    }
+}
+
+// seal, as URL-safe base64: for config values, env vars, JSON fields.
+pub fn seal_to_string(cipher: Cipher, key: &Key, plaintext: &[u8]) -> Result<String, CryptoError> {
+    Ok(URL_SAFE_NO_PAD.encode(seal(cipher, key, plaintext)?))
+}
+
+// open, from the text seal_to_string made; the plaintext may be any bytes (a whole file).
+pub fn open_base64(cipher: Cipher, key: &Key, sealed: &str) -> Result<Vec<u8>, CryptoError> {
+    open(cipher, key, &URL_SAFE_NO_PAD.decode(sealed.trim())?)
+}
+
+pub fn open_str(cipher: Cipher, key: &Key, sealed: &str) -> Result<String, CryptoError> {
+    String::from_utf8(open_base64(cipher, key, sealed)?).map_err(|_| CryptoError::NotUtf8)
+}
+
+fn seal_with<C: Aead + AeadCore + KeyInit>(
+    key: &Key,
+    plaintext: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let cipher = C::new_from_slice(&key.0).map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
+    let nonce = C::generate_nonce(&mut OsRng); // unique per message
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| CryptoError::Encrypt)?;
+    Ok(nonce.into_iter().chain(ciphertext).collect())
+}
+
+fn open_with<C: Aead + AeadCore + KeyInit>(
+    key: &Key,
+    sealed: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    // not even room for the nonce and the tag: not something seal() made
+    if sealed.len() < Cipher::NONCE_LEN + Cipher::TAG_LEN {
+        return Err(CryptoError::TooShort(sealed.len()));
+    }
+    let cipher = C::new_from_slice(&key.0).map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
+    let (nonce, ciphertext) = sealed.split_at(Cipher::NONCE_LEN);
+    cipher
+        .decrypt(Nonce::<C>::from_slice(nonce), ciphertext)
+        .map_err(|_| CryptoError::Decrypt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CIPHERS: [Cipher; 2] = [Cipher::ChaCha20Poly1305, Cipher::Aes256Gcm];
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let key = Key::generate();
+        for cipher in CIPHERS {
+            for plain in [&b""[..], b"postgres://app:hunter2@db", &[0xff; 4096]] {
+                let sealed = seal(cipher, &key, plain).unwrap();
+                assert_eq!(
+                    sealed.len(),
+                    Cipher::NONCE_LEN + plain.len() + Cipher::TAG_LEN
+                );
+                assert_eq!(open(cipher, &key, &sealed).unwrap(), plain, "{cipher:?}");
+            }
+            // a fresh nonce every time
+            assert_ne!(
+                seal(cipher, &key, b"same").unwrap(),
+                seal(cipher, &key, b"same").unwrap()
+            );
+            let token = seal_to_string(cipher, &key, b"secret").unwrap();
+            assert_eq!(open_str(cipher, &key, &token).unwrap(), "secret");
+        }
+    }
+
+    #[test]
+    fn wrong_key_or_modified_byte_fails_to_open() {
+        let key = Key::generate();
+        for cipher in CIPHERS {
+            let sealed = seal(cipher, &key, b"attack at dawn").unwrap();
+            assert!(matches!(
+                open(cipher, &Key::generate(), &sealed),
+                Err(CryptoError::Decrypt)
+            ));
+            for i in [0, Cipher::NONCE_LEN, sealed.len() - 1] {
+                let mut tampered = sealed.clone();
+                tampered[i] ^= 0x01;
+                assert!(
+                    matches!(open(cipher, &key, &tampered), Err(CryptoError::Decrypt)),
+                    "{cipher:?}, byte {i}"
+                );
+            }
+        }
+        // same key, other cipher
+        let sealed = seal(Cipher::ChaCha20Poly1305, &key, b"x").unwrap();
+        assert!(matches!(
+            open(Cipher::Aes256Gcm, &key, &sealed),
+            Err(CryptoError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn input_shorter_than_nonce_and_tag_is_too_short() {
+        let key = Key::generate();
+        let min = Cipher::NONCE_LEN + Cipher::TAG_LEN;
+        for cipher in CIPHERS {
+            for len in [0, Cipher::NONCE_LEN, min - 1] {
+                assert!(matches!(
+                    open(cipher, &key, &vec![0; len]),
+                    Err(CryptoError::TooShort(n)) if n == len
+                ));
+            }
+            assert!(matches!(
+                open(cipher, &key, &vec![0; min]),
+                Err(CryptoError::Decrypt)
+            ));
+        }
+    }
+
+    #[test]
+    fn key_from_base64_accepts_every_common_encoding() {
+        use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE};
+
+        // 0xfb / 0xff bytes encode to '+' '/' in standard base64 and '-' '_' in URL-safe
+        let bytes: Vec<u8> = (0..32).map(|i| [0xfb, 0xff, i][i as usize % 3]).collect();
+        let key = Key::from_bytes(&bytes).unwrap();
+        let standard = STANDARD.encode(&bytes);
+        assert!(standard.contains('+') && standard.contains('/') && standard.ends_with('='));
+        for encoded in [
+            standard,
+            STANDARD_NO_PAD.encode(&bytes),
+            URL_SAFE.encode(&bytes),
+            URL_SAFE_NO_PAD.encode(&bytes),
+            format!("  {}\n", URL_SAFE_NO_PAD.encode(&bytes)),
+        ] {
+            assert_eq!(Key::from_base64(&encoded).unwrap(), key, "{encoded}");
+        }
+        assert_eq!(Key::from_base64(key.to_base64().expose()).unwrap(), key);
+
+        for wrong in [
+            STANDARD.encode([7; 16]),
+            STANDARD.encode([7; 33]),
+            "not base64!".into(),
+        ] {
+            assert!(matches!(
+                Key::from_base64(&wrong),
+                Err(CryptoError::InvalidKey(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn debug_does_not_print_the_key() {
+        let key = Key::from_bytes(&[0xab; 32]).unwrap();
+        let debug = format!("{key:?} {key:#?}");
+        assert!(debug.contains("REDACTED"), "{debug}");
+        for leak in ["ab", "171", &key.to_base64().expose()[..8]] {
+            assert!(!debug.contains(leak), "{debug} contains {leak}");
+        }
+    }
+}
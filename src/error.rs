@@ -0,0 +1,145 @@
+// MyError: the library's error type (started life in examples/err.rs).
+// Library code returns Result<T, MyError>; binaries/examples wrap it in anyhow with .context(...).
+// It's also a miette Diagnostic, which is how the CLI (src/main.rs) prints it: the message, a stable
+// code (ecosystem::validation, same name as code()) and, where there is one, a hint on what to do:
+//
+//   Error: ecosystem::crypto
+//     × decryption failed: wrong key or corrupted data
+//     help: check that ECOSYSTEM_KEY and --cipher are the ones the value was encrypted with
+
+use std::{collections::BTreeMap, fmt};
+
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    crypto::CryptoError,
+    locale::{self, Locale, Localize},
+};
+
+#[derive(Error, Debug, Diagnostic)]
+pub enum MyError {
+    #[error("An I/O error occurred: {0}")]
+    #[diagnostic(code(ecosystem::io))]
+    Io(#[from] std::io::Error),
+    #[error("A parsing error occurred: {0}")]
+    #[diagnostic(code(ecosystem::parse))]
+    Parse(#[from] std::num::ParseIntError),
+    #[error("A serialization json error occurred: {0}")]
+    #[diagnostic(code(ecosystem::serialize))]
+    Serialize(#[from] serde_json::Error),
+    #[error("A custom error occurred: {0}")]
+    #[diagnostic(code(ecosystem::custom))]
+    Custom(String),
+    #[error("Validation failed: {0}")]
+    #[diagnostic(
+        code(ecosystem::validation),
+        help("fix the fields listed and try again")
+    )]
+    Validation(ValidationErrors),
+    #[error(transparent)]
+    #[diagnostic(
+        code(ecosystem::crypto),
+        help("check that ECOSYSTEM_KEY and --cipher are the ones the value was encrypted with")
+    )]
+    Crypto(#[from] CryptoError),
+}
+
+// Everything wrong with one input, by field, instead of stopping at the first problem:
+//   {"dob": ["is in the future"], "email": ["is not a valid email address"]}
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct ValidationErrors {
+    fields: BTreeMap<&'static str, Vec<String>>,
+}
+
+impl ValidationErrors {
+    pub fn add(&mut self, field: &'static str, message: impl Into<String>) {
+        self.fields.entry(field).or_default().push(message.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    // The messages of one field (empty if it's fine).
+    pub fn field(&self, field: &str) -> &[String] {
+        self.fields.get(field).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &[String])> + '_ {
+        self.fields
+            .iter()
+            .map(|(field, messages)| (*field, messages.as_slice()))
+    }
+
+    // Ok if nothing was added, MyError::Validation otherwise.
+    pub fn into_result(self) -> Result<(), MyError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(MyError::Validation(self))
+        }
+    }
+}
+
+// dob: is in the future; email: is not a valid email address
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (field, messages)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{field}: {}", messages.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl MyError {
+    // Stable, machine-readable name of the variant: recorded as error.type on spans
+    // and returned to HTTP clients (web::error), so failures can be filtered by kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            MyError::Io(_) => "io",
+            MyError::Parse(_) => "parse",
+            MyError::Serialize(_) => "serialize",
+            MyError::Custom(_) => "custom",
+            MyError::Validation(_) => "validation",
+            MyError::Crypto(_) => "crypto",
+        }
+    }
+    // Transient failures worth another attempt (worker::RetryPolicy): the same call may well
+    // succeed a moment later. Parse/serialize errors and custom ones fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        use std::io::ErrorKind;
+
+        match self {
+            MyError::Io(e) => matches!(
+                e.kind(),
+                ErrorKind::TimedOut
+                    | ErrorKind::Interrupted
+                    | ErrorKind::WouldBlock
+                    | ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::UnexpectedEof
+            ),
+            MyError::Parse(_)
+            | MyError::Serialize(_)
+            | MyError::Custom(_)
+            | MyError::Validation(_)
+            | MyError::Crypto(_) => false,
+        }
+    }
+}
+
+// The kind of failure in the reader's language ("校验失败"), for messages shown to end users;
+// the details (the Display above) stay in English, in logs, like code().
+impl Localize for MyError {
+    fn localize(&self, locale: Locale) -> String {
+        locale::text(locale, &format!("error.{}", self.code())).to_string()
+    }
+}
@@ -0,0 +1,169 @@
+// export (--features parquet): the users and the audit log as Parquet files, for loading into the
+// data warehouse. Columnar and compressed (Snappy), with the schema in the file, so the warehouse's
+// loader (BigQuery, Snowflake, DuckDB, Spark, ...) needs no mapping of its own.
+//
+//   let summary = export::export_to(Path::new("exports"), &users, &audit_entries, Utc::now())?;
+//   // exports/users-20261016T020000Z.parquet, exports/audit-20261016T020000Z.parquet
+//
+// The schema is derived from the row structs below by serde_arrow (Deserialize tracing), so a field
+// added to UserRecord / AuditRecord is a new column without touching anything else:
+//
+//   users   id UInt64, name Utf8, age UInt8, skills List<Utf8>, deleted_at Timestamp(ms, UTC)?
+//   audit   seq UInt64, at Timestamp(ms, UTC), actor Utf8, request_id Utf8?, action Utf8,
+//           entity Utf8, entity_id UInt64, diff Utf8 (the JSON diff as text)
+//
+// The rows are flat copies of web::users::User and audit::AuditEntry, not the structs themselves:
+// those are shaped for the API (a free-form JSON diff, fields skipped when empty), the export for
+// a table whose columns must not change from one file to the next.
+//
+// Each export is a snapshot: every user (soft-deleted ones too, with deleted_at) and the whole audit
+// log, in new files named after the export time. Written to a .tmp file and renamed, so a loader
+// watching the directory never picks up half a file.
+//
+// Triggered by `ecosystem export` (src/cli/export.rs, from the [app.storage] file and audit_log of a
+// stopped service), or by the service itself on the [app.export] schedule (web::app), from its live
+// stores; the live audit log only holds its newest entries (audit::DEFAULT_MAX_ENTRIES), the CLI
+// export reads the whole file.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use arrow_schema::{DataType, Field, FieldRef, TimeUnit};
+use chrono::{DateTime, Utc};
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_arrow::schema::{SchemaLike, TracingOptions};
+use thiserror::Error;
+
+use crate::{
+    audit::{AuditAction, AuditEntry},
+    web::users::{User, UserId},
+};
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("arrow conversion failed: {0}")]
+    Arrow(#[from] serde_arrow::Error),
+    #[error("parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserRecord {
+    pub id: UserId,
+    pub name: String,
+    pub age: u8,
+    pub skills: Vec<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl From<&User> for UserRecord {
+    fn from(user: &User) -> Self {
+        Self {
+            id: user.id,
+            name: user.name.clone(),
+            age: user.age,
+            skills: user.skills.clone(),
+            deleted_at: user.deleted_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    pub request_id: Option<String>,
+    pub action: AuditAction,
+    pub entity: String,
+    pub entity_id: u64,
+    pub diff: String,
+}
+
+impl From<&AuditEntry> for AuditRecord {
+    fn from(entry: &AuditEntry) -> Self {
+        Self {
+            seq: entry.seq,
+            at: entry.at,
+            actor: entry.actor.clone(),
+            request_id: entry.request_id.clone(),
+            action: entry.action,
+            entity: entry.entity.clone(),
+            entity_id: entry.entity_id,
+            diff: entry.diff.to_string(),
+        }
+    }
+}
+
+// What an export wrote.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportSummary {
+    pub users_file: PathBuf,
+    pub users: usize,
+    pub audit_file: PathBuf,
+    pub audit_entries: usize,
+}
+
+// users-<at>.parquet and audit-<at>.parquet in `dir` (created if missing).
+pub fn export_to(
+    dir: &Path,
+    users: &[User],
+    audit: &[AuditEntry],
+    at: DateTime<Utc>,
+) -> Result<ExportSummary, ExportError> {
+    std::fs::create_dir_all(dir)?;
+    let stamp = at.format("%Y%m%dT%H%M%SZ");
+
+    let users: Vec<UserRecord> = users.iter().map(UserRecord::from).collect();
+    let users_file = dir.join(format!("users-{stamp}.parquet"));
+    write_parquet(&users_file, &users, &["deleted_at"])?;
+
+    let audit: Vec<AuditRecord> = audit.iter().map(AuditRecord::from).collect();
+    let audit_file = dir.join(format!("audit-{stamp}.parquet"));
+    write_parquet(&audit_file, &audit, &["at"])?;
+
+    Ok(ExportSummary {
+        users_file,
+        users: users.len(),
+        audit_file,
+        audit_entries: audit.len(),
+    })
+}
+
+// One Parquet file of `rows`, schema traced from T. chrono serializes DateTime<Utc> as an RFC 3339
+// string; the fields named in `timestamps` are stored as Timestamp(ms, UTC) columns instead of text.
+pub fn write_parquet<T>(path: &Path, rows: &[T], timestamps: &[&str]) -> Result<(), ExportError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    // enum variants (AuditAction) as their names, not as dictionary-encoded unions
+    let options = TracingOptions::default().enums_without_data_as_strings(true);
+    let fields: Vec<FieldRef> = Vec::<FieldRef>::from_type::<T>(options)?
+        .into_iter()
+        .map(|field| match timestamps.contains(&field.name().as_str()) {
+            true => Arc::new(Field::new(
+                field.name(),
+                DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+                field.is_nullable(),
+            )),
+            false => field,
+        })
+        .collect();
+    let batch = serde_arrow::to_record_batch(&fields, &rows)?;
+
+    let tmp = path.with_extension("parquet.tmp");
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(File::create(&tmp)?, batch.schema(), Some(props))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
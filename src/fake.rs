@@ -0,0 +1,246 @@
+// fake: realistic-looking users for demos and load tests of the web service: names, ages, skills,
+// a homepage URL, and the personal fields (email, phone) sealed with crate::crypto when a key is given.
+//
+//   let users: Vec<FakeUser> = FakeUsers::new(42)
+//       .seal_with(Cipher::default(), Key::from_env()?)
+//       .take(1000)
+//       .collect::<Result<_, _>>()?;
+//
+//   {"id":1,"name":"Maya Okafor","age":34,"skills":["Rust","SQL"],"homepage":"https://mayaokafor.dev",
+//    "email":"<sealed token>","phone":"<sealed token>"}
+//
+// The same seed gives the same users (SplitMix64, below), so a load test can be replayed with the
+// same data, and a bug report can say "seed 42, user 17". Sealing uses a random nonce each time:
+// with a key, the tokens differ between runs even with the same seed, the plaintext doesn't.
+//
+// Only example domains (example.com, ...) and the 555-01xx phone range, which are reserved for
+// fiction: a generated user can't be a real person's address or number.
+//
+// Seeding the service: FakeUser::to_user gives the web::users::User the store holds (the web
+// service's users have no email / phone / homepage); `ecosystem generate users --seed-store`
+// writes them into the [app.storage] file.
+
+use serde::Serialize;
+
+use crate::{
+    crypto::{self, Key},
+    error::MyError,
+    modes::Cipher,
+    web::users::{User, UserId},
+};
+
+const FIRST_NAMES: &[&str] = &[
+    "Maya", "Liam", "Aiko", "Noah", "Sofia", "Mateo", "Amara", "Lucas", "Chen", "Elena", "Ravi",
+    "Zoe", "Omar", "Ingrid", "Kofi", "Lena", "Diego", "Yuki", "Hana", "Felix", "Priya", "Jonas",
+    "Nia", "Tomas",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Okafor",
+    "Nguyen",
+    "Schmidt",
+    "Tanaka",
+    "Garcia",
+    "Kowalski",
+    "Haddad",
+    "Johansson",
+    "Silva",
+    "Patel",
+    "Moreau",
+    "Rossi",
+    "Kim",
+    "Novak",
+    "Mensah",
+    "Larsen",
+    "Ortiz",
+    "Wang",
+    "Ivanova",
+    "Brennan",
+];
+
+const SKILLS: &[&str] = &[
+    "Rust",
+    "Go",
+    "Python",
+    "TypeScript",
+    "SQL",
+    "Kubernetes",
+    "Terraform",
+    "React",
+    "gRPC",
+    "Tokio",
+    "PostgreSQL",
+    "Redis",
+    "Kafka",
+    "Linux",
+    "WebAssembly",
+    "Figma",
+];
+
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+
+const SITES: &[&str] = &["dev", "blog", "io"];
+
+// The CSV column names, in FakeUser::csv_record order.
+pub const CSV_HEADER: &str = "id,name,age,skills,homepage,email,phone";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FakeUser {
+    pub id: UserId,
+    pub name: String,
+    pub age: u8,
+    pub skills: Vec<String>,
+    pub homepage: String,
+    // sealed tokens (crypto::seal_to_string) when the generator has a key, clear text otherwise
+    pub email: String,
+    pub phone: String,
+}
+
+impl FakeUser {
+    // The user as the web service stores it.
+    pub fn to_user(&self) -> User {
+        User {
+            id: self.id,
+            name: self.name.clone(),
+            age: self.age,
+            skills: self.skills.clone(),
+            deleted_at: None,
+        }
+    }
+
+    // One CSV line (no newline), skills joined with ";". Fields are quoted only when they need it.
+    pub fn csv_record(&self) -> String {
+        [
+            self.id.to_string(),
+            csv_field(&self.name),
+            self.age.to_string(),
+            csv_field(&self.skills.join(";")),
+            csv_field(&self.homepage),
+            csv_field(&self.email),
+            csv_field(&self.phone),
+        ]
+        .join(",")
+    }
+}
+
+// RFC 4180: quoted when it holds a comma, quote or line break; quotes doubled.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// An endless Iterator of users with ids counting up from 1 (or first_id). Items are Results
+// because sealing can fail; without a key they are always Ok.
+#[derive(Debug)]
+pub struct FakeUsers {
+    rng: SplitMix64,
+    next_id: UserId,
+    seal: Option<(Cipher, Key)>,
+}
+
+impl FakeUsers {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SplitMix64(seed),
+            next_id: 1,
+            seal: None,
+        }
+    }
+
+    // Ids from `id` on, e.g. after the users already in a store.
+    pub fn first_id(mut self, id: UserId) -> Self {
+        self.next_id = id;
+        self
+    }
+
+    // Seal email and phone with `key`; whoever reads them needs the same key and cipher.
+    pub fn seal_with(mut self, cipher: Cipher, key: Key) -> Self {
+        self.seal = Some((cipher, key));
+        self
+    }
+
+    fn user(&mut self) -> Result<FakeUser, MyError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let first = *self.rng.pick(FIRST_NAMES);
+        let last = *self.rng.pick(LAST_NAMES);
+        // adults of working age; a demo of the age filters needs a spread, not realism
+        let age = 18 + self.rng.below(50) as u8;
+        let mut skills: Vec<String> = Vec::new();
+        for _ in 0..self.rng.below(5) {
+            let skill = *self.rng.pick(SKILLS);
+            if !skills.iter().any(|s| s == skill) {
+                skills.push(skill.to_string());
+            }
+        }
+        let handle = format!("{first}{last}").to_ascii_lowercase();
+        let homepage = match self.rng.below(2) {
+            0 => format!("https://{handle}.{}", self.rng.pick(SITES)),
+            _ => format!("https://github.com/{handle}{id}"),
+        };
+        // the id keeps addresses unique when names repeat
+        let email = format!(
+            "{}.{}{id}@{}",
+            first.to_ascii_lowercase(),
+            last.to_ascii_lowercase(),
+            self.rng.pick(DOMAINS)
+        );
+        let phone = format!(
+            "+1-{}-555-01{:02}",
+            200 + self.rng.below(800),
+            self.rng.below(100)
+        );
+
+        Ok(FakeUser {
+            id,
+            name: format!("{first} {last}"),
+            age,
+            skills,
+            homepage,
+            email: self.sealed(email)?,
+            phone: self.sealed(phone)?,
+        })
+    }
+
+    fn sealed(&self, value: String) -> Result<String, MyError> {
+        match &self.seal {
+            Some((cipher, key)) => Ok(crypto::seal_to_string(*cipher, key, value.as_bytes())?),
+            None => Ok(value),
+        }
+    }
+}
+
+impl Iterator for FakeUsers {
+    type Item = Result<FakeUser, MyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.user())
+    }
+}
+
+// SplitMix64: a tiny, fast PRNG with good statistical quality. Not for secrets (keys and nonces
+// come from crypto's OsRng); here only reproducibility matters.
+#[derive(Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // 0..n; the modulo bias is negligible for the small n used here
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
@@ -0,0 +1,275 @@
+// formats: one serde data model, several wire formats.
+// Anything that implements Serialize can be encoded as JSON, YAML, TOML, MessagePack or CBOR through
+// Format::encode, and decoded back with Format::decode, so HTTP handlers, config tools, etc. don't
+// each pick their own serializer.
+
+// Format       Content-Type            Notes
+// Json         application/json        default; human readable
+// Yaml         application/yaml        human readable, friendly for ops tools
+// Toml         application/toml        config files; the top level must be a table (not a list)
+// MsgPack      application/msgpack     binary; smaller and faster to parse
+// Cbor         application/cbor        binary, an IETF standard (RFC 8949); "cbor" feature
+// Bincode      application/x-bincode   binary, no field names or types: both sides need the same
+//                                      Rust type; "bincode" feature, not offered over HTTP
+//
+// Binary formats report is_human_readable() == false to the types they serialize: chrono dates,
+// IP addresses, etc. pick a compact form there. Byte fields (Vec<u8>) opt in with
+// #[serde(with = "formats::bytes")] (formats/bytes.rs): base64 in text formats, raw bytes in
+// MessagePack / CBOR, instead of an array of numbers everywhere.
+
+// Converting between formats (`ecosystem convert`): transcode() decodes into a serde_yaml::Value,
+// which keeps map order and non-string keys, then encodes that. What the target can't express
+// fails instead of being dropped: null in TOML, non-string keys in JSON / TOML. Bincode can be a
+// target but not a source (it isn't self-describing).
+
+pub mod bytes;
+
+use std::{path::Path, str::FromStr};
+
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Json,
+    Yaml,
+    Toml,
+    MsgPack,
+    #[cfg(feature = "cbor")]
+    Cbor,
+    #[cfg(feature = "bincode")]
+    Bincode,
+}
+
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("unknown format: {0}")]
+    Unknown(String),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("yaml error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("toml error: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("toml encode error: {0}")]
+    TomlEncode(#[from] toml::ser::Error),
+    #[error("msgpack encode error: {0}")]
+    MsgPackEncode(#[from] rmp_serde::encode::Error),
+    #[error("msgpack decode error: {0}")]
+    MsgPackDecode(#[from] rmp_serde::decode::Error),
+    #[cfg(feature = "cbor")]
+    #[error("cbor encode error: {0}")]
+    CborEncode(#[from] ciborium::ser::Error<std::io::Error>),
+    #[cfg(feature = "cbor")]
+    #[error("cbor decode error: {0}")]
+    CborDecode(#[from] ciborium::de::Error<std::io::Error>),
+    #[cfg(feature = "bincode")]
+    #[error("bincode encode error: {0}")]
+    BincodeEncode(#[from] bincode::error::EncodeError),
+    #[cfg(feature = "bincode")]
+    #[error("bincode decode error: {0}")]
+    BincodeDecode(#[from] bincode::error::DecodeError),
+    // Format::self_describing is false: only Format::decode into the Rust type that was encoded works
+    #[error("{0} can't be decoded without knowing the type")]
+    NotSelfDescribing(&'static str),
+    #[error("not UTF-8 text: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+}
+
+impl Format {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Yaml => "application/yaml",
+            Format::Toml => "application/toml",
+            Format::MsgPack => "application/msgpack",
+            #[cfg(feature = "cbor")]
+            Format::Cbor => "application/cbor",
+            #[cfg(feature = "bincode")]
+            Format::Bincode => "application/x-bincode",
+        }
+    }
+
+    // The canonical name FromStr accepts ("json", "msgpack", ...), for storing which format was used.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+            Format::MsgPack => "msgpack",
+            #[cfg(feature = "cbor")]
+            Format::Cbor => "cbor",
+            #[cfg(feature = "bincode")]
+            Format::Bincode => "bincode",
+        }
+    }
+
+    // Text formats can be printed to a terminal; the others are bytes.
+    pub fn is_binary(&self) -> bool {
+        match self {
+            Format::Json | Format::Yaml | Format::Toml => false,
+            Format::MsgPack => true,
+            #[cfg(feature = "cbor")]
+            Format::Cbor => true,
+            #[cfg(feature = "bincode")]
+            Format::Bincode => true,
+        }
+    }
+
+    // Offered to HTTP clients (?format=, Accept). Not TOML: most responses are lists, which TOML
+    // can't have at the top level.
+    pub fn negotiable(&self) -> bool {
+        match self {
+            Format::Toml => false,
+            #[cfg(feature = "bincode")]
+            Format::Bincode => false,
+            _ => true,
+        }
+    }
+
+    // Can be decoded without knowing the type (into a serde_json::Value, by transcode, ...). Bincode
+    // can't: its bytes don't say where a field ends or what it is.
+    pub fn self_describing(&self) -> bool {
+        match self {
+            #[cfg(feature = "bincode")]
+            Format::Bincode => false,
+            _ => true,
+        }
+    }
+
+    // From a file extension: "users.yml" → Yaml; None for anything else.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        path.as_ref().extension()?.to_str()?.parse().ok()
+    }
+
+    // Maps a media type (already stripped of parameters like ";q=0.8") to a Format.
+    // "*/*" and "application/*" fall back to JSON.
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type.trim().to_ascii_lowercase().as_str() {
+            "application/json" | "application/*" | "*/*" => Some(Format::Json),
+            "application/yaml" | "application/x-yaml" | "text/yaml" => Some(Format::Yaml),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Format::MsgPack)
+            }
+            #[cfg(feature = "cbor")]
+            "application/cbor" => Some(Format::Cbor),
+            _ => None,
+        }
+    }
+
+    // A request's Content-Type ("application/yaml; charset=utf-8"); only concrete types, no wildcards.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        if media_type.contains('*') {
+            return None;
+        }
+        Self::from_media_type(media_type).filter(Format::negotiable)
+    }
+
+    pub fn encode<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, FormatError> {
+        let bytes = match self {
+            Format::Json => serde_json::to_vec(value)?,
+            Format::Yaml => serde_yaml::to_string(value)?.into_bytes(),
+            Format::Toml => toml::to_string(value)?.into_bytes(),
+            // to_vec_named keeps field names (maps instead of arrays), so the payload is self-describing
+            Format::MsgPack => rmp_serde::to_vec_named(value)?,
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes)?;
+                bytes
+            }
+            #[cfg(feature = "bincode")]
+            Format::Bincode => bincode::serde::encode_to_vec(value, bincode::config::standard())?,
+        };
+        Ok(bytes)
+    }
+
+    // encode, indented for people where the format has a choice (JSON, TOML arrays); YAML is always
+    // indented, and binary formats have no layout.
+    pub fn encode_pretty<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, FormatError> {
+        match self {
+            Format::Json => Ok(serde_json::to_vec_pretty(value)?),
+            Format::Toml => Ok(toml::to_string_pretty(value)?.into_bytes()),
+            _ => self.encode(value),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, FormatError> {
+        let value = match self {
+            Format::Json => serde_json::from_slice(bytes)?,
+            Format::Yaml => serde_yaml::from_slice(bytes)?,
+            Format::Toml => toml::from_str(std::str::from_utf8(bytes)?)?,
+            Format::MsgPack => rmp_serde::from_slice(bytes)?,
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::from_reader(bytes)?,
+            #[cfg(feature = "bincode")]
+            Format::Bincode => {
+                bincode::serde::decode_from_slice(bytes, bincode::config::standard())?.0
+            }
+        };
+        Ok(value)
+    }
+}
+
+// `bytes` in `from` re-encoded as `to`, e.g. a JSON fixture as YAML, a TOML config as JSON.
+pub fn transcode(
+    from: Format,
+    to: Format,
+    bytes: &[u8],
+    pretty: bool,
+) -> Result<Vec<u8>, FormatError> {
+    if !from.self_describing() {
+        return Err(FormatError::NotSelfDescribing(from.name()));
+    }
+    let value: serde_yaml::Value = from.decode(bytes)?;
+    if pretty {
+        to.encode_pretty(&value)
+    } else {
+        to.encode(&value)
+    }
+}
+
+// Used by `?format=yaml` style overrides, --from / --to flags and file extensions.
+impl FromStr for Format {
+    type Err = FormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            "toml" => Ok(Format::Toml),
+            "msgpack" | "mpk" => Ok(Format::MsgPack),
+            #[cfg(feature = "cbor")]
+            "cbor" => Ok(Format::Cbor),
+            #[cfg(feature = "bincode")]
+            "bincode" => Ok(Format::Bincode),
+            _ => Err(FormatError::Unknown(s.to_string())),
+        }
+    }
+}
+
+// Picks the best Format for an Accept header value, honouring q-values:
+// "application/yaml;q=0.9, application/msgpack" → MsgPack (implicit q=1 beats 0.9)
+// Returns None when nothing acceptable is offered (→ 406 Not Acceptable).
+pub fn negotiate(accept: &str) -> Option<Format> {
+    let mut candidates: Vec<(f32, &str)> = accept
+        .split(',')
+        .map(|item| {
+            let mut parts = item.split(';');
+            let media_type = parts.next().unwrap_or_default().trim();
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (q, media_type)
+        })
+        .filter(|(q, _)| *q > 0.0)
+        .collect();
+    // stable sort: equal q-values keep the client's order
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    candidates
+        .into_iter()
+        .find_map(|(_, media_type)| Format::from_media_type(media_type))
+}
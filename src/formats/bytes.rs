@@ -0,0 +1,79 @@
+// formats::bytes: a Vec<u8> field that is base64 text in human-readable formats (JSON, YAML, TOML)
+// and raw bytes in binary ones (MessagePack, CBOR), decided by the serializer's is_human_readable():
+//
+//   #[derive(Serialize, Deserialize)]
+//   struct Attachment {
+//       name: String,
+//       #[serde(with = "ecosystem::formats::bytes")]
+//       data: Vec<u8>,
+//   }
+//
+//   JSON     {"name":"logo.png","data":"iVBORw0KGgo..."}          URL-safe base64, no padding
+//   CBOR     a2 64 6e616d65 ... 64 64617461 58 1f 89504e47...     a byte string (major type 2)
+//   MsgPack  82 a4 6e616d65 ... a4 64617461 c4 1f 89504e47...     bin 8
+//
+// Without it serde writes Vec<u8> as a sequence of numbers in every format: [137, 80, 78, ...]
+// in JSON, and one integer per byte (up to 2 bytes each) in CBOR / MessagePack, instead of a
+// byte string. Deserializing accepts all of these, and standard or URL-safe base64, padded or not,
+// so data written before a field switched to this module still reads.
+
+use std::fmt;
+
+use base64::{
+    engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD},
+    Engine,
+};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserializer, Serializer,
+};
+
+pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&URL_SAFE_NO_PAD.encode(bytes))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(BytesVisitor)
+    } else {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("bytes, a base64 string or a sequence of bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Vec<u8>, E> {
+        let v = v.trim_end_matches('=');
+        URL_SAFE_NO_PAD
+            .decode(v)
+            .or_else(|_| STANDARD_NO_PAD.decode(v))
+            .map_err(|e| E::custom(format!("invalid base64: {e}")))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
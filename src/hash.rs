@@ -0,0 +1,224 @@
+// hash: blake3 hashing for inputs of any size.
+// blake3::hash (tokio1.rs, worker::expensive_blocking_task) runs on one thread. blake3 is a tree hash,
+// so a big input can be split across cores (the "rayon" feature of the blake3 crate) with the SAME
+// result as the single-threaded hash:
+//
+//   hash(&bytes)                                   → blake3::Hash, multi-threaded above PARALLEL_THRESHOLD
+//   keyed_hash(&key, &bytes)                       → a MAC: only holders of the 32-byte key can compute it
+//   derive_key("ecosystem 2025 session tokens", &secret) → a 32-byte subkey per context string
+//   hash_with(Mode::Keyed(key), &bytes)            → any of the three, picked at runtime
+//
+// Below PARALLEL_THRESHOLD splitting costs more than it saves, so small inputs stay on the calling thread.
+// Above it, the work runs on rayon's global pool (one thread per core), and the calling thread blocks
+// until it's done: call it from spawn_blocking or a WorkerPool job, not directly in async code.
+//
+// Files: hash_file(path).await reads CHUNK_SIZE at a time with tokio::fs, so memory stays at one chunk
+// whatever the file size (tokio1.rs's fs::read loads the whole file):
+//
+//   hash_file("big.iso").await?
+//   hash_file_with("big.iso", Mode::Hash, |p| info!(percent = ?p.percent(), "hashing")).await?
+//
+// Checksum files: checksum / checksum_file give the hex digest with a HashAlgorithm, BLAKE3 or
+// SHA-256 (what sha256sum and download pages use); parse_checksum_line reads their lines back:
+//
+//   checksum_file("big.iso", HashAlgorithm::Sha256).await?   → "9f86d081884c7d65..."
+//
+// Key flow:
+// open → file length (for Progress::total)
+// loop:
+//   ├→ read the next chunk (async, the runtime thread is free meanwhile)
+//   ├→ spawn_blocking: update(hasher, chunk) (multi-threaded: chunks are ≥ PARALLEL_THRESHOLD)
+//   └→ progress(Progress { hashed, total })
+// EOF → finalize
+//
+// derive_key's context string should be hardcoded, globally unique and application-specific
+// ("[application] [date] [purpose]"), never derived from user input; the key material is the secret.
+
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+use tokio::{fs::File, io::AsyncReadExt};
+
+use crate::{error::MyError, modes::HashAlgorithm};
+
+pub use blake3::Hash;
+
+// blake3 docs: update_rayon is slower than update below ~128 KiB
+pub const PARALLEL_THRESHOLD: usize = 128 * 1024;
+
+pub const CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Hash,
+    Keyed([u8; blake3::KEY_LEN]),
+    DeriveKey(&'static str),
+}
+
+impl Mode {
+    pub fn hasher(&self) -> blake3::Hasher {
+        match self {
+            Mode::Hash => blake3::Hasher::new(),
+            Mode::Keyed(key) => blake3::Hasher::new_keyed(key),
+            Mode::DeriveKey(context) => blake3::Hasher::new_derive_key(context),
+        }
+    }
+}
+
+pub fn hash(data: &[u8]) -> Hash {
+    hash_with(Mode::Hash, data)
+}
+
+pub fn keyed_hash(key: &[u8; blake3::KEY_LEN], data: &[u8]) -> Hash {
+    hash_with(Mode::Keyed(*key), data)
+}
+
+pub fn derive_key(context: &'static str, key_material: &[u8]) -> [u8; blake3::KEY_LEN] {
+    *hash_with(Mode::DeriveKey(context), key_material).as_bytes()
+}
+
+pub fn hash_with(mode: Mode, data: &[u8]) -> Hash {
+    let mut hasher = mode.hasher();
+    update(&mut hasher, data);
+    hasher.finalize()
+}
+
+// Feeds `data` to `hasher`, multi-threaded when it's large enough to pay off.
+pub fn update(hasher: &mut blake3::Hasher, data: &[u8]) {
+    if data.len() >= PARALLEL_THRESHOLD {
+        hasher.update_rayon(data);
+    } else {
+        hasher.update(data);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub hashed: u64,
+    // file length when hashing started; None if it couldn't be read (pipes, /proc files)
+    pub total: Option<u64>,
+}
+
+impl Progress {
+    pub fn percent(&self) -> Option<f64> {
+        self.total
+            .map(|total| (self.hashed as f64 / total.max(1) as f64 * 100.0).min(100.0))
+    }
+}
+
+pub async fn hash_file(path: impl AsRef<Path>) -> Result<Hash, MyError> {
+    hash_file_with(path, Mode::Hash, |_| {}).await
+}
+
+// `progress` is called after every chunk, on the calling task.
+pub async fn hash_file_with<F>(
+    path: impl AsRef<Path>,
+    mode: Mode,
+    progress: F,
+) -> Result<Hash, MyError>
+where
+    F: FnMut(Progress),
+{
+    let hasher = fold_file(path, mode.hasher(), update, progress).await?;
+    Ok(hasher.finalize())
+}
+
+// The hex digest of `data` with `algo`, the text of a checksum file line.
+pub fn checksum(algo: HashAlgorithm, data: &[u8]) -> String {
+    let mut hasher = Checksummer::new(algo);
+    hasher.update(data);
+    hasher.finalize_hex()
+}
+
+// checksum for a file, read in chunks like hash_file (BLAKE3 multi-threaded per chunk, SHA-256 not).
+pub async fn checksum_file(path: impl AsRef<Path>, algo: HashAlgorithm) -> Result<String, MyError> {
+    let hasher = fold_file(path, Checksummer::new(algo), Checksummer::update, |_| {}).await?;
+    Ok(hasher.finalize_hex())
+}
+
+// One "<hex>  <path>" line of a checksum file (b3sum / sha256sum format, "*" before the path is
+// the binary-mode marker of sha256sum -b); None for blank lines, comments and anything else.
+pub fn parse_checksum_line(line: &str) -> Option<(&str, &str)> {
+    let (digest, path) = line.split_once(' ')?;
+    let path = path.strip_prefix([' ', '*'])?;
+    let valid = !digest.is_empty() && digest.bytes().all(|b| b.is_ascii_hexdigit());
+    (valid && !path.is_empty()).then_some((digest, path))
+}
+
+// The BLAKE3 hasher is ~2 KiB (its chunk stack); boxed so a Sha256 checksummer doesn't carry that.
+enum Checksummer {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(Sha256),
+}
+
+impl Checksummer {
+    fn new(algo: HashAlgorithm) -> Self {
+        match algo {
+            HashAlgorithm::Blake3 => Self::Blake3(Box::default()),
+            HashAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Blake3(hasher) => update(hasher, data),
+            Self::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        match self {
+            Self::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+            Self::Sha256(hasher) => hasher
+                .finalize()
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect(),
+        }
+    }
+}
+
+// The chunk loop of hash_file_with, for any hasher: `update` runs on a blocking thread per chunk.
+async fn fold_file<H, F>(
+    path: impl AsRef<Path>,
+    mut hasher: H,
+    update: fn(&mut H, &[u8]),
+    mut progress: F,
+) -> Result<H, MyError>
+where
+    H: Send + 'static,
+    F: FnMut(Progress),
+{
+    let mut file = File::open(path).await?;
+    let total = file.metadata().await.ok().map(|m| m.len());
+    let mut chunk = vec![0; CHUNK_SIZE];
+    let mut hashed = 0;
+    loop {
+        let n = read_chunk(&mut file, &mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        // the hasher and buffer move to a blocking thread and back: no copy, no async thread blocked
+        (hasher, chunk) = tokio::task::spawn_blocking(move || {
+            update(&mut hasher, &chunk[..n]);
+            (hasher, chunk)
+        })
+        .await
+        .map_err(|e| MyError::Custom(format!("hashing task failed: {e}")))?;
+        hashed += n as u64;
+        progress(Progress { hashed, total });
+    }
+    Ok(hasher)
+}
+
+// Fills `buf` as far as the file allows (read() may return less than asked before EOF).
+async fn read_chunk(file: &mut File, buf: &mut [u8]) -> Result<usize, MyError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..]).await? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
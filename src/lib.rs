@@ -3,3 +3,29 @@
 // mod error; - Declares the error module (from src/error.rs or src/error/mod.rs)
 // pub use error::MyError; - Re-exports MyError from the error module, making it available at the crate root level
 // External users can import it as: use ecosystem::MyError; instead of use ecosystem::error::MyError;
+
+pub mod audit;
+pub mod bench;
+pub mod client;
+pub mod clock;
+pub mod codec;
+pub mod config;
+pub mod crypto;
+pub mod error;
+#[cfg(feature = "parquet")]
+pub mod export;
+pub mod fake;
+pub mod formats;
+pub mod hash;
+pub mod locale;
+pub mod model;
+pub mod modes;
+pub mod pipeline;
+pub mod proxy;
+pub mod runtime;
+pub mod scheduler;
+pub mod tagged;
+pub mod telemetry;
+pub mod units;
+pub mod web;
+pub mod worker;
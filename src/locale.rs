@@ -0,0 +1,151 @@
+// locale: translated, human-facing text for enums, picked at runtime, next to (not instead of) their
+// identifiers. serde / strum names ("onLeave", "validation") are for machines and never change with
+// the language; what a person reads comes from a table here:
+//
+//   let locale = Locale::from_env();                              // LANG=zh_CN.UTF-8 → Locale::Zh
+//   println!("{}", job.state().localized(locale));                // 休假至 2025-03-01
+//   println!("{}", job.state().localized(Locale::En));            // on leave until 2025-03-01
+//   let locale: Locale = "zh-CN".parse()?;                        // from a query parameter, a header, a flag
+//
+// Key flow:
+// value.localized(locale) → Localize::localize → locale::render(locale, "work_state.on_leave", args)
+//   ├→ MESSAGES row "work_state.on_leave", column of `locale`
+//   ├→ empty (not translated yet) → the English column
+//   └→ {placeholders} replaced by the args
+//
+// Adding a language: a Locale variant, and a column in MESSAGES (empty strings fall back to English).
+// Adding a message: a row; the key is "<type>.<variant>" by convention.
+
+use std::fmt;
+
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use strum::{Display, EnumString, VariantNames};
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    EnumString,
+    Display,
+    VariantNames,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum Locale {
+    #[default]
+    #[strum(to_string = "en", serialize = "en-us", serialize = "en-gb")]
+    En,
+    #[strum(to_string = "zh", serialize = "zh-cn", serialize = "zh-hans")]
+    Zh,
+}
+
+impl Locale {
+    // A POSIX locale or language tag: "zh_CN.UTF-8", "zh-TW", "en"; None if the language isn't supported.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let tag = tag
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .replace('_', "-");
+        tag.parse()
+            .ok()
+            .or_else(|| tag.split('-').next()?.parse().ok())
+    }
+
+    // LC_ALL, then LC_MESSAGES, then LANG, like gettext; English if none is set or supported.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_tag(&value))
+            .unwrap_or_default()
+    }
+
+    fn column(self) -> usize {
+        self as usize
+    }
+}
+
+// key, then one column per Locale, in declaration order
+const MESSAGES: &[(&str, [&str; 2])] = &[
+    (
+        "work_state.working",
+        ["working as {role}", "在职（{role}）"],
+    ),
+    (
+        "work_state.on_leave",
+        ["on leave until {until}", "休假至 {until}"],
+    ),
+    ("work_state.terminated", ["terminated", "已离职"]),
+    (
+        "transition.already_on_leave",
+        ["already on leave until {until}", "已在休假中，至 {until}"],
+    ),
+    ("transition.not_on_leave", ["not on leave", "当前未在休假"]),
+    (
+        "transition.leave_ends_in_past",
+        [
+            "leave must end in the future, not at {until}",
+            "休假结束时间必须晚于现在，而不是 {until}",
+        ],
+    ),
+    (
+        "transition.terminated",
+        ["employment is terminated", "雇佣关系已终止"],
+    ),
+    ("error.io", ["input/output error", "输入输出错误"]),
+    ("error.parse", ["invalid number", "数字格式错误"]),
+    ("error.serialize", ["invalid JSON", "JSON 格式错误"]),
+    ("error.custom", ["request failed", "请求失败"]),
+    ("error.validation", ["validation failed", "校验失败"]),
+    ("error.crypto", ["encryption error", "加密错误"]),
+];
+
+// The message for `key` in `locale`, the English one if it isn't translated, the key itself if unknown.
+pub fn text(locale: Locale, key: &str) -> &str {
+    let Some((_, columns)) = MESSAGES.iter().find(|(k, _)| *k == key) else {
+        return key;
+    };
+    match columns[locale.column()] {
+        "" => columns[Locale::En.column()],
+        translated => translated,
+    }
+}
+
+// text() with its {name} placeholders filled in.
+pub fn render(locale: Locale, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut out = text(locale, key).to_string();
+    for (name, value) in args {
+        out = out.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    out
+}
+
+pub trait Localize {
+    fn localize(&self, locale: Locale) -> String;
+
+    // For format!/println!: "{}" renders localize(locale).
+    fn localized(&self, locale: Locale) -> Localized<'_, Self> {
+        Localized {
+            value: self,
+            locale,
+        }
+    }
+}
+
+pub struct Localized<'a, T: ?Sized> {
+    value: &'a T,
+    locale: Locale,
+}
+
+impl<T: Localize + ?Sized> fmt::Display for Localized<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value.localize(self.locale))
+    }
+}
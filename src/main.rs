@@ -0,0 +1,56 @@
+// ecosystem: the library as a command-line tool. The examples show one feature each; this binary
+// bundles the ones useful outside a tutorial behind subcommands:
+//
+//   ecosystem serve --port 9000 --storage file:users.json   # the web service (web::app)
+//   ecosystem proxy --config proxy.toml --upstream 127.0.0.1:8080   # crate::proxy
+//   ecosystem hash Cargo.toml -                         # BLAKE3 of a file and of stdin (--algo sha256)
+//   ecosystem keygen                                    # a key for ECOSYSTEM_KEY or --key-file
+//   echo -n 'hunter2' | ecosystem encrypt               # → URL-safe base64 token
+//   echo "$TOKEN" | ecosystem decrypt
+//   ecosystem config check server.toml proxy.toml       # every problem, exit code 1 on errors
+//   ecosystem convert --from json --to yaml < users.json   # JSON/YAML/TOML/MessagePack/CBOR
+//   ecosystem generate users -n 1000 --format ndjson     # fake users (--seed-store: into users.json)
+//   ecosystem jobs list --state dead                    # the durable queue (--features sqlite-queue)
+//   ecosystem bench http://127.0.0.1:8081/users -d 30s  # throughput and latency percentiles
+//   ecosystem proxy --daemon --pid-file proxy.pid       # in the background; `ecosystem stop --pid-file proxy.pid`
+//   ecosystem completions zsh                           # and the hidden `mangen`, for packaging
+//
+// Global flags (before or after the subcommand):
+//   -c, --config <file>   server.toml by default (or .yaml / .json), or ECOSYSTEM_CONFIG; [runtime], [logging] and the
+//                         server settings come from it, with their usual env overrides
+//   --log-level <level>   instead of [logging] level / LOG_LEVEL ("debug", "info,sqlx=warn")
+//   --log-format <fmt>    full, pretty or json
+//   --set <key>=<value>   any config value, over file and env: --set logging.level=debug --set listen=0.0.0.0:9000
+//
+// Logs go to stderr, so stdout carries only the command's output (hashes, tokens, keys) and can be piped.
+// Errors are printed by miette: MyError is a Diagnostic, so the message comes with its code and a hint.
+//
+// Key flow:
+// main() → Cli::parse()
+//   ├→ --daemon → fork, the parent exits (cli/daemon.rs)
+//   ├→ runtime::build(RuntimeConfig from --config)
+//   └→ runtime.block_on(cli.run())
+//       ├→ telemetry from [logging] + flag overrides, console on stderr
+//       └→ the subcommand (src/cli/*.rs) → Err → miette report, exit code 1
+
+mod cli;
+
+use clap::Parser;
+use ecosystem::{
+    config,
+    runtime::{self, RuntimeConfig},
+};
+use miette::{IntoDiagnostic, Result};
+
+use crate::cli::Cli;
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    // before the first config::load, [runtime] included
+    config::set_overrides(cli.overrides.clone());
+    // before the runtime: a forked child keeps only the forking thread
+    let _pid_file = cli.detach()?;
+    let runtime =
+        runtime::build(&RuntimeConfig::load(&cli.config).into_diagnostic()?).into_diagnostic()?;
+    runtime.block_on(cli.run())
+}
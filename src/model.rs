@@ -0,0 +1,16 @@
+// model: domain types with their rules, so every way of creating one (code, config files, API payloads)
+// goes through the same validation.
+//
+// model::User (model/user.rs): examples/builder.rs's derive_builder User, where build() validates
+//   every field and fails with MyError::Validation listing all problems, not just the first missing field.
+//   Builders also come from JSON / TOML / any serde source (UserBuilder::from_json), validated the same way.
+// TypedUserBuilder (model/typed_builder.rs): the same User, with name and dob required at compile time.
+// Employment (model/work_state.rs): serde1.rs's WorkState as a state machine, transitions checked and logged.
+
+mod typed_builder;
+mod user;
+mod work_state;
+
+pub use typed_builder::{Set, TypedUserBuilder, Unset};
+pub use user::{User, UserBuilder};
+pub use work_state::{Employment, TransitionError, WorkEvent, WorkState};
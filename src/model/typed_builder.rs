@@ -0,0 +1,179 @@
+// TypedUserBuilder: a typestate builder for model::User. Whether name and dob are set is part of the type,
+// so build() only exists once both are, and forgetting one is a compile error instead of an Err at runtime:
+//
+//   let user = TypedUserBuilder::new()
+//       .name("Alice")
+//       .dob(Utc.with_ymd_and_hms(1990, 1, 1, 0, 0, 0).unwrap())
+//       .skill("Rust")
+//       .build()?;
+//
+//   TypedUserBuilder::new().name("Alice").build()
+//   // error[E0599]: no method named `build` found for struct `TypedUserBuilder<Set<String>, Unset>`
+//
+// Key flow (type parameters N = name, D = dob):
+// TypedUserBuilder<Unset, Unset> ─.name()→ <Set<String>, Unset> ─.dob()→ <Set<String>, Set<DateTime<Utc>>> ─.build()→ User
+//                                (either order; .email() / .age() / .skill() keep the state)
+//
+// Setting a field twice doesn't compile either (name() only exists while it's Unset).
+// build() hands everything to UserBuilder::build(), so the rules the types can't express (blank name,
+// email format, dob in the future ...) are checked in one place. UserBuilder stays the builder for
+// input that's only known at runtime: JSON / TOML / config (UserBuilder::from_json).
+
+use chrono::{DateTime, Utc};
+
+use super::user::{User, UserBuilder};
+use crate::{
+    clock::{Clock, SystemClock},
+    error::MyError,
+};
+
+// A required field not given yet.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unset;
+
+// A required field given.
+#[derive(Debug, Clone)]
+pub struct Set<T>(T);
+
+#[derive(Debug, Clone, Default)]
+pub struct TypedUserBuilder<N = Unset, D = Unset> {
+    name: N,
+    dob: D,
+    email: Option<String>,
+    age: Option<u32>,
+    skills: Vec<String>,
+}
+
+impl TypedUserBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<D> TypedUserBuilder<Unset, D> {
+    pub fn name(self, name: impl Into<String>) -> TypedUserBuilder<Set<String>, D> {
+        TypedUserBuilder {
+            name: Set(name.into()),
+            dob: self.dob,
+            email: self.email,
+            age: self.age,
+            skills: self.skills,
+        }
+    }
+}
+
+impl<N> TypedUserBuilder<N, Unset> {
+    pub fn dob(self, dob: DateTime<Utc>) -> TypedUserBuilder<N, Set<DateTime<Utc>>> {
+        TypedUserBuilder {
+            name: self.name,
+            dob: Set(dob),
+            email: self.email,
+            age: self.age,
+            skills: self.skills,
+        }
+    }
+}
+
+impl<N, D> TypedUserBuilder<N, D> {
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    // The age the caller expects; build() fails if dob says otherwise.
+    pub fn age(mut self, age: u32) -> Self {
+        self.age = Some(age);
+        self
+    }
+
+    pub fn skill(mut self, skill: impl Into<String>) -> Self {
+        self.skills.push(skill.into());
+        self
+    }
+}
+
+impl TypedUserBuilder<Set<String>, Set<DateTime<Utc>>> {
+    pub fn build(self) -> Result<User, MyError> {
+        self.build_with(&SystemClock)
+    }
+
+    pub fn build_with(self, clock: &dyn Clock) -> Result<User, MyError> {
+        let mut builder = UserBuilder::default();
+        builder.name(self.name.0).dob_utc(self.dob.0);
+        if let Some(email) = self.email {
+            builder.email(email);
+        }
+        if let Some(age) = self.age {
+            builder.age(age);
+        }
+        for skill in self.skills {
+            builder.skill(skill);
+        }
+        builder.build_with(clock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::clock::FixedClock;
+
+    fn clock() -> FixedClock {
+        FixedClock::new(Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap())
+    }
+
+    fn born(year: i32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, 6, 15, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn builds_once_name_and_dob_are_set_in_either_order() {
+        let user = TypedUserBuilder::new()
+            .name("Alice")
+            .skill("Rust")
+            .dob(born(1990))
+            .email("alice@example.com")
+            .skill("Go")
+            .build_with(&clock())
+            .unwrap();
+        assert_eq!(user.name, "Alice");
+        assert_eq!(user.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(user.age, 34);
+        assert_eq!(user.skills, ["Rust", "Go"]);
+
+        let user = TypedUserBuilder::new()
+            .dob(born(1990))
+            .age(34)
+            .name("Bob")
+            .build_with(&clock())
+            .unwrap();
+        assert_eq!((user.name.as_str(), user.age), ("Bob", 34));
+    }
+
+    // what the types can't express is still checked, by UserBuilder
+    #[test]
+    fn runtime_rules_still_apply() {
+        let err = TypedUserBuilder::new()
+            .name(" ")
+            .dob(born(2030))
+            .email("nope")
+            .build_with(&clock())
+            .unwrap_err();
+        let MyError::Validation(errors) = err else {
+            panic!("expected validation errors, got {err:?}");
+        };
+        assert_eq!(errors.field("name"), ["must not be blank"]);
+        assert_eq!(errors.field("dob"), ["is in the future"]);
+        assert_eq!(errors.field("email"), ["is not a valid email address"]);
+
+        let err = TypedUserBuilder::new()
+            .name("Alice")
+            .dob(born(1990))
+            .age(30)
+            .build_with(&clock())
+            .unwrap_err();
+        assert!(matches!(err, MyError::Validation(e) if !e.field("age").is_empty()));
+    }
+}
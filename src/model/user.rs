@@ -0,0 +1,382 @@
+// User built with derive_builder, like examples/builder.rs, plus validation in build():
+//
+//   let user = User::builder()
+//       .name("Alice")
+//       .email("alice@example.com")
+//       .dob("1990-01-01T00:00:00Z")
+//       .skill("Rust")
+//       .build()?;                 // Result<User, MyError>
+//
+// Key flow:
+// setters → UserBuilder (dob kept as the parse result, so a bad date is reported, not just "not set")
+// build() → every rule checked, every failure collected in ValidationErrors
+//   ├→ any failure → Err(MyError::Validation): {"dob": ["is in the future"], "email": ["is not a valid email address"]}
+//   └→ all fine    → _priv_build() (derive_builder's build) → age computed from dob → Ok(User)
+//
+// From config files and API payloads: UserBuilder implements Deserialize, filling the builder through the
+// same setters (dob parsed by dob(), skills defaulting to []), so build() validates them like any other:
+//
+//   let user = UserBuilder::from_json(r#"{"name":"Alice","dob":"1990-01-01T00:00:00Z"}"#)?.build()?;
+//   let user = UserBuilder::from_toml(&std::fs::read_to_string("user.toml")?)?.skill("Rust").build()?;
+//   let user = UserBuilder::from_yaml("name: Alice\ndob: 1990-01-01T00:00:00Z\n")?.build()?;
+//   let builder: UserBuilder = figment.focus("admin").extract()?;     // any serde format / source
+//
+// The input may be partial (missing fields are build()'s to report), but unknown keys are rejected:
+// a typo like "emial" would otherwise be dropped silently.
+//
+// Time comes from a Clock (crate::clock): build() uses the system clock, build_with(&clock) any other,
+// so tests and backfills can say what "today" is. The age is counted in the dob's own UTC offset:
+// "1990-06-01T00:30:00+08:00" is a June 1 birthday, although it's still May 31 in UTC.
+//
+// Rules: name required and not blank; email (optional) looks like an address; dob required, valid RFC 3339,
+// not in the future, at most MAX_AGE years ago; age (optional) must agree with dob: it's computed anyway,
+// .age(n) only states what the caller expects (e.g. a form with both fields).
+
+use chrono::{DateTime, FixedOffset, Utc};
+use derive_builder::Builder;
+use figment::{
+    providers::{Format, Toml},
+    Figment,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{
+    clock::{self, Clock, SystemClock},
+    error::{MyError, ValidationErrors},
+    telemetry::redact::Redact,
+};
+
+// Older dates of birth are typos (1899 for 1989), not customers.
+pub const MAX_AGE: u32 = 150;
+
+// Debug via Redact: the email is personal data and shows as [REDACTED] in logs.
+#[derive(Builder, Redact, Clone, PartialEq, Serialize, Deserialize)]
+#[builder(build_fn(private, name = "_priv_build"))]
+pub struct User {
+    #[builder(setter(into))]
+    pub name: String,
+    #[builder(setter(into, strip_option), default)]
+    #[redact]
+    pub email: Option<String>,
+    // custom setter parsing RFC 3339; the builder field keeps the parse error for build() to report,
+    // and the offset, for the local date of birth
+    #[builder(
+        setter(custom),
+        field(
+            ty = "Option<Result<DateTime<FixedOffset>, chrono::ParseError>>",
+            build = "self.dob.and_then(Result::ok).map(|dob| dob.to_utc()).unwrap_or_default()"
+        )
+    )]
+    pub dob: DateTime<Utc>,
+    // computed from dob in build()
+    #[builder(setter(custom), default)]
+    pub age: u32,
+    #[builder(default, setter(each(name = "skill", into)))]
+    pub skills: Vec<String>,
+}
+
+impl User {
+    pub fn builder() -> UserBuilder {
+        UserBuilder::default()
+    }
+}
+
+impl UserBuilder {
+    // RFC 3339, e.g. "1990-01-01T00:00:00Z" or "1990-01-01T08:00:00+08:00" (stored as UTC).
+    pub fn dob(&mut self, value: &str) -> &mut Self {
+        self.dob = Some(DateTime::parse_from_rfc3339(value));
+        self
+    }
+
+    // An already parsed dob (TypedUserBuilder, code that has a DateTime).
+    pub fn dob_utc(&mut self, value: DateTime<Utc>) -> &mut Self {
+        self.dob = Some(Ok(value.fixed_offset()));
+        self
+    }
+
+    // The age the caller expects; build() fails if dob says otherwise.
+    pub fn age(&mut self, age: u32) -> &mut Self {
+        self.age = Some(age);
+        self
+    }
+
+    // A builder from a JSON object; not validated until build().
+    pub fn from_json(input: &str) -> Result<Self, MyError> {
+        Ok(serde_json::from_str(input)?)
+    }
+
+    // A builder from a YAML document (a mapping at the top level); not validated until build().
+    pub fn from_yaml(input: &str) -> Result<Self, MyError> {
+        serde_yaml::from_str(input).map_err(|e| MyError::Custom(format!("invalid YAML user: {e}")))
+    }
+
+    // A builder from a TOML document (the fields at the top level); not validated until build().
+    pub fn from_toml(input: &str) -> Result<Self, MyError> {
+        Figment::from(Toml::string(input))
+            .extract()
+            .map_err(|e| MyError::Custom(format!("invalid TOML user: {e}")))
+    }
+
+    pub fn build(&self) -> Result<User, MyError> {
+        self.build_with(&SystemClock)
+    }
+
+    // build() as of clock.now().
+    pub fn build_with(&self, clock: &dyn Clock) -> Result<User, MyError> {
+        let mut errors = ValidationErrors::default();
+        match self.name.as_deref().map(str::trim) {
+            None => errors.add("name", "is required"),
+            Some("") => errors.add("name", "must not be blank"),
+            Some(_) => {}
+        }
+        if let Some(Some(email)) = &self.email {
+            if !is_valid_email(email) {
+                errors.add("email", "is not a valid email address");
+            }
+        }
+        let age = self.check_dob(clock.now(), &mut errors);
+        errors.into_result()?;
+
+        // every required field is set by now
+        let mut user = self
+            ._priv_build()
+            .map_err(|e| MyError::Custom(e.to_string()))?;
+        user.age = age.unwrap_or_default();
+        Ok(user)
+    }
+
+    // dob's rules, and age's (it depends on dob); the age when dob is valid.
+    fn check_dob(&self, now: DateTime<Utc>, errors: &mut ValidationErrors) -> Option<u32> {
+        let dob = match self.dob {
+            None => {
+                errors.add("dob", "is required");
+                return None;
+            }
+            Some(Err(e)) => {
+                errors.add("dob", format!("is not an RFC 3339 date: {e}"));
+                return None;
+            }
+            Some(Ok(dob)) => dob,
+        };
+        if dob > now {
+            errors.add("dob", "is in the future");
+            return None;
+        }
+        // today where the person was born (as far as the offset tells)
+        let today = now.with_timezone(dob.offset()).date_naive();
+        let age = clock::age_on(dob.date_naive(), today);
+        if age > MAX_AGE {
+            errors.add("dob", format!("is more than {MAX_AGE} years ago"));
+        }
+        if let Some(expected) = self.age {
+            if expected != age {
+                errors.add("age", format!("is {expected}, but dob makes it {age}"));
+            }
+        }
+        Some(age)
+    }
+}
+
+// The serialized form of a builder: every field optional, dob as the RFC 3339 string the setter takes.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserInput {
+    name: Option<String>,
+    email: Option<String>,
+    dob: Option<String>,
+    age: Option<u32>,
+    #[serde(default)]
+    skills: Vec<String>,
+}
+
+impl<'de> Deserialize<'de> for UserBuilder {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let input = UserInput::deserialize(deserializer)?;
+        let mut builder = UserBuilder::default();
+        if let Some(name) = input.name {
+            builder.name(name);
+        }
+        if let Some(email) = input.email {
+            builder.email(email);
+        }
+        if let Some(dob) = input.dob {
+            builder.dob(&dob);
+        }
+        if let Some(age) = input.age {
+            builder.age(age);
+        }
+        for skill in input.skills {
+            builder.skill(skill);
+        }
+        Ok(builder)
+    }
+}
+
+// Deliberately loose (only sending a mail proves an address): something@domain.tld, no spaces.
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && domain.contains('.')
+        && domain.split('.').all(|label| !label.is_empty())
+        && !domain.contains('@')
+        && !email.chars().any(char::is_whitespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::clock::FixedClock;
+
+    // 2024-06-15 12:00 UTC
+    fn clock() -> FixedClock {
+        FixedClock::new(Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap())
+    }
+
+    fn alice() -> UserBuilder {
+        let mut builder = User::builder();
+        builder
+            .name("Alice")
+            .email("alice@example.com")
+            .dob("1990-06-15T00:00:00Z");
+        builder
+    }
+
+    fn invalid(builder: &UserBuilder) -> ValidationErrors {
+        match builder.build_with(&clock()) {
+            Err(MyError::Validation(errors)) => errors,
+            other => panic!("expected validation errors, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn valid_input_builds_with_the_age_from_dob() {
+        let user = alice().skill("Rust").build_with(&clock()).unwrap();
+        assert_eq!(user.name, "Alice");
+        assert_eq!(user.age, 34);
+        assert_eq!(user.skills, ["Rust"]);
+        assert_eq!(alice().age(34).build_with(&clock()).unwrap().age, 34);
+        // born on June 16 in UTC+8: 20:00 on June 15 there, the birthday is tomorrow
+        let user = alice()
+            .dob("1990-06-16T00:30:00+08:00")
+            .build_with(&clock())
+            .unwrap();
+        assert_eq!(user.age, 33);
+    }
+
+    #[test]
+    fn blank_name_is_rejected() {
+        let errors = invalid(alice().name("   "));
+        assert_eq!(errors.field("name"), ["must not be blank"]);
+        let errors = invalid(User::builder().dob("1990-06-15T00:00:00Z"));
+        assert_eq!(errors.field("name"), ["is required"]);
+    }
+
+    #[test]
+    fn malformed_email_is_rejected() {
+        for email in [
+            "alice",
+            "alice@",
+            "@example.com",
+            "alice@example",
+            "a b@example.com",
+            "a@b@c.com",
+            "alice@example..com",
+        ] {
+            let errors = invalid(alice().email(email));
+            assert_eq!(
+                errors.field("email"),
+                ["is not a valid email address"],
+                "{email}"
+            );
+        }
+    }
+
+    #[test]
+    fn dob_must_be_in_the_past_and_parse() {
+        let errors = invalid(alice().dob("2024-06-16T00:00:00Z"));
+        assert_eq!(errors.field("dob"), ["is in the future"]);
+        let errors = invalid(alice().dob("15/06/1990"));
+        assert!(errors.field("dob")[0].starts_with("is not an RFC 3339 date"));
+    }
+
+    #[test]
+    fn age_must_match_dob() {
+        let errors = invalid(alice().age(35));
+        assert_eq!(errors.field("age"), ["is 35, but dob makes it 34"]);
+        assert!(errors.field("dob").is_empty());
+    }
+
+    #[test]
+    fn age_above_max_age_is_rejected() {
+        let errors = invalid(alice().dob("1873-06-15T00:00:00Z"));
+        assert_eq!(errors.field("dob"), ["is more than 150 years ago"]);
+        assert!(alice()
+            .dob("1874-06-15T00:00:00Z")
+            .build_with(&clock())
+            .is_ok());
+    }
+
+    #[test]
+    fn every_problem_is_reported_at_once() {
+        let mut builder = User::builder();
+        builder
+            .name("")
+            .email("nope")
+            .dob("2030-01-01T00:00:00Z")
+            .age(3);
+        let errors = invalid(&builder);
+        let fields: Vec<_> = errors.iter().map(|(field, _)| field).collect();
+        assert_eq!(fields, ["dob", "email", "name"]);
+        assert_eq!(
+            errors.to_string(),
+            "dob: is in the future; email: is not a valid email address; name: must not be blank"
+        );
+    }
+
+    #[test]
+    fn json_and_toml_fill_the_builder_and_build_validates() {
+        let user = UserBuilder::from_json(
+            r#"{"name":"Alice","dob":"1990-06-15T00:00:00Z","skills":["Rust"]}"#,
+        )
+        .unwrap()
+        .build_with(&clock())
+        .unwrap();
+        assert_eq!((user.age, user.skills), (34, vec!["Rust".to_string()]));
+
+        let builder =
+            UserBuilder::from_toml("name = \"Alice\"\ndob = \"1990-06-15T00:00:00Z\"\nage = 34\n")
+                .unwrap();
+        assert_eq!(builder.build_with(&clock()).unwrap().age, 34);
+
+        // partial input is fine until build()
+        let builder = UserBuilder::from_json(r#"{"email":"nope"}"#).unwrap();
+        let errors = invalid(&builder);
+        assert_eq!(errors.iter().count(), 3);
+    }
+
+    // UserBuilder has no Debug (derive_builder doesn't add one), so no unwrap_err
+    fn rejected(parsed: Result<UserBuilder, MyError>) -> String {
+        match parsed {
+            Ok(_) => panic!("input was accepted"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let err = rejected(UserBuilder::from_json(
+            r#"{"name":"Alice","emial":"a@b.c"}"#,
+        ));
+        assert!(err.contains("unknown field `emial`"), "{err}");
+        let err = rejected(UserBuilder::from_toml(
+            "name = \"Alice\"\nemial = \"a@b.c\"\n",
+        ));
+        assert!(err.contains("emial"), "{err}");
+        let err = rejected(UserBuilder::from_yaml("name: Alice\nemial: a@b.c\n"));
+        assert!(err.contains("emial"), "{err}");
+    }
+}
@@ -0,0 +1,183 @@
+// WorkState from examples/serde1.rs, with its rules: an Employment holds the current state and only
+// changes it through transition methods, which reject what the business doesn't allow and log the rest.
+//
+// Key flow (the allowed transitions):
+//
+//   Working(role) ──start_leave(until)──→ OnLeave(until) ──return_to_work()──→ Working(role)
+//        │                                     │
+//        └──────────terminate()────────────────┴──────terminate()──────→ Terminated (final)
+//
+// Anything else is an Err(TransitionError) and leaves the state as it was:
+//   start_leave while on leave → AlreadyOnLeave, return_to_work while working → NotOnLeave,
+//   a leave ending in the past → LeaveEndsInPast, anything after terminate() → Terminated.
+//
+//   let mut job = Employment::new("Rust Engineer");
+//   job.start_leave(Utc::now() + Duration::days(14))?;
+//   job.return_to_work()?;                         // Working("Rust Engineer") again
+//   job.terminate()?;
+//   job.history()                                  // 3 WorkEvents: when, from, to
+//
+// Serialized, the state keeps serde1.rs's adjacently tagged shape, with the history next to it:
+//   {"state":{"type":"terminated"},"history":[{"at":"...","from":{"type":"working","details":"Rust Engineer"},...}]}
+// Time comes from a Clock (crate::clock): the system clock unless with_clock() says otherwise.
+// For people: state.localized(locale) and error.localized(locale) (crate::locale), e.g. "休假至 2025-03-01".
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    clock::{SharedClock, SystemClock},
+    locale::{self, Locale, Localize},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type", content = "details")]
+pub enum WorkState {
+    // the role
+    Working(String),
+    // until when
+    OnLeave(DateTime<Utc>),
+    Terminated,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TransitionError {
+    #[error("already on leave until {until}")]
+    AlreadyOnLeave { until: DateTime<Utc> },
+    #[error("not on leave")]
+    NotOnLeave,
+    #[error("leave must end in the future, not at {until}")]
+    LeaveEndsInPast { until: DateTime<Utc> },
+    #[error("employment is terminated")]
+    Terminated,
+}
+
+impl Localize for WorkState {
+    fn localize(&self, locale: Locale) -> String {
+        match self {
+            WorkState::Working(role) => {
+                locale::render(locale, "work_state.working", &[("role", role)])
+            }
+            WorkState::OnLeave(until) => locale::render(
+                locale,
+                "work_state.on_leave",
+                &[("until", &until.format("%Y-%m-%d"))],
+            ),
+            WorkState::Terminated => locale::text(locale, "work_state.terminated").to_string(),
+        }
+    }
+}
+
+impl Localize for TransitionError {
+    fn localize(&self, locale: Locale) -> String {
+        match self {
+            TransitionError::AlreadyOnLeave { until } => locale::render(
+                locale,
+                "transition.already_on_leave",
+                &[("until", &until.format("%Y-%m-%d"))],
+            ),
+            TransitionError::LeaveEndsInPast { until } => locale::render(
+                locale,
+                "transition.leave_ends_in_past",
+                &[("until", &until.format("%Y-%m-%d"))],
+            ),
+            TransitionError::NotOnLeave => {
+                locale::text(locale, "transition.not_on_leave").to_string()
+            }
+            TransitionError::Terminated => {
+                locale::text(locale, "transition.terminated").to_string()
+            }
+        }
+    }
+}
+
+// One accepted transition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkEvent {
+    pub at: DateTime<Utc>,
+    pub from: WorkState,
+    pub to: WorkState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Employment {
+    state: WorkState,
+    history: Vec<WorkEvent>,
+    // the role to return to after a leave
+    role: String,
+    #[serde(skip, default = "system_clock")]
+    clock: SharedClock,
+}
+
+fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+impl Employment {
+    pub fn new(role: impl Into<String>) -> Self {
+        let role = role.into();
+        Self {
+            state: WorkState::Working(role.clone()),
+            history: Vec::new(),
+            role,
+            clock: system_clock(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn state(&self) -> &WorkState {
+        &self.state
+    }
+
+    // Oldest first.
+    pub fn history(&self) -> &[WorkEvent] {
+        &self.history
+    }
+
+    pub fn start_leave(&mut self, until: DateTime<Utc>) -> Result<&WorkEvent, TransitionError> {
+        match self.state {
+            WorkState::Working(_) if until <= self.clock.now() => {
+                Err(TransitionError::LeaveEndsInPast { until })
+            }
+            WorkState::Working(_) => Ok(self.transition(WorkState::OnLeave(until))),
+            WorkState::OnLeave(until) => Err(TransitionError::AlreadyOnLeave { until }),
+            WorkState::Terminated => Err(TransitionError::Terminated),
+        }
+    }
+
+    // Back to the role held before the leave; allowed before the leave's planned end too.
+    pub fn return_to_work(&mut self) -> Result<&WorkEvent, TransitionError> {
+        match self.state {
+            WorkState::OnLeave(_) => Ok(self.transition(WorkState::Working(self.role.clone()))),
+            WorkState::Working(_) => Err(TransitionError::NotOnLeave),
+            WorkState::Terminated => Err(TransitionError::Terminated),
+        }
+    }
+
+    pub fn terminate(&mut self) -> Result<&WorkEvent, TransitionError> {
+        match self.state {
+            WorkState::Working(_) | WorkState::OnLeave(_) => {
+                Ok(self.transition(WorkState::Terminated))
+            }
+            WorkState::Terminated => Err(TransitionError::Terminated),
+        }
+    }
+
+    // Only called with an allowed `to`: the methods above are the rules.
+    fn transition(&mut self, to: WorkState) -> &WorkEvent {
+        let from = std::mem::replace(&mut self.state, to.clone());
+        self.history.push(WorkEvent {
+            at: self.clock.now(),
+            from,
+            to,
+        });
+        self.history.last().expect("event just pushed")
+    }
+}
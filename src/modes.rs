@@ -0,0 +1,155 @@
+// modes: the "pick one of these" settings, defined once as enums and parsed the same way everywhere.
+// Each enum derives strum's EnumString (FromStr) and Display, and serde_with's DeserializeFromStr /
+// SerializeDisplay, so the accepted spellings live in one place:
+//
+//   config file    strategy = "least-conn"           → serde → FromStr
+//   env var        PROXY_STRATEGY=LeastConnections   → figment → serde → FromStr
+//   command line   --strategy lc                     → clap's value_parser! picks up FromStr
+//   output         BalanceStrategy::LeastConnections.to_string() == "least-connections"
+//
+// Parsing ignores ASCII case; the first spelling (to_string) is the canonical one that Display and
+// serialization write back, the others (serialize) are aliases accepted on input only.
+// VARIANTS (strum::VariantNames) lists the canonical names, for error messages and --help.
+// With clap, FromStr and Display are all a flag needs:
+//
+//   #[arg(long, default_value_t)]     // parsed by FromStr, default shown with Display
+//   strategy: BalanceStrategy,
+//
+// An unknown value fails with strum::ParseError::VariantNotFound ("Matching variant not found");
+// figment adds the key and the file or env var it came from.
+//
+// LogFormat (telemetry) follows the same pattern and is re-exported here.
+
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use strum::{Display, EnumString, VariantNames};
+
+pub use crate::telemetry::LogFormat;
+
+// How a proxy spreads connections over several upstreams.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    EnumString,
+    Display,
+    VariantNames,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum BalanceStrategy {
+    // each upstream in turn
+    #[default]
+    #[strum(to_string = "round-robin", serialize = "roundrobin", serialize = "rr")]
+    RoundRobin,
+    // the upstream with the fewest open connections
+    #[strum(
+        to_string = "least-connections",
+        serialize = "leastconnections",
+        serialize = "least-conn",
+        serialize = "lc"
+    )]
+    LeastConnections,
+    #[strum(to_string = "random")]
+    Random,
+    // the same client address always lands on the same upstream (sticky sessions)
+    #[strum(to_string = "ip-hash", serialize = "iphash", serialize = "sticky")]
+    IpHash,
+}
+
+// The AEAD used to seal sensitive values (examples/serde1.rs uses ChaCha20-Poly1305).
+// Both take a 32-byte key and a 12-byte nonce; AES-GCM is faster on CPUs with AES instructions,
+// ChaCha20-Poly1305 everywhere else.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    EnumString,
+    Display,
+    VariantNames,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum Cipher {
+    #[default]
+    #[strum(
+        to_string = "chacha20-poly1305",
+        serialize = "chacha20poly1305",
+        serialize = "chacha"
+    )]
+    ChaCha20Poly1305,
+    #[strum(
+        to_string = "aes-256-gcm",
+        serialize = "aes256gcm",
+        serialize = "aes-gcm",
+        serialize = "aes"
+    )]
+    Aes256Gcm,
+}
+
+impl Cipher {
+    pub const KEY_LEN: usize = 32;
+    pub const NONCE_LEN: usize = 12;
+    pub const TAG_LEN: usize = 16;
+}
+
+// The digest of checksum files (crate::hash::checksum). BLAKE3 is faster and uses every core on big
+// files; SHA-256 is what sha256sum and most download pages publish.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    EnumString,
+    Display,
+    VariantNames,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum HashAlgorithm {
+    #[default]
+    #[strum(to_string = "blake3", serialize = "b3")]
+    Blake3,
+    #[strum(to_string = "sha256", serialize = "sha-256", serialize = "sha2")]
+    Sha256,
+}
+
+// How a list of records is written out (`ecosystem generate users --format`). JSON is one array;
+// NDJSON one object per line, streamable and appendable; CSV for spreadsheets and database imports.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Default,
+    EnumString,
+    Display,
+    VariantNames,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum RecordFormat {
+    #[default]
+    #[strum(to_string = "json")]
+    Json,
+    #[strum(to_string = "ndjson", serialize = "jsonl")]
+    Ndjson,
+    #[strum(to_string = "csv")]
+    Csv,
+}
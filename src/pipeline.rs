@@ -0,0 +1,204 @@
+// pipeline: typed stages connected by bounded channels, each with its own concurrency.
+// The producer → channel → workers → results wiring tokio2.rs builds by hand, as a reusable piece:
+//
+//   let (input, mut output, handle) = Pipeline::new(32)
+//       .stage("read", 8, |path: PathBuf| async move { Ok(tokio::fs::read(path).await?) })   // I/O: many at once
+//       .blocking_stage("hash", 4, |bytes: Vec<u8>| Ok(blake3::hash(&bytes).to_hex().to_string())) // CPU: one per core
+//       .stage("persist", 2, move |hash: String| { let db = db.clone(); async move { save(&db, &hash).await } })
+//       .build();
+//   for path in paths { input.send(path).await?; }   // waits when "read" is 32 items behind
+//   drop(input);                                      // end of input: stages finish and close in order
+//   while let Some(id) = output.recv().await { ... }
+//   let reports = handle.join().await;                // [StageReport { name: "read", processed, failed }, ...]
+//
+// Key flow per stage:
+// recv item from the previous channel
+//   ├→ wait for one of `concurrency` slots (a slow stage backs up its input channel → backpressure upstream)
+//   ├→ run f(item) in its own task, inside a "pipeline.stage" span
+//   └→ Ok(out) → next channel, Err → logged and counted in the StageReport, the item goes no further
+// Items can finish out of order within a stage (concurrency > 1); use concurrency 1 where order matters.
+// To keep failed items, make the stage's output a Result and handle it downstream.
+
+use std::{future::Future, sync::Arc};
+
+use tokio::{
+    sync::{mpsc, Semaphore},
+    task::{JoinHandle, JoinSet},
+};
+use tracing::{info_span, warn, Instrument};
+
+use crate::error::MyError;
+
+pub struct Pipeline<In, Out> {
+    input: mpsc::Sender<In>,
+    // the last stage's output, becomes the next stage's input
+    output: mpsc::Receiver<Out>,
+    stages: Vec<JoinHandle<StageReport>>,
+    capacity: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageReport {
+    pub name: &'static str,
+    pub processed: u64,
+    // errors and panics
+    pub failed: u64,
+}
+
+pub struct PipelineHandle {
+    stages: Vec<JoinHandle<StageReport>>,
+}
+
+impl PipelineHandle {
+    // Resolves once every stage has finished (after the input sender is dropped), in stage order.
+    pub async fn join(self) -> Vec<StageReport> {
+        let mut reports = Vec::with_capacity(self.stages.len());
+        for stage in self.stages {
+            match stage.await {
+                Ok(report) => reports.push(report),
+                Err(e) => warn!("pipeline stage task failed: {e}"),
+            }
+        }
+        reports
+    }
+}
+
+impl<T: Send + 'static> Pipeline<T, T> {
+    // `capacity`: size of every channel between stages (and of the input channel).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (input, output) = mpsc::channel(capacity);
+        Self {
+            input,
+            output,
+            stages: Vec::new(),
+            capacity,
+        }
+    }
+}
+
+impl<In, Out: Send + 'static> Pipeline<In, Out> {
+    // Async stage: up to `concurrency` items in f at once. Must be called inside a runtime.
+    pub fn stage<U, F, Fut>(self, name: &'static str, concurrency: usize, f: F) -> Pipeline<In, U>
+    where
+        U: Send + 'static,
+        F: Fn(Out) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<U, MyError>> + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(self.capacity);
+        let mut stages = self.stages;
+        stages.push(tokio::spawn(run_stage(
+            name,
+            concurrency.max(1),
+            self.output,
+            tx,
+            Arc::new(f),
+        )));
+        Pipeline {
+            input: self.input,
+            output: rx,
+            stages,
+            capacity: self.capacity,
+        }
+    }
+
+    // CPU-heavy or blocking stage: f runs on Tokio's blocking pool (spawn_blocking), `concurrency` at a time.
+    pub fn blocking_stage<U, F>(
+        self,
+        name: &'static str,
+        concurrency: usize,
+        f: F,
+    ) -> Pipeline<In, U>
+    where
+        U: Send + 'static,
+        F: Fn(Out) -> Result<U, MyError> + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        self.stage(name, concurrency, move |item| {
+            let f = f.clone();
+            async move {
+                tokio::task::spawn_blocking(move || f(item))
+                    .await
+                    .map_err(|e| MyError::Custom(format!("blocking stage panicked: {e}")))?
+            }
+        })
+    }
+
+    // The input sender, the last stage's output, and the handle to wait for the stages.
+    pub fn build(self) -> (mpsc::Sender<In>, mpsc::Receiver<Out>, PipelineHandle) {
+        (
+            self.input,
+            self.output,
+            PipelineHandle {
+                stages: self.stages,
+            },
+        )
+    }
+}
+
+async fn run_stage<I, O, F, Fut>(
+    name: &'static str,
+    concurrency: usize,
+    mut rx: mpsc::Receiver<I>,
+    tx: mpsc::Sender<O>,
+    f: Arc<F>,
+) -> StageReport
+where
+    I: Send + 'static,
+    O: Send + 'static,
+    F: Fn(I) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<O, MyError>> + Send + 'static,
+{
+    let slots = Arc::new(Semaphore::new(concurrency));
+    let mut running = JoinSet::new();
+    let mut report = StageReport {
+        name,
+        processed: 0,
+        failed: 0,
+    };
+
+    // stops early when the downstream receiver is gone: nobody wants the output anymore
+    while !tx.is_closed() {
+        let Some(item) = rx.recv().await else {
+            break; // input closed and drained
+        };
+        let permit = slots
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("stage semaphore never closed");
+        while let Some(done) = running.try_join_next() {
+            tally(&mut report, done);
+        }
+        let f = f.clone();
+        let tx = tx.clone();
+        running.spawn(
+            async move {
+                let _permit = permit;
+                let out = f(item).await?;
+                // Err: downstream closed; the item is dropped, like the rest of the input
+                let _ = tx.send(out).await;
+                Ok(())
+            }
+            .instrument(info_span!("pipeline.stage", stage.name = name)),
+        );
+    }
+    while let Some(done) = running.join_next().await {
+        tally(&mut report, done);
+    }
+    report
+}
+
+fn tally(report: &mut StageReport, done: Result<Result<(), MyError>, tokio::task::JoinError>) {
+    match done {
+        Ok(Ok(())) => report.processed += 1,
+        Ok(Err(e)) => {
+            report.failed += 1;
+            warn!(stage.name = report.name, error = %e, "pipeline item failed");
+        }
+        Err(e) => {
+            report.failed += 1;
+            warn!(stage.name = report.name, "pipeline item panicked: {e}");
+        }
+    }
+}
@@ -0,0 +1,223 @@
+// proxy: the TCP relay of examples/minginx.rs as a library, with its addresses from a config file
+// instead of hardcoded, and more than one upstream. Used by `ecosystem proxy` (src/cli/proxy.rs).
+//
+// proxy.toml (overridable with PROXY_<KEY>, e.g. PROXY_LISTEN=0.0.0.0:9000):
+//   listen = "0.0.0.0:8081"
+//   upstreams = ["10.0.0.1:8080", "10.0.0.2:8080"]
+//   strategy = "least-conn"            # round-robin (default), least-connections, random, ip-hash
+//   buffer_size = "16 KiB"             # per direction, per connection
+//   max_idle_buffers = 512
+//   connect_timeout_ms = "5s"
+//
+//   let proxy = Proxy::new(ProxyConfig::load("proxy.toml")?)?;
+//   proxy.run().await?;                                    // binds `listen`, runs until an accept error
+//
+// Key flow (one connection):
+// accept (client, peer)
+//   ├→ Balancer::pick(peer) → upstream index, by `strategy`
+//   └→ spawned task
+//        ├→ connect to the upstream (within connect_timeout) → error: logged, client dropped
+//        ├→ BufPool::copy both ways until either side closes
+//        └→ "proxied 1.5 MiB to upstream, 12 KiB back", the upstream's open count released
+//
+// It's L4: bytes are relayed without being parsed, so no HTTP routing or traceparent propagation
+// (see the note in examples/minginx.rs).
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    io,
+    net::SocketAddr,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+use tracing::{info, warn};
+
+use crate::{
+    codec::BufPool,
+    config::{self, ConfigError},
+    error::{MyError, ValidationErrors},
+    modes::BalanceStrategy,
+    units::{ByteCount, Millis},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxyConfig {
+    pub listen: String,
+    pub upstreams: Vec<String>,
+    pub strategy: BalanceStrategy,
+    pub buffer_size: ByteCount,
+    // buffers kept for reuse, the rest is freed (2 per connection)
+    pub max_idle_buffers: usize,
+    pub connect_timeout_ms: Millis,
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            listen: "0.0.0.0:8081".to_string(),
+            upstreams: vec!["127.0.0.1:8080".to_string()],
+            strategy: BalanceStrategy::default(),
+            buffer_size: ByteCount::kib(16),
+            max_idle_buffers: 512,
+            connect_timeout_ms: Millis::from_secs(5),
+        }
+    }
+}
+
+impl ProxyConfig {
+    pub fn load(file: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        config::load(file, "PROXY_")
+    }
+
+    pub fn validate(&self) -> Result<(), MyError> {
+        let mut errors = ValidationErrors::default();
+        if self.listen.is_empty() {
+            errors.add("listen", "is empty");
+        }
+        if self.upstreams.is_empty() {
+            errors.add("upstreams", "needs at least one address");
+        }
+        if self.upstreams.iter().any(String::is_empty) {
+            errors.add("upstreams", "contains an empty address");
+        }
+        if self.buffer_size == ByteCount::ZERO {
+            errors.add("buffer_size", "must be more than 0");
+        }
+        errors.into_result()
+    }
+}
+
+pub struct Proxy {
+    config: Arc<ProxyConfig>,
+    balancer: Arc<Balancer>,
+    buffers: BufPool,
+}
+
+impl Proxy {
+    // Fails with MyError::Validation when the config can't work (no upstreams, ...).
+    pub fn new(config: ProxyConfig) -> Result<Self, MyError> {
+        config.validate()?;
+        let buffers = BufPool::new(
+            "proxy",
+            usize::try_from(config.buffer_size.get()).unwrap_or(usize::MAX),
+            config.max_idle_buffers,
+        );
+        Ok(Self {
+            balancer: Arc::new(Balancer::new(config.strategy, config.upstreams.len())),
+            config: Arc::new(config),
+            buffers,
+        })
+    }
+
+    pub fn config(&self) -> &ProxyConfig {
+        &self.config
+    }
+
+    // Binds `listen` and serves on it.
+    pub async fn run(self) -> io::Result<()> {
+        let listener = TcpListener::bind(&self.config.listen).await?;
+        self.serve(listener).await
+    }
+
+    // Accepts until the listener fails; a failed connection is logged and doesn't stop the others.
+    pub async fn serve(self, listener: TcpListener) -> io::Result<()> {
+        info!(
+            listen = %listener.local_addr()?,
+            upstreams = ?self.config.upstreams,
+            strategy = %self.config.strategy,
+            "proxying"
+        );
+        loop {
+            let (client, peer) = listener.accept().await?;
+            let index = self.balancer.pick(peer);
+            let config = self.config.clone();
+            let balancer = self.balancer.clone();
+            let buffers = self.buffers.clone();
+            tokio::spawn(async move {
+                let upstream = &config.upstreams[index];
+                if let Err(e) = relay(client, upstream, &config, &buffers).await {
+                    warn!(%peer, upstream, "error proxying: {e}");
+                }
+                balancer.release(index);
+            });
+        }
+    }
+}
+
+async fn relay(
+    mut client: TcpStream,
+    upstream: &str,
+    config: &ProxyConfig,
+    buffers: &BufPool,
+) -> io::Result<()> {
+    let mut upstream = timeout(
+        config.connect_timeout_ms.as_duration(),
+        TcpStream::connect(upstream),
+    )
+    .await
+    .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "connect timed out"))??;
+    let (mut client_read, mut client_write) = client.split();
+    let (mut upstream_read, mut upstream_write) = upstream.split();
+    let (sent, received) = tokio::try_join!(
+        buffers.copy(&mut client_read, &mut upstream_write),
+        buffers.copy(&mut upstream_read, &mut client_write),
+    )?;
+    info!("proxied {sent} to upstream, {received} back");
+    Ok(())
+}
+
+// Picks the upstream of each connection. Open connections are counted per upstream for every
+// strategy (pick → +1, release → -1), least-connections is the one that reads them.
+struct Balancer {
+    strategy: BalanceStrategy,
+    next: AtomicUsize,
+    open: Vec<AtomicUsize>,
+    // random: a hash of a counter with per-process random keys, no RNG needed
+    hasher: RandomState,
+}
+
+impl Balancer {
+    fn new(strategy: BalanceStrategy, upstreams: usize) -> Self {
+        Self {
+            strategy,
+            next: AtomicUsize::new(0),
+            open: (0..upstreams).map(|_| AtomicUsize::new(0)).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    fn pick(&self, peer: SocketAddr) -> usize {
+        let n = self.open.len();
+        let index = match self.strategy {
+            BalanceStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % n,
+            // not atomic with the increment below: two connections at the same instant may pick the
+            // same upstream, which evens out on the next ones
+            BalanceStrategy::LeastConnections => (0..n)
+                .min_by_key(|&i| self.open[i].load(Ordering::Relaxed))
+                .unwrap_or(0),
+            BalanceStrategy::Random => {
+                let draw = self.next.fetch_add(1, Ordering::Relaxed);
+                (self.hasher.hash_one(draw) % n as u64) as usize
+            }
+            // the IP only: a client's connections come from different ports
+            BalanceStrategy::IpHash => (self.hasher.hash_one(peer.ip()) % n as u64) as usize,
+        };
+        self.open[index].fetch_add(1, Ordering::Relaxed);
+        index
+    }
+
+    fn release(&self, index: usize) {
+        self.open[index].fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,187 @@
+// runtime: Tokio runtimes built from config, shared by the binaries (proxy, web, worker) and tokio1.rs.
+// #[tokio::main] hides the runtime; building it by hand (tokio1.rs) gives control over its shape.
+// runtime::build does the hand-building once:
+//
+//   [runtime]                        # server.toml, overridable with RUNTIME_FLAVOR, RUNTIME_WORKER_THREADS, ...
+//   flavor = "multi_thread"          # or "current_thread": everything on the calling thread (tokio1.rs)
+//   worker_threads = 4               # default: one per core
+//   max_blocking_threads = 64        # spawn_blocking pool, default 512
+//   thread_name = "web"              # threads show up as web-0, web-1, ... in top -H, gdb, panics
+//   metrics_interval_ms = 5000       # sample runtime metrics every 5s (off when missing)
+//
+//   fn main() -> anyhow::Result<()> {
+//       let runtime = runtime::build(&RuntimeConfig::load("server.toml")?)?;
+//       runtime.block_on(run())
+//   }
+//
+// Key flow (metrics sampling):
+// build(config)
+//   ├→ tokio::runtime::Builder (flavor, threads, names), enable_all
+//   └→ metrics_interval set → a sampler task on the new runtime, every interval:
+//        ├→ runtime.workers{runtime.name}        gauge: worker threads
+//        ├→ runtime.tasks.alive{runtime.name}    gauge: spawned tasks not yet finished
+//        └→ runtime.queue.depth{runtime.name}    gauge: tasks waiting in the global (injection) queue
+// A growing queue depth means the workers can't keep up (or one is blocked, see tokio1.rs);
+// tasks.alive that only grows is a task leak. The first sample is taken one interval after build,
+// so telemetry (the global meter provider) can be initialized inside block_on before it.
+
+use std::{
+    io,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        LazyLock,
+    },
+    time::Duration,
+};
+
+use opentelemetry::{global, metrics::Gauge, KeyValue};
+use serde::{Deserialize, Serialize};
+use tokio::runtime::{Builder, Handle, Runtime};
+use tracing::debug;
+
+use crate::config::{self, ConfigError};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Flavor {
+    CurrentThread,
+    #[default]
+    MultiThread,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    pub flavor: Flavor,
+    // multi_thread only; None: one per core
+    pub worker_threads: Option<usize>,
+    pub max_blocking_threads: Option<usize>,
+    // also the runtime.name label of the sampled metrics
+    pub thread_name: String,
+    pub metrics_interval_ms: Option<u64>,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            flavor: Flavor::default(),
+            worker_threads: None,
+            max_blocking_threads: None,
+            thread_name: "tokio".to_string(),
+            metrics_interval_ms: None,
+        }
+    }
+}
+
+impl RuntimeConfig {
+    pub fn current_thread(name: impl Into<String>) -> Self {
+        Self {
+            flavor: Flavor::CurrentThread,
+            thread_name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn multi_thread(name: impl Into<String>) -> Self {
+        Self {
+            flavor: Flavor::MultiThread,
+            thread_name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    // [runtime] of `file`, RUNTIME_* env vars on top.
+    pub fn load(file: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        config::load_section(file, "runtime", "RUNTIME_")
+    }
+
+    pub fn worker_threads(mut self, n: usize) -> Self {
+        self.worker_threads = Some(n);
+        self
+    }
+
+    pub fn max_blocking_threads(mut self, n: usize) -> Self {
+        self.max_blocking_threads = Some(n);
+        self
+    }
+
+    pub fn sample_metrics(mut self, every: Duration) -> Self {
+        self.metrics_interval_ms = Some(every.as_millis() as u64);
+        self
+    }
+}
+
+pub fn build(config: &RuntimeConfig) -> io::Result<Runtime> {
+    let mut builder = match config.flavor {
+        Flavor::CurrentThread => Builder::new_current_thread(),
+        Flavor::MultiThread => {
+            let mut builder = Builder::new_multi_thread();
+            if let Some(n) = config.worker_threads {
+                builder.worker_threads(n);
+            }
+            builder
+        }
+    };
+    if let Some(n) = config.max_blocking_threads {
+        builder.max_blocking_threads(n);
+    }
+    let name = config.thread_name.clone();
+    let next = AtomicUsize::new(0);
+    builder.thread_name_fn(move || format!("{name}-{}", next.fetch_add(1, Ordering::Relaxed)));
+    let runtime = builder.enable_all().build()?;
+
+    if let Some(ms) = config.metrics_interval_ms.filter(|ms| *ms > 0) {
+        let name = config.thread_name.clone();
+        runtime.spawn(sample(
+            runtime.handle().clone(),
+            name,
+            Duration::from_millis(ms),
+        ));
+    }
+    Ok(runtime)
+}
+
+struct RuntimeMetrics {
+    workers: Gauge<u64>,
+    tasks_alive: Gauge<u64>,
+    queue_depth: Gauge<u64>,
+}
+
+// Created on first use, like task_metrics: after telemetry installed the global provider.
+static METRICS: LazyLock<RuntimeMetrics> = LazyLock::new(|| {
+    let meter = global::meter("ecosystem.runtime");
+    RuntimeMetrics {
+        workers: meter
+            .u64_gauge("runtime.workers")
+            .with_description("Worker threads of the Tokio runtime")
+            .build(),
+        tasks_alive: meter
+            .u64_gauge("runtime.tasks.alive")
+            .with_description("Tasks spawned on the Tokio runtime that haven't finished")
+            .build(),
+        queue_depth: meter
+            .u64_gauge("runtime.queue.depth")
+            .with_description("Tasks waiting in the Tokio runtime's global queue")
+            .build(),
+    }
+});
+
+async fn sample(runtime: Handle, name: String, every: Duration) {
+    let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + every, every);
+    let labels = [KeyValue::new("runtime.name", name.clone())];
+    loop {
+        ticks.tick().await;
+        let metrics = runtime.metrics();
+        let workers = metrics.num_workers() as u64;
+        let tasks_alive = metrics.num_alive_tasks() as u64;
+        let queue_depth = metrics.global_queue_depth() as u64;
+        METRICS.workers.record(workers, &labels);
+        METRICS.tasks_alive.record(tasks_alive, &labels);
+        METRICS.queue_depth.record(queue_depth, &labels);
+        debug!(
+            runtime.name = %name,
+            workers, tasks_alive, queue_depth, "runtime metrics"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+// scheduler: recurring jobs for the WorkerPool, on a cron expression or a fixed interval.
+//
+//   let scheduler = Scheduler::new(pool.clone())
+//       .schedule(Schedule::new("nightly-report", "0 0 3 * * *".parse()?, "report"))        // 03:00 UTC daily
+//       .schedule(Schedule::new("heartbeat", "@every 30s".parse()?, "ping").missed(Missed::Skip));
+//   let task = scheduler.start(cancel.clone());
+//
+// Triggers:
+//   cron:   sec min hour day-of-month month day-of-week [year] (cron crate syntax, UTC):
+//           "0 */5 * * * *" every 5 minutes, "0 30 9 * * Mon-Fri" weekdays at 09:30
+//   @every: "@every 500ms", "@every 30s", "@every 5m", "@every 1h"
+//
+// Missed runs: a fire time can pass unnoticed while the scheduler is waiting for queue space
+// (submit() applies the pool's backpressure) or the machine was suspended. Per schedule:
+//   Missed::CatchUp  submit one job per missed fire time (up to MAX_CATCH_UP), for jobs that must run
+//                    once per period (hourly aggregation)
+//   Missed::Skip     submit one job for the latest fire time, drop the rest (heartbeats, cache refresh)
+//
+// Every run gets a "schedule.run" span (schedule.name, schedule.scheduled_at, schedule.lateness_ms,
+// job.id), which also covers awaiting the job's result, so a run and its outcome are one trace.
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{field::Empty, info, info_span, warn, Instrument};
+
+use crate::worker::WorkerPool;
+
+// upper bound of catch-up runs submitted at once, so a long suspend doesn't flood the pool
+pub const MAX_CATCH_UP: usize = 100;
+
+#[derive(Error, Debug)]
+pub enum TriggerError {
+    #[error("invalid cron expression {expr:?}: {source}")]
+    Cron {
+        expr: String,
+        source: cron::error::Error,
+    },
+    #[error("invalid interval {0:?} (expected e.g. 500ms, 30s, 5m, 1h)")]
+    Interval(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Trigger {
+    Cron(Box<cron::Schedule>),
+    Every(Duration),
+}
+
+impl FromStr for Trigger {
+    type Err = TriggerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().strip_prefix("@every ") {
+            Some(interval) => parse_interval(interval.trim()).map(Trigger::Every),
+            None => cron::Schedule::from_str(s)
+                .map(|schedule| Trigger::Cron(Box::new(schedule)))
+                .map_err(|source| TriggerError::Cron {
+                    expr: s.to_string(),
+                    source,
+                }),
+        }
+    }
+}
+
+impl Trigger {
+    // First fire time strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Trigger::Cron(schedule) => schedule.after(&after).next(),
+            Trigger::Every(interval) => chrono::Duration::from_std(*interval)
+                .ok()
+                .map(|interval| after + interval),
+        }
+    }
+}
+
+// "500ms", "30s", "5m", "1h"
+fn parse_interval(s: &str) -> Result<Duration, TriggerError> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| TriggerError::Interval(s.to_string()))?;
+    let secs = |factor: u64| value.checked_mul(factor).map(Duration::from_secs);
+    let interval = match unit {
+        "ms" => Some(Duration::from_millis(value)),
+        "s" => Some(Duration::from_secs(value)),
+        "m" => secs(60),
+        "h" => secs(3600),
+        _ => None,
+    }
+    .ok_or_else(|| TriggerError::Interval(s.to_string()))?;
+    if interval.is_zero() {
+        return Err(TriggerError::Interval(s.to_string()));
+    }
+    Ok(interval)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Missed {
+    CatchUp,
+    #[default]
+    Skip,
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    name: String,
+    trigger: Trigger,
+    input: String,
+    missed: Missed,
+}
+
+impl Schedule {
+    // `input` is what gets submitted to the pool on every run.
+    pub fn new(name: impl Into<String>, trigger: Trigger, input: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            trigger,
+            input: input.into(),
+            missed: Missed::default(),
+        }
+    }
+
+    pub fn missed(mut self, missed: Missed) -> Self {
+        self.missed = missed;
+        self
+    }
+}
+
+pub struct Scheduler {
+    pool: Arc<WorkerPool>,
+    schedules: Vec<Schedule>,
+}
+
+impl Scheduler {
+    pub fn new(pool: Arc<WorkerPool>) -> Self {
+        Self {
+            pool,
+            schedules: Vec::new(),
+        }
+    }
+
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.schedules.push(schedule);
+        self
+    }
+
+    // One task per schedule; the returned task ends when `cancel` is cancelled
+    // (pass pool.cancellation_token() to stop together with the pool).
+    pub fn start(self, cancel: CancellationToken) -> JoinHandle<()> {
+        let tasks: Vec<_> = self
+            .schedules
+            .into_iter()
+            .map(|schedule| tokio::spawn(run_schedule(schedule, self.pool.clone(), cancel.clone())))
+            .collect();
+        tokio::spawn(async move {
+            for task in tasks {
+                let _ = task.await;
+            }
+        })
+    }
+}
+
+async fn run_schedule(schedule: Schedule, pool: Arc<WorkerPool>, cancel: CancellationToken) {
+    let Some(mut next) = schedule.trigger.next_after(Utc::now()) else {
+        warn!(schedule.name = %schedule.name, "schedule never fires");
+        return;
+    };
+    info!(schedule.name = %schedule.name, next = %next, "schedule started");
+    loop {
+        let wait = (next - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = cancel.cancelled() => break,
+        }
+
+        // every fire time up to now: normally just `next`, more if we fell behind
+        let now = Utc::now();
+        let mut due = vec![next];
+        while let Some(t) = schedule
+            .trigger
+            .next_after(*due.last().expect("never empty"))
+        {
+            if t > now || due.len() >= MAX_CATCH_UP {
+                break;
+            }
+            due.push(t);
+        }
+        if schedule.missed == Missed::Skip && due.len() > 1 {
+            warn!(schedule.name = %schedule.name, skipped = due.len() - 1, "skipping missed runs");
+            due.drain(..due.len() - 1);
+        }
+
+        for scheduled_at in &due {
+            if !submit_run(&schedule, *scheduled_at, &pool).await {
+                return; // pool closed
+            }
+        }
+        // CatchUp continues after the last submitted run (more catch-up if MAX_CATCH_UP cut it short),
+        // Skip continues from now
+        let after = match schedule.missed {
+            Missed::CatchUp => *due.last().expect("never empty"),
+            Missed::Skip => now,
+        };
+        match schedule.trigger.next_after(after) {
+            Some(t) => next = t,
+            None => break,
+        }
+    }
+    info!(schedule.name = %schedule.name, "schedule stopped");
+}
+
+// false once the pool no longer accepts jobs
+async fn submit_run(schedule: &Schedule, scheduled_at: DateTime<Utc>, pool: &WorkerPool) -> bool {
+    let lateness_ms = (Utc::now() - scheduled_at).num_milliseconds().max(0);
+    let span = info_span!(
+        "schedule.run",
+        schedule.name = %schedule.name,
+        schedule.scheduled_at = %scheduled_at,
+        schedule.lateness_ms = lateness_ms,
+        job.id = Empty,
+    );
+    let handle = match pool
+        .submit(schedule.input.clone())
+        .instrument(span.clone())
+        .await
+    {
+        Ok(handle) => handle,
+        Err(e) => {
+            span.in_scope(|| warn!("not submitted: {e}"));
+            return false;
+        }
+    };
+    span.record("job.id", handle.id());
+    // await the outcome in the background: the next run isn't held up by this one
+    tokio::spawn(
+        async move {
+            match handle.await {
+                Ok(_) => info!("scheduled job completed"),
+                Err(e) => warn!("scheduled job failed: {e}"),
+            }
+        }
+        .instrument(span),
+    );
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn every_accepts_the_interval_units() {
+        let every = |s: &str| match s.parse::<Trigger>() {
+            Ok(Trigger::Every(interval)) => interval,
+            other => panic!("{s}: {other:?}"),
+        };
+        assert_eq!(every("@every 500ms"), Duration::from_millis(500));
+        assert_eq!(every("@every 30s"), Duration::from_secs(30));
+        assert_eq!(every("@every 5m"), Duration::from_secs(300));
+        assert_eq!(every(" @every 1h "), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn invalid_intervals_are_rejected() {
+        for s in [
+            "@every 0s",
+            "@every 10",
+            "@every 10d",
+            "@every s",
+            "@every -1s",
+            // u64::MAX hours doesn't fit a Duration
+            "@every 18446744073709551615h",
+        ] {
+            assert!(
+                matches!(s.parse::<Trigger>(), Err(TriggerError::Interval(_))),
+                "{s} parsed"
+            );
+        }
+    }
+
+    #[test]
+    fn cron_expressions_fire_on_schedule() {
+        let trigger: Trigger = "0 30 9 * * *".parse().unwrap();
+        let after = DateTime::parse_from_rfc3339("2026-10-16T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            trigger.next_after(after).unwrap().to_rfc3339(),
+            "2026-10-17T09:30:00+00:00"
+        );
+        assert!(matches!(
+            "not a cron".parse::<Trigger>(),
+            Err(TriggerError::Cron { .. })
+        ));
+    }
+
+    #[test]
+    fn every_fires_one_interval_later() {
+        let trigger = Trigger::Every(Duration::from_secs(30));
+        let now = Utc::now();
+        assert_eq!(
+            trigger.next_after(now).unwrap(),
+            now + chrono::Duration::seconds(30)
+        );
+    }
+
+    #[tokio::test]
+    async fn scheduler_submits_runs_until_cancelled() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let pool = Arc::new(WorkerPool::new(1, 4, {
+            let runs = runs.clone();
+            move |s| {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Ok(s)
+            }
+        }));
+        let cancel = CancellationToken::new();
+        let task = Scheduler::new(pool.clone())
+            .schedule(Schedule::new(
+                "tick",
+                "@every 10ms".parse().unwrap(),
+                "tick",
+            ))
+            .start(cancel.clone());
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while runs.load(Ordering::SeqCst) < 3 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("fewer than 3 runs in 5s");
+        cancel.cancel();
+        tokio::time::timeout(Duration::from_secs(5), task)
+            .await
+            .expect("scheduler didn't stop")
+            .unwrap();
+    }
+}
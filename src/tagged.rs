@@ -0,0 +1,111 @@
+// tagged: an enum whose variants each carry a payload, sent as (which variant, the payload) instead of
+// as one serde value. The variant goes where the receiver looks first (the frame header's msg_type,
+// the job envelope's "type"), so it can route, reject or count a message before touching its body.
+//
+// The "which variant" type is the enum's discriminants, generated by strum's EnumDiscriminants
+// (examples/enum.rs), and tagged! connects the two:
+//
+//   #[derive(Debug, Serialize, Deserialize, EnumDiscriminants)]
+//   #[strum_discriminants(name(RequestKind), derive(IntoStaticStr, EnumString))]
+//   #[strum_discriminants(strum(serialize_all = "snake_case"))]
+//   enum Request {
+//       Get(Get),
+//       Put(Put),
+//   }
+//   tagged!(Request => RequestKind { Get = 1, Put = 2 });   // names from strum, codes from here
+//
+//   Request::Put(put).tag()              → RequestKind::Put
+//   Request::tag_name(RequestKind::Put)  → "put"        (job envelopes: JobEnvelope::from_tagged)
+//   Request::code(RequestKind::Put)      → 2            (frames: TaggedCodec's msg_type)
+//
+// Key flow:
+// send: value → tag() → code / name in the header     + serialize_payload → just the payload's fields
+// recv: header → from_code / from name → Tag → deserialize_payload(tag, body) → the right variant
+//
+// The payload is serialized without any enum wrapper, so a Put on the wire is exactly a Put struct:
+// producers in other languages only need the codes (or names) and the payload types.
+// Codes are the protocol: never reuse one for a different variant, add new variants with new codes.
+// tagged! handles one-field tuple variants (the payload); a variant without data can carry ().
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserializer, Serializer};
+
+pub trait Tagged: Sized {
+    // the discriminants enum (EnumDiscriminants), with strum's IntoStaticStr / EnumString for names
+    type Tag: Copy + Eq + fmt::Debug + Into<&'static str> + FromStr + Send + Sync + 'static;
+
+    // Every tag, for registering all variants at once (JobRegistry::register_tagged).
+    const TAGS: &'static [Self::Tag];
+
+    fn tag(&self) -> Self::Tag;
+
+    // The wire code of a tag (frame msg_type) and back; None for codes this side doesn't know.
+    fn code(tag: Self::Tag) -> u16;
+    fn from_code(code: u16) -> Option<Self::Tag>;
+
+    // The payload of the variant, as itself (no variant name around it).
+    fn serialize_payload<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+
+    // The variant `tag` says, with its payload read from `deserializer`.
+    fn deserialize_payload<'de, D: Deserializer<'de>>(
+        tag: Self::Tag,
+        deserializer: D,
+    ) -> Result<Self, D::Error>;
+
+    fn tag_name(tag: Self::Tag) -> &'static str {
+        tag.into()
+    }
+
+    fn from_name(name: &str) -> Option<Self::Tag> {
+        name.parse().ok()
+    }
+}
+
+// tagged!(Enum => EnumDiscriminants { Variant = code, ... }): implements Tagged for Enum.
+// A code listed twice is an unreachable pattern warning in from_code (and a protocol bug).
+#[macro_export]
+macro_rules! tagged {
+    ($enum:ty => $tag:ident { $($variant:ident = $code:literal),+ $(,)? }) => {
+        impl $crate::tagged::Tagged for $enum {
+            type Tag = $tag;
+
+            const TAGS: &'static [$tag] = &[$($tag::$variant),+];
+
+            fn tag(&self) -> $tag {
+                $tag::from(self)
+            }
+
+            fn code(tag: $tag) -> u16 {
+                match tag {
+                    $($tag::$variant => $code,)+
+                }
+            }
+
+            fn from_code(code: u16) -> Option<$tag> {
+                match code {
+                    $($code => Some($tag::$variant),)+
+                    _ => None,
+                }
+            }
+
+            fn serialize_payload<S: ::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                match self {
+                    $(Self::$variant(payload) => ::serde::Serialize::serialize(payload, serializer),)+
+                }
+            }
+
+            fn deserialize_payload<'de, D: ::serde::Deserializer<'de>>(
+                tag: $tag,
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                Ok(match tag {
+                    $($tag::$variant => Self::$variant(::serde::Deserialize::deserialize(deserializer)?),)+
+                })
+            }
+        }
+    };
+}
@@ -0,0 +1,587 @@
+// telemetry: logging/tracing setup shared by the examples and binaries (TelemetryBuilder at the bottom).
+// OpenTelemetry export (traces, metrics, logs) lives in telemetry::otel, owned together (with a
+// deterministic shutdown) by telemetry::Telemetry,
+// span-derived RED metrics in telemetry::span_metrics, slow-operation warnings in telemetry::slow_spans,
+// per-task duration histograms in telemetry::task_metrics, size/time-rotated log files in telemetry::rolling,
+// optional Sentry forwarding in telemetry::error_reporting.
+
+pub mod error_reporting;
+pub mod otel;
+mod pipeline;
+pub mod redact;
+pub mod rolling;
+pub mod slow_spans;
+pub mod span_metrics;
+pub mod task_metrics;
+
+pub use pipeline::{ShutdownReport, Telemetry};
+
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use strum::{Display, EnumString, VariantNames};
+use thiserror::Error;
+use tracing::{info, Subscriber};
+use tracing_subscriber::{
+    filter::ParseError,
+    fmt::{self, format::FmtSpan, MakeWriter},
+    registry::LookupSpan,
+    reload, EnvFilter, Layer,
+};
+
+use crate::config::{self, ConfigError};
+use redact::{RedactingFields, RedactingJson, Redaction};
+use slow_spans::SlowSpanConfig;
+
+// RUST_LOG wins when set, otherwise `default` is used, so operators can do
+//   RUST_LOG=ecosystem::web=trace,hyper=warn cargo run --example axum_serde
+// without recompiling. Both are EnvFilter directives: "info", "debug,hyper=warn", "ecosystem=trace", ...
+// An invalid RUST_LOG falls back to `default` instead of silently disabling logging.
+pub fn env_filter(default: &str) -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default))
+}
+
+// Panics go through tracing instead of straight to stderr: one ERROR event with the message,
+// location and a backtrace, emitted inside the panicking thread's current span — so the file/JSON/OTel
+// layers get it, with the span context (and request_id, for requests traced by web::trace).
+// Call after the subscriber is installed. The hook that was there before (the default one printing
+// to stderr, or one installed by a test harness or crash reporter) still runs afterwards.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| info.payload().downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_default();
+        let backtrace = std::backtrace::Backtrace::force_capture();
+        tracing::error!(
+            panic.message = message,
+            panic.location = location,
+            panic.thread = std::thread::current().name().unwrap_or("<unnamed>"),
+            panic.backtrace = %backtrace,
+            "panicked: {message}"
+        );
+        previous(info);
+    }));
+}
+
+// tokio-console layer when built with `--features tokio-console`, None otherwise
+// (an Option<Layer> is a no-op layer when None, so callers can always .with() it).
+// Starts the console's gRPC server (127.0.0.1:6669) on a background thread; not filtered by
+// RUST_LOG, it needs the runtime's own tokio=trace/runtime=trace events.
+pub fn console_layer<S>() -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    #[cfg(feature = "tokio-console")]
+    {
+        Some(console_subscriber::spawn().boxed())
+    }
+    #[cfg(not(feature = "tokio-console"))]
+    {
+        None
+    }
+}
+
+// The [logging] section of a config file (server.toml), with LOG_* env overrides:
+//
+//   [logging]
+//   level = "info"          # LOG_LEVEL
+//   format = "pretty"       # LOG_FORMAT
+//
+//   redact = ["password", "token", "sensitive"]   # field names written as [REDACTED] (telemetry::redact)
+//
+//   [logging.levels]        # per-module levels, declarative instead of a long RUST_LOG
+//   "ecosystem::web" = "debug"
+//   sqlx = "warn"
+//
+//   [logging.slow_spans]    # WARN when a span stays open longer than this many ms (telemetry::slow_spans)
+//   long_task = 50
+//
+//   [logging.file]          # a rolling log file (telemetry::rolling), for binaries that add it with
+//   dir = "/var/log/ecosystem"   # TelemetryBuilder::file; `ecosystem --daemon` logs only there
+//   file_name = "proxy.log"
+//
+// The filter is built as  level, then [logging.levels], then RUST_LOG (if set) — later directives
+// for the same target win, so RUST_LOG can still override anything for a one-off run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub level: String,
+    pub format: LogFormat,
+    pub levels: BTreeMap<String, String>,
+    pub redact: Redaction,
+    pub slow_spans: SlowSpanConfig,
+    pub file: Option<rolling::FileLogConfig>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::default(),
+            levels: BTreeMap::new(),
+            redact: Redaction::default(),
+            slow_spans: SlowSpanConfig::default(),
+            file: None,
+        }
+    }
+}
+
+impl LoggingConfig {
+    pub fn load(file: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        config::load_section(file, "logging", "LOG_")
+    }
+
+    // "sqlx=warn", "ecosystem::web=debug", ...
+    pub fn module_directives(&self) -> Vec<String> {
+        self.levels
+            .iter()
+            .map(|(target, level)| format!("{target}={level}"))
+            .collect()
+    }
+
+    pub fn env_filter(&self) -> EnvFilter {
+        let mut directives = vec![self.level.clone()];
+        directives.extend(self.module_directives());
+        directives.extend(std::env::var("RUST_LOG").ok());
+        // invalid directives are skipped (with a warning on stderr) instead of failing startup
+        EnvFilter::builder().parse_lossy(directives.join(","))
+    }
+
+    // Like reloadable_env_filter, but PUT /admin/log-level and SIGUSR1 keep the [logging.levels]:
+    // setting "debug" raises the default level, "sqlx" stays at "warn".
+    pub fn reloadable_filter<S>(&self) -> (reload::Layer<EnvFilter, S>, LogLevelHandle)
+    where
+        S: Subscriber + 'static,
+    {
+        reloadable(self.env_filter(), self.module_directives())
+    }
+}
+
+// How fmt layers render events.
+// Full / Pretty are for humans (Pretty spans several lines per event);
+// Json is one object per line with event fields flattened to the top level, which log shippers can parse:
+//   {"timestamp":"...","level":"INFO","message":"job completed","job.id":1,"target":"ecosystem::worker","span":{...}}
+// Parsing is case-insensitive and goes through FromStr everywhere (LOG_FORMAT, [logging] format,
+// command-line flags), so "JSON", "json" and "Json" mean the same thing in all of them; see crate::modes.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    EnumString,
+    Display,
+    VariantNames,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+#[strum(ascii_case_insensitive)]
+pub enum LogFormat {
+    #[default]
+    #[strum(to_string = "full", serialize = "text")]
+    Full,
+    #[strum(to_string = "pretty")]
+    Pretty,
+    #[strum(to_string = "json")]
+    Json,
+}
+
+impl LogFormat {
+    // LOG_FORMAT=json|pretty|full, `default` when unset or invalid.
+    pub fn from_env(default: LogFormat) -> Self {
+        std::env::var("LOG_FORMAT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default)
+    }
+
+    // A fmt layer writing to `writer` (stdout, a tracing-appender non-blocking file, ...).
+    // Boxed because each format is a different concrete type; add .with_filter(...) on the result.
+    // Fields named like password/token/sensitive are written as [REDACTED] (see redact::Redaction).
+    pub fn layer<S, W>(self, writer: W, span_events: FmtSpan) -> Box<dyn Layer<S> + Send + Sync>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    {
+        self.redacted_layer(writer, span_events, Redaction::default())
+    }
+
+    // Same, with the redacted field-name patterns from [logging] redact.
+    pub fn redacted_layer<S, W>(
+        self,
+        writer: W,
+        span_events: FmtSpan,
+        redaction: Redaction,
+    ) -> Box<dyn Layer<S> + Send + Sync>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+    {
+        let layer = fmt::layer()
+            .with_writer(writer)
+            .with_span_events(span_events);
+        match self {
+            LogFormat::Full => layer.fmt_fields(RedactingFields::text(redaction)).boxed(),
+            LogFormat::Pretty => layer
+                .pretty()
+                .fmt_fields(RedactingFields::text(redaction))
+                .boxed(),
+            // flattened event fields + current span, like .json().flatten_event(true).with_current_span(true)
+            LogFormat::Json => layer
+                .event_format(RedactingJson::new(redaction.clone()))
+                .fmt_fields(RedactingFields::json(redaction))
+                .boxed(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum LogLevelError {
+    #[error("invalid log level directives: {0}")]
+    Parse(#[from] ParseError),
+    #[error("failed to reload log filter: {0}")]
+    Reload(#[from] reload::Error),
+}
+
+// Changes the level of a running process, e.g. turn on debug logging on a live proxy without a restart.
+// reload::Handle is generic over the subscriber type; the closures erase it so the handle can be
+// stored in axum state or moved into a signal task without naming the whole layer stack.
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    reload: Arc<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>,
+    current: Arc<dyn Fn() -> Option<String> + Send + Sync>,
+    // [logging.levels], re-applied under every new level
+    module_directives: Arc<[String]>,
+}
+
+impl LogLevelHandle {
+    // Same directive syntax as RUST_LOG: "debug", "info,ecosystem::web=trace", ...
+    pub fn set(&self, directives: &str) -> Result<(), LogLevelError> {
+        let merged: Vec<&str> = self
+            .module_directives
+            .iter()
+            .map(String::as_str)
+            .chain([directives])
+            .collect();
+        let filter = EnvFilter::try_new(merged.join(","))?;
+        (self.reload)(filter)?;
+        info!("log level set to {directives}");
+        Ok(())
+    }
+
+    pub fn current(&self) -> Option<String> {
+        (self.current)()
+    }
+}
+
+// env_filter(default), wrapped so it can be swapped at runtime through the returned handle.
+// Use it wherever env_filter would go:  layer.with_filter(filter)
+pub fn reloadable_env_filter<S>(default: &str) -> (reload::Layer<EnvFilter, S>, LogLevelHandle)
+where
+    S: Subscriber + 'static,
+{
+    reloadable(env_filter(default), Vec::new())
+}
+
+fn reloadable<S>(
+    filter: EnvFilter,
+    module_directives: Vec<String>,
+) -> (reload::Layer<EnvFilter, S>, LogLevelHandle)
+where
+    S: Subscriber + 'static,
+{
+    let (filter, handle) = reload::Layer::new(filter);
+    let current = handle.clone();
+    let handle = LogLevelHandle {
+        reload: Arc::new(move |filter| handle.reload(filter)),
+        current: Arc::new(move || current.with_current(|f| f.to_string()).ok()),
+        module_directives: module_directives.into(),
+    };
+    (filter, handle)
+}
+
+// kill -USR1 <pid> toggles between the startup filter and `debug_directives`,
+// for hosts where the admin endpoint isn't reachable (or doesn't exist, like the TCP proxy).
+#[cfg(unix)]
+pub fn spawn_sigusr1_toggle(handle: LogLevelHandle, debug_directives: &str) -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut usr1 = signal(SignalKind::user_defined1())?;
+    let normal = handle.current().unwrap_or_else(|| "info".to_string());
+    let debug = debug_directives.to_string();
+    tokio::spawn(async move {
+        let mut debugging = false;
+        while usr1.recv().await.is_some() {
+            debugging = !debugging;
+            let next = if debugging { &debug } else { &normal };
+            if let Err(e) = handle.set(next) {
+                tracing::warn!("SIGUSR1: {e}");
+            }
+        }
+    });
+    Ok(())
+}
+
+// ---- TelemetryBuilder: the whole subscriber stack in one place ----
+// What axum_tracing.rs used to assemble by hand (console + file + OTel + extras), so every binary
+// gets the same setup from a few lines:
+//
+//   let telemetry = TelemetryBuilder::new("minginx")
+//       .logging(LoggingConfig::load("server.toml")?)   // level, format, [logging.levels]
+//       .console_level("debug")
+//       .file(FileLogConfig::default())
+//       .otel(OtlpConfig::default())                      // traces + metrics + logs, env-configured
+//       .init()?;
+//   ...
+//   eprintln!("{:?}", telemetry.shutdown(Duration::from_secs(5)));
+//
+// Sinks: console (on by default), file, OTel export (with span RED metrics); plus tokio-console and
+// Sentry when their features are enabled, and the panic hook. Each can be turned on/off.
+
+#[derive(Error, Debug)]
+pub enum TelemetryError {
+    #[error("failed to build OTLP exporter: {0}")]
+    Exporter(#[from] opentelemetry_otlp::ExporterBuildError),
+    #[error("failed to open log file: {0}")]
+    File(#[from] std::io::Error),
+    #[error("failed to install the global subscriber: {0}")]
+    Init(#[from] tracing_subscriber::util::TryInitError),
+}
+
+pub struct TelemetryBuilder {
+    resource: otel::ResourceConfig,
+    logging: LoggingConfig,
+    console: bool,
+    console_stderr: bool,
+    console_level: Option<String>,
+    console_span_events: FmtSpan,
+    file: Option<rolling::FileLogConfig>,
+    file_level: Option<String>,
+    otel: Option<otel::OtlpConfig>,
+    sampling: otel::SamplingConfig,
+    tokio_console: bool,
+    error_reporting: bool,
+    panic_hook: bool,
+}
+
+// Keep it alive until the end of main: it owns the file writer's flush guard and the OTel providers.
+pub struct TelemetryGuard {
+    log_level: LogLevelHandle,
+    otel: Option<Telemetry>,
+    _file: Option<tracing_appender::non_blocking::WorkerGuard>,
+    _error_reporting: error_reporting::ErrorReportingGuard,
+}
+
+impl TelemetryBuilder {
+    // `service_name` is the OTel service.name (OTEL_SERVICE_NAME still wins)
+    pub fn new(service_name: impl Into<String>) -> Self {
+        Self {
+            resource: otel::ResourceConfig::new(service_name),
+            logging: LoggingConfig::default(),
+            console: true,
+            console_stderr: false,
+            console_level: None,
+            console_span_events: FmtSpan::NONE,
+            file: None,
+            file_level: None,
+            otel: None,
+            sampling: otel::SamplingConfig::from_env(),
+            tokio_console: true,
+            error_reporting: true,
+            panic_hook: true,
+        }
+    }
+
+    // Default level, format and per-module levels for every sink.
+    pub fn logging(mut self, logging: LoggingConfig) -> Self {
+        self.logging = logging;
+        self
+    }
+
+    pub fn console(mut self, enabled: bool) -> Self {
+        self.console = enabled;
+        self
+    }
+
+    // Console logs on stderr instead of stdout: for CLIs whose stdout is their output (ecosystem encrypt > out).
+    pub fn console_stderr(mut self, enabled: bool) -> Self {
+        self.console_stderr = enabled;
+        self
+    }
+
+    // Overrides logging.level for the console only.
+    pub fn console_level(mut self, level: impl Into<String>) -> Self {
+        self.console_level = Some(level.into());
+        self
+    }
+
+    pub fn console_span_events(mut self, events: FmtSpan) -> Self {
+        self.console_span_events = events;
+        self
+    }
+
+    pub fn file(mut self, file: rolling::FileLogConfig) -> Self {
+        self.file = Some(file);
+        self
+    }
+
+    pub fn file_level(mut self, level: impl Into<String>) -> Self {
+        self.file_level = Some(level.into());
+        self
+    }
+
+    // Export traces, metrics and logs over OTLP (and derive span RED metrics).
+    pub fn otel(mut self, exporter: otel::OtlpConfig) -> Self {
+        self.otel = Some(exporter);
+        self
+    }
+
+    pub fn resource(mut self, resource: otel::ResourceConfig) -> Self {
+        self.resource = resource;
+        self
+    }
+
+    pub fn sampling(mut self, sampling: otel::SamplingConfig) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
+    // Only has an effect with --features tokio-console.
+    pub fn tokio_console(mut self, enabled: bool) -> Self {
+        self.tokio_console = enabled;
+        self
+    }
+
+    // Only has an effect with --features sentry and SENTRY_DSN set.
+    pub fn error_reporting(mut self, enabled: bool) -> Self {
+        self.error_reporting = enabled;
+        self
+    }
+
+    pub fn panic_hook(mut self, enabled: bool) -> Self {
+        self.panic_hook = enabled;
+        self
+    }
+
+    fn logging_for(&self, level: &Option<String>) -> LoggingConfig {
+        LoggingConfig {
+            level: level.clone().unwrap_or_else(|| self.logging.level.clone()),
+            ..self.logging.clone()
+        }
+    }
+
+    pub fn init(self) -> Result<TelemetryGuard, TelemetryError> {
+        use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Registry};
+
+        let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = Vec::new();
+        let format = self.logging.format;
+
+        // the console filter is the reloadable one (PUT /admin/log-level, SIGUSR1), or the file
+        // filter when there's no console (a daemon); the other sink keeps its startup level
+        let reloaded_level = if self.console {
+            &self.console_level
+        } else {
+            &self.file_level
+        };
+        let (reload_filter, log_level) = self.logging_for(reloaded_level).reloadable_filter();
+        let mut reload_filter = Some(reload_filter);
+        if self.console {
+            let (span_events, redact) = (
+                self.console_span_events.clone(),
+                self.logging.redact.clone(),
+            );
+            let console = if self.console_stderr {
+                format.redacted_layer(std::io::stderr, span_events, redact)
+            } else {
+                format.redacted_layer(std::io::stdout, span_events, redact)
+            };
+            if let Some(filter) = reload_filter.take() {
+                layers.push(console.with_filter(filter).boxed());
+            }
+        }
+
+        let file_guard = match &self.file {
+            Some(file) => {
+                let (writer, guard) = tracing_appender::non_blocking(file.writer()?);
+                let layer =
+                    format.redacted_layer(writer, FmtSpan::NONE, self.logging.redact.clone());
+                layers.push(match reload_filter.take() {
+                    Some(filter) => layer.with_filter(filter).boxed(),
+                    None => layer
+                        .with_filter(self.logging_for(&self.file_level).env_filter())
+                        .boxed(),
+                });
+                Some(guard)
+            }
+            None => None,
+        };
+
+        let otel = match &self.otel {
+            Some(exporter) => {
+                use opentelemetry::trace::TracerProvider as _;
+
+                let telemetry = Telemetry::init(&self.resource, &self.sampling, exporter)?;
+                let tracer = telemetry
+                    .tracer_provider()
+                    .tracer(self.resource.service_name.clone());
+                layers.push(tracing_opentelemetry::layer().with_tracer(tracer).boxed());
+                layers.push(
+                    otel::log_layer(telemetry.logger_provider(), &self.logging.level).boxed(),
+                );
+                layers.push(span_metrics::SpanMetricsLayer::new().boxed());
+                Some(telemetry)
+            }
+            None => None,
+        };
+
+        if !self.logging.slow_spans.is_empty() {
+            layers.push(slow_spans::SlowSpanLayer::new(self.logging.slow_spans.clone()).boxed());
+        }
+        if self.tokio_console {
+            layers.extend(console_layer());
+        }
+        let error_reporting = if self.error_reporting {
+            layers.extend(error_reporting::layer());
+            error_reporting::init()
+        } else {
+            error_reporting::ErrorReportingGuard::disabled()
+        };
+
+        tracing_subscriber::registry().with(layers).try_init()?;
+        if self.panic_hook {
+            install_panic_hook();
+        }
+
+        Ok(TelemetryGuard {
+            log_level,
+            otel,
+            _file: file_guard,
+            _error_reporting: error_reporting,
+        })
+    }
+}
+
+impl TelemetryGuard {
+    // Runtime control of the console level (of the file level without a console):
+    // web::admin::router(guard.log_level().clone(), token), spawn_sigusr1_toggle(guard.log_level().clone(), "debug").
+    // With neither sink there's nothing to reload, and set() fails.
+    pub fn log_level(&self) -> &LogLevelHandle {
+        &self.log_level
+    }
+
+    // Flushes and shuts down the OTel pipeline (None if OTel wasn't enabled); the file writer
+    // and Sentry flush when the guard is dropped right after.
+    pub fn shutdown(mut self, timeout: std::time::Duration) -> Option<ShutdownReport> {
+        self.otel.take().map(|otel| otel.shutdown(timeout))
+    }
+}
@@ -0,0 +1,87 @@
+// Optional error tracking (Sentry), for teams that look at issues rather than search logs.
+// Built with `--features sentry` and enabled at runtime by SENTRY_DSN; otherwise everything here is a no-op,
+// so callers never need #[cfg] themselves.
+//
+//   ERROR events        → Sentry events (with the span context)
+//   INFO/WARN events    → breadcrumbs attached to the next error
+//   capture_error(&e)   → one event per error, with its whole source() chain (e.g. MyError::Io → io::Error)
+//
+// SENTRY_ENVIRONMENT (default "dev") and the crate version are sent as environment/release.
+
+use std::error::Error;
+
+use tracing::Subscriber;
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+// Keep it alive for the whole program: dropping it flushes queued events.
+pub struct ErrorReportingGuard {
+    #[cfg(feature = "sentry")]
+    _sentry: Option<sentry::ClientInitGuard>,
+}
+
+impl ErrorReportingGuard {
+    pub fn disabled() -> Self {
+        Self {
+            #[cfg(feature = "sentry")]
+            _sentry: None,
+        }
+    }
+}
+
+pub fn init() -> ErrorReportingGuard {
+    #[cfg(feature = "sentry")]
+    {
+        let sentry = std::env::var("SENTRY_DSN").ok().map(|dsn| {
+            sentry::init((
+                dsn,
+                sentry::ClientOptions {
+                    release: sentry::release_name!(),
+                    environment: Some(
+                        std::env::var("SENTRY_ENVIRONMENT")
+                            .unwrap_or_else(|_| "dev".to_string())
+                            .into(),
+                    ),
+                    ..Default::default()
+                },
+            ))
+        });
+        ErrorReportingGuard { _sentry: sentry }
+    }
+    #[cfg(not(feature = "sentry"))]
+    {
+        ErrorReportingGuard {}
+    }
+}
+
+pub fn layer<S>() -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    #[cfg(feature = "sentry")]
+    {
+        use sentry::integrations::tracing::EventFilter;
+        use tracing::Level;
+
+        Some(
+            sentry::integrations::tracing::layer()
+                .event_filter(|md| match *md.level() {
+                    Level::ERROR => EventFilter::Event,
+                    Level::WARN | Level::INFO => EventFilter::Breadcrumb,
+                    _ => EventFilter::Ignore,
+                })
+                .boxed(),
+        )
+    }
+    #[cfg(not(feature = "sentry"))]
+    {
+        None
+    }
+}
+
+// For errors that are handled (so never logged at ERROR) but should still show up as issues.
+pub fn capture_error(err: &(dyn Error + 'static)) {
+    #[cfg(feature = "sentry")]
+    sentry::capture_error(err);
+    #[cfg(not(feature = "sentry"))]
+    let _ = err;
+}
@@ -0,0 +1,217 @@
+// Telemetry: the three OTel providers (traces, metrics, logs) owned together, so main can end with
+//   let report = telemetry.shutdown(Duration::from_secs(5));
+// instead of dropping the providers and hoping the batch exporters got everything out.
+//
+// shutdown() force-flushes, then shuts down every provider, all within `timeout` (an unreachable
+// Collector would otherwise hang the exit). The report has the errors of the steps that finished,
+// also when it timed out, and says how many spans never made it:
+//   spans_ended    sampled spans handed to the batch processor
+//   spans_exported spans the exporter acknowledged
+//   spans_dropped  the difference: queue overflow, failed exports, or still queued at the timeout
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use opentelemetry::Context;
+use opentelemetry_otlp::ExporterBuildError;
+use opentelemetry_sdk::{
+    error::OTelSdkResult,
+    logs::SdkLoggerProvider,
+    metrics::SdkMeterProvider,
+    trace::{SdkTracerProvider, Span, SpanData, SpanExporter, SpanProcessor},
+    Resource,
+};
+
+use super::otel::{
+    logger_provider, meter_provider, span_exporter, tracer_provider, OtlpConfig, ResourceConfig,
+    SamplingConfig,
+};
+
+pub struct Telemetry {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+    logger_provider: SdkLoggerProvider,
+    spans: Arc<SpanCounters>,
+}
+
+#[derive(Debug, Default)]
+struct SpanCounters {
+    ended: AtomicU64,
+    exported: AtomicU64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    pub spans_ended: u64,
+    pub spans_exported: u64,
+    pub spans_dropped: u64,
+    // providers that failed to flush/shut down, with the reason
+    pub errors: Vec<String>,
+    pub timed_out: bool,
+}
+
+impl Telemetry {
+    // Same as init_tracer_provider + init_meter_provider + init_logger_provider (all installed globally
+    // where OTel has a global), with span accounting for the shutdown report and an explicit exporter.
+    pub fn init(
+        resource: &ResourceConfig,
+        sampling: &SamplingConfig,
+        otlp: &OtlpConfig,
+    ) -> Result<Self, ExporterBuildError> {
+        let spans = Arc::new(SpanCounters::default());
+        let exporter = CountingExporter {
+            inner: span_exporter(otlp)?,
+            spans: spans.clone(),
+        };
+        // processors run in registration order: count first, then queue for export
+        let tracer_provider = tracer_provider(resource, sampling, |builder| {
+            builder
+                .with_span_processor(CountingProcessor(spans.clone()))
+                .with_batch_exporter(exporter)
+        });
+
+        Ok(Self {
+            tracer_provider,
+            meter_provider: meter_provider(resource, otlp)?,
+            logger_provider: logger_provider(resource, otlp)?,
+            spans,
+        })
+    }
+
+    pub fn tracer_provider(&self) -> &SdkTracerProvider {
+        &self.tracer_provider
+    }
+
+    pub fn meter_provider(&self) -> &SdkMeterProvider {
+        &self.meter_provider
+    }
+
+    pub fn logger_provider(&self) -> &SdkLoggerProvider {
+        &self.logger_provider
+    }
+
+    // Blocks for at most `timeout` (the SDK's flush/shutdown calls are blocking; they run on a helper
+    // thread so a hung exporter can't keep the process alive). Log the report with eprintln!, not tracing:
+    // the log pipeline is one of the things being shut down.
+    pub fn shutdown(self, timeout: Duration) -> ShutdownReport {
+        let (tx, rx) = mpsc::channel();
+        let Telemetry {
+            tracer_provider,
+            meter_provider,
+            logger_provider,
+            spans,
+        } = self;
+        // each error is sent as soon as its step fails, so a timeout still reports the earlier ones;
+        // None means every step finished
+        thread::spawn(move || {
+            let check = |what: &str, result: OTelSdkResult| {
+                if let Err(e) = result {
+                    let _ = tx.send(Some(format!("{what}: {e}")));
+                }
+            };
+            // traces first: exporting spans may still emit log records and metrics
+            check("traces flush", tracer_provider.force_flush());
+            check("traces shutdown", tracer_provider.shutdown());
+            check("metrics flush", meter_provider.force_flush());
+            check("metrics shutdown", meter_provider.shutdown());
+            check("logs flush", logger_provider.force_flush());
+            check("logs shutdown", logger_provider.shutdown());
+            let _ = tx.send(None);
+        });
+        // None for a timeout too long to add: recv_timeout(Duration::MAX) just waits
+        let deadline = Instant::now().checked_add(timeout);
+        let mut errors = Vec::new();
+        let timed_out = loop {
+            let left = deadline.map_or(Duration::MAX, |d| {
+                d.saturating_duration_since(Instant::now())
+            });
+            match rx.recv_timeout(left) {
+                Ok(Some(error)) => errors.push(error),
+                Ok(None) => break false,
+                Err(_) => break true,
+            }
+        };
+
+        let spans_ended = spans.ended.load(Ordering::Relaxed);
+        let spans_exported = spans.exported.load(Ordering::Relaxed);
+        ShutdownReport {
+            spans_ended,
+            spans_exported,
+            spans_dropped: spans_ended.saturating_sub(spans_exported),
+            errors,
+            timed_out,
+        }
+    }
+}
+
+impl fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "telemetry shutdown: {} spans ended, {} exported, {} dropped",
+            self.spans_ended, self.spans_exported, self.spans_dropped
+        )?;
+        if self.timed_out {
+            write!(f, " (timed out)")?;
+        }
+        for e in &self.errors {
+            write!(f, "; {e}")?;
+        }
+        Ok(())
+    }
+}
+
+// Counts every sampled span that ends (only sampled spans reach on_end).
+#[derive(Debug)]
+struct CountingProcessor(Arc<SpanCounters>);
+
+impl SpanProcessor for CountingProcessor {
+    fn on_start(&self, _span: &mut Span, _cx: &Context) {}
+
+    fn on_end(&self, _span: SpanData) {
+        self.0.ended.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        Ok(())
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        Ok(())
+    }
+}
+
+// Counts the spans of every batch the OTLP exporter successfully sent.
+#[derive(Debug)]
+struct CountingExporter<E> {
+    inner: E,
+    spans: Arc<SpanCounters>,
+}
+
+impl<E: SpanExporter> SpanExporter for CountingExporter<E> {
+    async fn export(&self, batch: Vec<SpanData>) -> OTelSdkResult {
+        let len = batch.len() as u64;
+        self.inner.export(batch).await?;
+        self.spans.exported.fetch_add(len, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn shutdown_with_timeout(&mut self, timeout: Duration) -> OTelSdkResult {
+        self.inner.shutdown_with_timeout(timeout)
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
@@ -0,0 +1,338 @@
+// Keeping secrets out of the logs.
+//
+// Three complementary tools:
+//
+// 1. Redacted<T>: a wrapper whose Debug/Display print "[REDACTED]". Use it for values that must never
+//    be logged, wherever they end up (console, file, OTel span attributes, Sentry, a stray println!):
+//      info!(user = %name, password = ?Redacted::new(&password), "login");
+//      → user=alice password=[REDACTED] login
+//    It (de)serializes as the inner value, so it can sit in a config struct or next to field encryption
+//    (examples/serde1.rs: SensitiveData is encrypted by serde and Redacted in Debug output).
+//
+// 2. Redaction: key patterns (password, token, sensitive by default) for the fmt sinks. Every field
+//    whose name contains one of them, case-insensitively, is written as [REDACTED] by the console and
+//    file layers, however it was recorded:
+//      info!(api_token = %token, "calling upstream")  → api_token=[REDACTED] calling upstream
+//    Configured in [logging] (`redact = ["password", "token", "sensitive", "api_key"]`); used by
+//    LogFormat::redacted_layer and TelemetryBuilder. A tracing Layer can't rewrite an event for the
+//    other layers, so this is done in the formatters (RedactingFields, and RedactingJson for LOG_FORMAT=json);
+//    OTel/Sentry export still needs Redacted<T>.
+//
+// 3. #[derive(Redact)] (the ecosystem-derive crate): a Debug impl that prints the fields marked #[redact]
+//    as [REDACTED], for structs holding secrets or personal data (model::User's email, OtlpConfig's endpoint),
+//    so a stray {:?} of the whole struct can't leak them. The fields keep their types, unlike Redacted<T>.
+
+use std::{fmt, sync::Arc};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    field::RecordFields,
+    fmt::{format::Writer, FmtContext, FormatEvent, FormatFields, FormattedFields},
+    registry::LookupSpan,
+};
+
+pub use ecosystem_derive::Redact;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+// ---- Redacted<T> ----
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Redacted<T>(T);
+
+impl<T> Redacted<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    // The only way to get at the value: greppable, so reviews can spot where secrets are used.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Redacted<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T: Serialize> Serialize for Redacted<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Redacted<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+// ---- Redaction: field-name patterns ----
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Redaction {
+    patterns: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self::new(["password", "token", "sensitive"])
+    }
+}
+
+impl Redaction {
+    pub fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        Self {
+            patterns: patterns
+                .into_iter()
+                .map(|p| p.into().to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    // Nothing is redacted (Redacted<T> values still are).
+    pub fn none() -> Self {
+        Self {
+            patterns: Vec::new(),
+        }
+    }
+
+    // "password", "user.password", "PasswordHash", "refresh_token", ...
+    pub fn is_sensitive(&self, field: &str) -> bool {
+        let field = field.to_ascii_lowercase();
+        self.patterns.iter().any(|p| field.contains(p.as_str()))
+    }
+}
+
+// ---- fmt integration ----
+
+// FormatFields for the fmt layers: key=value (Full/Pretty) or a JSON object (Json),
+// with sensitive fields replaced. Span fields go through it too (they're formatted once,
+// when the span is created or recorded, and cached in the span's extensions).
+#[derive(Debug, Clone)]
+pub struct RedactingFields {
+    redaction: Arc<Redaction>,
+    json: bool,
+}
+
+impl RedactingFields {
+    pub fn text(redaction: Redaction) -> Self {
+        Self {
+            redaction: Arc::new(redaction),
+            json: false,
+        }
+    }
+
+    pub fn json(redaction: Redaction) -> Self {
+        Self {
+            redaction: Arc::new(redaction),
+            json: true,
+        }
+    }
+}
+
+impl<'writer> FormatFields<'writer> for RedactingFields {
+    fn format_fields<R: RecordFields>(
+        &self,
+        mut writer: Writer<'writer>,
+        fields: R,
+    ) -> fmt::Result {
+        if self.json {
+            let mut visitor = JsonVisitor::new(&self.redaction);
+            fields.record(&mut visitor);
+            write!(writer, "{}", Value::Object(visitor.map))
+        } else {
+            let mut visitor = TextVisitor {
+                writer,
+                redaction: &self.redaction,
+                first: true,
+                result: Ok(()),
+            };
+            fields.record(&mut visitor);
+            visitor.result
+        }
+    }
+
+    fn add_fields(
+        &self,
+        current: &'writer mut FormattedFields<Self>,
+        fields: &tracing::span::Record<'_>,
+    ) -> fmt::Result {
+        if self.json {
+            // merge into the cached object instead of appending a second one
+            let mut visitor = JsonVisitor::new(&self.redaction);
+            visitor.map = serde_json::from_str(&current.fields).unwrap_or_default();
+            fields.record(&mut visitor);
+            current.fields = Value::Object(visitor.map).to_string();
+            Ok(())
+        } else {
+            if !current.fields.is_empty() {
+                current.fields.push(' ');
+            }
+            self.format_fields(current.as_writer(), fields)
+        }
+    }
+}
+
+struct TextVisitor<'w, 'a> {
+    writer: Writer<'w>,
+    redaction: &'a Redaction,
+    first: bool,
+    result: fmt::Result,
+}
+
+impl Visit for TextVisitor<'_, '_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        // like DefaultFields: strings quoted, except the message
+        self.record_debug(field, &value)
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.result.is_err() {
+            return;
+        }
+        let sep = if self.first { "" } else { " " };
+        self.first = false;
+        let name = field.name();
+        self.result = if self.redaction.is_sensitive(name) {
+            write!(self.writer, "{sep}{name}={REDACTED}")
+        } else if name == "message" {
+            write!(self.writer, "{sep}{value:?}")
+        } else {
+            write!(self.writer, "{sep}{name}={value:?}")
+        };
+    }
+}
+
+struct JsonVisitor<'a> {
+    map: Map<String, Value>,
+    redaction: &'a Redaction,
+}
+
+impl<'a> JsonVisitor<'a> {
+    fn new(redaction: &'a Redaction) -> Self {
+        Self {
+            map: Map::new(),
+            redaction,
+        }
+    }
+
+    fn insert(&mut self, field: &Field, value: Value) {
+        let value = if self.redaction.is_sensitive(field.name()) {
+            Value::from(REDACTED)
+        } else {
+            value
+        };
+        self.map.insert(field.name().to_string(), value);
+    }
+}
+
+impl Visit for JsonVisitor<'_> {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+}
+
+// The JSON event format of LogFormat::Json (flattened event fields + current span), but with the
+// event fields visited by JsonVisitor: the stock Json formatter records them itself, bypassing FormatFields.
+//   {"timestamp":"...","level":"INFO","message":"login","password":"[REDACTED]","target":"...","span":{...}}
+#[derive(Debug, Clone)]
+pub struct RedactingJson {
+    redaction: Arc<Redaction>,
+}
+
+impl RedactingJson {
+    pub fn new(redaction: Redaction) -> Self {
+        Self {
+            redaction: Arc::new(redaction),
+        }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for RedactingJson
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let mut visitor = JsonVisitor::new(&self.redaction);
+        visitor.map.insert(
+            "timestamp".into(),
+            chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+                .into(),
+        );
+        visitor
+            .map
+            .insert("level".into(), meta.level().as_str().into());
+        event.record(&mut visitor);
+        let mut map = visitor.map;
+        map.insert("target".into(), meta.target().into());
+
+        if let Some(span) = ctx.lookup_current() {
+            // span fields were formatted (and redacted) by RedactingFields::json
+            let mut fields: Map<String, Value> = span
+                .extensions()
+                .get::<FormattedFields<N>>()
+                .and_then(|f| serde_json::from_str(&f.fields).ok())
+                .unwrap_or_default();
+            fields.insert("name".into(), span.name().into());
+            map.insert("span".into(), Value::Object(fields));
+        }
+        writeln!(writer, "{}", Value::Object(map))
+    }
+}
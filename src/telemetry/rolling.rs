@@ -0,0 +1,160 @@
+// File logging with bounded disk usage.
+// tracing_appender::rolling::daily() never deletes anything and a single busy day can fill the disk,
+// so this adds:
+//   - size-based rotation:  ecosystem.log → ecosystem.log.20250101T120000.123 once it exceeds max_bytes (50 MiB, see crate::units)
+//   - retention:            only the newest max_files rotated files are kept, older ones are deleted
+// Time-based rotation (daily/hourly) is still available, with the same max_files retention.
+//
+// Use the writer with tracing_appender::non_blocking, exactly like the daily appender:
+//   let (writer, _guard) = tracing_appender::non_blocking(FileLogConfig::default().writer()?);
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+use crate::units::ByteCount;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum LogRotation {
+    Size { max_bytes: ByteCount },
+    Hourly,
+    Daily,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileLogConfig {
+    pub dir: PathBuf,
+    pub file_name: String,
+    pub rotation: LogRotation,
+    // rotated files to keep (the active file is not counted)
+    pub max_files: usize,
+}
+
+impl Default for FileLogConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("/tmp/logs"),
+            file_name: "ecosystem.log".to_string(),
+            rotation: LogRotation::Size {
+                max_bytes: ByteCount::mib(50),
+            },
+            max_files: 10,
+        }
+    }
+}
+
+impl FileLogConfig {
+    pub fn writer(&self) -> io::Result<Box<dyn Write + Send>> {
+        let rotation = match self.rotation {
+            LogRotation::Size { max_bytes } => {
+                return Ok(Box::new(SizeRollingWriter::new(
+                    self.dir.clone(),
+                    self.file_name.clone(),
+                    max_bytes,
+                    self.max_files,
+                )?))
+            }
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+        };
+        let appender = RollingFileAppender::builder()
+            .rotation(rotation)
+            .filename_prefix(&self.file_name)
+            // tracing-appender counts the active file too
+            .max_log_files(self.max_files + 1)
+            .build(&self.dir)
+            .map_err(io::Error::other)?;
+        Ok(Box::new(appender))
+    }
+}
+
+pub struct SizeRollingWriter {
+    dir: PathBuf,
+    file_name: String,
+    max_bytes: ByteCount,
+    max_files: usize,
+    file: File,
+    written: u64,
+}
+
+impl SizeRollingWriter {
+    // Appends to dir/file_name if it already exists (a restart doesn't start a new file).
+    pub fn new(
+        dir: PathBuf,
+        file_name: String,
+        max_bytes: ByteCount,
+        max_files: usize,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let file = open_append(&dir, &file_name)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            dir,
+            file_name,
+            max_bytes,
+            max_files,
+            file,
+            written,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let rotated = format!(
+            "{}.{}",
+            self.file_name,
+            Utc::now().format("%Y%m%dT%H%M%S%.3f")
+        );
+        fs::rename(self.dir.join(&self.file_name), self.dir.join(rotated))?;
+        self.file = open_append(&self.dir, &self.file_name)?;
+        self.written = 0;
+        self.prune()
+    }
+
+    // The timestamp suffix sorts chronologically, so the oldest files come first.
+    fn prune(&self) -> io::Result<()> {
+        let prefix = format!("{}.", self.file_name);
+        let mut rotated: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+            .map(|entry| entry.path())
+            .collect();
+        rotated.sort();
+        let excess = rotated.len().saturating_sub(self.max_files);
+        for path in &rotated[..excess] {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingWriter {
+    // non_blocking hands over whole formatted events, so files are only split between events.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes.get() {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(dir: &std::path::Path, file_name: &str) -> io::Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(file_name))
+}
@@ -0,0 +1,106 @@
+// Slow-operation detection: a WARN event whenever a span stays open longer than its threshold.
+// Replaces hand-written `warn!(app.task_duration = elapsed, ...)` timing code in individual functions:
+// #[instrument] on the function + a line of config is enough.
+//
+//   [logging.slow_spans]        # milliseconds or a duration, by span name; "*" applies to every other span
+//   long_task = 50
+//   "http.request" = "1.5s"
+//   # "*" = 1000
+//
+// The event is emitted in the slow span's parent (so it carries e.g. the request span's request_id)
+// with target "slow_span" and structured fields, which log queries and alerts can match on:
+//   WARN slow_span: slow operation span_name="long_task" duration_ms=53 threshold_ms=50
+// Duration is wall time from creation to close, like span.duration in span_metrics.
+
+use std::{collections::BTreeMap, time::Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::{
+    span::{Attributes, Id},
+    Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+use crate::units::Millis;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SlowSpanConfig {
+    thresholds: BTreeMap<String, Millis>,
+}
+
+impl SlowSpanConfig {
+    pub fn with_threshold(mut self, span_name: impl Into<String>, threshold: Millis) -> Self {
+        self.thresholds.insert(span_name.into(), threshold);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.thresholds.is_empty()
+    }
+
+    // The span's own threshold, else the "*" one.
+    pub fn threshold(&self, span_name: &str) -> Option<Millis> {
+        self.thresholds
+            .get(span_name)
+            .or_else(|| self.thresholds.get("*"))
+            .copied()
+    }
+}
+
+pub struct SlowSpanLayer {
+    config: SlowSpanConfig,
+}
+
+// Start time and threshold, stored in the registry's span extensions (only for spans that have a threshold).
+struct SlowSpanTiming {
+    start: Instant,
+    threshold: Millis,
+}
+
+impl SlowSpanLayer {
+    pub fn new(config: SlowSpanConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S> Layer<S> for SlowSpanLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(threshold) = self.config.threshold(attrs.metadata().name()) else {
+            return;
+        };
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SlowSpanTiming {
+                start: Instant::now(),
+                threshold,
+            });
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SlowSpanTiming>() else {
+            return;
+        };
+        let duration = Millis::from(timing.start.elapsed());
+        if duration <= timing.threshold {
+            return;
+        }
+        let parent = span.parent().map(|p| p.id());
+        tracing::warn!(
+            target: "slow_span",
+            parent: parent,
+            span_name = span.name(),
+            span_target = span.metadata().target(),
+            // plain numbers, so log queries can compare them
+            duration_ms = duration.get(),
+            threshold_ms = timing.threshold.get(),
+            "slow operation"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+// RED metrics (Rate, Errors, Duration) derived from spans, for code paths that only have
+// #[instrument] today. Every closed span is one observation:
+//
+//   span.calls{span.name="long_task", error="false"}   counter    → rate and error ratio
+//   span.duration{span.name="long_task"}               histogram  → latency (seconds)
+//
+// A span counts as failed when an ERROR event happens directly inside it, or when it records
+// otel.status_code = "ERROR" (what web::trace does for 5xx responses).
+// Duration is wall time from creation to close, including time spent idle across .await points.
+
+use std::time::Instant;
+
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram},
+    KeyValue,
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+pub struct SpanMetricsLayer {
+    calls: Counter<u64>,
+    duration: Histogram<f64>,
+}
+
+// Per-span state, stored in the registry's span extensions.
+struct SpanTiming {
+    start: Instant,
+    error: bool,
+}
+
+impl SpanMetricsLayer {
+    // Instruments come from the global meter provider: create the layer after
+    // otel::init_meter_provider(), otherwise they are no-ops.
+    pub fn new() -> Self {
+        let meter = global::meter("ecosystem.spans");
+        Self {
+            calls: meter
+                .u64_counter("span.calls")
+                .with_description("Closed spans, by name and error")
+                .build(),
+            duration: meter
+                .f64_histogram("span.duration")
+                .with_description("Span duration, creation to close")
+                .with_unit("s")
+                .build(),
+        }
+    }
+}
+
+impl Default for SpanMetricsLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for SpanMetricsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanTiming {
+                start: Instant::now(),
+                error: false,
+            });
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = ErrorStatusVisitor(false);
+        values.record(&mut visitor);
+        if visitor.0 {
+            mark_error(Some(id.clone()), &ctx);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() == Level::ERROR {
+            mark_error(ctx.event_span(event).map(|s| s.id()), &ctx);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let name = KeyValue::new("span.name", span.name());
+        self.duration.record(
+            timing.start.elapsed().as_secs_f64(),
+            std::slice::from_ref(&name),
+        );
+        self.calls
+            .add(1, &[name, KeyValue::new("error", timing.error)]);
+    }
+}
+
+fn mark_error<S>(id: Option<Id>, ctx: &Context<'_, S>)
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    if let Some(span) = id.and_then(|id| ctx.span(&id)) {
+        if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+            timing.error = true;
+        }
+    }
+}
+
+// Looks for otel.status_code = "ERROR" among recorded fields.
+struct ErrorStatusVisitor(bool);
+
+impl Visit for ErrorStatusVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "otel.status_code" && value == "ERROR" {
+            self.0 = true;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
@@ -0,0 +1,43 @@
+// Durations of async tasks and background jobs as an OTel histogram, so p50/p99 per task are graphable:
+//
+//   task.duration{task.name="task2"}   histogram (seconds)
+//
+//   let (a, b) = join!(timed("task1", task1()), timed("task2", task2()));
+//   task_metrics::record("hash_job", start.elapsed());   // when there's no future to wrap
+//
+// Unlike span.duration (span_metrics), which covers every span, this is opt-in with stable, low-cardinality
+// task names, and has explicit buckets from 1ms to 10s (the SDK default buckets are meant for milliseconds).
+// Futures dropped before completion (cancelled, timed out) are not recorded.
+
+use std::{
+    future::Future,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use opentelemetry::{global, metrics::Histogram, KeyValue};
+
+// Created on first use: after otel::init_meter_provider() / Telemetry::init installed the global
+// provider, as long as nothing is recorded before telemetry is initialized (otherwise it stays a no-op).
+static TASK_DURATION: LazyLock<Histogram<f64>> = LazyLock::new(|| {
+    global::meter("ecosystem.tasks")
+        .f64_histogram("task.duration")
+        .with_description("Duration of instrumented tasks and jobs, by task name")
+        .with_unit("s")
+        .with_boundaries(vec![
+            0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+        ])
+        .build()
+});
+
+pub fn record(task: &'static str, elapsed: Duration) {
+    TASK_DURATION.record(elapsed.as_secs_f64(), &[KeyValue::new("task.name", task)]);
+}
+
+// Awaits `fut` and records how long it took (wall time, including time spent waiting at .await points).
+pub async fn timed<F: Future>(task: &'static str, fut: F) -> F::Output {
+    let start = Instant::now();
+    let output = fut.await;
+    record(task, start.elapsed());
+    output
+}
@@ -0,0 +1,413 @@
+// units: quantities with their unit in the type, the MyInt newtype of examples/more.rs grown up.
+// A bare u64 doesn't say whether it's bytes, kilobytes or milliseconds; ByteCount and Millis do,
+// so a size can't be passed where a timeout is expected, and values print and parse with their unit:
+//
+//   let copied = pool.copy(&mut r, &mut w).await?;          // ByteCount
+//   info!("proxied {copied}");                              // proxied 1.5 MiB
+//   let limit: ByteCount = "50 MiB".parse()?;               // also "50MiB", "52428800", "1.5 GB"
+//   let slow = Millis::from(started.elapsed());             // 250ms, 1.5s, 2m, 1h
+//
+// Arithmetic (all on the inner u64):
+//   ByteCount + ByteCount, ByteCount - ByteCount, ByteCount * u64, ByteCount / u64, iter.sum()
+//   checked_add / checked_sub / checked_mul → None on overflow
+//   saturating_add / saturating_sub / saturating_mul → clamped at 0 / MAX
+// The plain operators behave like u64's: overflow panics in debug builds, wraps in release.
+// Counters that can run for months (bytes proxied over a connection's lifetime) should use saturating_add.
+//
+// Serde: serialized as the plain number (metrics, JSON APIs), deserialized from a number or a string
+// with a unit, so config files can say what they mean:
+//   [logging.slow_spans]
+//   "http.request" = "1.5s"     # or 1500
+//   rotation = { kind = "size", max_bytes = "50 MiB" }     # or 52428800
+//
+// Byte units: B, KB/MB/GB/TB (powers of 1000), KiB/MiB/GiB/TiB (powers of 1024), case-insensitive.
+// Time units: ms, s, m (or min), h. Fractions are rounded to the nearest byte / millisecond.
+
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Div, Mul, Sub, SubAssign},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ByteCount(pub u64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Millis(pub u64);
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid {kind} {input:?} (expected a number with an optional unit: {units})")]
+pub struct ParseUnitError {
+    kind: &'static str,
+    input: String,
+    units: &'static str,
+}
+
+const BYTE_UNITS: &[(&str, u64)] = &[
+    ("B", 1),
+    ("KB", 1000),
+    ("MB", 1000 * 1000),
+    ("GB", 1000 * 1000 * 1000),
+    ("TB", 1000 * 1000 * 1000 * 1000),
+    ("KiB", 1 << 10),
+    ("MiB", 1 << 20),
+    ("GiB", 1 << 30),
+    ("TiB", 1 << 40),
+];
+
+const TIME_UNITS: &[(&str, u64)] = &[
+    ("ms", 1),
+    ("s", 1000),
+    ("m", 60 * 1000),
+    ("min", 60 * 1000),
+    ("h", 60 * 60 * 1000),
+];
+
+// Number and unit, e.g. "1.5 MiB" → 1572864. None if the unit is unknown or the value doesn't fit.
+fn parse_with_units(s: &str, units: &[(&str, u64)]) -> Option<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let unit = unit.trim();
+    let scale = if unit.is_empty() {
+        1
+    } else {
+        units
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(unit))?
+            .1
+    };
+    // whole numbers stay exact, fractions go through f64
+    if let Ok(n) = number.parse::<u64>() {
+        return n.checked_mul(scale);
+    }
+    let value = (number.parse::<f64>().ok()? * scale as f64).round();
+    // u64::MAX as f64 is 2^64, one past the largest u64: `as` would clamp it instead of failing
+    (value.is_finite() && value < u64::MAX as f64).then_some(value as u64)
+}
+
+// Everything shared by the quantity types: the u64 conversions, arithmetic and serde.
+macro_rules! quantity {
+    ($name:ident, $kind:literal, $units:ident, $unit_list:literal) => {
+        impl $name {
+            pub const ZERO: Self = Self(0);
+            pub const MAX: Self = Self(u64::MAX);
+
+            pub const fn get(self) -> u64 {
+                self.0
+            }
+
+            pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                self.0.checked_add(rhs.0).map(Self)
+            }
+
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                self.0.checked_sub(rhs.0).map(Self)
+            }
+
+            pub fn checked_mul(self, rhs: u64) -> Option<Self> {
+                self.0.checked_mul(rhs).map(Self)
+            }
+
+            pub fn saturating_add(self, rhs: Self) -> Self {
+                Self(self.0.saturating_add(rhs.0))
+            }
+
+            pub fn saturating_sub(self, rhs: Self) -> Self {
+                Self(self.0.saturating_sub(rhs.0))
+            }
+
+            pub fn saturating_mul(self, rhs: u64) -> Self {
+                Self(self.0.saturating_mul(rhs))
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(n: u64) -> Self {
+                Self(n)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(q: $name) -> u64 {
+                q.0
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        // scaling by a plain number: a size times a count is still a size
+        impl Mul<u64> for $name {
+            type Output = Self;
+
+            fn mul(self, rhs: u64) -> Self {
+                Self(self.0 * rhs)
+            }
+        }
+
+        impl Div<u64> for $name {
+            type Output = Self;
+
+            fn div(self, rhs: u64) -> Self {
+                Self(self.0 / rhs)
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, Add::add)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseUnitError;
+
+            fn from_str(s: &str) -> Result<Self, ParseUnitError> {
+                parse_with_units(s, $units)
+                    .map(Self)
+                    .ok_or_else(|| ParseUnitError {
+                        kind: $kind,
+                        input: s.to_string(),
+                        units: $unit_list,
+                    })
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u64(self.0)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                match NumberOrText::deserialize(deserializer)? {
+                    NumberOrText::Number(n) => Ok(Self(n)),
+                    NumberOrText::Text(s) => s.parse().map_err(de::Error::custom),
+                }
+            }
+        }
+    };
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrText {
+    Number(u64),
+    Text(String),
+}
+
+quantity!(
+    ByteCount,
+    "byte count",
+    BYTE_UNITS,
+    "B, KB, MB, GB, TB, KiB, MiB, GiB, TiB"
+);
+quantity!(Millis, "duration", TIME_UNITS, "ms, s, m, h");
+
+impl ByteCount {
+    pub const fn kib(n: u64) -> Self {
+        Self(n << 10)
+    }
+
+    pub const fn mib(n: u64) -> Self {
+        Self(n << 20)
+    }
+}
+
+// 512 B, 64 KiB, 1.5 MiB: binary units, exact when it's a whole number of them, one decimal otherwise.
+impl fmt::Display for ByteCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (unit, scale) = [
+            ("TiB", 1 << 40),
+            ("GiB", 1 << 30),
+            ("MiB", 1 << 20),
+            ("KiB", 1 << 10),
+        ]
+        .into_iter()
+        .find(|&(_, scale)| self.0 >= scale)
+        .unwrap_or(("B", 1));
+        if self.0.is_multiple_of(scale) {
+            write!(f, "{} {unit}", self.0 / scale)
+        } else {
+            write!(f, "{:.1} {unit}", self.0 as f64 / scale as f64)
+        }
+    }
+}
+
+impl Millis {
+    // Saturates at u64::MAX milliseconds instead of overflowing.
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(secs.saturating_mul(1000))
+    }
+
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_millis(self.0)
+    }
+}
+
+// Whole milliseconds; a Duration too long for u64 milliseconds (584 million years) becomes MAX.
+impl From<Duration> for Millis {
+    fn from(d: Duration) -> Self {
+        Self(u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+    }
+}
+
+impl From<Millis> for Duration {
+    fn from(ms: Millis) -> Duration {
+        ms.as_duration()
+    }
+}
+
+// 250ms, 1.5s, 2m, 1h: the largest unit that's exact, so the output parses back to the same value.
+impl fmt::Display for Millis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = self.0;
+        match ms {
+            0..=999 => write!(f, "{ms}ms"),
+            _ if ms.is_multiple_of(3_600_000) => write!(f, "{}h", ms / 3_600_000),
+            _ if ms.is_multiple_of(60_000) => write!(f, "{}m", ms / 60_000),
+            _ if ms.is_multiple_of(1000) => write!(f, "{}s", ms / 1000),
+            _ => {
+                let frac = format!("{:03}", ms % 1000);
+                write!(f, "{}.{}s", ms / 1000, frac.trim_end_matches('0'))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_numbers_with_and_without_units() {
+        let bytes = |s: &str| s.parse::<ByteCount>().map(ByteCount::get);
+        assert_eq!(bytes("1.5 MiB"), Ok(1_572_864));
+        assert_eq!(bytes("50MiB"), Ok(50 << 20));
+        assert_eq!(bytes("50 mib"), Ok(50 << 20));
+        assert_eq!(bytes("1.5 GB"), Ok(1_500_000_000));
+        assert_eq!(bytes("52428800"), Ok(52_428_800));
+        assert_eq!(bytes(" 12 B "), Ok(12));
+
+        let ms = |s: &str| s.parse::<Millis>().map(Millis::get);
+        assert_eq!(ms("1.5s"), Ok(1500));
+        assert_eq!(ms("250"), Ok(250));
+        assert_eq!(ms("2 min"), Ok(120_000));
+        assert_eq!(ms("1h"), Ok(3_600_000));
+    }
+
+    #[test]
+    fn rejects_unknown_units_negatives_and_overflow() {
+        for input in [
+            "5 parsecs",
+            "10 ms",
+            "-5 MiB",
+            "-1",
+            "",
+            "MiB",
+            "1.2.3 KB",
+            "20000000 TiB",
+        ] {
+            let err = input.parse::<ByteCount>().unwrap_err();
+            assert_eq!(err.input, input);
+        }
+        assert!("18446744073709551616".parse::<ByteCount>().is_err());
+        assert_eq!(
+            "18446744073709551615".parse::<ByteCount>(),
+            Ok(ByteCount::MAX)
+        );
+        for input in ["5 days", "-3s", "1e30 h"] {
+            assert!(input.parse::<Millis>().is_err(), "{input}");
+        }
+        assert_eq!(
+            "5 parsecs".parse::<ByteCount>().unwrap_err().to_string(),
+            "invalid byte count \"5 parsecs\" (expected a number with an optional unit: \
+             B, KB, MB, GB, TB, KiB, MiB, GiB, TiB)"
+        );
+    }
+
+    #[test]
+    fn display_parses_back_to_the_same_value() {
+        // exact for whole numbers of a unit; otherwise Display rounds to one decimal
+        for n in [0, 512, 1024, 64 << 10, 50 << 20, 3 << 40, 1536 << 20] {
+            let shown = ByteCount(n).to_string();
+            let parsed: ByteCount = shown.parse().unwrap();
+            assert_eq!(parsed, ByteCount(n), "{shown}");
+        }
+        assert_eq!(ByteCount(1_572_864).to_string(), "1.5 MiB");
+        assert_eq!(ByteCount::kib(64).to_string(), "64 KiB");
+
+        for ms in [0, 250, 1000, 1500, 1234, 120_000, 3_600_000, 5_400_000] {
+            let shown = Millis(ms).to_string();
+            assert_eq!(shown.parse::<Millis>(), Ok(Millis(ms)), "{shown}");
+        }
+        assert_eq!(Millis(1500).to_string(), "1.5s");
+        assert_eq!(Millis(5_400_000).to_string(), "90m");
+    }
+
+    #[test]
+    fn checked_and_saturating_arithmetic() {
+        assert_eq!(ByteCount::MAX.checked_add(ByteCount(1)), None);
+        assert_eq!(ByteCount(1).checked_sub(ByteCount(2)), None);
+        assert_eq!(ByteCount::MAX.checked_mul(2), None);
+        assert_eq!(ByteCount::MAX.saturating_add(ByteCount(1)), ByteCount::MAX);
+        assert_eq!(ByteCount(1).saturating_sub(ByteCount(2)), ByteCount::ZERO);
+        assert_eq!(
+            [ByteCount::kib(1), ByteCount(24)]
+                .into_iter()
+                .sum::<ByteCount>(),
+            ByteCount(1048)
+        );
+        assert_eq!(Millis::from_secs(u64::MAX), Millis::MAX);
+        assert_eq!(Millis::from(Duration::MAX), Millis::MAX);
+    }
+
+    #[test]
+    fn deserializes_from_a_number_or_a_string() {
+        #[derive(Deserialize)]
+        struct Limits {
+            max_bytes: ByteCount,
+            timeout: Millis,
+        }
+        let limits: Limits =
+            serde_json::from_str(r#"{"max_bytes": "50 MiB", "timeout": 1500}"#).unwrap();
+        assert_eq!(limits.max_bytes, ByteCount::mib(50));
+        assert_eq!(limits.timeout.as_duration(), Duration::from_millis(1500));
+        assert!(serde_json::from_str::<Limits>(r#"{"max_bytes": -1, "timeout": 1}"#).is_err());
+        assert_eq!(serde_json::to_string(&ByteCount::kib(1)).unwrap(), "1024");
+    }
+}
@@ -0,0 +1,82 @@
+// Operational endpoints for a running server.
+//   GET /admin/log-level                              → {"level": "info"}
+//   PUT /admin/log-level  {"level": "debug,hyper=warn"} → swaps the EnvFilter without a restart
+//
+// Every request needs `Authorization: Bearer <token>` with the token the router was built with
+// ([app] admin_token / APP_ADMIN_TOKEN for `ecosystem serve`, ADMIN_TOKEN for the examples):
+//   curl -X PUT -H "Authorization: Bearer $ADMIN_TOKEN" -H 'Content-Type: application/json' \
+//        -d '{"level": "debug"}' localhost:8080/admin/log-level
+// Without a token the endpoints are disabled (403), so merging the router never opens them up.
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{instrument, warn};
+
+use crate::telemetry::LogLevelHandle;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogLevel {
+    pub level: String,
+}
+
+pub fn router(log_level: LogLevelHandle, token: Option<String>) -> Router {
+    let token = token.filter(|t| !t.is_empty());
+    if token.is_none() {
+        warn!("no admin token configured: /admin endpoints are disabled");
+    }
+    Router::new()
+        .route("/admin/log-level", get(get_log_level).put(set_log_level))
+        .with_state(log_level)
+        .layer(middleware::from_fn_with_state(token, require_token))
+}
+
+async fn require_token(State(token): State<Option<String>>, req: Request, next: Next) -> Response {
+    let Some(token) = token else {
+        return (StatusCode::FORBIDDEN, "admin endpoints are disabled").into_response();
+    };
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    match presented {
+        Some(presented) if constant_time_eq(presented.as_bytes(), token.as_bytes()) => {
+            next.run(req).await
+        }
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            "admin token required",
+        )
+            .into_response(),
+    }
+}
+
+// Compares every byte, so the response time doesn't tell how much of a guess was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+async fn get_log_level(State(handle): State<LogLevelHandle>) -> Json<LogLevel> {
+    Json(LogLevel {
+        level: handle.current().unwrap_or_default(),
+    })
+}
+
+#[instrument(skip(handle))]
+async fn set_log_level(
+    State(handle): State<LogLevelHandle>,
+    Json(body): Json<LogLevel>,
+) -> Result<Json<LogLevel>, (StatusCode, String)> {
+    handle
+        .set(&body.level)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok(Json(body))
+}
@@ -0,0 +1,282 @@
+// app: the whole web service as one Router, built from config: what examples/axum_serde.rs wires by
+// hand (users API, search, HTML views, audit, jobs, assets, admin and the request layers), for
+// `ecosystem serve` and anything else that wants the service without copying the wiring.
+//
+// server.toml (overridable with APP_<KEY>, nested with "__": APP_STORAGE__KIND=file):
+//   [app]
+//   audit_log = "audit.jsonl"            # JSON lines; missing: audit entries are kept in memory only
+//   assets_dir = "assets"                # GET /assets/*; missing: not served
+//   retention_ms = "720h"                # soft-deleted users can be restored for 30 days, then are purged
+//   admin_token = "..."                  # bearer token for /admin/*; missing: /admin is disabled (403)
+//   [app.storage]
+//   kind = "file"                        # or "memory" (default): users are lost on restart
+//   path = "users.json"
+//   flush_interval_ms = "5s"
+//   [app.export]                         # --features parquet, see crate::export
+//   dir = "exports"                      # where `ecosystem export` and the schedule write
+//   schedule = "0 0 2 * * *"             # cron (or "@every 6h"); missing: only on demand
+//
+//   let router = app::router(&AppConfig::load("server.toml")?, telemetry.log_level().clone()).await?;
+//   web::server::serve(router, &ServerConfig::load("server.toml")?).await?;
+//
+// Key flow:
+// router(config, log_level)
+//   ├→ AuditLog::open(audit_log) or in memory
+//   ├→ UserStore + the purge task
+//   ├→ storage = file → restore users.json (if it exists), then a task saving a snapshot every
+//   │                   flush_interval when it changed (written to users.json.tmp, then renamed)
+//   ├→ export.schedule → a Scheduler firing a one-worker pool that writes the live users and
+//   │                    audit log as Parquet files (a run still going when the next is due: skipped)
+//   └→ Router: users, search, ui, audit, jobs, assets, admin
+//        + trace_id, trace span, X-Request-Id layers (outermost last)
+//
+// File storage is a snapshot, not a database: a crash loses what changed since the last flush,
+// and two processes must not share one file.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{middleware, Router};
+use serde::{Deserialize, Serialize};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tracing::{info, warn};
+
+use super::users::{UserSnapshot, UserStore};
+use crate::{
+    audit::AuditLog,
+    config::{self, ConfigError},
+    error::MyError,
+    telemetry::LogLevelHandle,
+    units::Millis,
+    worker::{HashJob, JobRegistry, JobStore},
+};
+#[cfg(feature = "parquet")]
+use crate::{
+    scheduler::{Schedule, Scheduler, Trigger},
+    worker::WorkerPool,
+};
+#[cfg(feature = "parquet")]
+use chrono::Utc;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub storage: StorageConfig,
+    pub audit_log: Option<PathBuf>,
+    pub assets_dir: Option<PathBuf>,
+    // how long soft-deleted users can still be restored
+    pub retention_ms: Millis,
+    // Authorization: Bearer token of the /admin endpoints (web::admin)
+    pub admin_token: Option<String>,
+    pub export: Option<ExportConfig>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            storage: StorageConfig::default(),
+            audit_log: None,
+            assets_dir: None,
+            retention_ms: Millis::from_secs(30 * 24 * 3600),
+            admin_token: None,
+            export: None,
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn load(file: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        config::load_section(file, "app", "APP_")
+    }
+}
+
+// Where the users live. On the command line: "memory" or "file:users.json" (FromStr below).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StorageConfig {
+    #[default]
+    Memory,
+    File {
+        path: PathBuf,
+        #[serde(default = "default_flush_interval")]
+        flush_interval_ms: Millis,
+    },
+}
+
+fn default_flush_interval() -> Millis {
+    Millis::from_secs(5)
+}
+
+// Parquet exports for the data warehouse (crate::export); only acted on with --features parquet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportConfig {
+    #[serde(default = "default_export_dir")]
+    pub dir: PathBuf,
+    // a scheduler::Trigger: cron expression or "@every <interval>"
+    pub schedule: Option<String>,
+}
+
+fn default_export_dir() -> PathBuf {
+    PathBuf::from("exports")
+}
+
+impl FromStr for StorageConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s.split_once(':') {
+            _ if s.eq_ignore_ascii_case("memory") => Ok(Self::Memory),
+            Some((kind, path)) if kind.eq_ignore_ascii_case("file") && !path.is_empty() => {
+                Ok(Self::File {
+                    path: path.into(),
+                    flush_interval_ms: default_flush_interval(),
+                })
+            }
+            _ => Err(format!(
+                "invalid storage {s:?} (expected \"memory\" or \"file:<path>\")"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StorageConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Memory => f.write_str("memory"),
+            Self::File { path, .. } => write!(f, "file:{}", path.display()),
+        }
+    }
+}
+
+// The service's Router, its stores opened and background tasks spawned; must be called inside a
+// Tokio runtime. `log_level` is the handle PUT /admin/log-level changes (TelemetryGuard::log_level).
+pub async fn router(config: &AppConfig, log_level: LogLevelHandle) -> Result<Router, MyError> {
+    let audit = match &config.audit_log {
+        Some(path) => AuditLog::open(path).await?,
+        None => AuditLog::in_memory(),
+    };
+    let users = UserStore::new().with_audit(audit.clone());
+    users.spawn_purge_task(config.retention_ms.as_duration(), Duration::from_secs(3600));
+    if let StorageConfig::File {
+        path,
+        flush_interval_ms,
+    } = &config.storage
+    {
+        let snapshot = read_snapshot(path).await?;
+        info!(path = %path.display(), users = snapshot.users.len(), "loaded users");
+        users.load_snapshot(snapshot);
+        spawn_flush_task(users.clone(), path.clone(), flush_interval_ms.as_duration());
+    }
+    if let Some(export) = &config.export {
+        schedule_export(export, users.clone(), audit.clone())?;
+    }
+
+    let jobs =
+        JobStore::new().with_registry(Arc::new(JobRegistry::new().register::<HashJob>("hash")));
+    let mut app = Router::new()
+        .merge(super::users::router(users.clone()))
+        .merge(super::search::router(users.clone()))
+        .merge(super::ui::router(users))
+        .merge(super::audit::router(audit))
+        .merge(super::jobs::router(jobs))
+        .merge(super::admin::router(log_level, config.admin_token.clone()));
+    if let Some(dir) = &config.assets_dir {
+        app = app.merge(super::assets::router(dir, Duration::from_secs(3600)));
+    }
+    Ok(app
+        .layer(middleware::from_fn(super::trace_id::middleware))
+        .layer(super::trace::layer())
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid)))
+}
+
+// [app.export] schedule: a Scheduler submitting to a pool of one worker, which writes the live
+// stores (not the storage file, which lags by a flush interval).
+#[cfg(feature = "parquet")]
+fn schedule_export(
+    export: &ExportConfig,
+    users: UserStore,
+    audit: AuditLog,
+) -> Result<(), MyError> {
+    let Some(schedule) = &export.schedule else {
+        return Ok(());
+    };
+    let trigger: Trigger = schedule
+        .parse()
+        .map_err(|e| MyError::Custom(format!("[app.export] schedule: {e}")))?;
+    let dir = export.dir.clone();
+    let pool = WorkerPool::new(1, 1, move |_| {
+        let summary =
+            crate::export::export_to(&dir, &users.snapshot().users, &audit.entries(), Utc::now())
+                .map_err(|e| MyError::Custom(format!("parquet export failed: {e}")))?;
+        info!(
+            users = summary.users,
+            audit_entries = summary.audit_entries,
+            dir = %dir.display(),
+            "exported to parquet"
+        );
+        Ok(summary.users_file.display().to_string())
+    })
+    .named("export");
+    let pool = Arc::new(pool);
+    Scheduler::new(pool.clone())
+        .schedule(Schedule::new("export-parquet", trigger, "export"))
+        .start(pool.cancellation_token());
+    info!(schedule, dir = %export.dir.display(), "parquet export scheduled");
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn schedule_export(
+    export: &ExportConfig,
+    _users: UserStore,
+    _audit: AuditLog,
+) -> Result<(), MyError> {
+    if export.schedule.is_some() {
+        warn!("[app.export] schedule ignored: built without the parquet feature");
+    }
+    Ok(())
+}
+
+// An empty snapshot when the file doesn't exist yet (first start). Also for tools filling the file
+// while the service is stopped (`ecosystem generate users --seed-store`).
+pub async fn read_snapshot(path: &Path) -> Result<UserSnapshot, MyError> {
+    match tokio::fs::read(path).await {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(UserSnapshot::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Every `every`, the store's snapshot replaces the file if it differs from the last one written.
+fn spawn_flush_task(users: UserStore, path: PathBuf, every: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(every);
+        let mut written = users.snapshot();
+        loop {
+            ticker.tick().await;
+            let snapshot = users.snapshot();
+            if snapshot == written {
+                continue;
+            }
+            match save_snapshot(&path, &snapshot).await {
+                Ok(()) => written = snapshot,
+                // kept dirty: retried on the next tick
+                Err(e) => warn!(path = %path.display(), "failed to save users: {e}"),
+            }
+        }
+    });
+}
+
+// Through a temporary file and a rename, so a crash mid-write leaves the previous snapshot intact.
+pub async fn save_snapshot(path: &Path, snapshot: &UserSnapshot) -> Result<(), MyError> {
+    let tmp = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp, serde_json::to_vec_pretty(snapshot)?).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
@@ -0,0 +1,187 @@
+// Static asset serving: GET /assets/* → files from a configurable directory.
+// ServeDir (tower-http) already handles Content-Type, Range and Last-Modified.
+// On top of it we add:
+// - Cache-Control: public, max-age=N  (browsers may reuse the file for N seconds without asking)
+// - a weak ETag + If-None-Match → 304 Not Modified (revalidation without re-downloading the body)
+//
+// Conditional requests (RFC 9110 13.1) are answered here rather than by ServeDir, so every 304
+// carries the ETag:
+// - If-None-Match: a comma-separated list of ETags, or *; weak comparison (W/"x" matches "x")
+// - If-Modified-Since: only when there's no If-None-Match, against the file's Last-Modified
+
+use std::{path::Path, time::Duration};
+
+use axum::{
+    extract::Request,
+    http::{
+        header::{
+            CACHE_CONTROL, CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+        },
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use chrono::DateTime;
+use tower_http::{services::ServeDir, set_header::SetResponseHeaderLayer};
+
+pub fn router(dir: impl AsRef<Path>, max_age: Duration) -> Router {
+    let cache_control = HeaderValue::from_str(&format!("public, max-age={}", max_age.as_secs()))
+        .expect("cache-control built from digits is always a valid header value");
+
+    Router::new()
+        .nest_service("/assets", ServeDir::new(dir))
+        .layer(middleware::from_fn(etag_middleware))
+        // if_not_present: keep whatever a more specific handler decided
+        .layer(SetResponseHeaderLayer::if_not_present(
+            CACHE_CONTROL,
+            cache_control,
+        ))
+}
+
+// Weak ETag (W/"...") because it is derived from metadata (size + mtime), not from the bytes themselves.
+async fn etag_middleware(mut req: Request, next: Next) -> Response {
+    let if_none_match = req.headers().get(IF_NONE_MATCH).cloned();
+    // ServeDir's own 304 would come without the ETag (and without the metadata to compute it)
+    let if_modified_since = req.headers_mut().remove(IF_MODIFIED_SINCE);
+    let mut res = next.run(req).await;
+    if res.status() != StatusCode::OK {
+        return res;
+    }
+
+    let Some(etag) = weak_etag(res.headers()) else {
+        return res;
+    };
+    // If-None-Match wins; If-Modified-Since is ignored when both are sent (RFC 9110 13.1.3)
+    let not_modified = match (&if_none_match, &if_modified_since) {
+        (Some(tags), _) => matches_any(tags, &etag),
+        (None, Some(since)) => not_modified_since(res.headers(), since),
+        (None, None) => false,
+    };
+    if not_modified {
+        let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+        let headers = not_modified.headers_mut();
+        if let Some(modified) = res.headers().get(LAST_MODIFIED) {
+            headers.insert(LAST_MODIFIED, modified.clone());
+        }
+        headers.insert(ETAG, etag);
+        return not_modified;
+    }
+    res.headers_mut().insert(ETAG, etag);
+    res
+}
+
+// If-None-Match: `*`, or a list like `W/"a", "b"`; weak comparison ignores the W/ prefix on both sides.
+fn matches_any(if_none_match: &HeaderValue, etag: &HeaderValue) -> bool {
+    let (Ok(tags), Ok(etag)) = (if_none_match.to_str(), etag.to_str()) else {
+        return false;
+    };
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    tags.split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+// Last-Modified at or before If-Modified-Since (both HTTP-dates, whole seconds).
+fn not_modified_since(headers: &HeaderMap, since: &HeaderValue) -> bool {
+    let date = |value: &HeaderValue| DateTime::parse_from_rfc2822(value.to_str().ok()?).ok();
+    match (headers.get(LAST_MODIFIED).and_then(date), date(since)) {
+        (Some(modified), Some(since)) => modified <= since,
+        _ => false,
+    }
+}
+
+fn weak_etag(headers: &HeaderMap) -> Option<HeaderValue> {
+    let len = headers.get(CONTENT_LENGTH)?.as_bytes();
+    let modified = headers.get(LAST_MODIFIED)?.as_bytes();
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(len);
+    hasher.update(modified);
+    let hash = hasher.finalize().to_hex();
+    HeaderValue::from_str(&format!("W/\"{}\"", &hash[..16])).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use reqwest::header::{HeaderName, HeaderValue as Value};
+
+    use super::*;
+
+    // Serves a temp dir holding app.js; returns its base URL.
+    async fn serve_assets() -> String {
+        let dir = std::env::temp_dir().join(format!("assets-{}", nanoid::nanoid!()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app.js"), "console.log('hi');").unwrap();
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = router(&dir, Duration::from_secs(60));
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{addr}/assets/app.js")
+    }
+
+    async fn get(url: &str, headers: &[(HeaderName, &str)]) -> reqwest::Response {
+        let mut req = reqwest::Client::new().get(url);
+        for (name, value) in headers {
+            req = req.header(name, Value::from_str(value).unwrap());
+        }
+        req.send().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn if_none_match_list_star_and_weak_comparison() {
+        let url = serve_assets().await;
+        let first = get(&url, &[]).await;
+        assert_eq!(first.status(), StatusCode::OK);
+        let etag = first.headers()[ETAG].to_str().unwrap().to_string();
+        assert!(etag.starts_with("W/\""), "{etag}");
+        let strong = etag.trim_start_matches("W/");
+
+        for header in [
+            etag.clone(),
+            format!("\"other\", {etag}"),
+            format!("W/\"other\",{strong}"),
+            "*".to_string(),
+        ] {
+            let res = get(&url, &[(IF_NONE_MATCH, &header)]).await;
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED, "{header}");
+            assert_eq!(res.headers()[ETAG], etag.as_str());
+            assert!(res.headers().contains_key(CACHE_CONTROL));
+        }
+        let res = get(&url, &[(IF_NONE_MATCH, "W/\"other\", \"more\"")]).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await.unwrap(), "console.log('hi');");
+    }
+
+    #[tokio::test]
+    async fn if_modified_since_304_carries_the_etag() {
+        let url = serve_assets().await;
+        let first = get(&url, &[]).await;
+        let etag = first.headers()[ETAG].to_str().unwrap().to_string();
+        let modified = first.headers()[LAST_MODIFIED].to_str().unwrap().to_string();
+
+        let res = get(&url, &[(IF_MODIFIED_SINCE, &modified)]).await;
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers()[ETAG], etag.as_str());
+        assert_eq!(res.headers()[LAST_MODIFIED], modified.as_str());
+
+        let res = get(
+            &url,
+            &[(IF_MODIFIED_SINCE, "Mon, 01 Jan 2001 00:00:00 GMT")],
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // If-None-Match wins over If-Modified-Since
+        let res = get(
+            &url,
+            &[(IF_NONE_MATCH, "\"other\""), (IF_MODIFIED_SINCE, &modified)],
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
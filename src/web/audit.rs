@@ -0,0 +1,53 @@
+// GET /audit?entity_id=1&actor=alice&action=update&limit=20 → audit entries, newest first.
+// Also home of the AuditContext extractor used by every mutating handler:
+//   actor      ← X-Actor header (there is no auth yet), "anonymous" if missing
+//   request_id ← X-Request-Id header (set by tower-http's SetRequestIdLayer when the client didn't send one)
+
+use std::convert::Infallible;
+
+use axum::{
+    extract::{FromRequestParts, Query, State},
+    http::request::Parts,
+    routing::get,
+    Router,
+};
+use tracing::instrument;
+
+use super::negotiate::{Negotiate, Negotiated};
+use crate::audit::{AuditContext, AuditEntry, AuditLog, AuditQuery};
+
+pub fn router(audit: AuditLog) -> Router {
+    Router::new()
+        .route("/audit", get(audit_handler))
+        .with_state(audit)
+}
+
+#[instrument(skip(audit))]
+async fn audit_handler(
+    State(audit): State<AuditLog>,
+    Query(query): Query<AuditQuery>,
+    Negotiate(format): Negotiate,
+) -> Negotiated<Vec<AuditEntry>> {
+    Negotiated(format, audit.query(&query))
+}
+
+impl<S> FromRequestParts<S> for AuditContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        Ok(AuditContext {
+            actor: header("x-actor").unwrap_or_else(|| "anonymous".to_string()),
+            request_id: header("x-request-id"),
+        })
+    }
+}
@@ -0,0 +1,92 @@
+// MyError as an HTTP response, so handlers can return Result<_, MyError> directly.
+// The body is an RFC 9457 problem details document (Content-Type: application/problem+json):
+//
+//   {"type":"about:blank","title":"Bad Request","status":400,
+//    "detail":"A parsing error occurred: invalid digit found in string",
+//    "code":"parse","trace_id":"4bf92f3577b34da6a3ce929d0e0e4736"}
+//
+// trace_id (the same value as the X-Trace-Id header, see web::trace_id) is only there when
+// OTel tracing is on; it's what support needs to find the failing request's trace.
+//
+// A 5xx is our fault and its message may name files, keys or internals (MyError::Io), so the client
+// only gets the generic "internal server error" detail; the real message is logged and goes on the
+// span, where the trace_id finds it.
+//
+// Besides the body, the response carries an ErrorInfo extension: web::trace's OnHttpResponse
+// picks it up and records error.type / exception.message on the request span and marks it as failed,
+// which makes failing traces filterable in the Collector UI (error.type = "parse", ...).
+
+use axum::{
+    http::{header::CONTENT_TYPE, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use tracing::error;
+
+use super::trace_id;
+use crate::{crypto::CryptoError, error::MyError};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+// What went wrong, for the tracing layer. The message is the full one, also for 5xx responses,
+// whose body only has a generic detail.
+#[derive(Debug, Clone)]
+pub struct ErrorInfo {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl MyError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            MyError::Parse(_) | MyError::Serialize(_) => StatusCode::BAD_REQUEST,
+            MyError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            // a sealed value that doesn't open is the client's; a missing or bad key is ours
+            MyError::Crypto(
+                CryptoError::Decrypt | CryptoError::Base64(_) | CryptoError::TooShort(_),
+            ) => StatusCode::BAD_REQUEST,
+            MyError::Io(_) | MyError::Custom(_) | MyError::Crypto(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+// A problem+json response; `code` is our machine-readable error kind (MyError::code()).
+// Called inside the request span, so the trace ID of the current request is picked up.
+pub fn problem(status: StatusCode, code: &str, detail: &str) -> Response {
+    let mut body = json!({
+        "type": "about:blank",
+        "title": status.canonical_reason().unwrap_or("Error"),
+        "status": status.as_u16(),
+        "detail": detail,
+        "code": code,
+    });
+    if let Some(trace_id) = trace_id::current() {
+        body["trace_id"] = trace_id.into();
+    }
+    let mut res = (status, Json(body)).into_response();
+    res.headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+    res
+}
+
+impl IntoResponse for MyError {
+    fn into_response(self) -> Response {
+        let info = ErrorInfo {
+            code: self.code(),
+            message: self.to_string(),
+        };
+        let status = self.status();
+        let detail = if status.is_server_error() {
+            error!(code = info.code, "request failed: {}", info.message);
+            "internal server error"
+        } else {
+            info.message.as_str()
+        };
+        let mut res = problem(status, info.code, detail);
+        res.extensions_mut().insert(info);
+        res
+    }
+}
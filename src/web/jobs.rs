@@ -0,0 +1,96 @@
+// Async job endpoints backed by the worker subsystem.
+// POST /jobs       → enqueue an expensive task, respond 202 Accepted with the job id
+//                    body {"input": "..."} (the hash task) or {"type": "hash", "payload": {...}} (any job type
+//                    in the store's JobRegistry; unknown type → 422; job queue full → 503 + Retry-After)
+// GET  /jobs/{id}  → poll status/result of that job; running jobs that report progress (worker::report_progress)
+//                    show it: {"id": 3, "status": "running", "progress": {"percent": 40.0}}
+
+// 202 Accepted (not 201 Created / 200 OK) tells the client: "I took your request, but the work isn't done yet."
+// The client then polls the URL from the response body until the status is completed or failed.
+
+use axum::{
+    extract::{Path, State},
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use super::{
+    error,
+    negotiate::{Negotiate, Negotiated},
+};
+use crate::worker::{JobEnvelope, JobId, JobStatus, JobStore, SubmitError};
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SubmitJob {
+    Typed(JobEnvelope),
+    Input { input: String },
+}
+
+#[derive(Debug, Serialize)]
+struct JobAccepted {
+    id: JobId,
+    status_url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct JobView {
+    id: JobId,
+    #[serde(flatten)]
+    status: JobStatus,
+}
+
+pub fn router(store: JobStore) -> Router {
+    Router::new()
+        .route("/jobs", post(submit_handler))
+        .route("/jobs/{id}", get(status_handler))
+        .with_state(store)
+}
+
+#[instrument(skip(store))]
+async fn submit_handler(
+    State(store): State<JobStore>,
+    Json(job): Json<SubmitJob>,
+) -> Result<impl IntoResponse, Response> {
+    let id = match job {
+        SubmitJob::Input { input } => store.submit(input),
+        SubmitJob::Typed(envelope) => store.submit_envelope(envelope),
+    }
+    .map_err(|e| match e {
+        SubmitError::Invalid(e) => error::problem(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "unknown_job_type",
+            &e.to_string(),
+        ),
+        // shed load instead of queueing without bound; the client backs off and retries
+        SubmitError::Busy | SubmitError::Closed => {
+            let mut res = error::problem(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "jobs_unavailable",
+                &e.to_string(),
+            );
+            res.headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from_static("1"));
+            res
+        }
+    })?;
+    let body = JobAccepted {
+        id,
+        status_url: format!("/jobs/{id}"),
+    };
+    Ok((StatusCode::ACCEPTED, Json(body)))
+}
+
+#[instrument(skip(store))]
+async fn status_handler(
+    State(store): State<JobStore>,
+    Path(id): Path<JobId>,
+    Negotiate(format): Negotiate,
+) -> Result<Negotiated<JobView>, StatusCode> {
+    let status = store.status(id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Negotiated(format, JobView { id, status }))
+}
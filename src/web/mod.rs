@@ -0,0 +1,18 @@
+// web: axum routers shared by the examples (and later the CLI).
+// Each submodule exposes a `router(...)` that already has its state attached (Router<()>),
+// so callers can simply `.merge()` it into their own app.
+
+pub mod admin;
+pub mod app;
+pub mod assets;
+pub mod audit;
+pub mod error;
+pub mod jobs;
+pub mod negotiate;
+pub mod search;
+pub mod server;
+pub mod tls;
+pub mod trace;
+pub mod trace_id;
+pub mod ui;
+pub mod users;
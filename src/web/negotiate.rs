@@ -0,0 +1,120 @@
+// Content negotiation glue between axum and the formats module.
+// Negotiate (extractor) decides the response Format:
+//   1. ?format=json|yaml|msgpack|cbor wins (handy in a browser or curl; cbor with the "cbor" feature)
+//   2. otherwise the Accept header (with q-values)
+//   3. no Accept header → JSON
+// Negotiated<T> (response) encodes T with that Format and sets Content-Type + Vary: Accept.
+// Decoded<T> (extractor) is the request side: the body decoded per its Content-Type, so
+//   curl -X POST /users -H 'Content-Type: application/yaml' --data-binary @user.yaml
+// works like the JSON request. No Content-Type → JSON; an unsupported one → 415; a body that
+// doesn't decode into T → 422 with the decoder's message.
+
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Query, Request},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE, VARY},
+        request::Parts,
+        StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::warn;
+
+use crate::formats::{self, Format};
+
+#[cfg(not(feature = "cbor"))]
+const SUPPORTED: &str = "supported: application/json, application/yaml, application/msgpack";
+#[cfg(feature = "cbor")]
+const SUPPORTED: &str =
+    "supported: application/json, application/yaml, application/msgpack, application/cbor";
+
+#[derive(Debug, Clone, Copy)]
+pub struct Negotiate(pub Format);
+
+#[derive(Debug, Deserialize)]
+struct FormatQuery {
+    format: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for Negotiate
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // Query::try_from_uri ignores unrelated query params (e.g. ?q=... on search endpoints)
+        if let Ok(Query(FormatQuery {
+            format: Some(format),
+        })) = Query::<FormatQuery>::try_from_uri(&parts.uri)
+        {
+            let format: Format = format
+                .parse()
+                .map_err(|e: formats::FormatError| (StatusCode::BAD_REQUEST, e.to_string()))?;
+            if !format.negotiable() {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("{format:?} is not offered over HTTP"),
+                ));
+            }
+            return Ok(Negotiate(format));
+        }
+
+        let Some(accept) = parts.headers.get(ACCEPT) else {
+            return Ok(Negotiate(Format::default()));
+        };
+        let accept = accept.to_str().unwrap_or_default();
+        formats::negotiate(accept)
+            .map(Negotiate)
+            .ok_or_else(|| (StatusCode::NOT_ACCEPTABLE, SUPPORTED.to_string()))
+    }
+}
+
+pub struct Negotiated<T>(pub Format, pub T);
+
+impl<T: Serialize> IntoResponse for Negotiated<T> {
+    fn into_response(self) -> Response {
+        let Negotiated(format, value) = self;
+        match format.encode(&value) {
+            Ok(body) => (
+                [(CONTENT_TYPE, format.content_type()), (VARY, "accept")],
+                body,
+            )
+                .into_response(),
+            Err(e) => {
+                warn!("failed to encode response as {format:?}: {e}");
+                StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            }
+        }
+    }
+}
+
+pub struct Decoded<T>(pub T);
+
+impl<T, S> FromRequest<S> for Decoded<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let format = match req.headers().get(CONTENT_TYPE) {
+            None => Format::default(),
+            Some(content_type) => content_type
+                .to_str()
+                .ok()
+                .and_then(Format::from_content_type)
+                .ok_or_else(|| (StatusCode::UNSUPPORTED_MEDIA_TYPE, SUPPORTED.to_string()))?,
+        };
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| (e.status(), e.body_text()))?;
+        let value = format
+            .decode(&body)
+            .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+        Ok(Decoded(value))
+    }
+}
@@ -0,0 +1,354 @@
+// GET /users/search?q=skill:Rust age>25 name~ali
+// A tiny query DSL so clients can filter on the server instead of downloading the whole list.
+
+// Grammar (terms are AND-ed, separated by whitespace; values may be "double quoted"):
+//   term  = field op value
+//   field = name | age | skill
+//   op    = ":"  equals (case-insensitive for text; for skill: "has this skill")
+//         | "~"  contains (text fields only)
+//         | ">" | ">=" | "<" | "<="  (age only)
+//
+// "skill:Rust age>25 name~ali" parses into the typed AST
+//   SearchQuery { filters: [
+//     Filter { field: Skill, op: Eq,       value: Text("Rust") },
+//     Filter { field: Age,   op: Gt,       value: Number(25) },
+//     Filter { field: Name,  op: Contains, value: Text("ali") },
+//   ] }
+
+use std::str::FromStr;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use thiserror::Error;
+use tracing::instrument;
+
+use super::{
+    negotiate::{Negotiate, Negotiated},
+    users::{User, UserStore},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    Age,
+    Skill,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Contains,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Text(String),
+    Number(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    pub field: Field,
+    pub op: Op,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub filters: Vec<Filter>,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    #[error("unterminated quote in query")]
+    UnterminatedQuote,
+    #[error("missing operator in term `{0}` (expected one of : ~ > >= < <=)")]
+    MissingOperator(String),
+    #[error("unknown field `{0}` (expected name, age or skill)")]
+    UnknownField(String),
+    #[error("empty value in term `{0}`")]
+    EmptyValue(String),
+    #[error("operator `{op}` is not supported on field `{field}`")]
+    UnsupportedOperator { field: String, op: String },
+    #[error("invalid number `{0}`")]
+    InvalidNumber(String),
+}
+
+impl FromStr for Field {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "name" => Ok(Field::Name),
+            "age" => Ok(Field::Age),
+            "skill" | "skills" => Ok(Field::Skill),
+            _ => Err(SearchError::UnknownField(s.to_string())),
+        }
+    }
+}
+
+impl FromStr for SearchQuery {
+    type Err = SearchError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let filters = tokenize(s)?
+            .iter()
+            .map(|term| parse_term(term))
+            .collect::<Result<_, _>>()?;
+        Ok(SearchQuery { filters })
+    }
+}
+
+impl SearchQuery {
+    pub fn matches(&self, user: &User) -> bool {
+        self.filters.iter().all(|f| f.matches(user))
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, user: &User) -> bool {
+        match (&self.field, &self.value) {
+            (Field::Age, Value::Number(n)) => match self.op {
+                Op::Eq => user.age == *n,
+                Op::Gt => user.age > *n,
+                Op::Ge => user.age >= *n,
+                Op::Lt => user.age < *n,
+                Op::Le => user.age <= *n,
+                Op::Contains => false,
+            },
+            (Field::Name, Value::Text(t)) => text_matches(&user.name, self.op, t),
+            (Field::Skill, Value::Text(t)) => {
+                user.skills.iter().any(|s| text_matches(s, self.op, t))
+            }
+            // parse_term never builds these combinations
+            _ => false,
+        }
+    }
+}
+
+fn text_matches(haystack: &str, op: Op, needle: &str) -> bool {
+    match op {
+        Op::Eq => haystack.eq_ignore_ascii_case(needle),
+        Op::Contains => haystack.to_lowercase().contains(&needle.to_lowercase()),
+        _ => false,
+    }
+}
+
+// Splits on whitespace, but keeps `name:"Ali Baba"` together (quotes are removed later).
+fn tokenize(s: &str) -> Result<Vec<String>, SearchError> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in s.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(SearchError::UnterminatedQuote);
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    Ok(terms)
+}
+
+fn parse_term(term: &str) -> Result<Filter, SearchError> {
+    let pos = term
+        .find([':', '~', '>', '<'])
+        .ok_or_else(|| SearchError::MissingOperator(term.to_string()))?;
+    let (field, rest) = term.split_at(pos);
+    // two-char operators first, so ">=" isn't read as ">" followed by "=..."
+    let (op, op_str, value) = if let Some(v) = rest.strip_prefix(">=") {
+        (Op::Ge, ">=", v)
+    } else if let Some(v) = rest.strip_prefix("<=") {
+        (Op::Le, "<=", v)
+    } else {
+        let op = match &rest[..1] {
+            ":" => Op::Eq,
+            "~" => Op::Contains,
+            ">" => Op::Gt,
+            _ => Op::Lt,
+        };
+        (op, &rest[..1], &rest[1..])
+    };
+
+    let field: Field = field.parse()?;
+    let value = value.trim_matches('"');
+    if value.is_empty() {
+        return Err(SearchError::EmptyValue(term.to_string()));
+    }
+
+    let value = match (field, op) {
+        (Field::Age, Op::Contains)
+        | (Field::Name | Field::Skill, Op::Gt | Op::Ge | Op::Lt | Op::Le) => {
+            return Err(SearchError::UnsupportedOperator {
+                field: format!("{field:?}").to_lowercase(),
+                op: op_str.to_string(),
+            })
+        }
+        (Field::Age, _) => Value::Number(
+            value
+                .parse()
+                .map_err(|_| SearchError::InvalidNumber(value.to_string()))?,
+        ),
+        _ => Value::Text(value.to_string()),
+    };
+    Ok(Filter { field, op, value })
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchParams {
+    #[serde(default)]
+    q: String,
+}
+
+pub fn router(store: UserStore) -> Router {
+    // "/users/search" is a static segment, so it wins over "/users/{id}" from web::users
+    Router::new()
+        .route("/users/search", get(search_handler))
+        .with_state(store)
+}
+
+#[instrument(skip(store))]
+async fn search_handler(
+    State(store): State<UserStore>,
+    Query(params): Query<SearchParams>,
+    Negotiate(format): Negotiate,
+) -> Result<Negotiated<Vec<User>>, (StatusCode, String)> {
+    let query: SearchQuery = params
+        .q
+        .parse()
+        .map_err(|e: SearchError| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let users = store
+        .list()
+        .into_iter()
+        .filter(|u| query.matches(u))
+        .collect();
+    Ok(Negotiated(format, users))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(field: Field, op: Op, value: Value) -> Filter {
+        Filter { field, op, value }
+    }
+
+    #[test]
+    fn two_char_operators_win_over_one_char() {
+        assert_eq!(
+            parse_term("age>=25").unwrap(),
+            filter(Field::Age, Op::Ge, Value::Number(25))
+        );
+        assert_eq!(
+            parse_term("age<=25").unwrap(),
+            filter(Field::Age, Op::Le, Value::Number(25))
+        );
+        assert_eq!(
+            parse_term("age>25").unwrap(),
+            filter(Field::Age, Op::Gt, Value::Number(25))
+        );
+        assert_eq!(
+            parse_term("age<25").unwrap(),
+            filter(Field::Age, Op::Lt, Value::Number(25))
+        );
+    }
+
+    #[test]
+    fn quoted_values_keep_their_spaces() {
+        assert_eq!(
+            tokenize(r#"name:"Ali Baba"  skill:Rust"#).unwrap(),
+            vec![r#"name:"Ali Baba""#, "skill:Rust"]
+        );
+        let query: SearchQuery = r#"name~"Ali Baba" age>25"#.parse().unwrap();
+        assert_eq!(
+            query.filters,
+            vec![
+                filter(Field::Name, Op::Contains, Value::Text("Ali Baba".into())),
+                filter(Field::Age, Op::Gt, Value::Number(25)),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_quote_is_an_error() {
+        assert_eq!(
+            tokenize(r#"name:"Ali Baba"#),
+            Err(SearchError::UnterminatedQuote)
+        );
+    }
+
+    #[test]
+    fn unknown_field_is_an_error() {
+        assert_eq!(
+            parse_term("email:a@b.c"),
+            Err(SearchError::UnknownField("email".into()))
+        );
+        assert_eq!(
+            parse_term("Rust"),
+            Err(SearchError::MissingOperator("Rust".into()))
+        );
+    }
+
+    #[test]
+    fn operator_must_fit_the_field_type() {
+        assert_eq!(
+            parse_term("name>x"),
+            Err(SearchError::UnsupportedOperator {
+                field: "name".into(),
+                op: ">".into()
+            })
+        );
+        assert_eq!(
+            parse_term("age~3"),
+            Err(SearchError::UnsupportedOperator {
+                field: "age".into(),
+                op: "~".into()
+            })
+        );
+    }
+
+    #[test]
+    fn empty_value_is_an_error() {
+        assert_eq!(
+            parse_term("name:"),
+            Err(SearchError::EmptyValue("name:".into()))
+        );
+        assert_eq!(
+            parse_term(r#"skill:"""#),
+            Err(SearchError::EmptyValue(r#"skill:"""#.into()))
+        );
+    }
+
+    #[test]
+    fn age_must_be_a_number() {
+        assert_eq!(
+            parse_term("age>old"),
+            Err(SearchError::InvalidNumber("old".into()))
+        );
+        assert_eq!(
+            parse_term("age:300"),
+            Err(SearchError::InvalidNumber("300".into()))
+        );
+    }
+}
@@ -0,0 +1,106 @@
+// ServerConfig: everything the axum examples used to hardcode ("0.0.0.0:8080", no timeouts, no limits).
+// Loaded with config::load from server.toml + SERVER_* env vars, e.g.
+//   SERVER_ADDR=127.0.0.1:9000 SERVER_CORS_ORIGINS='["http://localhost:3000"]' cargo run --example axum_serde
+//
+// server.toml:
+//   addr = "127.0.0.1:8080"             # loopback (the default); "0.0.0.0:8080" listens on every interface
+//   request_timeout_secs = 30
+//   body_limit = 2097152
+//   cors_origins = ["http://localhost:3000"]
+//   [tls]
+//   cert_path = "certs/cert.pem"
+//   key_path = "certs/key.pem"
+
+use std::{any::Any as PanicPayload, net::SocketAddr, time::Duration};
+
+use axum::{
+    extract::DefaultBodyLimit,
+    http::{HeaderValue, StatusCode},
+    response::Response,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use tower_http::{
+    catch_panic::CatchPanicLayer,
+    cors::{AllowOrigin, Any, CorsLayer},
+    timeout::TimeoutLayer,
+};
+use tracing::warn;
+
+use super::{
+    error,
+    tls::{self, TlsConfig},
+};
+use crate::config::{self, ConfigError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub addr: SocketAddr,
+    // whole request (including the handler) must finish within this, else 408
+    pub request_timeout_secs: u64,
+    // max request body size in bytes, else 413
+    pub body_limit: usize,
+    // empty: no cross-origin access; ["*"]: any origin
+    pub cors_origins: Vec<String>,
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            addr: SocketAddr::from(([127, 0, 0, 1], 8080)),
+            request_timeout_secs: 30,
+            body_limit: 2 * 1024 * 1024,
+            cors_origins: vec![],
+            tls: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn load(file: &str) -> Result<Self, ConfigError> {
+        config::load(file, "SERVER_")
+    }
+
+    fn cors_layer(&self) -> CorsLayer {
+        let origin = if self.cors_origins.iter().any(|o| o == "*") {
+            AllowOrigin::any()
+        } else {
+            let origins = self.cors_origins.iter().filter_map(|o| {
+                HeaderValue::from_str(o)
+                    .inspect_err(|e| warn!("ignoring invalid CORS origin {o:?}: {e}"))
+                    .ok()
+            });
+            AllowOrigin::list(origins)
+        };
+        CorsLayer::new()
+            .allow_origin(origin)
+            .allow_methods(Any)
+            .allow_headers(Any)
+    }
+}
+
+// Applies the config-driven layers and serves `app` (HTTPS when [tls] is configured).
+pub async fn serve(app: Router, config: &ServerConfig) -> anyhow::Result<()> {
+    let app = app
+        // a panicking handler answers 500 instead of dropping the connection;
+        // the panic itself is logged by telemetry::install_panic_hook
+        .layer(CatchPanicLayer::custom(panic_response))
+        .layer(DefaultBodyLimit::max(config.body_limit))
+        .layer(TimeoutLayer::with_status_code(
+            StatusCode::REQUEST_TIMEOUT,
+            Duration::from_secs(config.request_timeout_secs),
+        ))
+        .layer(config.cors_layer());
+    tls::serve(app, config.addr, config.tls.as_ref()).await
+}
+
+// The panic message stays in the logs, clients only get a generic problem+json error.
+fn panic_response(_err: Box<dyn PanicPayload + Send + 'static>) -> Response {
+    error::problem(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "panic",
+        "internal server error",
+    )
+}
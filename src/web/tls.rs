@@ -0,0 +1,105 @@
+// Serving axum directly over HTTPS (rustls) for deployments that don't sit behind the proxy.
+// Without TLS:  TcpListener + axum::serve   (plain HTTP, what the examples did so far)
+// With TLS:     axum_server::bind_rustls    (TLS handshake via rustls, then the same Router)
+// Optionally a second, plain-HTTP listener answers every request with 308 → https://host:port/path.
+// The host is public_host when configured; otherwise the request's Host header, which the client
+// controls, so set public_host wherever the redirect could point somewhere it shouldn't.
+
+use std::{net::SocketAddr, path::PathBuf};
+
+use axum::{
+    extract::Request,
+    http::{header::HOST, uri::Authority, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    // PEM files: certificate chain and private key
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    // e.g. 0.0.0.0:80 → redirect plain HTTP clients to the HTTPS listener
+    #[serde(default)]
+    pub redirect_http_from: Option<SocketAddr>,
+    // e.g. "example.com" → the redirect target's host, instead of the request's Host header
+    #[serde(default)]
+    pub public_host: Option<String>,
+}
+
+// Serves `app` on `addr`, over HTTPS when `tls` is Some, plain HTTP otherwise.
+pub async fn serve(app: Router, addr: SocketAddr, tls: Option<&TlsConfig>) -> anyhow::Result<()> {
+    let Some(tls) = tls else {
+        let listener = TcpListener::bind(addr).await?;
+        info!("Listening on http://{}", addr);
+        axum::serve(listener, app.into_make_service()).await?;
+        return Ok(());
+    };
+
+    let config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+    if let Some(http_addr) = tls.redirect_http_from {
+        tokio::spawn(redirect_http_to_https(
+            http_addr,
+            addr.port(),
+            tls.public_host.clone(),
+        ));
+    }
+    info!("Listening on https://{}", addr);
+    axum_server::bind_rustls(addr, config)
+        .serve(app.into_make_service())
+        .await?;
+    Ok(())
+}
+
+async fn redirect_http_to_https(
+    http_addr: SocketAddr,
+    https_port: u16,
+    public_host: Option<String>,
+) {
+    let app = Router::new().fallback(move |req: Request| {
+        let public_host = public_host.clone();
+        async move { redirect(req, https_port, public_host.as_deref()) }
+    });
+    let listener = match TcpListener::bind(http_addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("failed to bind HTTP redirect listener on {http_addr}: {e}");
+            return;
+        }
+    };
+    info!(
+        "Redirecting http://{} to HTTPS port {}",
+        http_addr, https_port
+    );
+    if let Err(e) = axum::serve(listener, app).await {
+        warn!("HTTP redirect listener stopped: {e}");
+    }
+}
+
+// 308 (not 301): keeps the method and body, so a POST stays a POST after the redirect.
+fn redirect(req: Request, https_port: u16, public_host: Option<&str>) -> Response {
+    let request_host = req
+        .headers()
+        .get(HOST)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.parse::<Authority>().ok());
+    // host() drops the HTTP port from "example.com:8080" and keeps the brackets of "[::1]:8080"
+    let host = match (public_host, &request_host) {
+        (Some(host), _) => host,
+        (None, Some(authority)) => authority.host(),
+        (None, None) => {
+            return (StatusCode::BAD_REQUEST, "missing or invalid Host header").into_response()
+        }
+    };
+    // add the HTTPS port unless it's the default
+    let authority = match https_port {
+        443 => host.to_string(),
+        port => format!("{host}:{port}"),
+    };
+    let path = req.uri().path_and_query().map_or("/", |pq| pq.as_str());
+    Redirect::permanent(&format!("https://{authority}{path}")).into_response()
+}
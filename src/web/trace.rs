@@ -0,0 +1,141 @@
+// One span per HTTP request, shared by every axum app (tower-http TraceLayer).
+// Field names follow the OTel HTTP semantic conventions, so the spans exported by
+// tracing-opentelemetry look like any other HTTP server's in the backend:
+//
+//   http.request.method  GET            http.route               /users/{id}
+//   url.path             /users/1       http.response.status_code 200
+//   user_agent.original  curl/8.5.0     latency_ms               12
+//   request_id           6f1c...        (X-Request-Id, see SetRequestIdLayer)
+//
+// Failed requests are marked on the span: otel.status_code = ERROR for every 5xx, and when the handler
+// returned a MyError (web::error), error.type = its code and exception.message = its message, for 4xx too.
+//
+// Requests carrying a W3C traceparent header (or B3 / uber-trace-id, see OTEL_PROPAGATORS)
+// join the caller's trace (extract_remote_context),
+// so handlers no longer need #[instrument(fields(http.uri = ...))] or their own extraction.
+
+use std::time::Duration;
+
+use axum::{
+    extract::MatchedPath,
+    http::{header::USER_AGENT, HeaderMap, Request, Response},
+};
+use opentelemetry::{global, propagation::Extractor, trace::TraceContextExt};
+use tower_http::{
+    classify::{ServerErrorsAsFailures, SharedClassifier},
+    trace::{DefaultOnRequest, MakeSpan, OnResponse, TraceLayer},
+};
+use tracing::{error, field::Empty, info, info_span, warn, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use super::error::ErrorInfo;
+
+pub type HttpTraceLayer = TraceLayer<
+    SharedClassifier<ServerErrorsAsFailures>,
+    MakeHttpSpan,
+    DefaultOnRequest,
+    OnHttpResponse,
+>;
+
+// Add with Router::layer (not around the whole service): only then has routing already
+// put MatchedPath into the request extensions, which gives http.route its template form.
+pub fn layer() -> HttpTraceLayer {
+    TraceLayer::new_for_http()
+        .make_span_with(MakeHttpSpan)
+        .on_response(OnHttpResponse)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MakeHttpSpan;
+
+impl<B> MakeSpan<B> for MakeHttpSpan {
+    fn make_span(&mut self, req: &Request<B>) -> Span {
+        let method = req.method().as_str();
+        // the template ("/users/{id}"), not the path: keeps span names low-cardinality
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or(req.uri().path(), |p| p.as_str());
+        let user_agent = req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+
+        let span = info_span!(
+            "http.request",
+            otel.name = format!("{method} {route}"),
+            otel.kind = "server",
+            otel.status_code = Empty,
+            http.request.method = method,
+            http.route = route,
+            url.path = req.uri().path(),
+            url.query = req.uri().query(),
+            user_agent.original = user_agent,
+            request_id = req
+                .headers()
+                .get("x-request-id")
+                .and_then(|v| v.to_str().ok()),
+            http.response.status_code = Empty,
+            latency_ms = Empty,
+            error.type = Empty,
+            exception.message = Empty,
+            otel.status_message = Empty,
+        );
+        if let Some(ctx) = extract_remote_context(req.headers()) {
+            span.set_parent(ctx);
+        }
+        span
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OnHttpResponse;
+
+impl<B> OnResponse<B> for OnHttpResponse {
+    fn on_response(self, res: &Response<B>, latency: Duration, span: &Span) {
+        let status = res.status().as_u16();
+        let latency_ms = latency.as_millis() as u64;
+        span.record("http.response.status_code", status);
+        span.record("latency_ms", latency_ms);
+        // 4xx is the client's fault; only 5xx marks the server span as failed
+        if res.status().is_server_error() {
+            span.record("otel.status_code", "ERROR");
+        }
+        match res.extensions().get::<ErrorInfo>() {
+            Some(err) => {
+                span.record("error.type", err.code);
+                span.record("exception.message", err.message.as_str());
+                span.record("otel.status_message", err.message.as_str());
+                if res.status().is_server_error() {
+                    error!(status, latency_ms, error.type = err.code, "{}", err.message);
+                } else {
+                    warn!(status, latency_ms, error.type = err.code, "{}", err.message);
+                }
+            }
+            None => info!(status, latency_ms, "finished processing request"),
+        }
+    }
+}
+
+// ---- trace context extraction (traceparent / tracestate, B3, Jaeger: whatever OTEL_PROPAGATORS enables) ----
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+// Uses the global propagator (installed by telemetry::otel::init_tracer_provider) to read the
+// parent context from the headers. Some(ctx) only if a valid remote SpanContext exists.
+pub fn extract_remote_context(headers: &HeaderMap) -> Option<opentelemetry::Context> {
+    if headers.is_empty() {
+        return None;
+    }
+    let ctx = global::get_text_map_propagator(|prop| prop.extract(&HeaderExtractor(headers)));
+    ctx.span().span_context().is_valid().then_some(ctx)
+}
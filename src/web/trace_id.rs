@@ -0,0 +1,37 @@
+// X-Trace-Id: the OTel trace ID of the request, on every response.
+// A user reporting "it failed" can copy it from the browser's network tab (or from the error body,
+// see web::error), and support pastes it into the Jaeger/Tempo search box: straight to the trace.
+//
+//   HTTP/1.1 400 Bad Request
+//   x-trace-id: 4bf92f3577b34da6a3ce929d0e0e4736
+//
+// Add it with Router::layer *before* web::trace::layer() (i.e. inside it): the middleware reads
+// the request span that the trace layer created. Without an OTel layer installed there is no trace ID
+// and no header.
+
+use axum::{extract::Request, http::HeaderValue, middleware::Next, response::Response};
+use opentelemetry::trace::TraceContextExt;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+pub const X_TRACE_ID: &str = "x-trace-id";
+
+// 32 lowercase hex chars, the same format as in traceparent and the tracing backends.
+pub fn current() -> Option<String> {
+    let ctx = Span::current().context();
+    let span = ctx.span();
+    let span_ctx = span.span_context();
+    span_ctx.is_valid().then(|| span_ctx.trace_id().to_string())
+}
+
+//   .layer(middleware::from_fn(web::trace_id::middleware))
+//   .layer(web::trace::layer())
+pub async fn middleware(req: Request, next: Next) -> Response {
+    // read before the handler runs: the span is the request span here for sure
+    let trace_id = current();
+    let mut res = next.run(req).await;
+    if let Some(value) = trace_id.and_then(|id| HeaderValue::from_str(&id).ok()) {
+        res.headers_mut().insert(X_TRACE_ID, value);
+    }
+    res
+}
@@ -0,0 +1,62 @@
+// Server-side HTML views rendered with askama.
+// GET /ui/users       → user list page
+// GET /ui/users/{id}  → user detail page
+
+// Same UserStore as the JSON API in web::users: the JSON handlers serialize a User,
+// these handlers put the very same User into a template.
+// askama compiles templates/*.html into Rust code at build time, so a typo like {{ user.nmae }}
+// is a compile error instead of a blank spot on the page.
+
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Html,
+    routing::get,
+    Router,
+};
+use tracing::{instrument, warn};
+
+use super::users::{User, UserId, UserStore};
+
+#[derive(Template)]
+#[template(path = "users.html")]
+struct UsersPage {
+    users: Vec<User>,
+}
+
+#[derive(Template)]
+#[template(path = "user.html")]
+struct UserPage {
+    user: User,
+}
+
+pub fn router(store: UserStore) -> Router {
+    Router::new()
+        .route("/ui/users", get(users_page))
+        .route("/ui/users/{id}", get(user_page))
+        .with_state(store)
+}
+
+#[instrument(skip(store))]
+async fn users_page(State(store): State<UserStore>) -> Result<Html<String>, StatusCode> {
+    render(UsersPage {
+        users: store.list(),
+    })
+}
+
+#[instrument(skip(store))]
+async fn user_page(
+    State(store): State<UserStore>,
+    Path(id): Path<UserId>,
+) -> Result<Html<String>, StatusCode> {
+    let user = store.get(id).ok_or(StatusCode::NOT_FOUND)?;
+    render(UserPage { user })
+}
+
+fn render(page: impl Template) -> Result<Html<String>, StatusCode> {
+    page.render().map(Html).map_err(|e| {
+        warn!("failed to render template: {e}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
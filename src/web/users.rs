@@ -0,0 +1,645 @@
+// User API backed by an in-memory store.
+// GET    /users        → list users
+// POST   /users        → create a user, 201 Created
+// GET    /users/{id}   → one user
+// PATCH  /users/{id}   → partial update (same UserUpdate shape as axum_serde.rs)
+// DELETE /users/{id}   → soft delete (sets deleted_at), 204 No Content
+// POST   /users/{id}/restore → undo a soft delete
+// POST   /users:batchUpdate  → apply many (id, patch) pairs atomically: all succeed or none is applied
+
+// Soft delete: a deleted user stays in the store with deleted_at = Some(ts) and is hidden from
+// list/get/update. That keeps undo (restore) and auditing possible; the purge task
+// (UserStore::spawn_purge_task) hard-deletes records once they are older than the retention period.
+
+// Timestamps (deleted_at, the purge cutoff) come from the store's Clock (crate::clock), the system
+// clock unless UserStore::with_clock says otherwise: tests can soft-delete, advance a FixedClock past
+// the retention period and purge, without sleeping.
+
+// Every mutation is recorded in the AuditLog (actor + request id come from the AuditContext extractor).
+
+// The store is shared with the HTML views (web::ui), so both render from the same state.
+// Read endpoints negotiate the response format (JSON / YAML / MsgPack), see web::negotiate; request
+// bodies may be any of them too (Content-Type).
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{info, instrument};
+
+use super::negotiate::{Decoded, Negotiate, Negotiated};
+use crate::{
+    audit::{AuditAction, AuditContext, AuditLog},
+    clock::{SharedClock, SystemClock},
+};
+
+pub type UserId = u64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    pub id: UserId,
+    pub name: String,
+    pub age: u8,
+    pub skills: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl User {
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewUser {
+    pub name: String,
+    pub age: u8,
+    #[serde(default)]
+    pub skills: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserUpdate {
+    pub age: Option<u8>,
+    pub skills: Option<Vec<String>>,
+}
+
+impl UserUpdate {
+    fn apply(self, user: &mut User) {
+        if let Some(age) = self.age {
+            user.age = age;
+        }
+        if let Some(skills) = self.skills {
+            user.skills = skills;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchUpdateItem {
+    pub id: UserId,
+    pub patch: UserUpdate,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchUpdateRequest {
+    pub items: Vec<BatchUpdateItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchItemResult {
+    Updated { id: UserId, user: User },
+    NotFound { id: UserId },
+    // this item was fine, but another item failed so the whole batch was rolled back
+    RolledBack { id: UserId },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchUpdateResponse {
+    pub applied: bool,
+    pub results: Vec<BatchItemResult>,
+}
+
+// Everything a store holds, soft-deleted users included, for saving it to disk and loading it back
+// (web::app's file storage). next_id is kept so ids of purged users aren't handed out again.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserSnapshot {
+    pub next_id: UserId,
+    pub users: Vec<User>,
+}
+
+// BTreeMap (not HashMap/DashMap) so listings come back ordered by id.
+// RwLock: reads (list/get, HTML pages) vastly outnumber writes.
+#[derive(Debug, Clone)]
+pub struct UserStore {
+    inner: Arc<RwLock<Inner>>,
+    audit: AuditLog,
+    clock: SharedClock,
+}
+
+impl Default for UserStore {
+    fn default() -> Self {
+        Self {
+            inner: Arc::default(),
+            audit: AuditLog::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    users: BTreeMap<UserId, User>,
+    next_id: UserId,
+}
+
+impl UserStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Without this, mutations are still audited but only kept in memory.
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    pub fn create(&self, ctx: &AuditContext, new: NewUser) -> User {
+        let mut inner = self.inner.write().unwrap();
+        inner.next_id += 1;
+        let user = User {
+            id: inner.next_id,
+            name: new.name,
+            age: new.age,
+            skills: new.skills,
+            deleted_at: None,
+        };
+        inner.users.insert(user.id, user.clone());
+        self.audit
+            .record(ctx, AuditAction::Create, "user", user.id, None, Some(&user));
+        user
+    }
+
+    // Active (not soft-deleted) users only.
+    pub fn list(&self) -> Vec<User> {
+        self.inner
+            .read()
+            .unwrap()
+            .users
+            .values()
+            .filter(|u| !u.is_deleted())
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, id: UserId) -> Option<User> {
+        let inner = self.inner.read().unwrap();
+        inner.users.get(&id).filter(|u| !u.is_deleted()).cloned()
+    }
+
+    // The audit entry is recorded while the write lock is held, so `before` is exactly
+    // the state this update replaced (no other writer can sneak in between).
+    pub fn update(&self, ctx: &AuditContext, id: UserId, update: UserUpdate) -> Option<User> {
+        let mut inner = self.inner.write().unwrap();
+        let user = inner.users.get_mut(&id).filter(|u| !u.is_deleted())?;
+        let before = user.clone();
+        update.apply(user);
+        self.audit.record(
+            ctx,
+            AuditAction::Update,
+            "user",
+            id,
+            Some(&before),
+            Some(&*user),
+        );
+        Some(user.clone())
+    }
+
+    // "Transaction" over the in-memory store: one write lock for the whole batch.
+    // Phase 1 checks every id while holding the lock; only if all exist does phase 2 apply the patches,
+    // so readers never observe a half-applied batch and a failure leaves the store untouched.
+    pub fn batch_update(
+        &self,
+        ctx: &AuditContext,
+        items: Vec<BatchUpdateItem>,
+    ) -> BatchUpdateResponse {
+        let mut inner = self.inner.write().unwrap();
+        let exists = |id: &UserId| inner.users.get(id).is_some_and(|u| !u.is_deleted());
+
+        if !items.iter().all(|item| exists(&item.id)) {
+            let results = items
+                .iter()
+                .map(|item| match exists(&item.id) {
+                    true => BatchItemResult::RolledBack { id: item.id },
+                    false => BatchItemResult::NotFound { id: item.id },
+                })
+                .collect();
+            return BatchUpdateResponse {
+                applied: false,
+                results,
+            };
+        }
+
+        let results = items
+            .into_iter()
+            .map(|BatchUpdateItem { id, patch }| {
+                // checked above, and we still hold the write lock
+                let user = inner.users.get_mut(&id).expect("user checked in phase 1");
+                let before = user.clone();
+                patch.apply(user);
+                self.audit.record(
+                    ctx,
+                    AuditAction::Update,
+                    "user",
+                    id,
+                    Some(&before),
+                    Some(&*user),
+                );
+                BatchItemResult::Updated {
+                    id,
+                    user: user.clone(),
+                }
+            })
+            .collect();
+        BatchUpdateResponse {
+            applied: true,
+            results,
+        }
+    }
+
+    // Soft delete; returns None if the user doesn't exist or is already deleted.
+    pub fn delete(&self, ctx: &AuditContext, id: UserId) -> Option<User> {
+        let mut inner = self.inner.write().unwrap();
+        let user = inner.users.get_mut(&id).filter(|u| !u.is_deleted())?;
+        let before = user.clone();
+        user.deleted_at = Some(self.clock.now());
+        self.audit.record(
+            ctx,
+            AuditAction::Delete,
+            "user",
+            id,
+            Some(&before),
+            Some(&*user),
+        );
+        Some(user.clone())
+    }
+
+    // Clears deleted_at; restoring an active user is a no-op that still returns it.
+    pub fn restore(&self, ctx: &AuditContext, id: UserId) -> Option<User> {
+        let mut inner = self.inner.write().unwrap();
+        let user = inner.users.get_mut(&id)?;
+        // restoring an active user changes nothing: no audit entry
+        if user.deleted_at.is_none() {
+            return Some(user.clone());
+        }
+        let before = user.clone();
+        user.deleted_at = None;
+        self.audit.record(
+            ctx,
+            AuditAction::Restore,
+            "user",
+            id,
+            Some(&before),
+            Some(&*user),
+        );
+        Some(user.clone())
+    }
+
+    pub fn snapshot(&self) -> UserSnapshot {
+        let inner = self.inner.read().unwrap();
+        UserSnapshot {
+            next_id: inner.next_id,
+            users: inner.users.values().cloned().collect(),
+        }
+    }
+
+    // Replaces the store's contents, at startup before serving; not audited (nothing changed,
+    // the store is picking up where it left off).
+    pub fn load_snapshot(&self, snapshot: UserSnapshot) {
+        let mut inner = self.inner.write().unwrap();
+        let highest = snapshot.users.iter().map(|u| u.id).max().unwrap_or(0);
+        inner.next_id = snapshot.next_id.max(highest);
+        inner.users = snapshot.users.into_iter().map(|u| (u.id, u)).collect();
+    }
+
+    // Hard-deletes users that were soft-deleted before `cutoff`; returns how many were removed.
+    pub fn purge_deleted_before(&self, cutoff: DateTime<Utc>) -> usize {
+        let mut inner = self.inner.write().unwrap();
+        let expired: Vec<UserId> = inner
+            .users
+            .values()
+            .filter(|u| u.deleted_at.is_some_and(|ts| ts < cutoff))
+            .map(|u| u.id)
+            .collect();
+        for id in &expired {
+            if let Some(user) = inner.users.remove(id) {
+                self.audit.record(
+                    &AuditContext::system(),
+                    AuditAction::Purge,
+                    "user",
+                    *id,
+                    Some(&user),
+                    None,
+                );
+            }
+        }
+        expired.len()
+    }
+
+    // Purge policy: every `every`, hard-delete users soft-deleted more than `retention` ago.
+    pub fn spawn_purge_task(&self, retention: Duration, every: Duration) -> JoinHandle<()> {
+        let store = self.clone();
+        let retention = chrono::Duration::from_std(retention).unwrap_or(chrono::Duration::MAX);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            loop {
+                ticker.tick().await;
+                let cutoff = store
+                    .clock
+                    .now()
+                    .checked_sub_signed(retention)
+                    .unwrap_or(DateTime::<Utc>::MIN_UTC);
+                let purged = store.purge_deleted_before(cutoff);
+                if purged > 0 {
+                    info!(purged, "purged soft-deleted users");
+                }
+            }
+        })
+    }
+}
+
+pub fn router(store: UserStore) -> Router {
+    Router::new()
+        .route("/users", get(list_handler).post(create_handler))
+        .route(
+            "/users/{id}",
+            get(get_handler)
+                .patch(update_handler)
+                .delete(delete_handler),
+        )
+        .route("/users/{id}/restore", post(restore_handler))
+        // a literal path (Google API style custom method), not a {param}
+        .route("/users:batchUpdate", post(batch_update_handler))
+        .with_state(store)
+}
+
+#[instrument(skip(store))]
+async fn list_handler(
+    State(store): State<UserStore>,
+    Negotiate(format): Negotiate,
+) -> Negotiated<Vec<User>> {
+    Negotiated(format, store.list())
+}
+
+#[instrument(skip(store))]
+async fn create_handler(
+    State(store): State<UserStore>,
+    Negotiate(format): Negotiate,
+    ctx: AuditContext,
+    Decoded(new): Decoded<NewUser>,
+) -> impl IntoResponse {
+    (
+        StatusCode::CREATED,
+        Negotiated(format, store.create(&ctx, new)),
+    )
+}
+
+#[instrument(skip(store))]
+async fn get_handler(
+    State(store): State<UserStore>,
+    Path(id): Path<UserId>,
+    Negotiate(format): Negotiate,
+) -> Result<Negotiated<User>, StatusCode> {
+    let user = store.get(id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Negotiated(format, user))
+}
+
+#[instrument(skip(store))]
+async fn update_handler(
+    State(store): State<UserStore>,
+    Path(id): Path<UserId>,
+    Negotiate(format): Negotiate,
+    ctx: AuditContext,
+    Decoded(update): Decoded<UserUpdate>,
+) -> Result<Negotiated<User>, StatusCode> {
+    let user = store
+        .update(&ctx, id, update)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Negotiated(format, user))
+}
+
+#[instrument(skip(store))]
+async fn delete_handler(
+    State(store): State<UserStore>,
+    Path(id): Path<UserId>,
+    ctx: AuditContext,
+) -> StatusCode {
+    match store.delete(&ctx, id) {
+        Some(_) => StatusCode::NO_CONTENT,
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+#[instrument(skip(store))]
+async fn restore_handler(
+    State(store): State<UserStore>,
+    Path(id): Path<UserId>,
+    Negotiate(format): Negotiate,
+    ctx: AuditContext,
+) -> Result<Negotiated<User>, StatusCode> {
+    let user = store.restore(&ctx, id).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Negotiated(format, user))
+}
+
+// 200 when the batch was applied, 422 with per-item results when it was rolled back.
+#[instrument(skip(store, req))]
+async fn batch_update_handler(
+    State(store): State<UserStore>,
+    Negotiate(format): Negotiate,
+    ctx: AuditContext,
+    Decoded(req): Decoded<BatchUpdateRequest>,
+) -> impl IntoResponse {
+    let res = store.batch_update(&ctx, req.items);
+    let status = match res.applied {
+        true => StatusCode::OK,
+        false => StatusCode::UNPROCESSABLE_ENTITY,
+    };
+    (status, Negotiated(format, res))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::response::IntoResponse;
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{
+        clock::{Clock, FixedClock},
+        formats::Format,
+    };
+
+    fn ctx() -> AuditContext {
+        AuditContext {
+            actor: "alice".to_string(),
+            request_id: Some("req-1".to_string()),
+        }
+    }
+
+    fn store_with(names: &[&str]) -> UserStore {
+        let store = UserStore::new();
+        for name in names {
+            store.create(
+                &ctx(),
+                NewUser {
+                    name: name.to_string(),
+                    age: 30,
+                    skills: vec![],
+                },
+            );
+        }
+        store
+    }
+
+    fn set_age(id: UserId, age: u8) -> BatchUpdateItem {
+        BatchUpdateItem {
+            id,
+            patch: UserUpdate {
+                age: Some(age),
+                skills: None,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_with_a_missing_or_deleted_user_changes_nothing() {
+        let store = store_with(&["ann", "bob", "cid"]);
+        store.delete(&ctx(), 3);
+        let before = store.snapshot();
+        let audited = store.audit().entries().len();
+
+        let items = vec![
+            set_age(1, 40),
+            set_age(2, 41),
+            set_age(3, 42),
+            set_age(99, 43),
+        ];
+        let res = store.batch_update(&ctx(), items.clone());
+        assert!(!res.applied);
+        assert!(matches!(
+            res.results.as_slice(),
+            [
+                BatchItemResult::RolledBack { id: 1 },
+                BatchItemResult::RolledBack { id: 2 },
+                BatchItemResult::NotFound { id: 3 },
+                BatchItemResult::NotFound { id: 99 },
+            ]
+        ));
+        assert_eq!(store.snapshot(), before);
+        assert_eq!(store.audit().entries().len(), audited);
+
+        let res = batch_update_handler(
+            State(store.clone()),
+            Negotiate(Format::Json),
+            ctx(),
+            Decoded(BatchUpdateRequest { items }),
+        )
+        .await
+        .into_response();
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(store.snapshot(), before);
+    }
+
+    #[test]
+    fn applied_batch_writes_one_audit_entry_per_item() {
+        let store = store_with(&["ann", "bob"]);
+        let audited = store.audit().entries().len();
+
+        let res = store.batch_update(&ctx(), vec![set_age(1, 40), set_age(2, 41)]);
+        assert!(res.applied);
+        assert_eq!(store.get(1).unwrap().age, 40);
+        assert_eq!(store.get(2).unwrap().age, 41);
+
+        let entries = store.audit().entries();
+        let batch = &entries[audited..];
+        assert_eq!(batch.len(), 2);
+        for (entry, id) in batch.iter().zip([1, 2]) {
+            assert_eq!(entry.action, AuditAction::Update);
+            assert_eq!(entry.entity_id, id);
+            assert_eq!(entry.actor, "alice");
+            assert_eq!(entry.diff["age"]["to"], 39 + id);
+        }
+    }
+
+    fn clocked(names: &[&str]) -> (UserStore, FixedClock) {
+        let clock = FixedClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+        let store = store_with(names).with_clock(Arc::new(clock.clone()));
+        (store, clock)
+    }
+
+    #[test]
+    fn deleted_user_is_hidden_until_restored() {
+        let (store, clock) = clocked(&["ann", "bob"]);
+        let deleted = store.delete(&ctx(), 1).unwrap();
+        assert_eq!(deleted.deleted_at, Some(clock.now()));
+
+        assert_eq!(store.list().iter().map(|u| u.id).collect::<Vec<_>>(), [2]);
+        assert_eq!(store.get(1), None);
+        assert_eq!(store.update(&ctx(), 1, set_age(1, 40).patch), None);
+        assert_eq!(store.delete(&ctx(), 1), None);
+
+        let restored = store.restore(&ctx(), 1).unwrap();
+        assert_eq!(restored.deleted_at, None);
+        assert_eq!(store.get(1), Some(restored));
+        assert_eq!(store.list().len(), 2);
+        assert_eq!(
+            store.update(&ctx(), 1, set_age(1, 40).patch).unwrap().age,
+            40
+        );
+        assert_eq!(
+            store.audit().entries().last().map(|e| e.action),
+            Some(AuditAction::Update)
+        );
+    }
+
+    #[test]
+    fn restoring_an_active_user_is_a_no_op() {
+        let (store, _) = clocked(&["ann"]);
+        let before = store.snapshot();
+        let audited = store.audit().entries().len();
+
+        assert_eq!(store.restore(&ctx(), 1), store.get(1));
+        assert_eq!(store.snapshot(), before);
+        assert_eq!(store.audit().entries().len(), audited);
+        assert_eq!(store.restore(&ctx(), 99), None);
+    }
+
+    #[test]
+    fn purge_removes_only_users_deleted_before_the_cutoff() {
+        let (store, clock) = clocked(&["ann", "bob", "cid"]);
+        let retention = chrono::Duration::days(30);
+        store.delete(&ctx(), 1);
+        clock.advance(chrono::Duration::days(10));
+        store.delete(&ctx(), 2);
+
+        // 29 days after the first delete: nothing is old enough yet
+        clock.advance(chrono::Duration::days(19));
+        assert_eq!(store.purge_deleted_before(clock.now() - retention), 0);
+        assert_eq!(store.snapshot().users.len(), 3);
+
+        clock.advance(chrono::Duration::days(2));
+        assert_eq!(store.purge_deleted_before(clock.now() - retention), 1);
+        let left: Vec<_> = store.snapshot().users.iter().map(|u| u.id).collect();
+        assert_eq!(left, [2, 3]);
+        let purge = store.audit().entries().pop().unwrap();
+        assert_eq!((purge.action, purge.entity_id), (AuditAction::Purge, 1));
+        assert_eq!(purge.actor, "system");
+        assert_eq!(store.restore(&ctx(), 1), None);
+
+        clock.advance(chrono::Duration::days(10));
+        assert_eq!(store.purge_deleted_before(clock.now() - retention), 1);
+        assert_eq!(store.list().iter().map(|u| u.id).collect::<Vec<_>>(), [3]);
+    }
+}
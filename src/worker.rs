@@ -0,0 +1,442 @@
+// worker.rs: background job subsystem
+// The web layer hands expensive, blocking work (the blake3 hashing from tokio2.rs) to this module
+// and gets back a job id immediately. Callers poll the id later to see the status/result.
+
+// Key flow:
+// JobStore::submit(input)
+//   ├→ allocates a job id, queues the job on the store's WorkerPool without waiting
+//   │     (queue full → SubmitError::Busy, the web layer answers 503 instead of piling up tasks)
+//   ├→ JobStatus::Queued until a worker thread picks it up, then Running
+//   │     (the job's worker::report_progress calls show up as Running { progress }, worker/progress.rs)
+//   │     (run time → task.duration{task.name="expensive_blocking_task"}, see telemetry::task_metrics)
+//   ├→ records Completed { result } or Failed { error } when the job returns
+//   └→ finished jobs are kept for a while to be polled, then dropped: after `retention` ttl, or oldest
+//      first once more than `max` have finished (JobStore::retention)
+// JobStore::submit_envelope({type, payload}) does the same for any job type in the store's JobRegistry
+// (worker/registry.rs), with the job's output as JSON.
+//
+// WorkerPool (worker/pool.rs): a bounded set of dedicated threads for streams of blocking jobs (tokio2.rs).
+// Batcher (worker/batch.rs): groups items into one call per batch (bulk inserts).
+// worker::durable (--features sqlite-queue): a SQLite-backed queue feeding the pool, survives restarts.
+
+mod batch;
+mod builder;
+mod dead_letter;
+mod delay;
+#[cfg(feature = "sqlite-queue")]
+pub mod durable;
+mod idempotency;
+mod job;
+mod metrics;
+mod pool;
+mod progress;
+mod rate_limit;
+mod registry;
+mod results;
+mod retry;
+mod timeout;
+
+pub use batch::{BatchError, BatchTicket, Batcher};
+pub use builder::WorkerPoolBuilder;
+pub use dead_letter::{Attempt, DeadLetter, DeadLetterQueue};
+pub use job::{AsyncJob, Job};
+pub use pool::{JobHandle, ShutdownSummary, WorkerError, WorkerPool};
+pub use progress::{report_progress, JobProgress};
+pub use rate_limit::RateLimiter;
+pub use registry::{JobEnvelope, JobRegistry};
+pub use results::{JobEvent, JobEvents, JobResult, JobResults};
+pub use retry::RetryPolicy;
+pub use timeout::{job_cancellation, job_cancelled};
+
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::{error::MyError, telemetry::task_metrics};
+
+pub type JobId = u64;
+
+// tag = "status" keeps the JSON flat for pollers:
+// {"status":"queued"} / {"status":"running","progress":{"percent":40.0}} / {"status":"completed","result":"eb5c..."}
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        progress: Option<JobProgress>,
+    },
+    Completed {
+        result: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+// How long finished jobs stay pollable, and how many at most.
+const DEFAULT_RETENTION: Duration = Duration::from_secs(600);
+const DEFAULT_MAX_FINISHED: usize = 10_000;
+
+#[derive(Error, Debug)]
+pub enum SubmitError {
+    // unknown job type, or no registry configured
+    #[error(transparent)]
+    Invalid(MyError),
+    // every queue slot is taken; try again later
+    #[error("job queue is full")]
+    Busy,
+    #[error("job store is shutting down")]
+    Closed,
+}
+
+#[derive(Debug)]
+enum Entry {
+    // queued or running: `started` is set by the job itself when a worker picks it up
+    Pending {
+        started: Arc<AtomicBool>,
+        progress: watch::Receiver<Option<JobProgress>>,
+    },
+    Finished(JobStatus),
+}
+
+// Cheap to clone: every field is Arc, so every handler shares the same job table and pool.
+#[derive(Clone)]
+pub struct JobStore {
+    jobs: Arc<DashMap<JobId, Entry>>,
+    // finished ids, oldest first, for pruning
+    finished: Arc<Mutex<VecDeque<(JobId, Instant)>>>,
+    next_id: Arc<AtomicU64>,
+    pool: Arc<WorkerPool>,
+    registry: Option<Arc<JobRegistry>>,
+    retention: Duration,
+    max_finished: usize,
+}
+
+impl fmt::Debug for JobStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobStore")
+            .field("jobs", &self.jobs.len())
+            .field("workers", &self.pool.workers())
+            .field("registry", &self.registry)
+            .field("retention", &self.retention)
+            .field("max_finished", &self.max_finished)
+            .finish()
+    }
+}
+
+impl Default for JobStore {
+    // A pool of CPU-count "jobs-N" threads; JOBS_WORKERS / JOBS_QUEUE_CAPACITY override it.
+    fn default() -> Self {
+        let pool = WorkerPool::builder()
+            .thread_name("jobs")
+            .from_env("JOBS")
+            .build(|input| Ok(expensive_blocking_task(input)))
+            .named("jobs");
+        Self::with_pool(pool)
+    }
+}
+
+impl JobStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Runs the store's jobs on `pool` (its typed-job path: try_submit_job).
+    pub fn with_pool(pool: WorkerPool) -> Self {
+        Self {
+            jobs: Arc::default(),
+            finished: Arc::default(),
+            next_id: Arc::default(),
+            pool: Arc::new(pool),
+            registry: None,
+            retention: DEFAULT_RETENTION,
+            max_finished: DEFAULT_MAX_FINISHED,
+        }
+    }
+
+    // Job types submit_envelope accepts.
+    pub fn with_registry(mut self, registry: Arc<JobRegistry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    // Finished jobs are dropped `ttl` after they finished, or oldest first beyond `max` (at least 1).
+    pub fn retention(mut self, ttl: Duration, max: usize) -> Self {
+        self.retention = ttl;
+        self.max_finished = max.max(1);
+        self
+    }
+
+    // Returns as soon as the job is queued; the hashing itself runs on the store's pool threads
+    // so it never stalls the async worker threads that serve HTTP requests.
+    pub fn submit(&self, input: String) -> Result<JobId, SubmitError> {
+        self.spawn("expensive_blocking_task", move || {
+            Ok(expensive_blocking_task(input))
+        })
+    }
+
+    // A registered job type; fails right away (nothing recorded) if the type isn't registered.
+    // The result is the job's output as JSON.
+    pub fn submit_envelope(&self, envelope: JobEnvelope) -> Result<JobId, SubmitError> {
+        let registry = self.registry.clone().ok_or_else(|| {
+            SubmitError::Invalid(MyError::Custom("no job registry configured".to_string()))
+        })?;
+        let name = registry.name(&envelope.kind).ok_or_else(|| {
+            SubmitError::Invalid(MyError::Custom(format!(
+                "unknown job type {:?}",
+                envelope.kind
+            )))
+        })?;
+        self.spawn(name, move || {
+            Ok(serde_json::to_string(&registry.execute(envelope)?)?)
+        })
+    }
+
+    // `task`: the task.duration metric label
+    fn spawn<F>(&self, task: &'static str, run: F) -> Result<JobId, SubmitError>
+    where
+        F: FnOnce() -> Result<String, MyError> + Send + 'static,
+    {
+        self.prune();
+        let started = Arc::new(AtomicBool::new(false));
+        let job = StoreJob {
+            task,
+            started: started.clone(),
+            run: Box::new(run),
+        };
+        let handle = self.pool.try_submit_job(job).map_err(|e| match e {
+            WorkerError::QueueFull => SubmitError::Busy,
+            _ => SubmitError::Closed,
+        })?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.jobs.insert(
+            id,
+            Entry::Pending {
+                started,
+                progress: handle.watch_progress(),
+            },
+        );
+
+        let store = self.clone();
+        tokio::spawn(async move {
+            // a panic only fails this job: the worker catches it and carries on
+            let status = match handle.await {
+                Ok(result) => {
+                    info!(job.id = id, "job completed");
+                    JobStatus::Completed { result }
+                }
+                Err(WorkerError::Panicked(message)) => {
+                    warn!(job.id = id, "job panicked: {message}");
+                    JobStatus::Failed {
+                        error: format!("panicked: {message}"),
+                    }
+                }
+                Err(WorkerError::Failed(e)) => {
+                    warn!(job.id = id, "job failed: {e}");
+                    JobStatus::Failed {
+                        error: e.to_string(),
+                    }
+                }
+                Err(e) => {
+                    warn!(job.id = id, "job failed: {e}");
+                    JobStatus::Failed {
+                        error: e.to_string(),
+                    }
+                }
+            };
+            store.finish(id, status);
+        });
+
+        Ok(id)
+    }
+
+    fn finish(&self, id: JobId, status: JobStatus) {
+        self.jobs.insert(id, Entry::Finished(status));
+        self.finished
+            .lock()
+            .expect("finished jobs lock poisoned")
+            .push_back((id, Instant::now()));
+        self.prune();
+    }
+
+    // Drops finished jobs past the retention ttl, and the oldest beyond max_finished.
+    fn prune(&self) {
+        let mut finished = self.finished.lock().expect("finished jobs lock poisoned");
+        while let Some(&(id, at)) = finished.front() {
+            if finished.len() <= self.max_finished && at.elapsed() < self.retention {
+                break;
+            }
+            finished.pop_front();
+            self.jobs.remove(&id);
+        }
+    }
+
+    pub fn status(&self, id: JobId) -> Option<JobStatus> {
+        self.prune();
+        let entry = self.jobs.get(&id)?;
+        Some(match &*entry {
+            Entry::Pending { started, .. } if !started.load(Ordering::Relaxed) => JobStatus::Queued,
+            Entry::Pending { progress, .. } => JobStatus::Running {
+                progress: progress.borrow().clone(),
+            },
+            Entry::Finished(status) => status.clone(),
+        })
+    }
+}
+
+// A store job on the pool: flags itself started, records its run time (queueing for a worker
+// isn't the job's latency).
+struct StoreJob {
+    task: &'static str,
+    started: Arc<AtomicBool>,
+    run: Box<dyn FnOnce() -> Result<String, MyError> + Send>,
+}
+
+impl Job for StoreJob {
+    type Output = String;
+    type Error = MyError;
+
+    fn run(self) -> Result<String, MyError> {
+        self.started.store(true, Ordering::Relaxed);
+        let start = Instant::now();
+        let result = (self.run)();
+        task_metrics::record(self.task, start.elapsed());
+        result
+    }
+}
+
+// expensive_blocking_task as a typed, registrable job: {"type": "hash", "payload": {"input": "..."}}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashJob {
+    pub input: String,
+}
+
+impl Job for HashJob {
+    type Output = String;
+    type Error = Infallible;
+
+    fn run(self) -> Result<String, Infallible> {
+        Ok(expensive_blocking_task(self.input))
+    }
+}
+
+// Same work as tokio1.rs / tokio2.rs: 800ms of "CPU time" followed by a blake3 hash (crate::hash: multi-threaded
+// for large inputs).
+pub fn expensive_blocking_task(s: String) -> String {
+    thread::sleep(Duration::from_millis(800));
+    crate::hash::hash(s.as_bytes()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Echo {
+        text: String,
+    }
+
+    impl Job for Echo {
+        type Output = String;
+        type Error = Infallible;
+
+        fn run(self) -> Result<String, Infallible> {
+            Ok(self.text)
+        }
+    }
+
+    fn echo_store(retention: Duration, max: usize) -> JobStore {
+        JobStore::with_pool(WorkerPool::new(1, 8, Ok))
+            .with_registry(Arc::new(JobRegistry::new().register::<Echo>("echo")))
+            .retention(retention, max)
+    }
+
+    fn echo(text: &str) -> JobEnvelope {
+        JobEnvelope::new("echo", &Echo { text: text.into() }).unwrap()
+    }
+
+    async fn finished(store: &JobStore, id: JobId) -> JobStatus {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match store.status(id) {
+                    Some(JobStatus::Queued | JobStatus::Running { .. }) => {}
+                    Some(status) => return status,
+                    None => panic!("job {id} is gone"),
+                }
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("job didn't finish within 5s")
+    }
+
+    #[tokio::test]
+    async fn envelope_result_is_the_jobs_output_as_json() {
+        let store = echo_store(DEFAULT_RETENTION, DEFAULT_MAX_FINISHED);
+        let id = store.submit_envelope(echo("hi")).unwrap();
+        assert_eq!(
+            finished(&store, id).await,
+            JobStatus::Completed {
+                result: "\"hi\"".into()
+            }
+        );
+        assert!(matches!(
+            store.submit_envelope(JobEnvelope::new("nope", &()).unwrap()),
+            Err(SubmitError::Invalid(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn submit_is_refused_while_the_queue_is_full() {
+        let store = JobStore::with_pool(WorkerPool::new(1, 1, Ok));
+        let running = store.submit("a".into()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while store.status(running) == Some(JobStatus::Queued) {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let queued = store.submit("b".into()).unwrap();
+        assert_eq!(store.status(queued), Some(JobStatus::Queued));
+        assert!(matches!(store.submit("c".into()), Err(SubmitError::Busy)));
+    }
+
+    #[tokio::test]
+    async fn finished_jobs_beyond_the_cap_are_dropped_oldest_first() {
+        let store = echo_store(DEFAULT_RETENTION, 2);
+        let mut ids = Vec::new();
+        for text in ["a", "b", "c"] {
+            let id = store.submit_envelope(echo(text)).unwrap();
+            finished(&store, id).await;
+            ids.push(id);
+        }
+        assert_eq!(store.status(ids[0]), None);
+        assert!(store.status(ids[1]).is_some());
+        assert!(store.status(ids[2]).is_some());
+    }
+
+    #[tokio::test]
+    async fn finished_jobs_are_dropped_after_the_ttl() {
+        let store = echo_store(Duration::from_millis(20), DEFAULT_MAX_FINISHED);
+        let id = store.submit_envelope(echo("a")).unwrap();
+        finished(&store, id).await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert_eq!(store.status(id), None);
+    }
+}
@@ -0,0 +1,213 @@
+// Batcher: collects items and hands them to one closure call per batch, for work where the fixed cost
+// per call dominates (one INSERT with 500 rows vs 500 INSERTs, one HTTP request per 100 events).
+//
+// Key flow:
+// batcher.add(item).await          → queued (bounded: waits when `max_items * 4` are already waiting)
+//   └→ batch task collects until max_items are there, or max_wait passed since the batch's first item
+//        └→ process(Vec<item>).await, one batch at a time
+//             └→ every item's BatchTicket resolves with the batch's outcome (Ok, or the shared error)
+// batcher.close().await            → no more items; the last partial batch is processed before it returns
+//
+//   let batcher = Batcher::new(500, Duration::from_millis(50), move |rows: Vec<Url>| {
+//       let db = db.clone();
+//       async move { insert_all(&db, rows).await }
+//   });
+//   let ticket = batcher.add(url).await?;
+//   ticket.await?;   // optional: wait until its batch is written
+//
+// max_wait bounds the latency a lone item sees under low traffic; max_items bounds the batch size
+// under high traffic.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use thiserror::Error;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::Instant,
+};
+use tracing::{info_span, warn, Instrument};
+
+use crate::error::MyError;
+
+#[derive(Error, Debug, Clone)]
+pub enum BatchError {
+    #[error("batcher is closed")]
+    Closed,
+    // shared by every item of the failed batch
+    #[error("batch failed: {0}")]
+    Failed(Arc<MyError>),
+}
+
+type Item<T> = (T, oneshot::Sender<Result<(), BatchError>>);
+
+pub struct Batcher<T> {
+    items: mpsc::Sender<Item<T>>,
+    task: JoinHandle<()>,
+}
+
+// Resolves when the item's batch has been processed; dropping it doesn't cancel anything.
+#[derive(Debug)]
+pub struct BatchTicket {
+    rx: oneshot::Receiver<Result<(), BatchError>>,
+}
+
+impl Future for BatchTicket {
+    type Output = Result<(), BatchError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.rx)
+            .poll(cx)
+            .map(|reply| reply.unwrap_or(Err(BatchError::Closed)))
+    }
+}
+
+impl<T: Send + 'static> Batcher<T> {
+    // Must be called inside a runtime (spawns the batch task).
+    pub fn new<F, Fut>(max_items: usize, max_wait: Duration, process: F) -> Self
+    where
+        F: Fn(Vec<T>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), MyError>> + Send + 'static,
+    {
+        let max_items = max_items.max(1);
+        let (items, rx) = mpsc::channel(max_items * 4);
+        let task = tokio::spawn(run(rx, max_items, max_wait, process));
+        Self { items, task }
+    }
+
+    pub async fn add(&self, item: T) -> Result<BatchTicket, BatchError> {
+        let (reply, rx) = oneshot::channel();
+        self.items
+            .send((item, reply))
+            .await
+            .map_err(|_| BatchError::Closed)?;
+        Ok(BatchTicket { rx })
+    }
+
+    // Flushes what's collected so far and stops the batch task.
+    pub async fn close(self) {
+        drop(self.items);
+        if let Err(e) = self.task.await {
+            warn!("batch task failed: {e}");
+        }
+    }
+}
+
+async fn run<T, F, Fut>(
+    mut rx: mpsc::Receiver<Item<T>>,
+    max_items: usize,
+    max_wait: Duration,
+    process: F,
+) where
+    F: Fn(Vec<T>) -> Fut,
+    Fut: Future<Output = Result<(), MyError>>,
+{
+    // the first item starts the batch (and its max_wait clock)
+    while let Some(first) = rx.recv().await {
+        let deadline = Instant::now() + max_wait;
+        let mut batch = vec![first];
+        while batch.len() < max_items {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(item)) => batch.push(item),
+                // closed (flush what we have) or max_wait passed
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        let size = batch.len();
+        let (items, replies): (Vec<T>, Vec<_>) = batch.into_iter().unzip();
+        let result = process(items)
+            .instrument(info_span!("batch", batch.size = size))
+            .await
+            .map_err(|e| BatchError::Failed(Arc::new(e)));
+        if let Err(e) = &result {
+            warn!(batch.size = size, "{e}");
+        }
+        for reply in replies {
+            let _ = reply.send(result.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    type Batches = Arc<Mutex<Vec<Vec<u32>>>>;
+
+    // A batcher that records every batch it processes.
+    fn recording(max_items: usize, max_wait: Duration) -> (Batcher<u32>, Batches) {
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let batcher = Batcher::new(max_items, max_wait, {
+            let batches = batches.clone();
+            move |items: Vec<u32>| {
+                let batches = batches.clone();
+                async move {
+                    batches.lock().unwrap().push(items);
+                    Ok(())
+                }
+            }
+        });
+        (batcher, batches)
+    }
+
+    #[tokio::test]
+    async fn full_batches_are_processed_without_waiting() {
+        let (batcher, batches) = recording(3, Duration::from_secs(60));
+        let mut tickets = Vec::new();
+        for i in 0..6 {
+            tickets.push(batcher.add(i).await.unwrap());
+        }
+        for ticket in tickets {
+            ticket.await.unwrap();
+        }
+        assert_eq!(*batches.lock().unwrap(), [vec![0, 1, 2], vec![3, 4, 5]]);
+        batcher.close().await;
+    }
+
+    #[tokio::test]
+    async fn a_partial_batch_goes_out_after_max_wait() {
+        let (batcher, batches) = recording(100, Duration::from_millis(20));
+        let ticket = batcher.add(7).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), ticket)
+            .await
+            .expect("batch not flushed after max_wait")
+            .unwrap();
+        assert_eq!(*batches.lock().unwrap(), [vec![7]]);
+        batcher.close().await;
+    }
+
+    #[tokio::test]
+    async fn close_flushes_what_is_collected() {
+        let (batcher, batches) = recording(100, Duration::from_secs(60));
+        let a = batcher.add(1).await.unwrap();
+        let b = batcher.add(2).await.unwrap();
+        batcher.close().await;
+        a.await.unwrap();
+        b.await.unwrap();
+        assert_eq!(*batches.lock().unwrap(), [vec![1, 2]]);
+    }
+
+    #[tokio::test]
+    async fn every_item_gets_the_batch_error() {
+        let batcher = Batcher::new(2, Duration::from_secs(60), |_: Vec<u32>| async {
+            Err(MyError::Custom("insert failed".to_string()))
+        });
+        let a = batcher.add(1).await.unwrap();
+        let b = batcher.add(2).await.unwrap();
+        for ticket in [a, b] {
+            assert!(
+                matches!(ticket.await, Err(BatchError::Failed(e)) if e.to_string().contains("insert failed"))
+            );
+        }
+        batcher.close().await;
+    }
+}
@@ -0,0 +1,121 @@
+// WorkerPoolBuilder: the pool's thread budget, tunable per deployment instead of hard-coded.
+//
+//   let pool = WorkerPool::builder()
+//       .workers(8)                       // OS threads = max blocking jobs at once (default: CPU count)
+//       .queue_capacity(256)              // jobs waiting for a worker before submit() applies backpressure
+//       .thread_name("hash")              // threads "hash-0", "hash-1", ... (in top -H, gdb, panic messages)
+//       .stack_size(4 * 1024 * 1024)      // deep recursion in the job (default: Rust's 2 MiB)
+//       .retry(RetryPolicy::default())
+//       .from_env("HASH_POOL")            // HASH_POOL_WORKERS / _QUEUE_CAPACITY / _STACK_SIZE override the above
+//       .build(|s| Ok(expensive_blocking_task(s)));
+//
+// Tokio's own blocking pool (spawn_blocking, used by tokio::fs) is separate:
+// up to 512 threads by default. configure_runtime() gives it the same budget, so a runtime
+// running both doesn't end up with workers + 512 CPU-heavy threads competing for the cores:
+//
+//   let mut runtime = tokio::runtime::Builder::new_multi_thread();
+//   builder.configure_runtime(&mut runtime);
+//   let runtime = runtime.enable_all().build()?;
+
+use std::{env, str::FromStr, thread};
+
+use tracing::warn;
+
+use super::{retry::RetryPolicy, WorkerPool};
+use crate::error::MyError;
+
+#[derive(Debug, Clone)]
+pub struct WorkerPoolBuilder {
+    pub(super) workers: usize,
+    pub(super) queue_capacity: usize,
+    pub(super) thread_name: String,
+    pub(super) stack_size: Option<usize>,
+    pub(super) retry: RetryPolicy,
+}
+
+impl Default for WorkerPoolBuilder {
+    fn default() -> Self {
+        let workers = thread::available_parallelism().map_or(4, |n| n.get());
+        Self {
+            workers,
+            queue_capacity: workers * 8,
+            thread_name: "worker".to_string(),
+            stack_size: None,
+            retry: RetryPolicy::none(),
+        }
+    }
+}
+
+impl WorkerPoolBuilder {
+    // at least 1
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    // at least 1
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity.max(1);
+        self
+    }
+
+    // Threads are named "{prefix}-{i}".
+    pub fn thread_name(mut self, prefix: impl Into<String>) -> Self {
+        self.thread_name = prefix.into();
+        self
+    }
+
+    // Bytes per worker thread.
+    pub fn stack_size(mut self, bytes: usize) -> Self {
+        self.stack_size = Some(bytes);
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    // {PREFIX}_WORKERS, {PREFIX}_QUEUE_CAPACITY, {PREFIX}_STACK_SIZE, where set; invalid values
+    // are logged and ignored (the pool still starts with the configured value).
+    pub fn from_env(mut self, prefix: &str) -> Self {
+        if let Some(workers) = env_var(prefix, "WORKERS") {
+            self = self.workers(workers);
+        }
+        if let Some(capacity) = env_var(prefix, "QUEUE_CAPACITY") {
+            self = self.queue_capacity(capacity);
+        }
+        if let Some(bytes) = env_var(prefix, "STACK_SIZE") {
+            self = self.stack_size(bytes);
+        }
+        self
+    }
+
+    // Caps Tokio's blocking pool at this pool's worker count, with the same stack size.
+    // Note: thread_stack_size also applies to the runtime's async worker threads.
+    pub fn configure_runtime(&self, runtime: &mut tokio::runtime::Builder) {
+        runtime.max_blocking_threads(self.workers);
+        if let Some(bytes) = self.stack_size {
+            runtime.thread_stack_size(bytes);
+        }
+    }
+
+    pub fn build<F>(self, task: F) -> WorkerPool
+    where
+        F: Fn(String) -> Result<String, MyError> + Send + Sync + 'static,
+    {
+        WorkerPool::spawn(self, task)
+    }
+}
+
+fn env_var<T: FromStr>(prefix: &str, name: &str) -> Option<T> {
+    let key = format!("{prefix}_{name}");
+    let value = env::var(&key).ok()?;
+    match value.trim().parse() {
+        Ok(parsed) => Some(parsed),
+        Err(_) => {
+            warn!(%key, %value, "ignoring invalid worker pool setting");
+            None
+        }
+    }
+}
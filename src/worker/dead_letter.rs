@@ -0,0 +1,116 @@
+// Dead-letter queue: where a WorkerPool job ends up when it has permanently failed
+// (non-retryable error, or out of attempts), instead of only resolving its handle with the error.
+// Nobody may be awaiting that handle (fire-and-forget submits, scheduled runs), so without this
+// the failure would be gone after one log line.
+//
+// Key flow:
+// worker: job fails for good
+//   └→ DeadLetter { job_id, input, attempts: [each attempt's time + error], dead_at } → pool.dead_letters()
+// operator / admin endpoint:
+//   pool.dead_letters().list()          → what failed, oldest first
+//   pool.dead_letters().get(id)         → one entry, with its attempt history
+//   pool.requeue(id).await              → taken out of the DLQ and submitted again (new job id)
+//   pool.dead_letters().remove(id)      → given up on
+//
+// In memory and bounded (DEFAULT_CAPACITY): when full, the oldest entry is dropped with a warning.
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::warn;
+
+use super::JobId;
+
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Attempt {
+    pub at: DateTime<Utc>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadLetter {
+    pub job_id: JobId,
+    pub input: String,
+    // oldest first; the last one is the error that made it final
+    pub attempts: Vec<Attempt>,
+    pub dead_at: DateTime<Utc>,
+}
+
+impl DeadLetter {
+    pub fn last_error(&self) -> Option<&str> {
+        self.attempts.last().map(|a| a.error.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct DeadLetterQueue {
+    // job ids only grow, so BTreeMap order = order of submission
+    entries: Mutex<BTreeMap<JobId, DeadLetter>>,
+    capacity: usize,
+}
+
+impl Default for DeadLetterQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl DeadLetterQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(BTreeMap::new()),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub(super) fn push(&self, letter: DeadLetter) {
+        let mut entries = self.entries.lock().expect("dead letter lock poisoned");
+        if entries.len() >= self.capacity {
+            if let Some((dropped, _)) = entries.pop_first() {
+                warn!(
+                    job.id = dropped,
+                    "dead letter queue full, dropping oldest entry"
+                );
+            }
+        }
+        entries.insert(letter.job_id, letter);
+    }
+
+    pub fn list(&self) -> Vec<DeadLetter> {
+        self.entries
+            .lock()
+            .expect("dead letter lock poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, id: JobId) -> Option<DeadLetter> {
+        self.entries
+            .lock()
+            .expect("dead letter lock poisoned")
+            .get(&id)
+            .cloned()
+    }
+
+    pub fn remove(&self, id: JobId) -> Option<DeadLetter> {
+        self.entries
+            .lock()
+            .expect("dead letter lock poisoned")
+            .remove(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("dead letter lock poisoned")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
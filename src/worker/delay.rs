@@ -0,0 +1,65 @@
+// Delayed jobs for WorkerPool::submit_after / submit_at: one timer task per pool holding a
+// tokio-util DelayQueue (a hashed timer wheel), instead of one sleep-then-submit task per job.
+//
+// Key flow:
+// submit_after(delay, input)
+//   ├→ job id + JobHandle right away
+//   ├→ (deadline, job) → timer task (unbounded channel: delayed jobs don't take queue slots while waiting)
+//   └→ at the deadline: timer task sends the job into the normal queue (waits there if it's full)
+// Shutdown and drain cancel the timer task; jobs still waiting are dropped and their handles resolve to Aborted.
+// Started lazily by the first delayed submit, since WorkerPool::new isn't necessarily in a runtime.
+
+use std::future::poll_fn;
+
+use tokio::{sync::mpsc, time::Instant};
+use tokio_util::{sync::CancellationToken, time::DelayQueue};
+use tracing::debug;
+
+use super::{metrics, pool::QueuedJob};
+
+pub(super) type DelaySender = mpsc::UnboundedSender<(Instant, QueuedJob)>;
+
+enum Event {
+    Insert(Instant, QueuedJob),
+    Expired(QueuedJob),
+    Closed,
+}
+
+pub(super) fn spawn(jobs: mpsc::Sender<QueuedJob>, cancel: CancellationToken) -> DelaySender {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut queue: DelayQueue<QueuedJob> = DelayQueue::new();
+        let mut open = true;
+        loop {
+            // the branches only produce an Event; the queue is touched after select! ends
+            let event = tokio::select! {
+                _ = cancel.cancelled() => break,
+                msg = rx.recv(), if open => match msg {
+                    Some((at, job)) => Event::Insert(at, job),
+                    None => Event::Closed,
+                },
+                Some(expired) = poll_fn(|cx| queue.poll_expired(cx)), if !queue.is_empty() => {
+                    Event::Expired(expired.into_inner())
+                }
+                // pool dropped and nothing left waiting
+                else => break,
+            };
+            match event {
+                Event::Insert(at, job) => {
+                    queue.insert_at(job, at);
+                }
+                Event::Expired(mut job) => {
+                    debug!(job.id = job.id, "delayed job due");
+                    let job_type = job.job_type;
+                    job.mark_queued();
+                    if jobs.send(job).await.is_err() {
+                        metrics::queued(job_type, -1);
+                        break; // queue closed: the remaining handles resolve to Aborted
+                    }
+                }
+                Event::Closed => open = false,
+            }
+        }
+    });
+    tx
+}
@@ -0,0 +1,569 @@
+// Durable job queue in SQLite (--features sqlite-queue): enqueued jobs survive a restart.
+// The in-memory WorkerPool queue is lost with the process; this one is a table:
+//
+//   jobs(id, kind, payload, payload_format, attempts, lease_until, last_error, created_at, dead_at)
+//
+// Key flow (at-least-once delivery):
+// enqueue(kind, &payload)      → INSERT, payload serialized in the queue's payload format (JSON default)
+// lease()                      → the oldest live job that isn't leased (or whose lease expired) gets
+//                                lease_until = now + visibility_timeout, attempts + 1, atomically
+// ack(&lease)                  → DELETE, done
+// nack(&lease, error)          → lease released, last_error recorded, leased again later;
+//                                after max_attempts (default 5) dead instead: dead_at = now
+// (crash / hang)               → nobody acks; after visibility_timeout the job is leased again,
+//                                or, out of attempts, dead (lease() marks it before leasing)
+//
+// Dead jobs are the queue's dead letters: kept with their last error, never leased, until an
+// operator looks at them (list(Some(JobState::Dead))) and requeues them (attempts reset) or deletes them
+// (delete_dead).
+// `ecosystem jobs list / requeue` (src/cli/jobs.rs) does that from the command line.
+//
+// A job whose lease expired can be running twice (slow worker + new lease), so jobs should be idempotent.
+// ack/nack of an expired, re-leased job fail with QueueError::LeaseLost instead of touching the new lease.
+//
+//   let queue = Arc::new(SqliteQueue::open("sqlite://jobs.db?mode=rwc").await?);
+//   queue.enqueue("hash", &HashJob { input: "task 1".into() }).await?;
+//   tokio::spawn(queue.clone().feed(pool.clone(), pool.cancellation_token()));
+//
+// Payload format: JSON (TEXT, readable with the sqlite3 shell) unless with_payload_format says
+// otherwise; MessagePack (BLOB) is about half the size for typical job structs and faster to parse.
+// Each row records its format, so a queue can switch formats with old jobs still in it, and a
+// reader decodes whatever a row holds (Lease::payload, QueuedJob::payload_json).
+//
+//   let queue = SqliteQueue::open(url).await?.with_payload_format(Format::MsgPack);
+//
+// Format::Bincode ("bincode" feature) is smaller and faster still, but the bytes are only the field
+// values in order: the reader needs the same Rust type, and feed() can't turn them into a JobEnvelope
+// (such jobs fail and end up dead). For workers that lease() and decode Lease::payload::<T> themselves,
+// built from the same code as the producers; after changing T, drain the queue before deploying.
+//
+// feed() hands each job to the pool as a JobEnvelope {"type": kind, "payload": payload}, so a pool running
+// a JobRegistry (WorkerPool::new(.., registry.into_task())) executes any registered job type.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::{sqlite::SqlitePool, FromRow};
+use strum::{Display, EnumString, VariantNames};
+use thiserror::Error;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use tracing::{info_span, warn, Instrument};
+
+use super::{JobEnvelope, WorkerPool};
+use crate::formats::{Format, FormatError};
+
+#[derive(Error, Debug)]
+pub enum QueueError {
+    #[error("job queue database error: {0}")]
+    Db(#[from] sqlx::Error),
+    #[error("job payload (de)serialization failed: {0}")]
+    Payload(#[from] serde_json::Error),
+    #[error("job payload (de)serialization failed: {0}")]
+    Format(#[from] FormatError),
+    #[error("lease on job {0} expired and the job was leased again")]
+    LeaseLost(i64),
+}
+
+pub struct SqliteQueue {
+    db: SqlitePool,
+    visibility_timeout: Duration,
+    max_attempts: u32,
+    payload_format: Format,
+}
+
+// A leased job: run it, then ack (or nack) before the lease expires.
+#[derive(Debug, Clone, FromRow)]
+pub struct Lease {
+    pub id: i64,
+    pub kind: String,
+    payload: Vec<u8>,
+    payload_format: String,
+    pub attempts: i64,
+    // identifies this lease: ack/nack only match while it's still ours
+    lease_until: i64,
+}
+
+impl Lease {
+    pub fn payload<T: DeserializeOwned>(&self) -> Result<T, QueueError> {
+        decode_payload(&self.payload_format, &self.payload)
+    }
+
+    // As stored, in payload_format().
+    pub fn raw_payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn payload_format(&self) -> &str {
+        &self.payload_format
+    }
+}
+
+fn decode_payload<T: DeserializeOwned>(format: &str, bytes: &[u8]) -> Result<T, QueueError> {
+    let format: Format = format.parse()?;
+    Ok(format.decode(bytes)?)
+}
+
+// Without the payload's type: only self-describing formats.
+fn payload_value(format: &str, bytes: &[u8]) -> Result<serde_json::Value, QueueError> {
+    let format: Format = format.parse()?;
+    if !format.self_describing() {
+        return Err(FormatError::NotSelfDescribing(format.name()).into());
+    }
+    Ok(format.decode(bytes)?)
+}
+
+// Where a job is: waiting to be leased (first time or after a nack), leased by a worker right now,
+// or dead (out of attempts).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, Display, VariantNames, Serialize)]
+#[strum(ascii_case_insensitive)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    #[strum(to_string = "pending", serialize = "queued")]
+    Pending,
+    #[strum(to_string = "leased", serialize = "running")]
+    Leased,
+    #[strum(to_string = "dead", serialize = "failed")]
+    Dead,
+}
+
+// A row of the queue, for inspection (list); workers get Leases.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct QueuedJob {
+    pub id: i64,
+    pub kind: String,
+    #[serde(skip)]
+    payload: Vec<u8>,
+    pub payload_format: String,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    // Unix ms
+    pub created_at: i64,
+    lease_until: Option<i64>,
+    dead_at: Option<i64>,
+}
+
+impl QueuedJob {
+    pub fn state(&self) -> JobState {
+        match (self.dead_at, self.lease_until) {
+            (Some(_), _) => JobState::Dead,
+            (None, Some(until)) if until >= Utc::now().timestamp_millis() => JobState::Leased,
+            _ => JobState::Pending,
+        }
+    }
+
+    // The payload whatever its format, as JSON for display; an error for bincode payloads.
+    pub fn payload_json(&self) -> Result<serde_json::Value, QueueError> {
+        payload_value(&self.payload_format, &self.payload)
+    }
+
+    pub fn created(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.created_at).unwrap_or_default()
+    }
+
+    pub fn dead_since(&self) -> Option<DateTime<Utc>> {
+        self.dead_at.and_then(DateTime::from_timestamp_millis)
+    }
+}
+
+impl SqliteQueue {
+    // "sqlite://jobs.db?mode=rwc" (rwc: create the file if missing), "sqlite::memory:" for tests
+    pub async fn open(url: &str) -> Result<Self, QueueError> {
+        let db = SqlitePool::connect(url).await?;
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                lease_until INTEGER,
+                last_error TEXT,
+                created_at INTEGER NOT NULL,
+                dead_at INTEGER,
+                payload_format TEXT NOT NULL DEFAULT 'json'
+            )
+            "#,
+        )
+        .execute(&db)
+        .await?;
+        // queues created before these columns existed
+        add_column(&db, "dead_at", "INTEGER").await?;
+        add_column(&db, "payload_format", "TEXT NOT NULL DEFAULT 'json'").await?;
+        Ok(Self {
+            db,
+            visibility_timeout: Duration::from_secs(60),
+            max_attempts: 5,
+            payload_format: Format::Json,
+        })
+    }
+
+    // How long a leased job is invisible to other workers; longer than the slowest job. Default 60s.
+    pub fn with_visibility_timeout(mut self, timeout: Duration) -> Self {
+        self.visibility_timeout = timeout;
+        self
+    }
+
+    // Failed attempts after which a job is dead instead of leased again. Default 5; u32::MAX: never.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    // How enqueue serializes payloads from now on (Format::Json by default). feed() decodes payloads
+    // without knowing their type, so it needs a self-describing format (JSON, MessagePack, ...);
+    // Format::Bincode only for workers that lease() themselves.
+    pub fn with_payload_format(mut self, format: Format) -> Self {
+        self.payload_format = format;
+        self
+    }
+
+    pub async fn enqueue<T: Serialize>(&self, kind: &str, payload: &T) -> Result<i64, QueueError> {
+        let bytes = self.payload_format.encode(payload)?;
+        let query = sqlx::query_scalar(
+            r#"
+            INSERT INTO jobs (kind, payload, payload_format, created_at)
+            VALUES (?1, ?2, ?3, ?4) RETURNING id
+            "#,
+        )
+        .bind(kind);
+        // text formats stay TEXT, so `SELECT payload FROM jobs` in the sqlite3 shell shows them
+        let query = match self.payload_format.is_binary() {
+            true => query.bind(bytes),
+            false => query.bind(String::from_utf8_lossy(&bytes).into_owned()),
+        };
+        let id = query
+            .bind(self.payload_format.name())
+            .bind(Utc::now().timestamp_millis())
+            .fetch_one(&self.db)
+            .await?;
+        Ok(id)
+    }
+
+    // One statement, so two workers can't lease the same job. A job whose lease expired on its
+    // last attempt (the worker crashed or hung every time) goes dead instead: nack never ran for it.
+    pub async fn lease(&self) -> Result<Option<Lease>, QueueError> {
+        let now = Utc::now().timestamp_millis();
+        let max_attempts = i64::from(self.max_attempts);
+        sqlx::query(
+            r#"
+            UPDATE jobs SET dead_at = ?1, lease_until = NULL,
+                last_error = 'lease expired on the last attempt: the worker crashed or hung'
+            WHERE dead_at IS NULL AND lease_until < ?1 AND attempts >= ?2
+            "#,
+        )
+        .bind(now)
+        .bind(max_attempts)
+        .execute(&self.db)
+        .await?;
+
+        let lease_until = now + self.visibility_timeout.as_millis() as i64;
+        let lease = sqlx::query_as(
+            r#"
+            UPDATE jobs SET lease_until = ?1, attempts = attempts + 1
+            WHERE id = (
+                SELECT id FROM jobs
+                WHERE dead_at IS NULL
+                    AND (lease_until IS NULL OR (lease_until < ?2 AND attempts < ?3))
+                ORDER BY id LIMIT 1
+            )
+            RETURNING id, kind, payload, payload_format, attempts, lease_until
+            "#,
+        )
+        .bind(lease_until)
+        .bind(now)
+        .bind(max_attempts)
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(lease)
+    }
+
+    pub async fn ack(&self, lease: &Lease) -> Result<(), QueueError> {
+        let done = sqlx::query("DELETE FROM jobs WHERE id = ?1 AND lease_until = ?2")
+            .bind(lease.id)
+            .bind(lease.lease_until)
+            .execute(&self.db)
+            .await?;
+        if done.rows_affected() == 0 {
+            return Err(QueueError::LeaseLost(lease.id));
+        }
+        Ok(())
+    }
+
+    // Makes the job visible again right away (instead of after the visibility timeout), or dead if
+    // this was its last attempt.
+    pub async fn nack(&self, lease: &Lease, error: &str) -> Result<(), QueueError> {
+        let done = sqlx::query(
+            r#"
+            UPDATE jobs SET lease_until = NULL, last_error = ?3,
+                dead_at = CASE WHEN attempts >= ?4 THEN ?5 END
+            WHERE id = ?1 AND lease_until = ?2
+            "#,
+        )
+        .bind(lease.id)
+        .bind(lease.lease_until)
+        .bind(error)
+        .bind(i64::from(self.max_attempts))
+        .bind(Utc::now().timestamp_millis())
+        .execute(&self.db)
+        .await?;
+        if done.rows_affected() == 0 {
+            return Err(QueueError::LeaseLost(lease.id));
+        }
+        Ok(())
+    }
+
+    // Jobs not yet acked (queued or leased); dead jobs aren't counted.
+    pub async fn len(&self) -> Result<i64, QueueError> {
+        Ok(
+            sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE dead_at IS NULL")
+                .fetch_one(&self.db)
+                .await?,
+        )
+    }
+
+    pub async fn is_empty(&self) -> Result<bool, QueueError> {
+        Ok(self.len().await? == 0)
+    }
+
+    // The jobs in `state` (all when None), oldest first, at most `limit`.
+    pub async fn list(
+        &self,
+        state: Option<JobState>,
+        limit: i64,
+    ) -> Result<Vec<QueuedJob>, QueueError> {
+        let now = Utc::now().timestamp_millis();
+        let condition = match state {
+            None => "1",
+            Some(JobState::Pending) => {
+                "dead_at IS NULL AND (lease_until IS NULL OR lease_until < ?1)"
+            }
+            Some(JobState::Leased) => "dead_at IS NULL AND lease_until >= ?1",
+            Some(JobState::Dead) => "dead_at IS NOT NULL",
+        };
+        let jobs = sqlx::query_as(&format!(
+            r#"
+            SELECT id, kind, payload, payload_format, attempts, last_error, created_at,
+                lease_until, dead_at
+            FROM jobs WHERE {condition} ORDER BY id LIMIT ?2
+            "#
+        ))
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?;
+        Ok(jobs)
+    }
+
+    // A dead job back in the queue with its attempts reset; false if `id` isn't a dead job.
+    pub async fn requeue(&self, id: i64) -> Result<bool, QueueError> {
+        let done = sqlx::query(
+            "UPDATE jobs SET dead_at = NULL, attempts = 0, lease_until = NULL \
+             WHERE id = ?1 AND dead_at IS NOT NULL",
+        )
+        .bind(id)
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    // A dead job given up on; false if `id` isn't a dead job (live jobs are acked, not deleted).
+    pub async fn delete_dead(&self, id: i64) -> Result<bool, QueueError> {
+        let done = sqlx::query("DELETE FROM jobs WHERE id = ?1 AND dead_at IS NOT NULL")
+            .bind(id)
+            .execute(&self.db)
+            .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    // Every dead job back in the queue; returns how many.
+    pub async fn requeue_dead(&self) -> Result<u64, QueueError> {
+        let done = sqlx::query(
+            "UPDATE jobs SET dead_at = NULL, attempts = 0, lease_until = NULL \
+             WHERE dead_at IS NOT NULL",
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected())
+    }
+
+    // Leases jobs and runs them on `pool` (JobEnvelope JSON as the job input), at most pool.workers()
+    // at a time, acking each on success and nacking it on failure. Polls every second when empty.
+    // Returns when `cancel` is cancelled; unfinished leases simply expire and are picked up next run.
+    pub async fn feed(self: Arc<Self>, pool: Arc<WorkerPool>, cancel: CancellationToken) {
+        let slots = Arc::new(Semaphore::new(pool.workers()));
+        loop {
+            let permit = tokio::select! {
+                permit = slots.clone().acquire_owned() => permit.expect("semaphore never closed"),
+                _ = cancel.cancelled() => return,
+            };
+            let lease = match self.lease().await {
+                Ok(Some(lease)) => lease,
+                Ok(None) => {
+                    drop(permit);
+                    tokio::select! {
+                        _ = tokio::time::sleep(Duration::from_secs(1)) => continue,
+                        _ = cancel.cancelled() => return,
+                    }
+                }
+                Err(e) => {
+                    warn!("leasing job failed: {e}");
+                    drop(permit);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+
+            let queue = self.clone();
+            let pool = pool.clone();
+            let span = info_span!(
+                "durable_job",
+                job.queue_id = lease.id,
+                job.kind = %lease.kind,
+                job.attempts = lease.attempts,
+            );
+            tokio::spawn(
+                async move {
+                    let _permit = permit;
+                    let result = match envelope(&lease) {
+                        Ok(input) => match pool.submit(input).await {
+                            Ok(handle) => handle.await.map_err(|e| e.to_string()),
+                            Err(e) => Err(e.to_string()),
+                        },
+                        Err(e) => Err(e.to_string()),
+                    };
+                    let settled = match &result {
+                        Ok(_) => queue.ack(&lease).await,
+                        Err(e) => queue.nack(&lease, e).await,
+                    };
+                    if let Err(e) = settled {
+                        warn!("settling job failed: {e}");
+                    }
+                }
+                .instrument(span),
+            );
+        }
+    }
+}
+
+fn envelope(lease: &Lease) -> Result<String, QueueError> {
+    let envelope = JobEnvelope {
+        kind: lease.kind.clone(),
+        payload: payload_value(&lease.payload_format, &lease.payload)?,
+    };
+    Ok(serde_json::to_string(&envelope)?)
+}
+
+// ALTER TABLE ADD COLUMN, unless the table already has it (SQLite has no IF NOT EXISTS for columns).
+async fn add_column(db: &SqlitePool, name: &str, declaration: &str) -> Result<(), QueueError> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT COUNT(*) > 0 FROM pragma_table_info('jobs') WHERE name = ?1")
+            .bind(name)
+            .fetch_one(db)
+            .await?;
+    if !exists {
+        sqlx::query(&format!("ALTER TABLE jobs ADD COLUMN {name} {declaration}"))
+            .execute(db)
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    async fn queue() -> SqliteQueue {
+        SqliteQueue::open("sqlite::memory:").await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn enqueued_jobs_are_leased_in_order_and_acked() {
+        let queue = queue().await;
+        assert!(queue.is_empty().await.unwrap());
+        let first = queue.enqueue("email", &json!({"to": "a"})).await.unwrap();
+        let second = queue.enqueue("email", &json!({"to": "b"})).await.unwrap();
+        assert_eq!(queue.len().await.unwrap(), 2);
+
+        let lease = queue.lease().await.unwrap().unwrap();
+        assert_eq!((lease.id, lease.attempts), (first, 1));
+        assert_eq!(lease.kind, "email");
+        assert_eq!(
+            lease.payload::<serde_json::Value>().unwrap(),
+            json!({"to": "a"})
+        );
+        // leased: invisible to the next lease
+        assert_eq!(queue.lease().await.unwrap().unwrap().id, second);
+        assert!(queue.lease().await.unwrap().is_none());
+
+        queue.ack(&lease).await.unwrap();
+        assert_eq!(queue.len().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn nacked_jobs_come_back_until_they_are_dead() {
+        let queue = queue().await.with_max_attempts(2);
+        let id = queue.enqueue("email", &json!({})).await.unwrap();
+
+        let lease = queue.lease().await.unwrap().unwrap();
+        queue.nack(&lease, "smtp down").await.unwrap();
+        let lease = queue.lease().await.unwrap().unwrap();
+        assert_eq!((lease.id, lease.attempts), (id, 2));
+        queue.nack(&lease, "smtp still down").await.unwrap();
+        assert!(queue.lease().await.unwrap().is_none());
+
+        let dead = queue.list(Some(JobState::Dead), 10).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].last_error.as_deref(), Some("smtp still down"));
+        assert!(queue.is_empty().await.unwrap());
+
+        assert!(queue.requeue(id).await.unwrap());
+        assert!(!queue.requeue(id).await.unwrap());
+        assert_eq!(queue.lease().await.unwrap().unwrap().attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn an_expired_lease_is_leased_again_and_the_old_one_is_lost() {
+        let queue = queue()
+            .await
+            .with_visibility_timeout(Duration::from_millis(10));
+        queue.enqueue("email", &json!({})).await.unwrap();
+
+        let stale = queue.lease().await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let fresh = queue.lease().await.unwrap().unwrap();
+        assert_eq!((fresh.id, fresh.attempts), (stale.id, 2));
+
+        assert!(matches!(
+            queue.ack(&stale).await,
+            Err(QueueError::LeaseLost(id)) if id == stale.id
+        ));
+        queue.ack(&fresh).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_lease_expiring_on_the_last_attempt_makes_the_job_dead() {
+        let queue = queue()
+            .await
+            .with_visibility_timeout(Duration::from_millis(10))
+            .with_max_attempts(1);
+        let id = queue.enqueue("email", &json!({})).await.unwrap();
+
+        queue.lease().await.unwrap().unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(queue.lease().await.unwrap().is_none());
+
+        let dead = queue.list(Some(JobState::Dead), 10).await.unwrap();
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].id, id);
+        assert_eq!(dead[0].state(), JobState::Dead);
+        assert!(dead[0]
+            .last_error
+            .as_deref()
+            .unwrap()
+            .contains("lease expired"));
+    }
+}
@@ -0,0 +1,111 @@
+// Idempotency keys for WorkerPool::submit_idempotent: a producer that retries a submit (timeout,
+// reconnect, at-least-once delivery from a queue) doesn't get the work done twice.
+//
+// Key flow:
+// submit_idempotent(key, input)
+//   ├→ key unknown (or its result expired) → submitted as a new job; the key is now pending
+//   ├→ key pending (same job still queued/running) → a handle to that job's result, nothing submitted
+//   └→ key done within the TTL → a handle that resolves right away with the stored result
+// Every handle for a key has the original job's id and gets the same result (WorkerError is Clone).
+// Results are kept for the pool's idempotency TTL (5 min by default, WorkerPool::idempotency_ttl);
+// a failed job's result is kept too: resubmitting under a new key is how to run it again.
+// In memory: keys don't survive a restart (the durable queue's ids do that job).
+
+use std::{collections::HashMap, mem, sync::Mutex, time::Duration};
+
+use tokio::{sync::oneshot, time::Instant};
+
+use super::{
+    pool::{JobHandle, WorkerError},
+    JobId,
+};
+
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+type Waiter = oneshot::Sender<Result<String, WorkerError>>;
+
+enum Entry {
+    Pending {
+        id: JobId,
+        waiters: Vec<Waiter>,
+    },
+    Done {
+        id: JobId,
+        result: Result<String, WorkerError>,
+        at: Instant,
+    },
+}
+
+pub(super) enum Claim {
+    // the caller submits the job, then calls complete() (or release() if the submit fails)
+    New,
+    Existing(JobHandle<String>),
+}
+
+#[derive(Default)]
+pub(super) struct IdempotencyCache {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl IdempotencyCache {
+    // `id`: the job that will run if the key is new. Results older than `ttl` are forgotten.
+    pub(super) fn claim(&self, key: &str, id: JobId, ttl: Duration) -> Claim {
+        let mut entries = self.entries.lock().expect("idempotency lock poisoned");
+        entries.retain(|_, entry| !matches!(entry, Entry::Done { at, .. } if at.elapsed() > ttl));
+        match entries.get_mut(key) {
+            Some(Entry::Pending { id, waiters }) => {
+                let (waiter, handle) = JobHandle::waiter(*id);
+                waiters.push(waiter);
+                Claim::Existing(handle)
+            }
+            Some(Entry::Done { id, result, .. }) => {
+                let (waiter, handle) = JobHandle::waiter(*id);
+                let _ = waiter.send(result.clone());
+                Claim::Existing(handle)
+            }
+            None => {
+                entries.insert(
+                    key.to_string(),
+                    Entry::Pending {
+                        id,
+                        waiters: Vec::new(),
+                    },
+                );
+                Claim::New
+            }
+        }
+    }
+
+    // The job's result: stored for the TTL and sent to everyone who joined meanwhile.
+    pub(super) fn complete(&self, key: &str, result: Result<String, WorkerError>) {
+        let mut entries = self.entries.lock().expect("idempotency lock poisoned");
+        let Some(entry) = entries.get_mut(key) else {
+            return;
+        };
+        let Entry::Pending { id, waiters } = entry else {
+            return;
+        };
+        for waiter in mem::take(waiters) {
+            let _ = waiter.send(result.clone());
+        }
+        *entry = Entry::Done {
+            id: *id,
+            result,
+            at: Instant::now(),
+        };
+    }
+
+    // The submit failed: the key is free again; whoever joined gets the submit error.
+    pub(super) fn release(&self, key: &str, error: &WorkerError) {
+        let removed = self
+            .entries
+            .lock()
+            .expect("idempotency lock poisoned")
+            .remove(key);
+        if let Some(Entry::Pending { waiters, .. }) = removed {
+            for waiter in waiters {
+                let _ = waiter.send(Err(error.clone()));
+            }
+        }
+    }
+}
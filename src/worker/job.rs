@@ -0,0 +1,129 @@
+// Typed jobs: the job's input, output and error are Rust types end to end, instead of the pool's
+// String → Result<String, MyError> task closure.
+//
+//   struct HashFile { path: PathBuf }
+//   impl Job for HashFile {
+//       type Output = blake3::Hash;
+//       type Error = std::io::Error;
+//       fn run(self) -> Result<blake3::Hash, std::io::Error> {
+//           Ok(blake3::hash(&std::fs::read(self.path)?))
+//       }
+//   }
+//   let handle = pool.submit_job(HashFile { path }).await?;   // JobHandle<blake3::Hash, io::Error>
+//   match handle.await {
+//       Ok(hash) => ...,
+//       Err(WorkerError::Failed(e)) => ...,                    // e: Arc<io::Error>
+//       Err(other) => ...,                                     // Timeout, Panicked, Aborted, Closed
+//   }
+//
+// Typed jobs share the pool's threads, queue, rate limit, timeouts, metrics and panic isolation.
+// They aren't retried (run(self) consumes the job: retry inside run, or resubmit), don't go to the
+// dead letter queue (no serialized input to requeue) and don't appear in pool.results().
+//
+// AsyncJob: the same for async work; it runs on a worker thread via the submitting runtime's
+// Handle::block_on, so it counts against the pool's concurrency like any other job, and a timeout
+// really cancels it (its future is dropped), instead of just flagging it.
+
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{runtime::Handle, sync::oneshot};
+use tokio_util::sync::CancellationToken;
+
+use super::pool::{panic_message, WorkerError};
+
+pub trait Job: Send + 'static {
+    type Output: Send + 'static;
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn run(self) -> Result<Self::Output, Self::Error>;
+}
+
+pub trait AsyncJob: Send + 'static {
+    type Output: Send + 'static;
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn run(self) -> impl Future<Output = Result<Self::Output, Self::Error>> + Send;
+}
+
+// Runs the job and answers its handle; Err(error message) when it failed or timed out.
+type RunFn = Box<dyn FnOnce(&CancellationToken) -> Result<(), String> + Send>;
+
+// A typed job with its types erased, so it fits in the pool's queue.
+pub(super) struct TypedWork {
+    pub(super) run: RunFn,
+    // for the timeout watchdog: answers the handle with WorkerError::Timeout, unless run got there first
+    pub(super) expire: Box<dyn FnOnce(Duration) + Send>,
+}
+
+type Reply<O, E> = Arc<Mutex<Option<oneshot::Sender<Result<O, WorkerError<E>>>>>>;
+
+pub(super) fn blocking<J: Job>(
+    job: J,
+    reply: oneshot::Sender<Result<J::Output, WorkerError<J::Error>>>,
+) -> TypedWork {
+    erase(reply, move |_cancel| {
+        match panic::catch_unwind(AssertUnwindSafe(|| job.run())) {
+            Ok(result) => result.map_err(WorkerError::from),
+            Err(payload) => Err(WorkerError::Panicked(panic_message(&*payload))),
+        }
+    })
+}
+
+pub(super) fn non_blocking<J: AsyncJob>(
+    job: J,
+    runtime: Handle,
+    reply: oneshot::Sender<Result<J::Output, WorkerError<J::Error>>>,
+) -> TypedWork {
+    erase(reply, move |cancel| {
+        let cancel = cancel.clone();
+        let run = AssertUnwindSafe(|| {
+            runtime.block_on(async move {
+                tokio::select! {
+                    result = job.run() => Some(result),
+                    // timeout or shutdown: the job's future is dropped right here
+                    _ = cancel.cancelled() => None,
+                }
+            })
+        });
+        match panic::catch_unwind(run) {
+            Ok(Some(result)) => result.map_err(WorkerError::from),
+            // the watchdog (or shutdown) answers the handle
+            Ok(None) => Err(WorkerError::Aborted),
+            Err(payload) => Err(WorkerError::Panicked(panic_message(&*payload))),
+        }
+    })
+}
+
+fn erase<O, E, F>(reply: oneshot::Sender<Result<O, WorkerError<E>>>, run: F) -> TypedWork
+where
+    O: Send + 'static,
+    E: std::error::Error + Send + Sync + 'static,
+    F: FnOnce(&CancellationToken) -> Result<O, WorkerError<E>> + Send + 'static,
+{
+    let reply: Reply<O, E> = Arc::new(Mutex::new(Some(reply)));
+    let expire_reply = reply.clone();
+    TypedWork {
+        run: Box::new(move |cancel| {
+            let result = run(cancel);
+            let outcome = result.as_ref().map(|_| ()).map_err(|e| e.to_string());
+            match reply.lock().expect("job reply lock poisoned").take() {
+                // Err: the handle was dropped, nobody wants the result
+                Some(reply) => {
+                    let _ = reply.send(result);
+                    outcome
+                }
+                None => Err("timed out".to_string()),
+            }
+        }),
+        expire: Box::new(move |after| {
+            if let Some(reply) = expire_reply.lock().expect("job reply lock poisoned").take() {
+                let _ = reply.send(Err(WorkerError::Timeout(after)));
+            }
+        }),
+    }
+}
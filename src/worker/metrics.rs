@@ -0,0 +1,118 @@
+// WorkerPool metrics, through the same global OTel meter provider as telemetry::task_metrics:
+//
+//   worker.jobs.queued{job.type}              up-down counter: waiting for a free worker
+//   worker.jobs.in_flight{job.type}           up-down counter: running on a worker (retries included)
+//   worker.job.wait_time{job.type}            histogram (ms): queued → picked up by a worker
+//   worker.job.duration{job.type}             histogram (ms): picked up → done, retries and backoff included
+//   worker.job.input_size{job.type}           histogram (bytes): input of the pool's String task
+//   worker.jobs.finished{job.type, outcome}   counter, outcome = completed | failed
+//
+// Failure rate = rate(finished{outcome="failed"}) / rate(finished). job.type is the pool's
+// name (WorkerPool::named), "default" otherwise. A growing wait_time with a full queue means
+// more workers are needed. A growing duration means the jobs themselves got slower.
+// Delayed jobs (submit_after) count as queued once they're due, not while they wait for their time.
+// Times are Millis and sizes ByteCount (crate::units), so a wait can't be recorded as a size.
+
+use std::sync::LazyLock;
+
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram, UpDownCounter},
+    KeyValue,
+};
+
+use crate::units::{ByteCount, Millis};
+
+struct PoolMetrics {
+    queued: UpDownCounter<i64>,
+    in_flight: UpDownCounter<i64>,
+    wait_time: Histogram<u64>,
+    duration: Histogram<u64>,
+    input_size: Histogram<u64>,
+    finished: Counter<u64>,
+}
+
+// Created on first use, like task_metrics: after telemetry installed the global provider.
+static METRICS: LazyLock<PoolMetrics> = LazyLock::new(|| {
+    let meter = global::meter("ecosystem.worker");
+    let boundaries = vec![
+        1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1_000.0, 2_500.0, 5_000.0, 10_000.0,
+        30_000.0, 60_000.0,
+    ];
+    PoolMetrics {
+        queued: meter
+            .i64_up_down_counter("worker.jobs.queued")
+            .with_description("Jobs waiting in the worker pool queue")
+            .build(),
+        in_flight: meter
+            .i64_up_down_counter("worker.jobs.in_flight")
+            .with_description("Jobs currently running on a worker")
+            .build(),
+        wait_time: meter
+            .u64_histogram("worker.job.wait_time")
+            .with_description("Time a job spent in the queue before a worker picked it up")
+            .with_unit("ms")
+            .with_boundaries(boundaries.clone())
+            .build(),
+        duration: meter
+            .u64_histogram("worker.job.duration")
+            .with_description("Time a worker spent on a job, retries included")
+            .with_unit("ms")
+            .with_boundaries(boundaries)
+            .build(),
+        input_size: meter
+            .u64_histogram("worker.job.input_size")
+            .with_description("Size of the input of the pool's task")
+            .with_unit("By")
+            .with_boundaries(vec![
+                64.0,
+                256.0,
+                1024.0,
+                4096.0,
+                16_384.0,
+                65_536.0,
+                262_144.0,
+                1_048_576.0,
+            ])
+            .build(),
+        finished: meter
+            .u64_counter("worker.jobs.finished")
+            .with_description("Jobs finished by the worker pool, by outcome")
+            .build(),
+    }
+});
+
+// +1 when a job enters the queue, -1 if that fails or the job is discarded at shutdown.
+pub(super) fn queued(job_type: &'static str, delta: i64) {
+    METRICS
+        .queued
+        .add(delta, &[KeyValue::new("job.type", job_type)]);
+}
+
+pub(super) fn started(job_type: &'static str, waited: Millis) {
+    let attrs = [KeyValue::new("job.type", job_type)];
+    METRICS.queued.add(-1, &attrs);
+    METRICS.in_flight.add(1, &attrs);
+    METRICS.wait_time.record(waited.get(), &attrs);
+}
+
+// Typed jobs have no serialized input to measure.
+pub(super) fn input_size(job_type: &'static str, size: ByteCount) {
+    METRICS
+        .input_size
+        .record(size.get(), &[KeyValue::new("job.type", job_type)]);
+}
+
+pub(super) fn finished(job_type: &'static str, elapsed: Millis, ok: bool) {
+    let attrs = [KeyValue::new("job.type", job_type)];
+    METRICS.in_flight.add(-1, &attrs);
+    METRICS.duration.record(elapsed.get(), &attrs);
+    let outcome = if ok { "completed" } else { "failed" };
+    METRICS.finished.add(
+        1,
+        &[
+            KeyValue::new("job.type", job_type),
+            KeyValue::new("outcome", outcome),
+        ],
+    );
+}
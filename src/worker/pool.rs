@@ -0,0 +1,1146 @@
+// WorkerPool: a fixed number of OS threads for blocking work, fed from a bounded queue.
+// What tokio2.rs's worker() did by hand, without its two problems:
+//   - one new OS thread per message (unbounded under a fast producer) → `workers` long-lived threads
+//   - blocking on each result before taking the next message (effectively one job at a time)
+//     → every worker runs its own job; results are delivered as soon as each job finishes
+//
+// Key flow:
+// WorkerPool::new(workers, queue_capacity, task)   (or WorkerPool::builder(), worker/builder.rs)
+//   ├→ jobs:    bounded tokio mpsc (async submit waits when full = backpressure on the producer,
+//   │           try_submit_job fails with WorkerError::QueueFull instead)
+//   ├→ workers: N threads, each takes the next job (blocking_recv) and runs task(input),
+//   │           retrying transient errors per the pool's RetryPolicy (WorkerPool::with_retry)
+//   ├→ results: each job's output goes back through its own oneshot channel to its JobHandle
+//   ├→ timeout: optional per job, the handle resolves with WorkerError::Timeout (worker/timeout.rs)
+//   ├→ rate limit: optional, workers take a token before each job (WorkerPool::rate_limited, worker/rate_limit.rs)
+//   ├→ idempotency: submit_idempotent(key, ..) reuses the job already submitted under that key (worker/idempotency.rs)
+//   ├→ results: optionally also as one Stream of (id, result) for all jobs (pool.results(), worker/results.rs)
+//   ├→ progress: jobs call worker::report_progress, seen on the handle and pool.events() (worker/progress.rs)
+//   ├→ metrics: queue depth, in-flight, wait time, run time, outcomes per job type (worker/metrics.rs)
+//   └→ failed for good: also recorded in pool.dead_letters(), requeue with pool.requeue(id) (worker/dead_letter.rs)
+//
+//   let pool = WorkerPool::new(4, 32, |s| Ok(expensive_blocking_task(s)));
+//   let handle = pool.submit("task 1".to_string()).await?;   // queued
+//   let hash = handle.await?;                                  // this job's result, whenever it's done
+//   let later = pool.submit_after(Duration::from_secs(30), "task 2".to_string())?;   // see worker/delay.rs
+//   let typed = pool.submit_job(HashFile { path }).await?;    // typed Job / AsyncJob, see worker/job.rs
+//
+// Shutdown (pool.shutdown(Duration::from_secs(10)).await):
+//   ├→ cancels the pool's CancellationToken: submit() fails with WorkerError::Closed,
+//   │  also for producers currently waiting for queue space
+//   ├→ closes the queue; jobs still queued are discarded (not started, handles → WorkerError::Aborted)
+//   ├→ waits up to the deadline for the running jobs (blocking code can't be interrupted)
+//   └→ ShutdownSummary { completed, failed, aborted, timed_out }
+// Drain (pool.drain().await), for batch runs that must not lose queued work:
+//   ├→ stops intake like shutdown (submit() → Closed; delayed jobs not yet due → Aborted)
+//   └→ runs everything already queued, then resolves with the ShutdownSummary
+// Dropping the pool without shutdown() closes the queue too, but lets the workers finish what's queued.
+
+use std::{
+    any::Any,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, OnceLock,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread,
+    time::Duration,
+};
+
+use chrono::Utc;
+use thiserror::Error;
+use tokio::{
+    sync::{mpsc, oneshot, watch},
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, info_span, warn};
+
+use super::{
+    builder::WorkerPoolBuilder,
+    dead_letter::{Attempt, DeadLetter, DeadLetterQueue},
+    delay::{self, DelaySender},
+    idempotency::{self, Claim, IdempotencyCache},
+    job::{self, AsyncJob, Job, TypedWork},
+    metrics,
+    progress::{self, JobProgress},
+    rate_limit::RateLimiter,
+    results::{self, JobEvents, JobResults, ResultsSender, Subscriber},
+    retry::RetryPolicy,
+    timeout::{self, JobTimeout},
+    JobId,
+};
+use crate::{error::MyError, units::ByteCount};
+
+// E: the job's own error type; MyError for the pool's String task, Job::Error for typed jobs.
+#[derive(Error, Debug)]
+pub enum WorkerError<E = MyError> {
+    #[error("worker pool is closed")]
+    Closed,
+    // try_submit_job: every queue slot was taken, nothing was queued
+    #[error("worker pool queue is full")]
+    QueueFull,
+    // discarded at shutdown before it ran
+    #[error("job was aborted before it completed")]
+    Aborted,
+    // the job panicked; the worker caught it and carries on with the next job
+    #[error("job panicked: {0}")]
+    Panicked(String),
+    // the job's own error, after the last attempt (Arc: shared by every copy of the result)
+    #[error("job failed: {0}")]
+    Failed(Arc<E>),
+    // the handle gave up at the deadline; the job was flagged cancelled (see worker::job_cancelled)
+    #[error("job timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("no dead letter for job {0}")]
+    NotDead(JobId),
+}
+
+impl<E> From<E> for WorkerError<E> {
+    fn from(e: E) -> Self {
+        WorkerError::Failed(Arc::new(e))
+    }
+}
+
+// Clone (without E: Clone): a result can go to the job's handle and to pool.results() both.
+impl<E> Clone for WorkerError<E> {
+    fn clone(&self) -> Self {
+        match self {
+            WorkerError::Closed => WorkerError::Closed,
+            WorkerError::QueueFull => WorkerError::QueueFull,
+            WorkerError::Aborted => WorkerError::Aborted,
+            WorkerError::Panicked(message) => WorkerError::Panicked(message.clone()),
+            WorkerError::Failed(e) => WorkerError::Failed(e.clone()),
+            WorkerError::Timeout(after) => WorkerError::Timeout(*after),
+            WorkerError::NotDead(id) => WorkerError::NotDead(*id),
+        }
+    }
+}
+
+// The result of one submitted job: `.await` it (Result<T, WorkerError<E>>), or drop it if the
+// result isn't needed — the job still runs.
+#[derive(Debug)]
+pub struct JobHandle<T, E = MyError> {
+    id: JobId,
+    rx: oneshot::Receiver<Result<T, WorkerError<E>>>,
+    progress: watch::Receiver<Option<JobProgress>>,
+}
+
+impl<T, E> JobHandle<T, E> {
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    // The job's latest progress report (worker::report_progress), None before the first one.
+    pub fn progress(&self) -> Option<JobProgress> {
+        self.progress.borrow().clone()
+    }
+
+    // To wait for reports: watch.changed().await, then watch.borrow(). Keeps working after the handle is awaited.
+    pub fn watch_progress(&self) -> watch::Receiver<Option<JobProgress>> {
+        self.progress.clone()
+    }
+
+    // Another handle to job `id`'s result, fed through the returned Sender.
+    // Its progress stays None (idempotent duplicates don't see the original job's reports).
+    pub(super) fn waiter(id: JobId) -> (oneshot::Sender<Result<T, WorkerError<E>>>, Self) {
+        let (tx, rx) = oneshot::channel();
+        let (_, progress) = watch::channel(None);
+        (tx, JobHandle { id, rx, progress })
+    }
+}
+
+impl<T, E> Future for JobHandle<T, E> {
+    type Output = Result<T, WorkerError<E>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // the worker drops the Sender without sending when the job never ran (or didn't finish)
+        Pin::new(&mut self.rx).poll(cx).map(|reply| match reply {
+            Ok(result) => result,
+            Err(_) => Err(WorkerError::Aborted),
+        })
+    }
+}
+
+// What goes through the queue: the work, plus how to run it.
+pub(super) struct QueuedJob {
+    pub(super) id: JobId,
+    pub(super) job_type: &'static str,
+    work: Work,
+    queued_at: Instant,
+    rate_limit: Option<Arc<RateLimiter>>,
+    timeout: Option<JobTimeout>,
+    // the job's reports go to its handle(s) through here
+    progress: watch::Sender<Option<JobProgress>>,
+}
+
+enum Work {
+    // input for the pool's task: retried, dead-lettered, published to results()
+    Task {
+        input: String,
+        reply: oneshot::Sender<Result<String, WorkerError>>,
+    },
+    // a Job / AsyncJob, types erased (worker/job.rs)
+    Typed(TypedWork),
+}
+
+// try_submit_job's result: the typed job's handle, or why it wasn't queued.
+type TypedSubmit<J> =
+    Result<JobHandle<<J as Job>::Output, <J as Job>::Error>, WorkerError<<J as Job>::Error>>;
+
+// Where a Task's result goes; whoever takes it first (the worker or the timeout watchdog) answers the handle.
+type Reply = Arc<Mutex<Option<oneshot::Sender<Result<String, WorkerError>>>>>;
+
+impl QueuedJob {
+    // Right before the job goes into the queue: now for submit(), when it's due for submit_after().
+    pub(super) fn mark_queued(&mut self) {
+        self.queued_at = Instant::now();
+        metrics::queued(self.job_type, 1);
+    }
+}
+
+// Over the pool's lifetime: every accepted job is either completed, failed (after its retries) or aborted
+// (discarded from the queue at shutdown, or still running when the deadline passed).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    pub completed: u64,
+    pub failed: u64,
+    pub aborted: u64,
+    // some workers were still busy at the deadline (they finish in the background, handles still get the result)
+    pub timed_out: bool,
+}
+
+pub struct WorkerPool {
+    // None once shut down: dropping the last Sender closes the queue
+    jobs: Mutex<Option<mpsc::Sender<QueuedJob>>>,
+    next_id: AtomicU64,
+    // jobs that made it into the queue (ids are handed out before the send, which may fail)
+    accepted: AtomicU64,
+    completed: Arc<AtomicU64>,
+    failed: Arc<AtomicU64>,
+    dead_letters: Arc<DeadLetterQueue>,
+    cancel: CancellationToken,
+    // child of `cancel`: stops intake only (drain), the queue still runs
+    intake: CancellationToken,
+    // every worker holds a Sender clone; recv() returns None once all of them have exited
+    alive: tokio::sync::Mutex<mpsc::Receiver<()>>,
+    // timer task for submit_after / submit_at, started on first use
+    delayed: OnceLock<DelaySender>,
+    workers: usize,
+    queue_capacity: usize,
+    results: ResultsSender,
+    // metrics label, see WorkerPool::named
+    job_type: &'static str,
+    rate_limit: Option<Arc<RateLimiter>>,
+    // default for jobs submitted without their own timeout
+    job_timeout: Option<Duration>,
+    idempotency: Arc<IdempotencyCache>,
+    idempotency_ttl: Duration,
+}
+
+impl WorkerPool {
+    // `workers` threads (at least 1) running `task`; at most `queue_capacity` jobs wait for a free worker.
+    // Failed jobs aren't retried.
+    pub fn new<F>(workers: usize, queue_capacity: usize, task: F) -> Self
+    where
+        F: Fn(String) -> Result<String, MyError> + Send + Sync + 'static,
+    {
+        Self::with_retry(workers, queue_capacity, RetryPolicy::none(), task)
+    }
+
+    // Same, retrying jobs that fail with a transient error (RetryPolicy::retry_on).
+    // The backoff sleep happens on the worker thread: the job keeps its worker until it's done.
+    pub fn with_retry<F>(workers: usize, queue_capacity: usize, retry: RetryPolicy, task: F) -> Self
+    where
+        F: Fn(String) -> Result<String, MyError> + Send + Sync + 'static,
+    {
+        Self::builder()
+            .workers(workers)
+            .queue_capacity(queue_capacity)
+            .retry(retry)
+            .build(task)
+    }
+
+    // Thread count, queue size, thread names and stack size, e.g. from the environment.
+    pub fn builder() -> WorkerPoolBuilder {
+        WorkerPoolBuilder::default()
+    }
+
+    pub(super) fn spawn<F>(config: WorkerPoolBuilder, task: F) -> Self
+    where
+        F: Fn(String) -> Result<String, MyError> + Send + Sync + 'static,
+    {
+        let WorkerPoolBuilder {
+            workers,
+            queue_capacity,
+            thread_name,
+            stack_size,
+            retry,
+        } = config;
+        let workers = workers.max(1);
+        let (jobs, rx) = mpsc::channel::<QueuedJob>(queue_capacity.max(1));
+        let (alive, alive_rx) = mpsc::channel(1);
+        // one queue, many consumers: whoever holds the lock waits for the next job,
+        // the others wait for the lock; it's released before the job runs
+        let rx = Arc::new(Mutex::new(rx));
+        let task = Arc::new(task);
+        let completed = Arc::new(AtomicU64::new(0));
+        let failed = Arc::new(AtomicU64::new(0));
+        let dead_letters = Arc::new(DeadLetterQueue::default());
+        let results: ResultsSender = Arc::default();
+        let cancel = CancellationToken::new();
+
+        for i in 0..workers {
+            let rx = rx.clone();
+            let task = task.clone();
+            let completed = completed.clone();
+            let failed = failed.clone();
+            let dead_letters = dead_letters.clone();
+            let results = results.clone();
+            let cancel = cancel.clone();
+            let alive = alive.clone();
+            let mut spawner = thread::Builder::new().name(format!("{thread_name}-{i}"));
+            if let Some(bytes) = stack_size {
+                spawner = spawner.stack_size(bytes);
+            }
+            spawner
+                .spawn(move || {
+                    let _alive = alive;
+                    loop {
+                        let next = rx.lock().expect("job queue lock poisoned").blocking_recv();
+                        let Some(QueuedJob {
+                            id,
+                            job_type,
+                            work,
+                            queued_at,
+                            rate_limit,
+                            timeout,
+                            progress,
+                        }) = next
+                        else {
+                            break; // queue closed and drained
+                        };
+                        if cancel.is_cancelled() {
+                            metrics::queued(job_type, -1);
+                            // dropping `reply` resolves the handle with WorkerError::Aborted
+                            debug!(job.id = id, "discarding queued job, pool is shutting down");
+                            continue;
+                        }
+                        if let Some(limiter) = &rate_limit {
+                            limiter.acquire_blocking();
+                            if cancel.is_cancelled() {
+                                metrics::queued(job_type, -1);
+                                continue;
+                            }
+                        }
+                        metrics::started(job_type, queued_at.elapsed().into());
+                        let started = Instant::now();
+                        // cancelled by the timeout, or with the whole pool
+                        let job_cancel = cancel.child_token();
+                        let span = info_span!("job", job.id = id);
+                        let sink: progress::Sink = {
+                            let results = results.clone();
+                            Box::new(move |report: JobProgress| {
+                                progress.send_replace(Some(report.clone()));
+                                results::publish_progress(&results, id, report);
+                            })
+                        };
+                        let ok = match work {
+                            Work::Task { input, reply } => {
+                                metrics::input_size(job_type, ByteCount(input.len() as u64));
+                                let reply: Reply = Arc::new(Mutex::new(Some(reply)));
+                                let expire = expire_task(reply.clone());
+                                let watchdog = timeout.as_ref().map(|t| {
+                                    timeout::watchdog(t, id, job_cancel.clone(), expire)
+                                });
+                                let (result, mut attempts) = span.in_scope(|| {
+                                    let _current = timeout::enter(job_cancel.clone());
+                                    let _progress = progress::enter(sink);
+                                    run_with_retry(&*task, &input, &retry, &job_cancel)
+                                });
+                                if let Some(watchdog) = watchdog {
+                                    watchdog.abort();
+                                }
+                                // None: the watchdog already answered with WorkerError::Timeout
+                                let reply = reply.lock().expect("job reply lock poisoned").take();
+                                let result = match (reply.is_some(), result) {
+                                    (true, result) => result,
+                                    (false, _) => {
+                                        let after = timeout.map(|t| t.after).unwrap_or_default();
+                                        attempts.push(Attempt {
+                                            at: Utc::now(),
+                                            error: format!("timed out after {after:?}"),
+                                        });
+                                        Err(WorkerError::Timeout(after))
+                                    }
+                                };
+                                if let Err(e) = &result {
+                                    warn!(job.id = id, attempts = attempts.len(), error = %e, "job moved to dead letter queue");
+                                    dead_letters.push(DeadLetter {
+                                        job_id: id,
+                                        input,
+                                        attempts,
+                                        dead_at: Utc::now(),
+                                    });
+                                }
+                                let ok = result.is_ok();
+                                results::publish(&results, (id, result.clone()));
+                                // Err: the handle was dropped, nobody wants the result
+                                if let Some(reply) = reply {
+                                    if reply.send(result).is_err() {
+                                        debug!(job.id = id, "result dropped, handle is gone");
+                                    }
+                                }
+                                ok
+                            }
+                            Work::Typed(TypedWork { run, expire }) => {
+                                let watchdog = timeout.as_ref().map(|t| {
+                                    timeout::watchdog(t, id, job_cancel.clone(), expire)
+                                });
+                                let outcome = span.in_scope(|| {
+                                    let _current = timeout::enter(job_cancel.clone());
+                                    let _progress = progress::enter(sink);
+                                    run(&job_cancel)
+                                });
+                                if let Some(watchdog) = watchdog {
+                                    watchdog.abort();
+                                }
+                                if let Err(e) = &outcome {
+                                    warn!(job.id = id, error = %e, "job failed");
+                                }
+                                outcome.is_ok()
+                            }
+                        };
+                        metrics::finished(job_type, started.elapsed().into(), ok);
+                        if ok {
+                            completed.fetch_add(1, Ordering::Relaxed);
+                        } else {
+                            failed.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                })
+                .expect("failed to spawn worker thread");
+        }
+
+        Self {
+            jobs: Mutex::new(Some(jobs)),
+            next_id: AtomicU64::new(0),
+            accepted: AtomicU64::new(0),
+            completed,
+            failed,
+            dead_letters,
+            intake: cancel.child_token(),
+            cancel,
+            alive: tokio::sync::Mutex::new(alive_rx),
+            delayed: OnceLock::new(),
+            workers,
+            queue_capacity: queue_capacity.max(1),
+            results,
+            job_type: "default",
+            rate_limit: None,
+            job_timeout: None,
+            idempotency: Arc::default(),
+            idempotency_ttl: idempotency::DEFAULT_TTL,
+        }
+    }
+
+    // Name used as job.type in the pool's metrics (worker/metrics.rs); keep it a fixed,
+    // low-cardinality name like "hash" or "report", one per pool.
+    pub fn named(mut self, job_type: &'static str) -> Self {
+        self.job_type = job_type;
+        self
+    }
+
+    // Starts at most limiter's rate of jobs per second; share one Arc<RateLimiter> between
+    // pools to limit them together (e.g. all pools of one job type). Jobs over the limit stay queued.
+    pub fn rate_limited(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limit = Some(limiter);
+        self
+    }
+
+    // How long submit_idempotent remembers a key's result after the job finished (default 5 min).
+    pub fn idempotency_ttl(mut self, ttl: Duration) -> Self {
+        self.idempotency_ttl = ttl;
+        self
+    }
+
+    // Timeout for every job that isn't given its own (submit_with_timeout); see worker/timeout.rs.
+    pub fn job_timeout(mut self, timeout: Duration) -> Self {
+        self.job_timeout = Some(timeout);
+        self
+    }
+
+    // Waits for room in the queue, then returns the handle to the job's result.
+    pub async fn submit(&self, input: String) -> Result<JobHandle<String>, WorkerError> {
+        self.submit_inner(input, self.job_timeout).await
+    }
+
+    // Same, resolving the handle with WorkerError::Timeout if the job runs longer than `timeout`.
+    //   pool.submit_with_timeout("task 1".into(), Duration::from_secs(5)).await?
+    pub async fn submit_with_timeout(
+        &self,
+        input: String,
+        timeout: Duration,
+    ) -> Result<JobHandle<String>, WorkerError> {
+        self.submit_inner(input, Some(timeout)).await
+    }
+
+    // Submits under an idempotency key: while a job with that key is queued or running, or its result is
+    // younger than the TTL, returns a handle to that job instead of submitting again.
+    //   pool.submit_idempotent(format!("report:{date}"), input).await?
+    pub async fn submit_idempotent(
+        &self,
+        key: impl Into<String>,
+        input: String,
+    ) -> Result<JobHandle<String>, WorkerError> {
+        let key = key.into();
+        let (job, handle) = self.new_job(input, self.job_timeout);
+        if let Claim::Existing(existing) =
+            self.idempotency
+                .claim(&key, handle.id(), self.idempotency_ttl)
+        {
+            debug!(job.id = existing.id(), idempotency.key = %key, "duplicate submit, reusing job");
+            return Ok(existing);
+        }
+        let handle = match self.enqueue(job, handle).await {
+            Ok(handle) => handle,
+            Err(e) => {
+                self.idempotency.release(&key, &e);
+                return Err(e);
+            }
+        };
+        // the first caller's handle is a waiter like the others; the job's own result goes to the cache
+        let (waiter, mut shared) = JobHandle::waiter(handle.id());
+        shared.progress = handle.watch_progress();
+        let cache = self.idempotency.clone();
+        tokio::spawn(async move {
+            let result = handle.await;
+            let _ = waiter.send(result.clone());
+            cache.complete(&key, result);
+        });
+        Ok(shared)
+    }
+
+    // Runs a typed Job on the pool; the handle resolves with its own Output / Error types.
+    pub async fn submit_job<J: Job>(
+        &self,
+        job: J,
+    ) -> Result<JobHandle<J::Output, J::Error>, WorkerError<J::Error>> {
+        let (reply, rx) = oneshot::channel();
+        let work = Work::Typed(job::blocking(job, reply));
+        let job = self.queued(work, self.job_timeout);
+        let handle = JobHandle {
+            id: job.id,
+            rx,
+            progress: job.progress.subscribe(),
+        };
+        self.enqueue(job, handle).await
+    }
+
+    // submit_job without waiting for a queue slot: WorkerError::QueueFull when the queue is full,
+    // for callers that would rather shed load (HTTP 503) than hold the request open.
+    pub fn try_submit_job<J: Job>(&self, job: J) -> TypedSubmit<J> {
+        if self.intake.is_cancelled() {
+            return Err(WorkerError::Closed);
+        }
+        let jobs = self.sender()?;
+        let (reply, rx) = oneshot::channel();
+        let work = Work::Typed(job::blocking(job, reply));
+        let mut job = self.queued(work, self.job_timeout);
+        let handle = JobHandle {
+            id: job.id,
+            rx,
+            progress: job.progress.subscribe(),
+        };
+        job.mark_queued();
+        if let Err(e) = jobs.try_send(job) {
+            metrics::queued(self.job_type, -1);
+            return Err(match e {
+                mpsc::error::TrySendError::Full(_) => WorkerError::QueueFull,
+                mpsc::error::TrySendError::Closed(_) => WorkerError::Closed,
+            });
+        }
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+        Ok(handle)
+    }
+
+    // Same for an AsyncJob, driven to completion on a worker thread (see worker/job.rs).
+    pub async fn submit_async_job<J: AsyncJob>(
+        &self,
+        job: J,
+    ) -> Result<JobHandle<J::Output, J::Error>, WorkerError<J::Error>> {
+        let (reply, rx) = oneshot::channel();
+        let runtime = tokio::runtime::Handle::current();
+        let work = Work::Typed(job::non_blocking(job, runtime, reply));
+        let job = self.queued(work, self.job_timeout);
+        let handle = JobHandle {
+            id: job.id,
+            rx,
+            progress: job.progress.subscribe(),
+        };
+        self.enqueue(job, handle).await
+    }
+
+    async fn submit_inner(
+        &self,
+        input: String,
+        timeout: Option<Duration>,
+    ) -> Result<JobHandle<String>, WorkerError> {
+        let (job, handle) = self.new_job(input, timeout);
+        self.enqueue(job, handle).await
+    }
+
+    async fn enqueue<T, E>(
+        &self,
+        mut job: QueuedJob,
+        handle: JobHandle<T, E>,
+    ) -> Result<JobHandle<T, E>, WorkerError<E>> {
+        let jobs = self.sender::<E>()?;
+        job.mark_queued();
+        let sent = tokio::select! {
+            sent = jobs.send(job) => sent.is_ok(),
+            _ = self.intake.cancelled() => false,
+        };
+        if !sent {
+            metrics::queued(self.job_type, -1);
+            return Err(WorkerError::Closed);
+        }
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+        Ok(handle)
+    }
+
+    // Queues the job once `delay` has passed; returns its handle right away.
+    //   pool.submit_after(Duration::from_secs(30), "send reminder".into())?
+    pub fn submit_after(
+        &self,
+        delay: Duration,
+        input: String,
+    ) -> Result<JobHandle<String>, WorkerError> {
+        self.submit_at(Instant::now() + delay, input)
+    }
+
+    // Queues the job at `at` (tokio Instant, so it follows tokio::time::pause() in tests).
+    // The job still waits for a free worker after that, like any other.
+    pub fn submit_at(&self, at: Instant, input: String) -> Result<JobHandle<String>, WorkerError> {
+        if self.intake.is_cancelled() {
+            return Err(WorkerError::Closed);
+        }
+        let jobs = self.sender()?;
+        let delayed = self
+            .delayed
+            .get_or_init(|| delay::spawn(jobs, self.intake.clone()));
+        let (job, handle) = self.new_job(input, self.job_timeout);
+        delayed.send((at, job)).map_err(|_| WorkerError::Closed)?;
+        self.accepted.fetch_add(1, Ordering::Relaxed);
+        Ok(handle)
+    }
+
+    // Jobs that failed for good, with their input and the error of every attempt.
+    pub fn dead_letters(&self) -> &DeadLetterQueue {
+        &self.dead_letters
+    }
+
+    // Takes job `id` out of the dead letter queue and submits its input again, as a new job
+    // (new id, fresh attempts). If the submit fails the entry is put back.
+    pub async fn requeue(&self, id: JobId) -> Result<JobHandle<String>, WorkerError> {
+        let letter = self
+            .dead_letters
+            .remove(id)
+            .ok_or(WorkerError::NotDead(id))?;
+        match self.submit(letter.input.clone()).await {
+            Ok(handle) => {
+                info!(
+                    job.id = id,
+                    new_job.id = handle.id(),
+                    "dead letter requeued"
+                );
+                Ok(handle)
+            }
+            Err(e) => {
+                self.dead_letters.push(letter);
+                Err(e)
+            }
+        }
+    }
+
+    // Every job finished from now on, as a Stream of (id, result); see worker/results.rs.
+    pub fn results(&self) -> JobResults {
+        JobResults::new(self.subscribe(false))
+    }
+
+    // Same, plus every job's progress reports as they happen (JobEvent::Progress).
+    pub fn events(&self) -> JobEvents {
+        JobEvents::new(self.subscribe(true))
+    }
+
+    fn subscribe(&self, progress: bool) -> mpsc::Receiver<results::JobEvent> {
+        let (tx, rx) = mpsc::channel(self.queue_capacity);
+        *self.results.lock().expect("results lock poisoned") = Some(Subscriber::new(tx, progress));
+        rx
+    }
+
+    fn sender<E>(&self) -> Result<mpsc::Sender<QueuedJob>, WorkerError<E>> {
+        self.jobs
+            .lock()
+            .expect("job sender lock poisoned")
+            .clone()
+            .ok_or(WorkerError::Closed)
+    }
+
+    // Needs a runtime for the timeout watchdog (submit is async, submit_at spawns the timer task).
+    fn new_job(&self, input: String, timeout: Option<Duration>) -> (QueuedJob, JobHandle<String>) {
+        let (reply, rx) = oneshot::channel();
+        let job = self.queued(Work::Task { input, reply }, timeout);
+        let handle = JobHandle {
+            id: job.id,
+            rx,
+            progress: job.progress.subscribe(),
+        };
+        (job, handle)
+    }
+
+    fn queued(&self, work: Work, timeout: Option<Duration>) -> QueuedJob {
+        QueuedJob {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            job_type: self.job_type,
+            work,
+            queued_at: Instant::now(),
+            rate_limit: self.rate_limit.clone(),
+            timeout: timeout.map(|after| JobTimeout {
+                after,
+                runtime: tokio::runtime::Handle::current(),
+            }),
+            progress: watch::channel(None).0,
+        }
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    // Token cancelled when shutdown or drain starts; clone it into code that should stop feeding the pool.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.intake.clone()
+    }
+
+    pub async fn shutdown(&self, deadline: Duration) -> ShutdownSummary {
+        info!(workers = self.workers, "shutting down worker pool");
+        self.cancel.cancel();
+        self.jobs.lock().expect("job sender lock poisoned").take();
+
+        let timed_out = tokio::time::timeout(deadline, async {
+            self.alive.lock().await.recv().await;
+        })
+        .await
+        .is_err();
+        if timed_out {
+            warn!(
+                ?deadline,
+                "worker pool shutdown deadline passed with jobs still running"
+            );
+        }
+
+        // ends the results() stream (late finishers after a timeout aren't published)
+        self.results.lock().expect("results lock poisoned").take();
+        self.summary(timed_out)
+    }
+
+    // Stops intake, then waits (without a deadline) until every job already in the queue has run.
+    // Delayed jobs that aren't due yet are aborted; running jobs aren't flagged cancelled.
+    pub async fn drain(&self) -> ShutdownSummary {
+        info!(workers = self.workers, "draining worker pool");
+        self.intake.cancel();
+        self.jobs.lock().expect("job sender lock poisoned").take();
+        // the queue closes once the delay task has dropped its sender too; workers exit when it's empty
+        self.alive.lock().await.recv().await;
+        self.results.lock().expect("results lock poisoned").take();
+        self.summary(false)
+    }
+
+    fn summary(&self, timed_out: bool) -> ShutdownSummary {
+        let accepted = self.accepted.load(Ordering::Relaxed);
+        let completed = self.completed.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        ShutdownSummary {
+            completed,
+            failed,
+            aborted: accepted.saturating_sub(completed + failed),
+            timed_out,
+        }
+    }
+}
+
+// The watchdog's side of a Task's Reply.
+fn expire_task(reply: Reply) -> Box<dyn FnOnce(Duration) + Send> {
+    Box::new(move |after| {
+        if let Some(reply) = reply.lock().expect("job reply lock poisoned").take() {
+            let _ = reply.send(Err(WorkerError::Timeout(after)));
+        }
+    })
+}
+
+// Runs `task` until it succeeds, fails with a non-retryable error, runs out of attempts,
+// or the pool shuts down (no point in waiting out a backoff then).
+// A panic is caught (the worker thread survives) and not retried: it's a bug, not a transient failure.
+// Also returns every failed attempt, for the dead letter queue.
+fn run_with_retry<F>(
+    task: &F,
+    input: &str,
+    retry: &RetryPolicy,
+    cancel: &CancellationToken,
+) -> (Result<String, WorkerError>, Vec<Attempt>)
+where
+    F: Fn(String) -> Result<String, MyError>,
+{
+    let mut attempts = Vec::new();
+    loop {
+        // AssertUnwindSafe: after a panic the task's captured state is only reused for the next job,
+        // the same risk as in any caught panic (e.g. tower-http's CatchPanic)
+        match panic::catch_unwind(AssertUnwindSafe(|| task(input.to_string()))) {
+            Ok(Ok(output)) => return (Ok(output), attempts),
+            Err(payload) => {
+                let message = panic_message(&*payload);
+                attempts.push(Attempt {
+                    at: Utc::now(),
+                    error: format!("panicked: {message}"),
+                });
+                return (Err(WorkerError::Panicked(message)), attempts);
+            }
+            Ok(Err(e)) => {
+                attempts.push(Attempt {
+                    at: Utc::now(),
+                    error: e.to_string(),
+                });
+                let attempt = attempts.len() as u32;
+                if !retry.should_retry(attempt, &e) || cancel.is_cancelled() {
+                    return (Err(e.into()), attempts);
+                }
+                let delay = retry.delay(attempt);
+                warn!(attempt, ?delay, error = %e, "job failed, retrying");
+                if sleep_unless_cancelled(cancel, delay) {
+                    return (Err(e.into()), attempts);
+                }
+            }
+        }
+    }
+}
+
+// The backoff between attempts: sleeps for `timeout`, or until `cancel` fires (true then).
+// Worker threads aren't runtime threads, so the token's future is polled here, with a waker that
+// unparks this thread.
+fn sleep_unless_cancelled(cancel: &CancellationToken, timeout: Duration) -> bool {
+    struct Unpark(thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut cancelled = pin!(cancel.cancelled());
+    // None: a timeout too far out to represent, i.e. only cancellation ends it
+    let deadline = std::time::Instant::now().checked_add(timeout);
+    loop {
+        if cancelled.as_mut().poll(&mut cx).is_ready() {
+            return true;
+        }
+        match deadline {
+            Some(deadline) => {
+                let left = deadline.saturating_duration_since(std::time::Instant::now());
+                if left.is_zero() {
+                    return false;
+                }
+                thread::park_timeout(left);
+            }
+            None => thread::park(),
+        }
+    }
+}
+
+// The panic!() message, when it's a string (it almost always is).
+pub(super) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::atomic::{AtomicBool, AtomicU32},
+    };
+
+    use super::*;
+    use crate::worker::job_cancelled;
+
+    // Polls `done` until it's true; worker threads aren't awaitable.
+    async fn wait_until(done: impl Fn() -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !done() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .expect("condition not reached within 5s");
+    }
+
+    fn refused() -> MyError {
+        io::Error::from(io::ErrorKind::ConnectionRefused).into()
+    }
+
+    fn fast_retry(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn submit_returns_each_jobs_result() {
+        let pool = WorkerPool::new(2, 4, |s| Ok(s.to_uppercase()));
+        let a = pool.submit("a".to_string()).await.unwrap();
+        let b = pool.submit("b".to_string()).await.unwrap();
+        assert_ne!(a.id(), b.id());
+        assert_eq!(b.await.unwrap(), "B");
+        assert_eq!(a.await.unwrap(), "A");
+
+        let summary = pool.shutdown(Duration::from_secs(1)).await;
+        assert_eq!(summary.completed, 2);
+        assert_eq!(summary.aborted, 0);
+    }
+
+    #[tokio::test]
+    async fn failed_job_goes_to_dead_letters_and_can_be_requeued() {
+        let fail = Arc::new(AtomicBool::new(true));
+        let pool = WorkerPool::new(1, 4, {
+            let fail = fail.clone();
+            move |s| match fail.load(Ordering::SeqCst) {
+                true => Err(MyError::Custom(format!("bad input {s}"))),
+                false => Ok(s),
+            }
+        });
+        let handle = pool.submit("x".to_string()).await.unwrap();
+        let id = handle.id();
+        assert!(matches!(handle.await, Err(WorkerError::Failed(_))));
+        let letter = pool.dead_letters().get(id).unwrap();
+        assert_eq!(letter.input, "x");
+        assert_eq!(
+            letter.last_error(),
+            Some("A custom error occurred: bad input x")
+        );
+
+        fail.store(false, Ordering::SeqCst);
+        let retried = pool.requeue(id).await.unwrap();
+        assert_ne!(retried.id(), id);
+        assert_eq!(retried.await.unwrap(), "x");
+        assert!(pool.dead_letters().is_empty());
+        assert!(matches!(
+            pool.requeue(id).await,
+            Err(WorkerError::NotDead(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn panic_fails_only_its_job() {
+        let pool = WorkerPool::new(1, 4, |s| match s.as_str() {
+            "boom" => panic!("exploded"),
+            _ => Ok(s),
+        });
+        let boom = pool.submit("boom".to_string()).await.unwrap();
+        let ok = pool.submit("ok".to_string()).await.unwrap();
+        assert!(matches!(boom.await, Err(WorkerError::Panicked(m)) if m == "exploded"));
+        assert_eq!(ok.await.unwrap(), "ok");
+    }
+
+    #[tokio::test]
+    async fn transient_errors_are_retried() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let pool = WorkerPool::with_retry(1, 4, fast_retry(3), {
+            let calls = calls.clone();
+            move |s| match calls.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(refused()),
+                _ => Ok(s),
+            }
+        });
+        assert_eq!(
+            pool.submit("x".to_string()).await.unwrap().await.unwrap(),
+            "x"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retries_stop_at_max_attempts_and_skip_permanent_errors() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let pool = WorkerPool::with_retry(1, 4, fast_retry(2), {
+            let calls = calls.clone();
+            move |s| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                match s.as_str() {
+                    "transient" => Err(refused()),
+                    _ => Err(MyError::Custom("permanent".to_string())),
+                }
+            }
+        });
+
+        let handle = pool.submit("transient".to_string()).await.unwrap();
+        let id = handle.id();
+        assert!(matches!(handle.await, Err(WorkerError::Failed(_))));
+        assert_eq!(calls.swap(0, Ordering::SeqCst), 2);
+        assert_eq!(pool.dead_letters().get(id).unwrap().attempts.len(), 2);
+
+        let handle = pool.submit("permanent".to_string()).await.unwrap();
+        assert!(matches!(handle.await, Err(WorkerError::Failed(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn timeout_resolves_the_handle_and_cancels_the_job() {
+        let saw_cancel = Arc::new(AtomicBool::new(false));
+        let pool = WorkerPool::new(1, 4, {
+            let saw_cancel = saw_cancel.clone();
+            move |s| {
+                while !job_cancelled() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                saw_cancel.store(true, Ordering::SeqCst);
+                Ok(s)
+            }
+        });
+        let handle = pool
+            .submit_with_timeout("x".to_string(), Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert!(matches!(handle.await, Err(WorkerError::Timeout(_))));
+        wait_until(|| saw_cancel.load(Ordering::SeqCst)).await;
+        // the late result is discarded: the job counts as failed
+        let summary = pool.drain().await;
+        assert_eq!(summary.failed, 1);
+    }
+
+    #[tokio::test]
+    async fn shutdown_cancels_running_jobs_and_aborts_queued_ones() {
+        let started = Arc::new(AtomicBool::new(false));
+        let pool = WorkerPool::new(1, 4, {
+            let started = started.clone();
+            move |s| {
+                started.store(true, Ordering::SeqCst);
+                while !job_cancelled() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Ok(s)
+            }
+        });
+        let running = pool.submit("running".to_string()).await.unwrap();
+        wait_until(|| started.load(Ordering::SeqCst)).await;
+        let queued = pool.submit("queued".to_string()).await.unwrap();
+        let token = pool.cancellation_token();
+
+        let summary = pool.shutdown(Duration::from_secs(5)).await;
+        assert!(token.is_cancelled());
+        assert_eq!(running.await.unwrap(), "running");
+        assert!(matches!(queued.await, Err(WorkerError::Aborted)));
+        assert_eq!(
+            summary,
+            ShutdownSummary {
+                completed: 1,
+                failed: 0,
+                aborted: 1,
+                timed_out: false,
+            }
+        );
+        assert!(matches!(
+            pool.submit("late".to_string()).await,
+            Err(WorkerError::Closed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn shutdown_cuts_a_retry_backoff_short() {
+        let calls = Arc::new(AtomicU32::new(0));
+        let retry = RetryPolicy {
+            base_delay: Duration::from_secs(60),
+            ..fast_retry(5)
+        };
+        let pool = WorkerPool::with_retry(1, 4, retry, {
+            let calls = calls.clone();
+            move |_| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(refused())
+            }
+        });
+        let handle = pool.submit("x".to_string()).await.unwrap();
+        wait_until(|| calls.load(Ordering::SeqCst) == 1).await;
+
+        let summary = pool.shutdown(Duration::from_secs(5)).await;
+        assert!(!summary.timed_out);
+        assert_eq!(summary.failed, 1);
+        assert!(matches!(handle.await, Err(WorkerError::Failed(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn drain_runs_every_queued_job() {
+        let pool = WorkerPool::new(1, 8, |s| {
+            thread::sleep(Duration::from_millis(5));
+            Ok(s)
+        });
+        let mut handles = Vec::new();
+        for i in 0..4 {
+            handles.push(pool.submit(i.to_string()).await.unwrap());
+        }
+        let summary = pool.drain().await;
+        assert_eq!(summary.completed, 4);
+        assert_eq!(summary.aborted, 0);
+        for (i, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.await.unwrap(), i.to_string());
+        }
+    }
+
+    // counts itself started, then blocks its worker until `gate` opens
+    struct Gated(Arc<AtomicBool>, Arc<AtomicU32>);
+
+    impl Job for Gated {
+        type Output = ();
+        type Error = io::Error;
+
+        fn run(self) -> Result<(), io::Error> {
+            self.1.fetch_add(1, Ordering::SeqCst);
+            while !self.0.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn try_submit_job_refuses_when_the_queue_is_full() {
+        let pool = WorkerPool::new(1, 1, Ok);
+        let gate = Arc::new(AtomicBool::new(false));
+        let started = Arc::new(AtomicU32::new(0));
+        let job = || Gated(gate.clone(), started.clone());
+        let running = pool.try_submit_job(job()).unwrap();
+        wait_until(|| started.load(Ordering::SeqCst) == 1).await;
+        let queued = pool.try_submit_job(job()).unwrap();
+        assert!(matches!(
+            pool.try_submit_job(job()),
+            Err(WorkerError::QueueFull)
+        ));
+
+        gate.store(true, Ordering::SeqCst);
+        running.await.unwrap();
+        queued.await.unwrap();
+        assert_eq!(started.load(Ordering::SeqCst), 2);
+        pool.try_submit_job(job()).unwrap().await.unwrap();
+    }
+}
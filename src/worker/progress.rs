@@ -0,0 +1,85 @@
+// Progress of a running job, for UIs that want more than a spinner on a multi-minute task.
+// The job reports from inside (whatever thread it runs on), everyone else reads:
+//
+//   // in the job (WorkerPool task, Job / AsyncJob, or a JobStore job):
+//   worker::report_progress(JobProgress::percent(40.0));
+//   worker::report_progress(JobProgress::percent(80.0).with_detail(json!({"rows": 8_000})));
+//
+// Key flow:
+// report_progress(p) → the sink the worker installed for this job (thread-local, like worker::job_cancelled)
+//   ├→ WorkerPool: the job's JobHandle (handle.progress(), handle.watch_progress())
+//   │              and pool.events() as JobEvent::Progress(id, p) (worker/results.rs)
+//   └→ JobStore:   runs its jobs on a WorkerPool and reads the handle's progress as
+//                  JobStatus::Running { progress }, so GET /jobs/{id} shows it (web::jobs)
+//
+// Progress is "latest value wins": readers see the most recent report, not every one.
+// Outside of a job report_progress does nothing and returns false.
+
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// {"percent": 40.0} / {"detail": {...}} / both
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobProgress {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+    // anything the UI understands: current step, rows done, ETA, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<Value>,
+}
+
+impl JobProgress {
+    // Clamped to 0..=100.
+    pub fn percent(percent: f64) -> Self {
+        Self {
+            percent: Some(percent.clamp(0.0, 100.0)),
+            detail: None,
+        }
+    }
+
+    pub fn custom(detail: Value) -> Self {
+        Self {
+            percent: None,
+            detail: Some(detail),
+        }
+    }
+
+    pub fn with_detail(mut self, detail: Value) -> Self {
+        self.detail = Some(detail);
+        self
+    }
+}
+
+pub(super) type Sink = Box<dyn Fn(JobProgress)>;
+
+thread_local! {
+    // the running job's progress sink, on worker / blocking threads
+    static CURRENT_SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+// Reports the running job's progress; false outside of a job.
+pub fn report_progress(progress: JobProgress) -> bool {
+    CURRENT_SINK.with(|sink| match sink.borrow().as_ref() {
+        Some(sink) => {
+            sink(progress);
+            true
+        }
+        None => false,
+    })
+}
+
+// Makes `sink` the current job's sink on this thread until the guard is dropped.
+pub(super) fn enter(sink: Sink) -> CurrentSink {
+    CURRENT_SINK.with(|current| *current.borrow_mut() = Some(sink));
+    CurrentSink
+}
+
+pub(super) struct CurrentSink;
+
+impl Drop for CurrentSink {
+    fn drop(&mut self) {
+        CURRENT_SINK.with(|current| current.borrow_mut().take());
+    }
+}
@@ -0,0 +1,103 @@
+// RateLimiter: caps how many jobs per second a WorkerPool starts, whatever the producer does.
+// tokio2.rs's producer submits as fast as the queue lets it; with 4 workers on fast jobs that can
+// mean thousands of calls per second into whatever the jobs talk to (a database, a third-party API).
+//
+// Token bucket:
+//   `per_second` tokens are added per second, at most `burst` are kept (a burst after idle time)
+//   a worker takes a token before starting a job; no token → it sleeps until one is due
+// The workers being held up means the queue fills, and then submit().await waits:
+// excess jobs wait in the bounded queue, the producer gets backpressure, nothing is dropped.
+//
+//   let pool = WorkerPool::new(4, 32, task).rate_limited(Arc::new(RateLimiter::new(50.0, 10)));
+//
+// Per job type: one RateLimiter shared by every pool running that job type (Arc clone), so the
+// limit holds for the type as a whole; pools of other types get their own limiter or none.
+
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    // negative: tokens already promised to waiting workers
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    // `per_second` > 0; `burst` at least 1. Starts full.
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        assert!(per_second > 0.0, "rate limit must be positive");
+        let burst = f64::from(burst.max(1));
+        Self {
+            per_second,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    // Takes a token if one is available; Err(wait) says when the next one is.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.refill();
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(self.wait_for(bucket.tokens))
+        }
+    }
+
+    // Reserves a token and returns how long to wait until it's usable. Waiters queue up in
+    // call order (each one pushes the balance further negative), so nobody is starved.
+    pub fn reserve(&self) -> Duration {
+        let mut bucket = self.refill();
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            self.wait_for(bucket.tokens + 1.0)
+        }
+    }
+
+    // For async callers (e.g. limiting submits instead of execution).
+    pub async fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // For the worker threads.
+    pub(super) fn acquire_blocking(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    fn refill(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        let mut bucket = self.bucket.lock().expect("rate limiter lock poisoned");
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst);
+        bucket.refilled_at = now;
+        bucket
+    }
+
+    // time until the balance reaches 1 token
+    fn wait_for(&self, tokens: f64) -> Duration {
+        Duration::from_secs_f64((1.0 - tokens) / self.per_second)
+    }
+}
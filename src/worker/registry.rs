@@ -0,0 +1,157 @@
+// JobRegistry: job type name → (deserialize payload, run, serialize output), so a job written down as
+//
+//   {"type": "hash", "payload": {"input": "task 1"}}
+//
+// can be executed by any process that registered "hash": the durable queue's feed (worker/durable.rs),
+// POST /jobs (web::jobs), or a WorkerPool in another binary.
+//
+//   let registry = Arc::new(JobRegistry::new().register::<HashJob>("hash"));
+//   let pool = WorkerPool::new(4, 32, registry.clone().into_task());   // input: envelope JSON, output: JSON
+//   pool.submit(JobEnvelope::new("hash", &HashJob { input })?.to_json()?).await?;
+//
+// The envelope is the wire format: job types are registered by name, payloads are the job structs'
+// serde representation, so producers don't need to link the job's code (only agree on the JSON).
+//
+// A family of jobs can be one Tagged enum (crate::tagged) instead of a struct per name: every variant
+// is registered under its tag name, and producers build the envelope from the enum value:
+//
+//   let registry = JobRegistry::new().register_tagged::<Maintenance>();   // "vacuum", "reindex", ...
+//   pool.submit(JobEnvelope::from_tagged(&Maintenance::Vacuum(vacuum))?.to_json()?).await?;
+//
+// Errors (unknown type, bad payload, the job's own error) become MyError::Custom / MyError::Serialize,
+// so the pool's retry, dead letter and results machinery handles them like any other task failure.
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use super::job::Job;
+use crate::{error::MyError, tagged::Tagged};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobEnvelope {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+impl JobEnvelope {
+    pub fn new<J: Serialize>(kind: impl Into<String>, job: &J) -> Result<Self, MyError> {
+        Ok(Self {
+            kind: kind.into(),
+            payload: serde_json::to_value(job)?,
+        })
+    }
+
+    // {"type": <the variant's tag name>, "payload": <its payload>}
+    pub fn from_tagged<T: Tagged>(job: &T) -> Result<Self, MyError> {
+        Ok(Self {
+            kind: T::tag_name(job.tag()).to_string(),
+            payload: job.serialize_payload(serde_json::value::Serializer)?,
+        })
+    }
+
+    // Back to the enum; MyError::Custom if the type isn't one of T's tag names.
+    pub fn into_tagged<T: Tagged>(self) -> Result<T, MyError> {
+        let tag = T::from_name(&self.kind)
+            .ok_or_else(|| MyError::Custom(format!("unknown job type {:?}", self.kind)))?;
+        Ok(T::deserialize_payload(tag, self.payload)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, MyError> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+type Handler = Box<dyn Fn(Value) -> Result<Value, MyError> + Send + Sync>;
+
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: HashMap<&'static str, Handler>,
+}
+
+impl fmt::Debug for JobRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JobRegistry")
+            .field("jobs", &self.names())
+            .finish()
+    }
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Registers J under `name` (replacing an earlier registration of that name).
+    pub fn register<J>(mut self, name: &'static str) -> Self
+    where
+        J: Job + DeserializeOwned,
+        J::Output: Serialize,
+    {
+        let handler: Handler = Box::new(move |payload| {
+            let job: J = serde_json::from_value(payload)?;
+            let output = job
+                .run()
+                .map_err(|e| MyError::Custom(format!("{name} job failed: {e}")))?;
+            Ok(serde_json::to_value(output)?)
+        });
+        self.jobs.insert(name, handler);
+        self
+    }
+
+    // Registers every variant of J under its tag name; the payload is deserialized as that variant.
+    pub fn register_tagged<J>(mut self) -> Self
+    where
+        J: Job + Tagged,
+        J::Output: Serialize,
+    {
+        for &tag in J::TAGS {
+            let name = J::tag_name(tag);
+            let handler: Handler = Box::new(move |payload| {
+                let job = J::deserialize_payload(tag, payload)?;
+                let output = job
+                    .run()
+                    .map_err(|e| MyError::Custom(format!("{name} job failed: {e}")))?;
+                Ok(serde_json::to_value(output)?)
+            });
+            self.jobs.insert(name, handler);
+        }
+        self
+    }
+
+    // The registered name (with its 'static lifetime, for metric labels), if `kind` is registered.
+    pub fn name(&self, kind: &str) -> Option<&'static str> {
+        self.jobs.get_key_value(kind).map(|(name, _)| *name)
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.jobs.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+
+    // Blocking: runs the job on the calling thread.
+    pub fn execute(&self, envelope: JobEnvelope) -> Result<Value, MyError> {
+        let handler = self
+            .jobs
+            .get(envelope.kind.as_str())
+            .ok_or_else(|| MyError::Custom(format!("unknown job type {:?}", envelope.kind)))?;
+        handler(envelope.payload)
+    }
+
+    // Envelope JSON in, output JSON out.
+    pub fn execute_json(&self, envelope: &str) -> Result<String, MyError> {
+        let envelope: JobEnvelope = serde_json::from_str(envelope)?;
+        Ok(serde_json::to_string(&self.execute(envelope)?)?)
+    }
+
+    // The registry as a WorkerPool task.
+    pub fn into_task(
+        self: Arc<Self>,
+    ) -> impl Fn(String) -> Result<String, MyError> + Send + Sync + 'static {
+        move |envelope| self.execute_json(&envelope)
+    }
+}
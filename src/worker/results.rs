@@ -0,0 +1,224 @@
+// JobResults: every finished job's (id, result) as a Stream, in completion order.
+// The alternative to awaiting each JobHandle in its own task (tokio2.rs's producer): one consumer
+// loop handles completions as they come in.
+//
+//   let mut results = pool.results();
+//   while let Some((id, result)) = results.next().await {   // futures::StreamExt / tokio_stream::StreamExt
+//       match result { Ok(hash) => ..., Err(e) => ... }
+//   }
+//
+// JobEvents (pool.events()): the same, plus the jobs' progress reports (worker/progress.rs) as they happen:
+//
+//   while let Some(event) = events.next().await {
+//       match event {
+//           JobEvent::Progress(id, progress) => ...,   // e.g. push to the UI over a WebSocket
+//           JobEvent::Finished(id, result) => ...,
+//       }
+//   }
+//
+// - Covers jobs that finish after results() was called; handles still get their result too.
+// - One subscriber: calling results() (or events()) again ends the previous stream.
+// - Bounded (the pool's queue capacity): a consumer that stops reading without dropping
+//   the stream holds the workers up once it's full. Progress events are dropped instead of waiting.
+// - Ends once the pool is shut down (or drained) and every worker has exited.
+// - Timed-out jobs show up when the job actually returns, with WorkerError::Timeout.
+
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+
+use super::{pool::WorkerError, progress::JobProgress, JobId};
+
+pub type JobResult = (JobId, Result<String, WorkerError>);
+
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    Progress(JobId, JobProgress),
+    Finished(JobId, Result<String, WorkerError>),
+}
+
+// Shared by the pool (results() / events() install a subscriber) and the workers (send to it if there is one).
+pub(super) type ResultsSender = Arc<Mutex<Option<Subscriber>>>;
+
+#[derive(Clone)]
+pub(super) struct Subscriber {
+    tx: mpsc::Sender<JobEvent>,
+    progress: bool,
+}
+
+impl Subscriber {
+    pub(super) fn new(tx: mpsc::Sender<JobEvent>, progress: bool) -> Self {
+        Self { tx, progress }
+    }
+}
+
+#[derive(Debug)]
+pub struct JobResults {
+    rx: mpsc::Receiver<JobEvent>,
+}
+
+impl JobResults {
+    pub(super) fn new(rx: mpsc::Receiver<JobEvent>) -> Self {
+        Self { rx }
+    }
+
+    // Without a StreamExt import.
+    pub async fn recv(&mut self) -> Option<JobResult> {
+        std::future::poll_fn(|cx| self.poll_result(cx)).await
+    }
+
+    fn poll_result(&mut self, cx: &mut Context<'_>) -> Poll<Option<JobResult>> {
+        loop {
+            match self.rx.poll_recv(cx) {
+                Poll::Ready(Some(JobEvent::Finished(id, result))) => {
+                    return Poll::Ready(Some((id, result)))
+                }
+                // not sent to results() subscribers
+                Poll::Ready(Some(JobEvent::Progress(..))) => continue,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Stream for JobResults {
+    type Item = JobResult;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_result(cx)
+    }
+}
+
+#[derive(Debug)]
+pub struct JobEvents {
+    rx: mpsc::Receiver<JobEvent>,
+}
+
+impl JobEvents {
+    pub(super) fn new(rx: mpsc::Receiver<JobEvent>) -> Self {
+        Self { rx }
+    }
+
+    pub async fn recv(&mut self) -> Option<JobEvent> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for JobEvents {
+    type Item = JobEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+// From a worker thread; drops the subscriber when its stream is gone.
+pub(super) fn publish(results: &ResultsSender, (id, result): JobResult) {
+    let subscriber = results.lock().expect("results lock poisoned").clone();
+    if let Some(subscriber) = subscriber {
+        if subscriber
+            .tx
+            .blocking_send(JobEvent::Finished(id, result))
+            .is_err()
+        {
+            unsubscribe(results, &subscriber);
+        }
+    }
+}
+
+// From inside a running job: never waits, a full stream just misses this report.
+pub(super) fn publish_progress(results: &ResultsSender, id: JobId, progress: JobProgress) {
+    let subscriber = results.lock().expect("results lock poisoned").clone();
+    if let Some(subscriber) = subscriber.filter(|s| s.progress) {
+        if let Err(mpsc::error::TrySendError::Closed(_)) =
+            subscriber.tx.try_send(JobEvent::Progress(id, progress))
+        {
+            unsubscribe(results, &subscriber);
+        }
+    }
+}
+
+fn unsubscribe(results: &ResultsSender, subscriber: &Subscriber) {
+    let mut current = results.lock().expect("results lock poisoned");
+    // unless results() installed a new one meanwhile
+    if current
+        .as_ref()
+        .is_some_and(|s| s.tx.same_channel(&subscriber.tx))
+    {
+        current.take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio_stream::StreamExt;
+
+    use super::*;
+    use crate::{
+        error::MyError,
+        worker::{report_progress, WorkerPool},
+    };
+
+    #[tokio::test]
+    async fn results_stream_has_every_job_and_ends_at_shutdown() {
+        let pool = WorkerPool::new(2, 8, |s| match s.as_str() {
+            "bad" => Err(MyError::Custom("bad".to_string())),
+            _ => Ok(s),
+        });
+        let mut results = pool.results();
+        let ok = pool.submit("ok".to_string()).await.unwrap().id();
+        let bad = pool.submit("bad".to_string()).await.unwrap().id();
+
+        let mut seen = Vec::new();
+        for _ in 0..2 {
+            seen.push(results.next().await.unwrap());
+        }
+        seen.sort_by_key(|(id, _)| *id);
+        assert!(matches!(&seen[0], (id, Ok(out)) if *id == ok && out == "ok"));
+        assert!(matches!(&seen[1], (id, Err(WorkerError::Failed(_))) if *id == bad));
+
+        pool.shutdown(Duration::from_secs(1)).await;
+        assert!(results.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn a_new_subscriber_ends_the_previous_stream() {
+        let pool = WorkerPool::new(1, 4, Ok);
+        let mut first = pool.results();
+        let mut second = pool.results();
+        assert!(first.recv().await.is_none());
+
+        let id = pool.submit("x".to_string()).await.unwrap().id();
+        assert_eq!(second.recv().await.unwrap().0, id);
+    }
+
+    #[tokio::test]
+    async fn events_carry_progress_before_the_result() {
+        let pool = WorkerPool::new(1, 4, |s| {
+            report_progress(JobProgress::percent(50.0));
+            Ok(s)
+        });
+        let mut events = pool.events();
+        let id = pool.submit("x".to_string()).await.unwrap().id();
+
+        match events.recv().await.unwrap() {
+            JobEvent::Progress(job, progress) => {
+                assert_eq!(job, id);
+                assert_eq!(progress.percent, Some(50.0));
+            }
+            other => panic!("expected progress, got {other:?}"),
+        }
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            JobEvent::Finished(job, Ok(_)) if job == id
+        ));
+    }
+}
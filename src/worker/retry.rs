@@ -0,0 +1,163 @@
+// RetryPolicy: what the WorkerPool does when a job returns an error.
+// Only transient errors are retried (MyError::is_retryable by default: timeouts, refused/reset
+// connections, ...); a parse error fails the same way on every attempt, so it fails immediately.
+//
+// Delay before attempt n+1 = base_delay * 2^(n-1), capped at max_delay, then ± jitter:
+//   base 100ms, jitter 0.2 → ~100ms, ~200ms, ~400ms, ... each randomly within ±20%
+// Jitter spreads out retries of jobs that failed together (e.g. the database restarted),
+// instead of all of them hitting it again at the same instant.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use crate::error::MyError;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    // including the first one: 1 = no retries
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    // 0.0..=1.0, fraction of the delay to randomize by; NaN / infinite: no jitter
+    pub jitter: f64,
+    pub retry_on: fn(&MyError) -> bool,
+}
+
+impl Default for RetryPolicy {
+    // 3 attempts, 100ms → 200ms, ±20%
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            jitter: 0.2,
+            retry_on: MyError::is_retryable,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn should_retry(&self, attempt: u32, error: &MyError) -> bool {
+        attempt < self.max_attempts && (self.retry_on)(error)
+    }
+
+    // Delay after the `attempt`-th failed attempt (1-based), never more than max_delay.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        // clamp keeps NaN, which would make the factor NaN
+        let jitter = match self.jitter.is_finite() {
+            true => self.jitter.clamp(0.0, 1.0),
+            false => 0.0,
+        };
+        // random factor in [1 - jitter, 1 + jitter]
+        let factor = 1.0 + jitter * (2.0 * random_unit() - 1.0);
+        // the checked form of mul_f64: too large for a Duration → max_delay
+        Duration::try_from_secs_f64(exp.as_secs_f64() * factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+}
+
+// Uniform-ish in [0, 1). RandomState is seeded randomly per instance: good enough for jitter,
+// without pulling in a rand dependency.
+fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    fn no_jitter() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn delay_doubles_up_to_max_delay() {
+        let policy = no_jitter();
+        let delays: Vec<u128> = (1..=6).map(|n| policy.delay(n).as_millis()).collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_stays_within_its_fraction() {
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            max_delay: Duration::from_secs(10),
+            ..no_jitter()
+        };
+        for _ in 0..200 {
+            let delay = policy.delay(1);
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(150));
+        }
+    }
+
+    #[test]
+    fn non_finite_jitter_means_no_jitter() {
+        for jitter in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let policy = RetryPolicy {
+                jitter,
+                ..no_jitter()
+            };
+            assert_eq!(policy.delay(2), Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn huge_delays_saturate_at_max_delay() {
+        let policy = RetryPolicy {
+            base_delay: Duration::MAX,
+            max_delay: Duration::from_secs(3600),
+            jitter: 1.0,
+            ..no_jitter()
+        };
+        for attempt in [1, 2, 64, u32::MAX] {
+            assert!(policy.delay(attempt) <= Duration::from_secs(3600));
+        }
+        // exp * factor overflows a Duration: max_delay, not a panic
+        let unbounded = RetryPolicy {
+            max_delay: Duration::MAX,
+            ..policy
+        };
+        assert!(unbounded.delay(u32::MAX) <= Duration::MAX);
+    }
+
+    #[test]
+    fn only_retryable_errors_within_max_attempts_are_retried() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            ..no_jitter()
+        };
+        let transient: MyError = io::Error::from(io::ErrorKind::TimedOut).into();
+        let permanent = MyError::Custom("bad input".to_string());
+        assert!(policy.should_retry(1, &transient));
+        assert!(policy.should_retry(2, &transient));
+        assert!(!policy.should_retry(3, &transient));
+        assert!(!policy.should_retry(1, &permanent));
+        assert!(!RetryPolicy::none().should_retry(1, &transient));
+    }
+}
@@ -0,0 +1,75 @@
+// Per-job timeouts for the WorkerPool (submit_with_timeout, or WorkerPool::job_timeout for every job).
+//
+// A blocking job can't be interrupted from outside its thread, so a timeout does two things:
+//   ├→ the handle resolves with WorkerError::Timeout right at the deadline (the caller stops waiting)
+//   └→ the job's cancellation flag is set; the job sees it if it checks:
+//        blocking code:  loop over chunks { if worker::job_cancelled() { return Err(..) } ... }
+//        async code run from a job (Handle::block_on): select on worker::job_cancellation()
+//        AsyncJob (worker/job.rs): its future is dropped, no checks needed
+// A job that never checks keeps its worker until it returns; its late result is discarded.
+// The clock starts when a worker picks the job up, so time waiting in the queue doesn't count.
+//
+// The deadline is watched by a small task on the runtime the job was submitted from
+// (the worker thread itself is busy running the job).
+
+use std::{cell::RefCell, time::Duration};
+
+use tokio::{runtime::Handle, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use super::JobId;
+
+#[derive(Debug, Clone)]
+pub(super) struct JobTimeout {
+    pub(super) after: Duration,
+    pub(super) runtime: Handle,
+}
+
+thread_local! {
+    // the running job's token, on worker threads
+    static CURRENT_JOB: RefCell<Option<CancellationToken>> = const { RefCell::new(None) };
+}
+
+// True inside a job that timed out (or whose pool is shutting down): time to stop early.
+// Always false outside of WorkerPool jobs.
+pub fn job_cancelled() -> bool {
+    CURRENT_JOB.with(|job| job.borrow().as_ref().is_some_and(|t| t.is_cancelled()))
+}
+
+// The running job's cancellation token, for async work inside a job.
+pub fn job_cancellation() -> Option<CancellationToken> {
+    CURRENT_JOB.with(|job| job.borrow().clone())
+}
+
+// Makes `token` the current job's token on this thread until the guard is dropped.
+pub(super) fn enter(token: CancellationToken) -> CurrentJob {
+    CURRENT_JOB.with(|job| *job.borrow_mut() = Some(token));
+    CurrentJob
+}
+
+pub(super) struct CurrentJob;
+
+impl Drop for CurrentJob {
+    fn drop(&mut self) {
+        CURRENT_JOB.with(|job| job.borrow_mut().take());
+    }
+}
+
+// At the deadline: cancels the job's token and calls `expire`, which answers the handle with
+// WorkerError::Timeout (unless the worker answered first). Abort the returned task when the job finishes in time.
+pub(super) fn watchdog(
+    timeout: &JobTimeout,
+    id: JobId,
+    job: CancellationToken,
+    expire: Box<dyn FnOnce(Duration) + Send>,
+) -> JoinHandle<()> {
+    let after = timeout.after;
+    timeout.runtime.spawn(async move {
+        tokio::time::sleep(after).await;
+        warn!(job.id = id, ?after, "job timed out");
+        // answer first: a job that reacts to the cancellation must not beat the Timeout to its handle
+        expire(after);
+        job.cancel();
+    })
+}